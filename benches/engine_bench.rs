@@ -1,6 +1,9 @@
 use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
 use lb_sim::engine::run_simulation;
-use lb_sim::models::{AlgoConfig, RequestProfile, ServerConfig, SimConfig, TieBreakConfig};
+use lb_sim::models::{
+    AlgoConfig, EventOrder, RandomnessConfig, RequestProfile, RngKind, ServerConfig, ServerModel,
+    SimConfig,
+};
 
 const REQUESTS: usize = 1_000;
 const SERVERS: usize = 8;
@@ -11,6 +14,7 @@ fn build_servers(count: usize) -> Vec<ServerConfig> {
             name: format!("srv-{}", idx),
             base_latency_ms: 10 + idx as u64,
             weight: 1,
+            model: ServerModel::default(),
         })
         .collect()
 }
@@ -20,8 +24,17 @@ fn build_config(algo: AlgoConfig) -> SimConfig {
         servers: build_servers(SERVERS),
         requests: RequestProfile::FixedCount(REQUESTS),
         algo,
-        tie_break: TieBreakConfig::Stable,
-        seed: None,
+        randomness: RandomnessConfig::Stable,
+        rng: RngKind::Std,
+        event_order: EventOrder::CompletionsFirst,
+        allow_large: false,
+        timeseries_bucket_ms: None,
+        record_timelines: false,
+        slos: Vec::new(),
+        slowest_requests: 0,
+        explain: false,
+        max_virtual_time_ms: None,
+        max_events: None,
     }
 }
 
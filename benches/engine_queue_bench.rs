@@ -13,7 +13,7 @@ fn build_events(count: usize) -> Vec<ScheduledEvent> {
                 ScheduledEvent::new(
                     time_ms,
                     Event::RequestArrival(Request {
-                        id: idx,
+                        id: time_ms,
                         arrival_time_ms: time_ms,
                     }),
                 )
@@ -22,7 +22,7 @@ fn build_events(count: usize) -> Vec<ScheduledEvent> {
                     time_ms,
                     Event::RequestComplete {
                         server_id: idx % 8,
-                        request_id: idx,
+                        request_id: time_ms,
                     },
                 )
             }
@@ -16,7 +16,7 @@ fn build_servers(count: usize) -> Vec<ServerState> {
             base_latency_ms: 10 + idx as u64,
             weight: 1,
             active_connections: (idx % 3) as u32,
-            pick_count: (idx % 5) as u32,
+            pick_count: (idx % 5) as u64,
             in_flight: 0,
             next_available_ms: 0,
         })
@@ -43,7 +43,7 @@ fn bench_selection(c: &mut Criterion) {
                     || {
                         let servers = build_servers(SERVERS);
                         let rng = StdRng::seed_from_u64(1);
-                        let strategy = build_strategy(algo.clone());
+                        let strategy = build_strategy(algo.clone()).expect("built-in algorithm");
                         (servers, rng, strategy)
                     },
                     |(servers, mut rng, mut strategy)| {
@@ -1,43 +1,165 @@
+use std::cell::OnceCell;
+use std::cmp::Ordering;
+
 use rand::Rng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-use crate::algorithms::{Selection, SelectionContext, SelectionStrategy};
+use crate::algorithms::{ScoreMetric, Selection, SelectionContext, SelectionStrategy};
+use crate::state::ServerState;
 
+/// Keeps an index of connection counts that the engine updates as requests
+/// arrive and complete, so each pick is O(log n) rather than a scan. Callers
+/// driving it outside the engine must report count changes through `sync` or
+/// `server_updated`, as the `SelectionStrategy` contract requires; debug
+/// builds check the index on every pick.
 #[derive(Default)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct LeastConnectionsStrategy {
-    candidates: Vec<usize>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    index: ConnectionIndex,
+    /// Filled in only when `last_candidates` is asked for.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    candidates: OnceCell<Vec<usize>>,
 }
 
 impl SelectionStrategy for LeastConnectionsStrategy {
     fn select(&mut self, ctx: &mut SelectionContext) -> Selection {
-        let mut min_count = u32::MAX;
-        self.candidates.clear();
-        if self.candidates.capacity() < ctx.servers.len() {
-            self.candidates
-                .reserve(ctx.servers.len().saturating_sub(self.candidates.len()));
+        if self.index.len != ctx.servers.len() {
+            self.index.rebuild(ctx.servers);
         }
+        debug_assert!(
+            self.index.matches(ctx.servers),
+            "least-connections index is stale: call sync or server_updated after connection counts change"
+        );
+        self.candidates.take();
 
-        for (idx, server) in ctx.servers.iter().enumerate() {
-            if server.active_connections < min_count {
-                min_count = server.active_connections;
-                self.candidates.clear();
-                self.candidates.push(idx);
-            } else if server.active_connections == min_count {
-                self.candidates.push(idx);
-            }
-        }
-
-        let choice = if self.candidates.len() == 1 {
-            self.candidates[0]
+        let (min_count, tied) = self.index.nodes[1];
+        let choice = if tied == 1 {
+            self.index.nth_least(0)
         } else {
-            let pick = ctx.rng.gen_range(0..self.candidates.len());
-            self.candidates[pick]
+            let pick = ctx.rng.gen_range(0..tied as usize);
+            self.index.nth_least(pick)
         };
+        tracing::trace!(
+            server = choice,
+            connections = min_count,
+            tied,
+            "least-connections pick"
+        );
 
         Selection {
             server_id: choice,
-            score: None,
+            score: Some(u64::from(min_count)),
+        }
+    }
+
+    fn score_metric(&self) -> Option<ScoreMetric> {
+        Some(ScoreMetric::ActiveConnections)
+    }
+
+    fn sync(&mut self, servers: &[ServerState]) {
+        self.index.rebuild(servers);
+        self.candidates.take();
+    }
+
+    fn server_updated(&mut self, server: &ServerState) {
+        if server.id < self.index.len {
+            self.index.set(server.id, server.active_connections);
         }
     }
+
+    fn last_candidates(&self) -> &[usize] {
+        self.candidates.get_or_init(|| self.index.least())
+    }
+
+    #[cfg(feature = "serde")]
+    fn save_state(&self) -> serde_json::Value {
+        super::save_state(self)
+    }
+
+    #[cfg(feature = "serde")]
+    fn restore_state(&mut self, state: serde_json::Value) -> crate::error::Result<()> {
+        super::restore_state(self, state)
+    }
+}
+
+/// A min-tree over active connection counts. Each node holds the smallest
+/// count below it and how many servers share it, so the least-loaded servers
+/// and the n-th of them in index order are found without visiting the rest.
+#[derive(Clone, Debug, Default)]
+struct ConnectionIndex {
+    len: usize,
+    leaves: usize,
+    /// `(min connections, servers at that min)`; the root is `nodes[1]` and
+    /// server `i` is `nodes[leaves + i]`.
+    nodes: Vec<(u32, u32)>,
+}
+
+impl ConnectionIndex {
+    fn rebuild(&mut self, servers: &[ServerState]) {
+        self.len = servers.len();
+        self.leaves = servers.len().next_power_of_two();
+        self.nodes.clear();
+        self.nodes.resize(2 * self.leaves, (u32::MAX, 0));
+        for (idx, server) in servers.iter().enumerate() {
+            self.nodes[self.leaves + idx] = (server.active_connections, 1);
+        }
+        for node in (1..self.leaves).rev() {
+            self.nodes[node] = self.combine(node);
+        }
+    }
+
+    /// Whether every leaf holds the server's current connection count.
+    fn matches(&self, servers: &[ServerState]) -> bool {
+        self.len == servers.len()
+            && servers.iter().enumerate().all(|(idx, server)| {
+                self.nodes[self.leaves + idx] == (server.active_connections, 1)
+            })
+    }
+
+    fn set(&mut self, idx: usize, connections: u32) {
+        let mut node = self.leaves + idx;
+        self.nodes[node] = (connections, 1);
+        while node > 1 {
+            node /= 2;
+            self.nodes[node] = self.combine(node);
+        }
+    }
+
+    fn combine(&self, node: usize) -> (u32, u32) {
+        let (left, right) = (self.nodes[2 * node], self.nodes[2 * node + 1]);
+        match left.0.cmp(&right.0) {
+            Ordering::Less => left,
+            Ordering::Greater => right,
+            Ordering::Equal => (left.0, left.1 + right.1),
+        }
+    }
+
+    /// Index of the `n`-th server (in index order) with the fewest connections.
+    fn nth_least(&self, mut n: usize) -> usize {
+        let min = self.nodes[1].0;
+        let mut node = 1;
+        while node < self.leaves {
+            let (left_min, left_tied) = self.nodes[2 * node];
+            node = if left_min == min && n < left_tied as usize {
+                2 * node
+            } else {
+                if left_min == min {
+                    n -= left_tied as usize;
+                }
+                2 * node + 1
+            };
+        }
+        node - self.leaves
+    }
+
+    /// Every server with the fewest connections, in index order.
+    fn least(&self) -> Vec<usize> {
+        (0..self.nodes[1].1 as usize)
+            .map(|n| self.nth_least(n))
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -142,4 +264,120 @@ mod tests {
 
         assert_eq!(strategy.select(&mut ctx).server_id, expected);
     }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "least-connections index is stale")]
+    fn select_rejects_counts_changed_behind_the_index() {
+        let mut servers = (0..2)
+            .map(|id| ServerState {
+                id,
+                name: format!("s{}", id),
+                base_latency_ms: 10,
+                weight: 1,
+                active_connections: 0,
+                pick_count: 0,
+                in_flight: 0,
+                next_available_ms: 0,
+            })
+            .collect::<Vec<_>>();
+        let mut strategy = LeastConnectionsStrategy::default();
+        strategy.sync(&servers);
+        servers[0].active_connections = 5;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        strategy.select(&mut SelectionContext {
+            servers: &servers,
+            time_ms: 0,
+            rng: &mut rng,
+        });
+    }
+
+    #[test]
+    fn select_follows_counts_reported_through_sync_and_server_updated() {
+        let mut servers = (0..3)
+            .map(|id| ServerState {
+                id,
+                name: format!("s{}", id),
+                base_latency_ms: 10,
+                weight: 1,
+                active_connections: 1,
+                pick_count: 0,
+                in_flight: 0,
+                next_available_ms: 0,
+            })
+            .collect::<Vec<_>>();
+        let mut strategy = LeastConnectionsStrategy::default();
+        strategy.sync(&servers);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let mut pick = |strategy: &mut LeastConnectionsStrategy, servers: &[ServerState]| {
+            strategy
+                .select(&mut SelectionContext {
+                    servers,
+                    time_ms: 0,
+                    rng: &mut rng,
+                })
+                .server_id
+        };
+
+        servers[2].active_connections = 0;
+        strategy.server_updated(&servers[2]);
+        assert_eq!(pick(&mut strategy, &servers), 2);
+
+        servers[0].active_connections = 0;
+        servers[2].active_connections = 4;
+        strategy.sync(&servers);
+        assert_eq!(pick(&mut strategy, &servers), 0);
+        assert_eq!(strategy.last_candidates(), &[0]);
+    }
+
+    #[test]
+    fn indexed_picks_match_a_full_scan_as_connections_change() {
+        let mut servers = (0..13)
+            .map(|id| ServerState {
+                id,
+                name: format!("s{}", id),
+                base_latency_ms: 10,
+                weight: 1,
+                active_connections: 0,
+                pick_count: 0,
+                in_flight: 0,
+                next_available_ms: 0,
+            })
+            .collect::<Vec<_>>();
+        let mut strategy = LeastConnectionsStrategy::default();
+        strategy.sync(&servers);
+        let mut changes = rand::rngs::StdRng::seed_from_u64(7);
+        let mut picks = rand::rngs::StdRng::seed_from_u64(42);
+        let mut expected_picks = rand::rngs::StdRng::seed_from_u64(42);
+
+        for _ in 0..500 {
+            let min = servers.iter().map(|s| s.active_connections).min().unwrap();
+            let tied = servers
+                .iter()
+                .filter(|server| server.active_connections == min)
+                .map(|server| server.id)
+                .collect::<Vec<_>>();
+            let expected = if tied.len() == 1 {
+                tied[0]
+            } else {
+                tied[expected_picks.gen_range(0..tied.len())]
+            };
+            let mut ctx = SelectionContext {
+                servers: &servers,
+                time_ms: 0,
+                rng: &mut picks,
+            };
+            let choice = strategy.select(&mut ctx).server_id;
+            assert_eq!(choice, expected);
+            assert_eq!(strategy.last_candidates(), tied.as_slice());
+
+            servers[choice].active_connections += 1;
+            strategy.server_updated(&servers[choice]);
+            let done = changes.gen_range(0..servers.len());
+            if servers[done].active_connections > 0 {
+                servers[done].active_connections -= 1;
+                strategy.server_updated(&servers[done]);
+            }
+        }
+    }
 }
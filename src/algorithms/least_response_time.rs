@@ -1,8 +1,12 @@
 use rand::Rng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-use crate::algorithms::{Selection, SelectionContext, SelectionStrategy};
+use crate::algorithms::{ScoreMetric, Selection, SelectionContext, SelectionStrategy};
+use crate::state::ServerState;
 
 #[derive(Default)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct LeastResponseTimeStrategy {
     candidates: Vec<usize>,
 }
@@ -17,10 +21,7 @@ impl SelectionStrategy for LeastResponseTimeStrategy {
         }
 
         for (idx, server) in ctx.servers.iter().enumerate() {
-            let score = server
-                .next_available_ms
-                .max(ctx.time_ms)
-                .saturating_add(server.base_latency_ms);
+            let score = expected_completion_ms(server, ctx.time_ms);
             if score < min_score {
                 min_score = score;
                 self.candidates.clear();
@@ -36,12 +37,47 @@ impl SelectionStrategy for LeastResponseTimeStrategy {
             let pick = ctx.rng.gen_range(0..self.candidates.len());
             self.candidates[pick]
         };
+        tracing::trace!(
+            server = choice,
+            score = min_score,
+            tied = self.candidates.len(),
+            "least-response-time pick"
+        );
 
         Selection {
             server_id: choice,
             score: Some(min_score),
         }
     }
+
+    fn score_metric(&self) -> Option<ScoreMetric> {
+        Some(ScoreMetric::ExpectedCompletionMs)
+    }
+
+    fn last_candidates(&self) -> &[usize] {
+        &self.candidates
+    }
+
+    fn score(&self, server: &ServerState, time_ms: u64) -> Option<u64> {
+        Some(expected_completion_ms(server, time_ms))
+    }
+
+    #[cfg(feature = "serde")]
+    fn save_state(&self) -> serde_json::Value {
+        super::save_state(self)
+    }
+
+    #[cfg(feature = "serde")]
+    fn restore_state(&mut self, state: serde_json::Value) -> crate::error::Result<()> {
+        super::restore_state(self, state)
+    }
+}
+
+fn expected_completion_ms(server: &ServerState, time_ms: u64) -> u64 {
+    server
+        .next_available_ms
+        .max(time_ms)
+        .saturating_add(server.base_latency_ms)
 }
 
 #[cfg(test)]
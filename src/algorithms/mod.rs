@@ -3,18 +3,98 @@ mod least_response_time;
 mod round_robin;
 mod weighted_round_robin;
 
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock, PoisonError, RwLock};
+
 use rand::RngCore;
+#[cfg(feature = "serde")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+#[cfg(feature = "serde")]
+use serde_json::Value;
 
+use crate::error::{Error, Result};
 use crate::models::AlgoConfig;
 use crate::state::ServerState;
 
 pub use least_connections::LeastConnectionsStrategy;
 pub use least_response_time::LeastResponseTimeStrategy;
 pub use round_robin::RoundRobinStrategy;
+pub(crate) use weighted_round_robin::cycle_length;
 pub use weighted_round_robin::WeightedRoundRobinStrategy;
 
+/// A load-balancing policy the engine asks for one server per arrival.
+///
+/// Strategies may index server state rather than read `ctx.servers` on every
+/// pick (least-connections does), so whoever drives one owns keeping that
+/// index current: call `sync` whenever server state is replaced, and
+/// `server_updated` after each change to a server's connection counts,
+/// before the next `select`. The engine does both. A caller that skips them
+/// gets picks made from stale counts; least-connections panics on it in debug
+/// builds, and release builds do not check.
 pub trait SelectionStrategy {
+    /// Called once per arrival, so it should not allocate: keep any scratch
+    /// space (tie candidates, weight tables) in the strategy and reuse it, as
+    /// the built-in strategies do. `ctx.servers` must already have been
+    /// reported through `sync` and `server_updated`.
     fn select(&mut self, ctx: &mut SelectionContext) -> Selection;
+
+    /// Called when server state is replaced wholesale: a run starting, a
+    /// snapshot being restored, or a caller changing counts in bulk.
+    fn sync(&mut self, _servers: &[ServerState]) {}
+
+    /// Called after a server's connection counts change, for strategies that
+    /// index server state instead of scanning it on every selection.
+    fn server_updated(&mut self, _server: &ServerState) {}
+
+    /// Whether selection depends on connection counts, which completions
+    /// update. Strategies that return `false` let `SimulationEngine::run` skip
+    /// completion events when nothing else observes them.
+    fn needs_completions(&self) -> bool {
+        true
+    }
+
+    /// Servers that tied for the most recent selection, if the strategy
+    /// compares candidates at all.
+    fn last_candidates(&self) -> &[usize] {
+        &[]
+    }
+
+    /// What `Selection::score` measures, recorded in run metadata so reports
+    /// can label it. `None` reads as a duration in ms.
+    fn score_metric(&self) -> Option<ScoreMetric> {
+        None
+    }
+
+    /// The metric this strategy compares servers on, for `--explain`.
+    fn score(&self, _server: &ServerState, _time_ms: u64) -> Option<u64> {
+        None
+    }
+
+    /// Internal state for `SimulationEngine::snapshot`. Strategies that keep
+    /// state between selections should override this and `restore_state`.
+    #[cfg(feature = "serde")]
+    fn save_state(&self) -> Value {
+        Value::Null
+    }
+
+    #[cfg(feature = "serde")]
+    fn restore_state(&mut self, _state: Value) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+fn save_state<T: Serialize>(strategy: &T) -> Value {
+    serde_json::to_value(strategy).unwrap_or(Value::Null)
+}
+
+#[cfg(feature = "serde")]
+fn restore_state<T: DeserializeOwned>(strategy: &mut T, state: Value) -> Result<()> {
+    *strategy = serde_json::from_value(state)
+        .map_err(|err| Error::Snapshot(format!("strategy state: {}", err)))?;
+    Ok(())
 }
 
 pub struct SelectionContext<'a> {
@@ -26,14 +106,187 @@ pub struct SelectionContext<'a> {
 
 pub struct Selection {
     pub server_id: usize,
+    /// The decision metric for this pick, as named by `score_metric`.
     pub score: Option<u64>,
 }
 
-pub fn build_strategy(algo: AlgoConfig) -> Box<dyn SelectionStrategy> {
-    match algo {
+/// The decision metrics the built-in strategies report as `Selection::score`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScoreMetric {
+    /// Expected completion time of the picked server, in ms.
+    ExpectedCompletionMs,
+    /// Active connections on the picked server at pick time.
+    ActiveConnections,
+    /// Position in the (weighted) rotation that was picked.
+    RotationSlot,
+}
+
+impl ScoreMetric {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScoreMetric::ExpectedCompletionMs => "expected_completion_ms",
+            ScoreMetric::ActiveConnections => "active_connections",
+            ScoreMetric::RotationSlot => "rotation_slot",
+        }
+    }
+
+    /// The metric `name` names, as read back from run metadata.
+    pub fn from_name(name: &str) -> Option<ScoreMetric> {
+        [
+            ScoreMetric::ExpectedCompletionMs,
+            ScoreMetric::ActiveConnections,
+            ScoreMetric::RotationSlot,
+        ]
+        .into_iter()
+        .find(|metric| metric.name() == name)
+    }
+}
+
+type StrategyFactory = dyn Fn() -> Box<dyn SelectionStrategy> + Send + Sync;
+
+fn registry() -> &'static RwLock<BTreeMap<String, Arc<StrategyFactory>>> {
+    static REGISTRY: OnceLock<RwLock<BTreeMap<String, Arc<StrategyFactory>>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// Makes `name` usable wherever a built-in algorithm is accepted (`--algo`,
+/// config files, `list-algorithms`, `recommend`). Each run calls `factory`
+/// for a fresh strategy. Built-in names and aliases cannot be replaced.
+pub fn register<F>(name: &str, factory: F) -> Result<()>
+where
+    F: Fn() -> Box<dyn SelectionStrategy> + Send + Sync + 'static,
+{
+    if name.trim().is_empty() || name.trim() != name {
+        return Err(Error::AlgorithmRegistration(format!(
+            "invalid name '{}'",
+            name
+        )));
+    }
+    if AlgoConfig::builtin(name).is_some() {
+        return Err(Error::AlgorithmRegistration(format!(
+            "'{}' is a built-in algorithm",
+            name
+        )));
+    }
+    let mut registry = registry().write().unwrap_or_else(PoisonError::into_inner);
+    if registry.contains_key(name) {
+        return Err(Error::AlgorithmRegistration(format!(
+            "'{}' is already registered",
+            name
+        )));
+    }
+    registry.insert(name.to_string(), Arc::new(factory));
+    Ok(())
+}
+
+pub fn is_registered(name: &str) -> bool {
+    registry()
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .contains_key(name)
+}
+
+/// Built-in algorithms followed by registered ones, by name.
+pub fn available() -> Vec<AlgoConfig> {
+    let registry = registry().read().unwrap_or_else(PoisonError::into_inner);
+    AlgoConfig::BUILTIN
+        .into_iter()
+        .chain(registry.keys().cloned().map(AlgoConfig::Custom))
+        .collect()
+}
+
+pub fn build_strategy(algo: AlgoConfig) -> Result<Box<dyn SelectionStrategy>> {
+    Ok(match algo {
         AlgoConfig::RoundRobin => Box::new(RoundRobinStrategy::default()),
         AlgoConfig::WeightedRoundRobin => Box::new(WeightedRoundRobinStrategy::default()),
         AlgoConfig::LeastConnections => Box::new(LeastConnectionsStrategy::default()),
         AlgoConfig::LeastResponseTime => Box::new(LeastResponseTimeStrategy::default()),
+        AlgoConfig::Custom(name) => {
+            let factory = registry()
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .get(&name)
+                .cloned()
+                .ok_or(Error::UnknownAlgorithm(name))?;
+            factory()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "serde")]
+    use crate::engine;
+    use crate::models::SimConfig;
+
+    struct LastServer;
+
+    impl SelectionStrategy for LastServer {
+        fn select(&mut self, ctx: &mut SelectionContext) -> Selection {
+            Selection {
+                server_id: ctx.servers.len() - 1,
+                score: None,
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn registered_strategy_runs_by_name() {
+        register("last-server-test", || Box::new(LastServer)).expect("name should be free");
+        let algo: AlgoConfig =
+            serde_json::from_str("\"last-server-test\"").expect("algo should parse");
+        assert_eq!(algo, AlgoConfig::Custom("last-server-test".to_string()));
+        assert!(available().contains(&algo));
+
+        let config = SimConfig::builder()
+            .server("a", 10)
+            .server("b", 10)
+            .requests(3)
+            .algo(algo)
+            .build()
+            .expect("registered algorithm should validate");
+        let result = engine::run_simulation(&config).expect("simulation should run");
+        assert!(result
+            .assignments
+            .iter()
+            .all(|assignment| assignment.server_id == 1));
+        assert_eq!(result.metadata.algo, "last-server-test");
+    }
+
+    #[test]
+    fn register_rejects_builtin_and_duplicate_names() {
+        assert!(matches!(
+            register("lc", || Box::new(LastServer)),
+            Err(Error::AlgorithmRegistration(_))
+        ));
+        register("duplicate-test", || Box::new(LastServer)).expect("name should be free");
+        assert!(register("duplicate-test", || Box::new(LastServer)).is_err());
+    }
+
+    #[test]
+    fn unregistered_names_fail_validation() {
+        let config = SimConfig::builder()
+            .server("a", 10)
+            .requests(1)
+            .algo(AlgoConfig::Custom("missing-test".to_string()))
+            .build();
+        assert_eq!(
+            config.unwrap_err().to_string(),
+            "unknown algorithm 'missing-test' (see list-algorithms)"
+        );
+    }
+
+    #[test]
+    fn score_metrics_round_trip_through_their_names() {
+        for metric in [
+            ScoreMetric::ExpectedCompletionMs,
+            ScoreMetric::ActiveConnections,
+            ScoreMetric::RotationSlot,
+        ] {
+            assert_eq!(ScoreMetric::from_name(metric.name()), Some(metric));
+        }
+        assert_eq!(ScoreMetric::from_name("queue_depth"), None);
     }
 }
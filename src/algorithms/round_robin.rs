@@ -1,6 +1,10 @@
-use crate::algorithms::{Selection, SelectionContext, SelectionStrategy};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::algorithms::{ScoreMetric, Selection, SelectionContext, SelectionStrategy};
 
 #[derive(Default)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct RoundRobinStrategy {
     next_idx: usize,
 }
@@ -9,11 +13,30 @@ impl SelectionStrategy for RoundRobinStrategy {
     fn select(&mut self, ctx: &mut SelectionContext) -> Selection {
         let idx = self.next_idx % ctx.servers.len();
         self.next_idx = (self.next_idx + 1) % ctx.servers.len();
+        tracing::trace!(server = idx, "round-robin pick");
         Selection {
             server_id: idx,
-            score: None,
+            score: Some(idx as u64),
         }
     }
+
+    fn score_metric(&self) -> Option<ScoreMetric> {
+        Some(ScoreMetric::RotationSlot)
+    }
+
+    fn needs_completions(&self) -> bool {
+        false
+    }
+
+    #[cfg(feature = "serde")]
+    fn save_state(&self) -> serde_json::Value {
+        super::save_state(self)
+    }
+
+    #[cfg(feature = "serde")]
+    fn restore_state(&mut self, state: serde_json::Value) -> crate::error::Result<()> {
+        super::restore_state(self, state)
+    }
 }
 
 #[cfg(test)]
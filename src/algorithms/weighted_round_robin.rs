@@ -1,7 +1,11 @@
-use crate::algorithms::{Selection, SelectionContext, SelectionStrategy};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::algorithms::{ScoreMetric, Selection, SelectionContext, SelectionStrategy};
 use crate::state::ServerState;
 
 #[derive(Default)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct WeightedRoundRobinStrategy {
     cursor: u64,
     total_weight: u64,
@@ -10,13 +14,16 @@ pub struct WeightedRoundRobinStrategy {
 }
 
 impl WeightedRoundRobinStrategy {
+    /// Weights are divided by their GCD, so 2000:1000 interleaves like 2:1
+    /// instead of sending a thousand requests in a row to each server.
     fn rebuild_cache(&mut self, servers: &[ServerState]) {
+        let gcd = weight_gcd(servers.iter().map(|server| server.weight));
         self.total_weight = 0;
         self.prefix_sums.clear();
         self.prefix_sums.reserve(servers.len());
 
         for server in servers {
-            self.total_weight += server.weight as u64;
+            self.total_weight += u64::from(server.weight / gcd);
             self.prefix_sums.push(self.total_weight);
         }
 
@@ -24,14 +31,37 @@ impl WeightedRoundRobinStrategy {
     }
 }
 
+/// The number of picks before the rotation repeats: the total weight after
+/// GCD reduction. A u64 of u32 weights cannot overflow for any server list
+/// that fits in memory.
+pub(crate) fn cycle_length(weights: impl Iterator<Item = u32> + Clone) -> u64 {
+    let gcd = weight_gcd(weights.clone());
+    weights.map(|weight| u64::from(weight / gcd)).sum()
+}
+
+fn weight_gcd(weights: impl Iterator<Item = u32>) -> u32 {
+    weights
+        .fold(0, |mut a, mut b| {
+            while b != 0 {
+                (a, b) = (b, a % b);
+            }
+            a
+        })
+        .max(1)
+}
+
 impl SelectionStrategy for WeightedRoundRobinStrategy {
     fn select(&mut self, ctx: &mut SelectionContext) -> Selection {
         if self.prefix_sums.is_empty() || self.cached_len != ctx.servers.len() {
             self.rebuild_cache(ctx.servers);
         }
 
-        let target = self.cursor % self.total_weight;
-        self.cursor = (self.cursor + 1) % self.total_weight;
+        let target = self.cursor.checked_rem(self.total_weight).unwrap_or(0);
+        self.cursor = if target + 1 < self.total_weight {
+            target + 1
+        } else {
+            0
+        };
 
         let selected = self
             .prefix_sums
@@ -43,12 +73,36 @@ impl SelectionStrategy for WeightedRoundRobinStrategy {
                 }
             })
             .unwrap_or_else(|idx| idx);
+        tracing::trace!(
+            server = selected,
+            slot = target,
+            total_weight = self.total_weight,
+            "weighted-round-robin pick"
+        );
 
         Selection {
             server_id: selected,
-            score: None,
+            score: Some(target),
         }
     }
+
+    fn score_metric(&self) -> Option<ScoreMetric> {
+        Some(ScoreMetric::RotationSlot)
+    }
+
+    fn needs_completions(&self) -> bool {
+        false
+    }
+
+    #[cfg(feature = "serde")]
+    fn save_state(&self) -> serde_json::Value {
+        super::save_state(self)
+    }
+
+    #[cfg(feature = "serde")]
+    fn restore_state(&mut self, state: serde_json::Value) -> crate::error::Result<()> {
+        super::restore_state(self, state)
+    }
 }
 
 #[cfg(test)]
@@ -151,4 +205,49 @@ mod tests {
             .collect();
         assert_eq!(picks, vec![0, 1]);
     }
+
+    #[test]
+    fn weighted_round_robin_reduces_weights_and_wraps_without_overflow() {
+        let server = |id: usize, weight: u32| ServerState {
+            id,
+            name: format!("s{}", id),
+            base_latency_ms: 10,
+            weight,
+            active_connections: 0,
+            pick_count: 0,
+            in_flight: 0,
+            next_available_ms: 0,
+        };
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let servers = vec![server(0, 2_000_000), server(1, 1_000_000)];
+        let mut strategy = WeightedRoundRobinStrategy::default();
+        let mut ctx = SelectionContext {
+            servers: &servers,
+            time_ms: 0,
+            rng: &mut rng,
+        };
+        let picks: Vec<usize> = (0..6)
+            .map(|_| strategy.select(&mut ctx).server_id)
+            .collect();
+        assert_eq!(picks, vec![0, 0, 1, 0, 0, 1]);
+
+        let servers = vec![server(0, u32::MAX), server(1, u32::MAX - 1)];
+        let mut strategy = WeightedRoundRobinStrategy::default();
+        let mut ctx = SelectionContext {
+            servers: &servers,
+            time_ms: 0,
+            rng: &mut rng,
+        };
+        strategy.select(&mut ctx);
+        strategy.cursor = strategy.total_weight - 1;
+        assert_eq!(strategy.select(&mut ctx).server_id, 1);
+        assert_eq!(strategy.cursor, 0);
+        assert_eq!(strategy.select(&mut ctx).server_id, 0);
+
+        assert_eq!(cycle_length([6, 4, 2].into_iter()), 6);
+        assert_eq!(
+            cycle_length([u32::MAX, u32::MAX - 1].into_iter()),
+            2 * u64::from(u32::MAX) - 1
+        );
+    }
 }
@@ -0,0 +1,130 @@
+use crate::models::{RequestProfile, SimConfig};
+use crate::state::AnalyticComparison;
+
+/// Squared coefficient of variation of service times; the simulator's
+/// service times are deterministic (`base_latency_ms`).
+const SERVICE_SCV: f64 = 0.0;
+
+/// Closed-form M/M/c reference for Poisson workloads over identical servers.
+///
+/// The simulator dispatches to per-server FIFO queues with deterministic
+/// service, so alongside exact M/M/c figures this reports the Allen–Cunneen
+/// approximation `Wq ≈ Wq(M/M/c) · (Ca² + Cs²) / 2`, which is the closer
+/// reference. Returns `None` when the config doesn't map onto the model.
+pub fn mmc_comparison(
+    config: &SimConfig,
+    simulated_wait_ms: f64,
+    simulated_utilization: f64,
+) -> Option<AnalyticComparison> {
+    let RequestProfile::Poisson { rate, .. } = config.requests else {
+        return None;
+    };
+    let service_time_ms = config.servers.first()?.base_latency_ms;
+    if config.servers.iter().any(|server| {
+        server.base_latency_ms != service_time_ms || !server.model.is_deterministic_single_slot()
+    }) {
+        return None;
+    }
+
+    let servers = config.servers.len();
+    let service_time_ms = service_time_ms as f64;
+    let offered_load = rate / 1000.0 * service_time_ms;
+    let utilization = offered_load / servers as f64;
+    let mmc_wait_ms = (utilization < 1.0).then(|| {
+        erlang_c(servers, offered_load) * service_time_ms / (servers as f64 - offered_load)
+    });
+
+    Some(AnalyticComparison {
+        model: "M/M/c".to_string(),
+        servers,
+        arrival_rate_rps: rate,
+        service_time_ms,
+        utilization: round_to(utilization, 4),
+        simulated_utilization: round_to(simulated_utilization, 4),
+        mmc_wait_ms: mmc_wait_ms.map(|wait| round_to(wait, 4)),
+        approx_wait_ms: mmc_wait_ms.map(|wait| round_to(wait * (1.0 + SERVICE_SCV) / 2.0, 4)),
+        simulated_wait_ms: round_to(simulated_wait_ms, 4),
+    })
+}
+
+/// Probability an arrival has to wait in M/M/c, via the numerically stable
+/// Erlang B recurrence.
+pub fn erlang_c(servers: usize, offered_load: f64) -> f64 {
+    let mut erlang_b = 1.0;
+    for k in 1..=servers {
+        erlang_b = offered_load * erlang_b / (k as f64 + offered_load * erlang_b);
+    }
+    let utilization = offered_load / servers as f64;
+    erlang_b / (1.0 - utilization * (1.0 - erlang_b))
+}
+
+fn round_to(value: f64, decimals: i32) -> f64 {
+    let factor = 10_f64.powi(decimals);
+    (value * factor).round() / factor
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        AlgoConfig, EventOrder, RandomnessConfig, RngKind, ServerConfig, ServerModel,
+    };
+
+    fn poisson_config(latencies: &[u64], rate: f64) -> SimConfig {
+        SimConfig {
+            servers: latencies
+                .iter()
+                .enumerate()
+                .map(|(idx, latency)| ServerConfig {
+                    name: format!("s{}", idx),
+                    base_latency_ms: *latency,
+                    weight: 1,
+                    model: ServerModel::default(),
+                })
+                .collect(),
+            requests: RequestProfile::Poisson {
+                rate,
+                duration_ms: 1000,
+            },
+            algo: AlgoConfig::LeastConnections,
+            randomness: RandomnessConfig::Stable,
+            rng: RngKind::Std,
+            event_order: EventOrder::CompletionsFirst,
+            allow_large: false,
+            timeseries_bucket_ms: None,
+            record_timelines: false,
+            slos: Vec::new(),
+            slowest_requests: 0,
+            explain: false,
+            max_virtual_time_ms: None,
+            max_events: None,
+        }
+    }
+
+    #[test]
+    fn erlang_c_matches_known_values() {
+        // M/M/1: P(wait) equals utilization.
+        assert!((erlang_c(1, 0.5) - 0.5).abs() < 1e-12);
+        // Two servers at 1 Erlang: C = 1/3.
+        assert!((erlang_c(2, 1.0) - 1.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn mm1_wait_matches_closed_form() {
+        // λ = 50 rps, S = 10ms → ρ = 0.5, Wq = ρS / (1 - ρ) = 10ms.
+        let comparison = mmc_comparison(&poisson_config(&[10], 50.0), 4.0, 0.5)
+            .expect("single server Poisson maps onto M/M/1");
+        assert_eq!(comparison.utilization, 0.5);
+        assert_eq!(comparison.mmc_wait_ms, Some(10.0));
+        assert_eq!(comparison.approx_wait_ms, Some(5.0));
+    }
+
+    #[test]
+    fn heterogeneous_or_unstable_configs() {
+        assert!(mmc_comparison(&poisson_config(&[10, 20], 50.0), 0.0, 0.0).is_none());
+        let overloaded = mmc_comparison(&poisson_config(&[10, 10], 400.0), 0.0, 1.0)
+            .expect("homogeneous servers map onto M/M/c");
+        assert_eq!(overloaded.utilization, 2.0);
+        assert_eq!(overloaded.mmc_wait_ms, None);
+    }
+}
@@ -0,0 +1,136 @@
+use crate::error::{Error, Result};
+use crate::state::SimulationResult;
+
+/// Limits checked after a run so CI can gate on simulated behavior.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AssertionThresholds {
+    pub max_imbalance: Option<f64>,
+    pub p99_ms: Option<u64>,
+    pub max_avg_wait_ms: Option<u64>,
+}
+
+impl AssertionThresholds {
+    pub fn is_empty(&self) -> bool {
+        self.max_imbalance.is_none() && self.p99_ms.is_none() && self.max_avg_wait_ms.is_none()
+    }
+}
+
+/// Largest relative deviation of any server's request count from an even
+/// split, e.g. 0.1 means some server got 10% more or fewer than its share.
+pub fn request_imbalance(result: &SimulationResult) -> f64 {
+    if result.totals.is_empty() {
+        return 0.0;
+    }
+    let total = result
+        .totals
+        .iter()
+        .map(|summary| summary.requests as f64)
+        .sum::<f64>();
+    if total == 0.0 {
+        return 0.0;
+    }
+    let mean = total / result.totals.len() as f64;
+    result
+        .totals
+        .iter()
+        .map(|summary| (summary.requests as f64 - mean).abs() / mean)
+        .fold(0.0, f64::max)
+}
+
+pub fn check_assertions(result: &SimulationResult, thresholds: &AssertionThresholds) -> Result<()> {
+    let mut violations = Vec::new();
+    if let Some(limit) = thresholds.max_imbalance {
+        let imbalance = request_imbalance(result);
+        if imbalance > limit {
+            violations.push(format!(
+                "request imbalance {:.4} exceeds {}",
+                imbalance, limit
+            ));
+        }
+    }
+    if let Some(limit) = thresholds.p99_ms {
+        if let Some(p99) = result.phase1_metrics.response_time.p99_ms {
+            if p99 > limit {
+                violations.push(format!("p99 {}ms exceeds {}ms", p99, limit));
+            }
+        }
+    }
+    if let Some(limit) = thresholds.max_avg_wait_ms {
+        let avg_wait = result.phase1_metrics.avg_wait_ms;
+        if avg_wait > limit {
+            violations.push(format!("avg wait {}ms exceeds {}ms", avg_wait, limit));
+        }
+    }
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::AssertionFailed(violations.join("; ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::run_simulation;
+    use crate::models::{
+        AlgoConfig, EventOrder, RandomnessConfig, RequestProfile, RngKind, ServerConfig,
+        ServerModel, SimConfig,
+    };
+
+    fn result_for(servers: &[(&str, u64)], requests: usize) -> SimulationResult {
+        let config = SimConfig {
+            servers: servers
+                .iter()
+                .map(|(name, latency)| ServerConfig {
+                    name: name.to_string(),
+                    base_latency_ms: *latency,
+                    weight: 1,
+                    model: ServerModel::default(),
+                })
+                .collect(),
+            requests: RequestProfile::FixedCount(requests),
+            algo: AlgoConfig::LeastResponseTime,
+            randomness: RandomnessConfig::Stable,
+            rng: RngKind::Std,
+            event_order: EventOrder::CompletionsFirst,
+            allow_large: false,
+            timeseries_bucket_ms: None,
+            record_timelines: false,
+            slos: Vec::new(),
+            slowest_requests: 0,
+            explain: false,
+            max_virtual_time_ms: None,
+            max_events: None,
+        };
+        run_simulation(&config).expect("simulation should succeed")
+    }
+
+    #[test]
+    fn passing_thresholds_are_ok() {
+        let result = result_for(&[("a", 10), ("b", 10)], 4);
+        let thresholds = AssertionThresholds {
+            max_imbalance: Some(0.0),
+            p99_ms: Some(100),
+            max_avg_wait_ms: Some(100),
+        };
+        assert!(check_assertions(&result, &thresholds).is_ok());
+    }
+
+    #[test]
+    fn violations_are_reported_together() {
+        let result = result_for(&[("fast", 10), ("slow", 100)], 4);
+        let thresholds = AssertionThresholds {
+            max_imbalance: Some(0.1),
+            p99_ms: Some(20),
+            max_avg_wait_ms: None,
+        };
+        let err = check_assertions(&result, &thresholds).expect_err("should fail");
+        let message = err.to_string();
+        assert!(
+            message.contains("request imbalance 1.0000 exceeds 0.1"),
+            "{}",
+            message
+        );
+        assert!(message.contains("exceeds 20ms"), "{}", message);
+    }
+}
@@ -0,0 +1,200 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::state::{Assignment, ServerState};
+
+/// Receives each assignment as it is made (`SimulationEngine::with_assignment_sink`),
+/// so huge runs can write per-request output without keeping it in memory.
+pub trait AssignmentSink {
+    fn write_assignment(&mut self, assignment: &Assignment, server: &ServerState) -> Result<()>;
+
+    /// Called once the run finishes.
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// One JSON object per line, shaped like the entries of `--format json`'s
+/// `assignments`.
+#[cfg(feature = "serde")]
+pub struct NdjsonAssignmentSink<W: Write> {
+    writer: W,
+}
+
+#[cfg(feature = "serde")]
+impl<W: Write> NdjsonAssignmentSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<W: Write> AssignmentSink for NdjsonAssignmentSink<W> {
+    fn write_assignment(&mut self, assignment: &Assignment, server: &ServerState) -> Result<()> {
+        let line = NdjsonAssignment {
+            request_id: assignment.request_id,
+            server_id: assignment.server_id,
+            server_name: &server.name,
+            arrival_time_ms: assignment.arrival_time_ms,
+            started_at: assignment.started_at,
+            completed_at: assignment.completed_at,
+            queued_ms: assignment.queued_ms,
+            service_ms: assignment.service_ms,
+            score: assignment.score,
+        };
+        serde_json::to_writer(&mut self.writer, &line).map_err(sink_error)?;
+        self.writer.write_all(b"\n").map_err(sink_error)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.writer.flush().map_err(sink_error)
+    }
+}
+
+const CSV_HEADER: &str = "request_id,server_id,server_name,arrival_time_ms,started_at,\
+completed_at,queued_ms,service_ms,score";
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct NdjsonAssignment<'a> {
+    request_id: u64,
+    server_id: usize,
+    server_name: &'a str,
+    arrival_time_ms: u64,
+    started_at: u64,
+    completed_at: u64,
+    queued_ms: u64,
+    service_ms: u64,
+    score: Option<u64>,
+}
+
+/// CSV with a header row; `score` is empty when the strategy reports none.
+pub struct CsvAssignmentSink<W: Write> {
+    writer: W,
+    wrote_header: bool,
+}
+
+impl<W: Write> CsvAssignmentSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            wrote_header: false,
+        }
+    }
+}
+
+impl<W: Write> AssignmentSink for CsvAssignmentSink<W> {
+    fn write_assignment(&mut self, assignment: &Assignment, server: &ServerState) -> Result<()> {
+        if !self.wrote_header {
+            writeln!(self.writer, "{}", CSV_HEADER).map_err(sink_error)?;
+            self.wrote_header = true;
+        }
+        writeln!(
+            self.writer,
+            "{},{},{},{},{},{},{},{},{}",
+            assignment.request_id,
+            assignment.server_id,
+            server.name,
+            assignment.arrival_time_ms,
+            assignment.started_at,
+            assignment.completed_at,
+            assignment.queued_ms,
+            assignment.service_ms,
+            assignment
+                .score
+                .map(|score| score.to_string())
+                .unwrap_or_default()
+        )
+        .map_err(sink_error)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.writer.flush().map_err(sink_error)
+    }
+}
+
+/// Opens `path` as CSV when it ends in `.csv` and as NDJSON otherwise.
+pub fn create_assignment_sink(path: &Path) -> Result<Box<dyn AssignmentSink>> {
+    let file = File::create(path).map_err(|err| {
+        Error::Output(format!(
+            "failed to create assignment stream '{}': {}",
+            path.display(),
+            err
+        ))
+    })?;
+    let writer = BufWriter::new(file);
+    if path.extension().is_some_and(|ext| ext == "csv") {
+        return Ok(Box::new(CsvAssignmentSink::new(writer)));
+    }
+    #[cfg(feature = "serde")]
+    return Ok(Box::new(NdjsonAssignmentSink::new(writer)));
+    #[cfg(not(feature = "serde"))]
+    Err(Error::Output(format!(
+        "cannot stream assignments to '{}': NDJSON needs the `serde` feature (use a .csv path)",
+        path.display()
+    )))
+}
+
+fn sink_error(err: impl std::fmt::Display) -> Error {
+    Error::Output(format!("failed to write assignment stream: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::SimulationEngine;
+    use crate::models::{AlgoConfig, SimConfig};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn streamed_runs_write_every_assignment_and_keep_none() {
+        let config = SimConfig::builder()
+            .server("a", 10)
+            .server("b", 30)
+            .requests(200)
+            .algo(AlgoConfig::RoundRobin)
+            .build()
+            .expect("config should build");
+        let buffer = SharedBuffer::default();
+        let mut engine = SimulationEngine::new(
+            config.clone(),
+            crate::algorithms::build_strategy(config.algo.clone()).expect("built-in"),
+        )
+        .with_assignment_sink(Box::new(CsvAssignmentSink::new(buffer.clone())));
+        let streamed = engine.run(false).expect("simulation should run");
+        let exact = crate::engine::run_simulation(&config).expect("simulation should run");
+
+        let csv = String::from_utf8(buffer.0.lock().unwrap().clone()).expect("CSV is UTF-8");
+        let mut lines = csv.lines();
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with("request_id,server_id,server_name"));
+        assert_eq!(lines.next(), Some("1,0,a,0,0,10,0,10,0"));
+        assert_eq!(csv.lines().count(), 201);
+        assert!(streamed.assignments.is_empty());
+
+        assert_eq!(streamed.global.total_requests, 200);
+        for (streamed, exact) in streamed.totals.iter().zip(&exact.totals) {
+            assert_eq!(streamed.requests, exact.requests);
+            assert_eq!(streamed.avg_response_ms, exact.avg_response_ms);
+            let (streamed_p99, exact_p99) = (streamed.p99_ms.unwrap(), exact.p99_ms.unwrap());
+            assert!(streamed_p99 >= exact_p99 && streamed_p99 - exact_p99 <= exact_p99 / 8);
+        }
+    }
+}
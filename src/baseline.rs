@@ -0,0 +1,147 @@
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+
+/// Scalar metrics compared against a stored `--format json` result, as JSON
+/// pointers into that document.
+const CHECKED_METRICS: [&str; 7] = [
+    "/global/makespan_ms",
+    "/global/throughput_rps",
+    "/global/avg_concurrency",
+    "/phase1_metrics/response_time/p95_ms",
+    "/phase1_metrics/response_time/p99_ms",
+    "/phase1_metrics/avg_wait_ms",
+    "/phase1_metrics/jain_fairness",
+];
+const CHECKED_SERVER_FIELDS: [&str; 2] = ["requests", "avg_response_ms"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetricComparison {
+    pub metric: String,
+    pub baseline: Option<f64>,
+    pub actual: Option<f64>,
+    pub within_tolerance: bool,
+}
+
+pub fn parse_baseline(contents: &str) -> Result<Value> {
+    serde_json::from_str(contents).map_err(|err| Error::json("baseline JSON", None, err))
+}
+
+/// Compares `actual` to `baseline` with a relative `tolerance` (0.05 = 5%).
+/// Both documents use the JSON formatter's shape; servers are matched by name.
+pub fn compare_to_baseline(
+    actual: &Value,
+    baseline: &Value,
+    tolerance: f64,
+) -> Vec<MetricComparison> {
+    let mut comparisons = CHECKED_METRICS
+        .iter()
+        .map(|pointer| {
+            compare_metric(
+                pointer.trim_start_matches('/').replace('/', "."),
+                baseline.pointer(pointer),
+                actual.pointer(pointer),
+                tolerance,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    for server in servers(actual).chain(servers(baseline)) {
+        let name = server.get("name").and_then(Value::as_str).unwrap_or("");
+        if comparisons
+            .iter()
+            .any(|comparison| comparison.metric.starts_with(&format!("totals.{}.", name)))
+        {
+            continue;
+        }
+        let baseline_server = find_server(baseline, name);
+        let actual_server = find_server(actual, name);
+        for field in CHECKED_SERVER_FIELDS {
+            comparisons.push(compare_metric(
+                format!("totals.{}.{}", name, field),
+                baseline_server.and_then(|server| server.get(field)),
+                actual_server.and_then(|server| server.get(field)),
+                tolerance,
+            ));
+        }
+    }
+    comparisons
+}
+
+fn servers(document: &Value) -> impl Iterator<Item = &Value> {
+    document
+        .get("totals")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+}
+
+fn find_server<'a>(document: &'a Value, name: &str) -> Option<&'a Value> {
+    servers(document).find(|server| server.get("name").and_then(Value::as_str) == Some(name))
+}
+
+fn compare_metric(
+    metric: String,
+    baseline: Option<&Value>,
+    actual: Option<&Value>,
+    tolerance: f64,
+) -> MetricComparison {
+    let baseline = baseline.and_then(Value::as_f64);
+    let actual = actual.and_then(Value::as_f64);
+    let within_tolerance = match (baseline, actual) {
+        (Some(expected), Some(value)) => {
+            (value - expected).abs() <= tolerance * expected.abs() + f64::EPSILON
+        }
+        (None, None) => true,
+        _ => false,
+    };
+    MetricComparison {
+        metric,
+        baseline,
+        actual,
+        within_tolerance,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn document(p99: u64, requests: [u64; 2]) -> Value {
+        json!({
+            "totals": [
+                { "name": "a", "requests": requests[0], "avg_response_ms": 10 },
+                { "name": "b", "requests": requests[1], "avg_response_ms": 20 }
+            ],
+            "global": { "makespan_ms": 100, "throughput_rps": 50.0, "avg_concurrency": 1.5 },
+            "phase1_metrics": {
+                "response_time": { "p95_ms": 20, "p99_ms": p99 },
+                "avg_wait_ms": 3,
+                "jain_fairness": 0.9
+            }
+        })
+    }
+
+    #[test]
+    fn identical_documents_are_within_tolerance() {
+        let baseline = document(30, [5, 5]);
+        let comparisons = compare_to_baseline(&baseline, &baseline, 0.0);
+        assert_eq!(comparisons.len(), 11);
+        assert!(comparisons
+            .iter()
+            .all(|comparison| comparison.within_tolerance));
+    }
+
+    #[test]
+    fn deviations_beyond_tolerance_are_flagged() {
+        let baseline = document(30, [5, 5]);
+        let actual = document(32, [6, 4]);
+        let failing = compare_to_baseline(&actual, &baseline, 0.1)
+            .into_iter()
+            .filter(|comparison| !comparison.within_tolerance)
+            .map(|comparison| comparison.metric)
+            .collect::<Vec<_>>();
+        assert_eq!(failing, vec!["totals.a.requests", "totals.b.requests"]);
+    }
+}
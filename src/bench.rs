@@ -0,0 +1,95 @@
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::time::Instant;
+
+use crate::engine;
+use crate::error::Result;
+use crate::models::{
+    AlgoConfig, EventOrder, RequestProfile, RngKind, ServerConfig, ServerModel, SimConfig,
+};
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct BenchRow {
+    pub algo: String,
+    pub servers: usize,
+    pub requests: usize,
+    pub events: u64,
+    pub best_ms: f64,
+    pub events_per_sec: f64,
+}
+
+/// A fixed-count workload over `servers` heterogeneous servers (latencies
+/// 5..=50ms, weights 1..=3), so every algorithm sees the same input.
+pub fn synthetic_config(algo: AlgoConfig, servers: usize, requests: usize) -> SimConfig {
+    SimConfig {
+        servers: (0..servers)
+            .map(|idx| ServerConfig {
+                name: format!("s{}", idx + 1),
+                base_latency_ms: 5 + (idx as u64 % 10) * 5,
+                weight: 1 + (idx as u32 % 3),
+                model: ServerModel::default(),
+            })
+            .collect(),
+        requests: RequestProfile::FixedCount(requests),
+        algo,
+        randomness: Default::default(),
+        rng: RngKind::Std,
+        event_order: EventOrder::CompletionsFirst,
+        allow_large: false,
+        timeseries_bucket_ms: None,
+        record_timelines: false,
+        slos: Vec::new(),
+        slowest_requests: 0,
+        explain: false,
+        max_virtual_time_ms: None,
+        max_events: None,
+    }
+}
+
+/// Times `iterations` summary-only runs of `config` and keeps the fastest.
+/// Each request is two engine events: its arrival and its completion.
+pub fn bench_config(config: &SimConfig, iterations: usize) -> Result<BenchRow> {
+    let mut best = f64::INFINITY;
+    let mut events = 0;
+    for _ in 0..iterations.max(1) {
+        let started = Instant::now();
+        let result = engine::run_simulation_summary(config)?;
+        best = best.min(started.elapsed().as_secs_f64());
+        events = result.global.total_requests * 2;
+    }
+    let requests = match config.requests {
+        RequestProfile::FixedCount(count) => count,
+        RequestProfile::Burst { count, .. } => count,
+        RequestProfile::Poisson { .. } => (events / 2) as usize,
+    };
+    Ok(BenchRow {
+        algo: config.algo.to_string(),
+        servers: config.servers.len(),
+        requests,
+        events,
+        best_ms: best * 1000.0,
+        events_per_sec: if best > 0.0 {
+            events as f64 / best
+        } else {
+            0.0
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bench_counts_arrival_and_completion_events() {
+        let config = synthetic_config(AlgoConfig::LeastConnections, 4, 50);
+        let row = bench_config(&config, 2).expect("bench should run");
+
+        assert_eq!(row.algo, "least-connections");
+        assert_eq!(row.servers, 4);
+        assert_eq!(row.requests, 50);
+        assert_eq!(row.events, 100);
+        assert!(row.events_per_sec > 0.0);
+    }
+}
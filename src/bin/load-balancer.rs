@@ -1,7 +1,50 @@
-use lb_sim::config::{self, format_config, Command, FormatArg, RunArgs};
-use lb_sim::engine;
-use lb_sim::error::Result;
-use lb_sim::output::{Formatter, HumanFormatter, JsonFormatter, SummaryFormatter};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use lb_sim::algorithms::{self, build_strategy};
+use lb_sim::assertions::check_assertions;
+use lb_sim::assignment_sink::create_assignment_sink;
+use lb_sim::baseline;
+use lb_sim::bench;
+use lb_sim::calibrate;
+use lb_sim::chart;
+use lb_sim::chrome_trace::to_chrome_trace;
+use lb_sim::clock::RealTimeClock;
+use lb_sim::config::{
+    self, config_schema, format_config, AlgoArg, BenchArgs, CalibrateArgs, CheckArgs, Command,
+    ExportArgs, ExportWorkloadArgs, FormatArg, ImportEnvoyArgs, RecommendArgs, RenderArgs,
+    RenderFormatArg, ReplayArgs, RunArgs, ServeArgs, ValidateArgs,
+};
+use lb_sim::engine::{self, EngineRng, PcgRng, SimulationEngine, XoshiroRng};
+use lb_sim::envoy;
+use lb_sim::error::{Error, Result};
+use lb_sim::event_log::JsonlEventLog;
+use lb_sim::export;
+use lb_sim::loadtest;
+use lb_sim::logging;
+use lb_sim::models::{AlgoConfig, RngKind, SimConfig};
+use lb_sim::otel::{self, OtlpTarget};
+use lb_sim::output::{
+    self, baseline_comparison_to_text, bench_report_to_json, bench_report_to_text,
+    json_output_schema, metric_diff_to_text, recommendation_to_json, recommendation_to_text,
+    replication_report_to_json, replication_report_to_text, scenario_report_to_json,
+    scenario_report_to_text, seed_sweep_report_to_json, seed_sweep_report_to_text, summary_line,
+    timelines_to_csv, CsvFormatter, Formatter, HumanFormatter, JsonFormatter, MarkdownFormatter,
+    OutputOptions, SummaryFormatter,
+};
+use lb_sim::prompt;
+use lb_sim::recommend::{self, RecommendCriteria};
+use lb_sim::replay;
+use lb_sim::replication;
+use lb_sim::scenario;
+use lb_sim::state::SimulationResult;
+use lb_sim::step::StepDebugger;
+use lb_sim::validation;
+use rand::{RngCore, SeedableRng};
+
+const WATCH_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
 
 fn main() {
     if let Err(err) = run() {
@@ -11,48 +54,702 @@ fn main() {
 }
 
 fn run() -> Result<()> {
-    let command = config::parse_command()?;
+    let (command, log) = config::parse_command()?;
+    logging::init(&log)?;
 
     match command {
         Command::Run(run_args) => run_simulation(run_args),
         Command::ListAlgorithms => list_algorithms(),
+        Command::ListFormats => {
+            print!("{}", output::formats_to_text(output::FORMATS));
+            Ok(())
+        }
         Command::ShowConfig(run_args) => show_config(run_args),
+        Command::Check(check_args) => check_baseline(check_args),
+        Command::Validate(validate_args) => validate_config(validate_args),
+        Command::Render(render_args) => render_result(render_args),
+        Command::Export(export_args) => export_weights(export_args),
+        Command::ExportWorkload(workload_args) => export_workload(workload_args),
+        Command::Replay(replay_args) => replay_trace(replay_args),
+        Command::Bench(bench_args) => run_bench(bench_args),
+        Command::Recommend(recommend_args) => run_recommend(recommend_args),
+        Command::Serve(serve_args) => serve(serve_args),
+        Command::Calibrate(calibrate_args) => run_calibrate(calibrate_args),
+        Command::ImportEnvoy(import_args) => import_envoy(import_args),
+        Command::Completions { shell } => {
+            config::write_completions(shell, &mut std::io::stdout());
+            Ok(())
+        }
+        Command::Schema(args) => {
+            let schema = if args.config {
+                config_schema()
+            } else {
+                json_output_schema()
+            };
+            println!("{}", schema);
+            Ok(())
+        }
     }
 }
 
-fn run_simulation(run_args: RunArgs) -> Result<()> {
+fn run_calibrate(args: CalibrateArgs) -> Result<()> {
+    let timeout = std::time::Duration::from_millis(args.timeout_ms);
+    let calibrations = match (&args.prometheus, &args.targets) {
+        (Some(base_url), _) => {
+            let query = args.query.as_deref().unwrap_or_default();
+            let histogram = calibrate::query_prometheus(base_url, query, timeout)?;
+            let rates = args
+                .rate_query
+                .as_deref()
+                .map(|query| calibrate::query_prometheus(base_url, query, timeout))
+                .transpose()?;
+            calibrate::calibrate_prometheus(&histogram, rates.as_deref(), &args.server_label)?
+        }
+        (None, Some(targets)) => {
+            let source = targets.display().to_string();
+            let contents = std::fs::read_to_string(targets).map_err(|err| Error::Io {
+                what: "targets",
+                path: targets.clone(),
+                source: err,
+            })?;
+            calibrate::parse_targets(&source, &contents)?
+                .iter()
+                .map(|target| {
+                    let samples = calibrate::probe(&target.url, args.probes as usize, timeout)?;
+                    Ok(calibrate::calibrate(target, &samples))
+                })
+                .collect::<Result<Vec<_>>>()?
+        }
+        (None, None) => unreachable!("clap requires --targets or --prometheus"),
+    };
+    match args.output.as_ref() {
+        Some(path) if path.extension().is_some_and(|ext| ext == "json") => write_output_file(
+            path,
+            &calibrate::calibrations_to_json(&calibrations)?,
+            "servers",
+        ),
+        Some(path) => write_output_file(
+            path,
+            &calibrate::calibrations_to_toml(&calibrations)?,
+            "servers",
+        ),
+        None => {
+            print!("{}", calibrate::calibrations_to_toml(&calibrations)?);
+            Ok(())
+        }
+    }
+}
+
+fn import_envoy(args: ImportEnvoyArgs) -> Result<()> {
+    let contents = std::fs::read_to_string(&args.input).map_err(|err| Error::Io {
+        what: "Envoy config",
+        path: args.input.clone(),
+        source: err,
+    })?;
+    let import = envoy::import_envoy(&contents, args.cluster.as_deref(), args.latency_ms)?;
+    for note in &import.notes {
+        tracing::warn!(cluster = %import.cluster, "{}", note);
+    }
+    match args.output.as_ref() {
+        Some(path) if path.extension().is_some_and(|ext| ext == "json") => {
+            write_output_file(path, &envoy::import_to_json(&import)?, "config")
+        }
+        Some(path) => write_output_file(path, &envoy::import_to_toml(&import)?, "config"),
+        None => {
+            print!("{}", envoy::import_to_toml(&import)?);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "grpc")]
+fn serve(args: ServeArgs) -> Result<()> {
+    lb_sim::grpc::serve(args.addr)
+}
+
+#[cfg(not(feature = "grpc"))]
+fn serve(args: ServeArgs) -> Result<()> {
+    Err(Error::Grpc(format!(
+        "cannot serve on {}: lb-sim was built without the `grpc` feature",
+        args.addr
+    )))
+}
+
+fn run_simulation(mut run_args: RunArgs) -> Result<()> {
+    if prompt::should_prompt(&run_args) {
+        let stdin = std::io::stdin();
+        prompt::prompt_missing(&mut run_args, stdin.lock(), std::io::stderr())?;
+    }
+    if run_args.dry_run {
+        return dry_run(run_args);
+    }
+    if let Some(count) = run_args.replications {
+        return run_replications(run_args, count);
+    }
+    if let Some(range) = run_args.seed_range.clone() {
+        return run_seed_sweep(run_args, &range);
+    }
+    if let Some(path) = run_args.scenario.clone() {
+        return run_scenario(run_args, &path);
+    }
+    if run_args.watch {
+        return watch_simulation(run_args);
+    }
+    let chart_path = run_args.chart.clone();
+    let timelines_csv = run_args.timelines_csv.clone();
+    let chrome_trace_path = run_args.chrome_trace.clone();
+    let otel_endpoint = run_args.otel_endpoint.clone();
+    let otel_file = run_args.otel_file.clone();
+    let sink = OutputSink::from_run_args(&run_args);
+    let thresholds = config::assertion_thresholds_from_run_args(&run_args);
+    let mut trace = open_trace(&run_args)?;
+    let record_config = run_args.trace_file.is_some();
+    let step = run_args.step;
+    let real_time = run_args.real_time;
+    let event_log = run_args.event_log.clone();
+    let stream_assignments = run_args.stream_assignments.clone();
+    let stats_memory = run_args.stats_memory;
+    let histogram_percentiles = run_args.histogram_percentiles;
+    let sample_assignments = run_args.sample_assignments;
+    let verify_determinism = run_args.verify_determinism;
+    let options = config::output_options_from_run_args(&run_args);
     let (config, format) = config::build_config_from_run_args(run_args)?;
-    let result = match format {
-        FormatArg::Summary => engine::run_simulation_summary(&config)?,
-        _ => engine::run_simulation(&config)?,
+    if verify_determinism {
+        let events = engine::verify_determinism(&config)?;
+        if !sink.quiet {
+            eprintln!(
+                "determinism verified: {} events identical across two runs",
+                events
+            );
+        }
+    }
+    if let (Some(writer), true) = (trace.as_mut(), record_config) {
+        replay::write_config_header(writer.as_mut(), &config)?;
+    }
+    let store_assignments = stream_assignments.is_none()
+        && (format != FormatArg::Summary || options.heatmap_bucket_ms.is_some())
+        || chart_path.is_some()
+        || chrome_trace_path.is_some()
+        || otel_endpoint.is_some()
+        || otel_file.is_some();
+    let setup = EngineSetup {
+        real_time,
+        event_log,
+        stream_assignments,
+        histogram_percentiles,
+        sample_assignments,
+        trace,
+        step,
+        store_assignments,
+        stats_memory,
+    };
+    let result = match config.rng {
+        RngKind::Std => run_engine::<EngineRng>(&config, setup)?,
+        RngKind::Small => run_engine::<PcgRng>(&config, setup)?,
+        RngKind::Xoshiro => run_engine::<XoshiroRng>(&config, setup)?,
     };
+    if let Some(path) = chart_path.as_ref() {
+        chart::render_chart(&result, path)?;
+    }
+    if let Some(path) = chrome_trace_path.as_ref() {
+        write_output_file(path, &to_chrome_trace(&result), "Chrome trace")?;
+    }
+    if let Some(path) = otel_file.as_ref() {
+        otel::export_spans(&result, OtlpTarget::File(path))?;
+    }
+    if let Some(endpoint) = otel_endpoint.as_deref() {
+        otel::export_spans(&result, OtlpTarget::Endpoint(endpoint))?;
+    }
+    if let (Some(path), Some(timelines)) = (timelines_csv.as_ref(), result.timelines.as_ref()) {
+        write_output_file(path, &timelines_to_csv(timelines), "timelines")?;
+    }
 
-    let formatter = formatter_for(&format);
-    let output = formatter.write(&result);
-    print!("{}", output);
+    let formatter = formatter_for(&format, options);
+    sink.emit_result(formatter.as_ref(), &result)?;
+    check_assertions(&result, &thresholds)?;
+    if sink.summary_line {
+        println!("{}", summary_line(&result));
+    }
+
+    Ok(())
+}
+
+/// How `run` wires up the engine beyond the config itself.
+struct EngineSetup {
+    real_time: Option<f64>,
+    event_log: Option<PathBuf>,
+    stream_assignments: Option<PathBuf>,
+    histogram_percentiles: bool,
+    sample_assignments: Option<usize>,
+    trace: Option<Box<dyn Write>>,
+    step: bool,
+    store_assignments: bool,
+    stats_memory: bool,
+}
+
+fn run_engine<R: RngCore + SeedableRng>(
+    config: &SimConfig,
+    setup: EngineSetup,
+) -> Result<SimulationResult> {
+    let strategy = build_strategy(config.algo.clone())?;
+    let mut engine = SimulationEngine::<R>::seeded(config.clone(), strategy);
+    if let Some(speed) = setup.real_time {
+        engine = engine.with_clock(Box::new(RealTimeClock::new(speed)));
+    }
+    if let Some(path) = setup.event_log.as_ref() {
+        engine = engine.with_event_log(Box::new(JsonlEventLog::create(path)?));
+    }
+    if let Some(path) = setup.stream_assignments.as_ref() {
+        engine = engine.with_assignment_sink(create_assignment_sink(path)?);
+    }
+    if setup.histogram_percentiles {
+        engine = engine.with_histogram_percentiles();
+    }
+    if let Some(limit) = setup.sample_assignments {
+        engine = engine.with_assignment_sample(limit);
+    }
+    engine = match (setup.trace, setup.step) {
+        (trace, true) => {
+            let stdin = std::io::stdin();
+            let debugger = StepDebugger::new(stdin.lock(), std::io::stderr());
+            let trace = trace.unwrap_or_else(|| Box::new(std::io::stderr()));
+            engine.with_trace(trace).with_step_hook(Box::new(debugger))
+        }
+        (Some(writer), false) => engine.with_trace(writer),
+        (None, false) => engine,
+    };
+    let result = engine.run(setup.store_assignments)?;
+    if setup.stats_memory {
+        let memory = engine.memory_stats();
+        eprintln!(
+            "memory: peak_queued_events={} stored_assignments={} retained_samples={}",
+            memory.peak_queued_events, memory.stored_assignments, memory.retained_samples
+        );
+    }
+    Ok(result)
+}
+
+struct OutputSink {
+    path: Option<PathBuf>,
+    quiet: bool,
+    summary_line: bool,
+}
+
+impl OutputSink {
+    fn from_run_args(run_args: &RunArgs) -> Self {
+        Self {
+            path: run_args.output.clone(),
+            quiet: run_args.quiet,
+            summary_line: run_args.summary_line,
+        }
+    }
+
+    fn emit(&self, contents: &str) -> Result<()> {
+        if let Some(path) = self.path.as_ref() {
+            return write_output_file(path, contents, "output");
+        }
+        if !self.quiet {
+            print!("{}", contents);
+        }
+        Ok(())
+    }
+
+    /// Streams `formatter`'s output instead of rendering it to a string first.
+    fn emit_result(&self, formatter: &dyn Formatter, result: &SimulationResult) -> Result<()> {
+        match self.path.as_ref() {
+            Some(path) => write_formatted(formatter, result, Some(path)),
+            None if self.quiet => Ok(()),
+            None => write_formatted(formatter, result, None),
+        }
+    }
+}
+
+/// Writes `formatter`'s output for `result` to `path`, or stdout without one.
+fn write_formatted(
+    formatter: &dyn Formatter,
+    result: &SimulationResult,
+    path: Option<&Path>,
+) -> Result<()> {
+    let failed = |err: std::io::Error| match path {
+        Some(path) => Error::Output(format!(
+            "failed to write output '{}': {}",
+            path.display(),
+            err
+        )),
+        None => Error::Output(format!("failed to write output: {}", err)),
+    };
+    let mut writer: Box<dyn Write> = match path {
+        Some(path) => Box::new(BufWriter::new(File::create(path).map_err(failed)?)),
+        None => Box::new(BufWriter::new(std::io::stdout().lock())),
+    };
+    formatter.write_to(result, &mut writer).map_err(failed)?;
+    writer.flush().map_err(failed)
+}
+
+fn run_replications(run_args: RunArgs, count: usize) -> Result<()> {
+    if count == 0 {
+        return Err(Error::InvalidReplications);
+    }
+    reject_per_run_outputs(&run_args, "--replications")?;
+    let sink = OutputSink::from_run_args(&run_args);
+    let (config, format) = config::build_config_from_run_args(run_args)?;
+    let seeds = replication::derive_seeds(config.randomness.resolve().seed().unwrap_or(0), count);
+    let results = engine::run_replications(&config, &seeds)?;
+    let report = replication::summarize_replications(&results, &seeds);
+    let output = match format {
+        FormatArg::Json => replication_report_to_json(&report),
+        FormatArg::Human | FormatArg::Summary => replication_report_to_text(&report),
+    };
+    sink.emit(&format!("{}\n", output.trim_end()))?;
+    if sink.summary_line {
+        println!("ok replications={}", report.replications);
+    }
+    Ok(())
+}
+
+fn dry_run(run_args: RunArgs) -> Result<()> {
+    let (config, format) = config::build_config_from_run_args(run_args)?;
+    let problems = validation::config_problems(&config);
+    if !problems.is_empty() {
+        for problem in &problems {
+            eprintln!("{}", problem);
+        }
+        return Err(Error::InvalidConfig(problems.len()));
+    }
+    print!("{}", config::format_resolved_config(&config, &format)?);
+    Ok(())
+}
 
+fn run_seed_sweep(run_args: RunArgs, range: &str) -> Result<()> {
+    reject_per_run_outputs(&run_args, "--seed-range")?;
+    let seeds = config::parse_seed_range(range)?;
+    let sink = OutputSink::from_run_args(&run_args);
+    let (config, format) = config::build_config_from_run_args(run_args)?;
+    let results = engine::run_replications(&config, &seeds)?;
+    let report = replication::summarize_seed_sweep(&results, &seeds);
+    let output = match format {
+        FormatArg::Json => seed_sweep_report_to_json(&report),
+        FormatArg::Human | FormatArg::Summary => seed_sweep_report_to_text(&report),
+    };
+    sink.emit(&format!("{}\n", output.trim_end()))?;
+    if sink.summary_line {
+        println!("ok seeds={}", report.runs);
+    }
+    Ok(())
+}
+
+fn reject_per_run_outputs(run_args: &RunArgs, flag: &str) -> Result<()> {
+    if run_args.trace
+        || run_args.trace_file.is_some()
+        || run_args.chart.is_some()
+        || run_args.chrome_trace.is_some()
+        || run_args.otel_endpoint.is_some()
+        || run_args.otel_file.is_some()
+        || run_args.timelines_csv.is_some()
+        || !config::assertion_thresholds_from_run_args(run_args).is_empty()
+    {
+        return Err(Error::Output(format!(
+            "{} cannot be combined with per-run exports, tracing or assertions",
+            flag
+        )));
+    }
+    Ok(())
+}
+
+fn run_scenario(run_args: RunArgs, path: &Path) -> Result<()> {
+    reject_per_run_outputs(&run_args, "--scenario")?;
+    let sink = OutputSink::from_run_args(&run_args);
+    let format = config::format_arg_from_run_args(&run_args);
+    let runs = scenario::load_scenario(path, run_args.lenient)?;
+    if run_args.deterministic {
+        for run in &runs {
+            config::check_deterministic(&run.config, None, false)?;
+        }
+    }
+    let configs = runs
+        .iter()
+        .map(|run| run.config.clone())
+        .collect::<Vec<_>>();
+    let results = runs
+        .into_iter()
+        .map(|run| run.name)
+        .zip(engine::run_summaries(&configs)?)
+        .collect::<Vec<_>>();
+    let report = scenario::summarize_scenario(&results);
+    let output = match format {
+        FormatArg::Json => scenario_report_to_json(&report),
+        FormatArg::Human | FormatArg::Summary => scenario_report_to_text(&report),
+    };
+    sink.emit(&format!("{}\n", output.trim_end()))?;
+    if sink.summary_line {
+        println!("ok runs={}", report.runs.len());
+    }
     Ok(())
 }
 
+/// Polls the config file's modification time and reruns on change. Errors from
+/// a half-saved file are reported and the previous result is kept.
+fn watch_simulation(run_args: RunArgs) -> Result<()> {
+    let path = run_args
+        .config
+        .clone()
+        .ok_or_else(|| Error::Cli("--watch requires --config".to_string()))?;
+    let options = config::output_options_from_run_args(&run_args);
+    let mut previous: Option<SimulationResult> = None;
+    let mut last_modified = None;
+    loop {
+        let modified = std::fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        if modified != last_modified {
+            last_modified = modified;
+            let outcome = config::build_config_from_run_args(run_args.clone()).and_then(
+                |(config, format)| {
+                    engine::run_simulation_summary(&config).map(|result| (result, format))
+                },
+            );
+            match outcome {
+                Ok((result, format)) => {
+                    match previous.as_ref() {
+                        Some(previous) => {
+                            println!("Reloaded {}:", path.display());
+                            print!("{}", metric_diff_to_text(previous, &result));
+                        }
+                        None => {
+                            print!("{}", formatter_for(&format, options.clone()).write(&result))
+                        }
+                    }
+                    previous = Some(result);
+                }
+                Err(err) => eprintln!("Error: {}", err),
+            }
+            std::io::stdout().flush().ok();
+        }
+        std::thread::sleep(WATCH_POLL_INTERVAL);
+    }
+}
+
+fn check_baseline(check_args: CheckArgs) -> Result<()> {
+    let contents = std::fs::read_to_string(&check_args.baseline).map_err(|err| Error::Io {
+        what: "baseline",
+        path: check_args.baseline.clone(),
+        source: err,
+    })?;
+    let baseline = baseline::parse_baseline(&contents)?;
+    let (config, _) = config::build_config_from_run_args(check_args.run)?;
+    let result = engine::run_simulation_summary(&config)?;
+    let actual = serde_json::to_value(&result)
+        .map_err(|err| Error::Output(format!("failed to serialize result: {}", err)))?;
+    let comparisons = baseline::compare_to_baseline(&actual, &baseline, check_args.tolerance);
+    print!("{}", baseline_comparison_to_text(&comparisons));
+    let failures = comparisons
+        .iter()
+        .filter(|comparison| !comparison.within_tolerance)
+        .count();
+    if failures > 0 {
+        return Err(Error::BaselineMismatch(failures));
+    }
+    Ok(())
+}
+
+fn export_weights(export_args: ExportArgs) -> Result<()> {
+    let contents = std::fs::read_to_string(&export_args.input).map_err(|err| Error::Io {
+        what: "result",
+        path: export_args.input.clone(),
+        source: err,
+    })?;
+    let result = output::parse_json_result(&contents)?;
+    let snippet = export::export_snippet(
+        &result,
+        export_args.format.into(),
+        &export_args.name,
+        export_args.port,
+    );
+    match export_args.output.as_ref() {
+        Some(path) => write_output_file(path, &snippet, "export"),
+        None => {
+            print!("{}", snippet);
+            Ok(())
+        }
+    }
+}
+
+fn export_workload(workload_args: ExportWorkloadArgs) -> Result<()> {
+    let mut run_args = workload_args.run;
+    reject_per_run_outputs(&run_args, "export-workload")?;
+    if run_args.config.is_none() && run_args.algo.is_none() {
+        run_args.algo = Some(AlgoConfig::RoundRobin);
+    }
+    let sink = OutputSink::from_run_args(&run_args);
+    let (config, _) = config::build_config_from_run_args(run_args)?;
+    let arrivals = engine::build_config_requests(&config)?
+        .iter()
+        .map(|request| request.arrival_time_ms)
+        .collect::<Vec<_>>();
+    sink.emit(&loadtest::export_workload(
+        &arrivals,
+        workload_args.tool.into(),
+        &workload_args.url,
+        workload_args.bucket_ms,
+    ))
+}
+
+fn render_result(render_args: RenderArgs) -> Result<()> {
+    let contents = std::fs::read_to_string(&render_args.input).map_err(|err| Error::Io {
+        what: "result",
+        path: render_args.input.clone(),
+        source: err,
+    })?;
+    let result = output::parse_json_result(&contents)?;
+    let options = config::output_options(&render_args.display);
+    let formatter: Box<dyn Formatter> = match render_args.format {
+        RenderFormatArg::Human => Box::new(HumanFormatter { options }),
+        RenderFormatArg::Summary => Box::new(SummaryFormatter { options }),
+        RenderFormatArg::Json => Box::new(JsonFormatter { options }),
+        RenderFormatArg::Csv => Box::new(CsvFormatter { options }),
+        RenderFormatArg::Markdown => Box::new(MarkdownFormatter { options }),
+        RenderFormatArg::Chart => {
+            let path = render_args
+                .output
+                .as_ref()
+                .expect("clap requires --output for chart");
+            return chart::render_chart(&result, path);
+        }
+    };
+    write_formatted(formatter.as_ref(), &result, render_args.output.as_deref())
+}
+
+fn run_recommend(recommend_args: RecommendArgs) -> Result<()> {
+    let mut run_args = recommend_args.run;
+    if run_args.algo.is_some() {
+        return Err(Error::Cli(
+            "recommend runs every algorithm; drop --algo".to_string(),
+        ));
+    }
+    reject_per_run_outputs(&run_args, "recommend")?;
+    if run_args.config.is_none() {
+        run_args.algo = Some(AlgoConfig::RoundRobin);
+    }
+    let sink = OutputSink::from_run_args(&run_args);
+    let (config, format) = config::build_config_from_run_args(run_args)?;
+    let algos = algorithms::available();
+    let criteria = RecommendCriteria {
+        rank_by: recommend_args.rank_by.into(),
+        min_fairness: recommend_args.min_fairness,
+        max_p99_ms: recommend_args.max_p99_ms,
+    };
+    let report = recommend::recommend(&config, &algos, &criteria)?;
+    let output = match format {
+        FormatArg::Json => recommendation_to_json(&report),
+        FormatArg::Human | FormatArg::Summary => recommendation_to_text(&report),
+    };
+    sink.emit(&format!("{}\n", output.trim_end()))
+}
+
+fn run_bench(bench_args: BenchArgs) -> Result<()> {
+    let algos = if bench_args.algo.is_empty() {
+        AlgoArg::value_variants().to_vec()
+    } else {
+        bench_args.algo
+    };
+    if cfg!(debug_assertions) {
+        eprintln!("note: this is a debug build; use --release for representative numbers");
+    }
+    let mut rows = Vec::new();
+    for &servers in &bench_args.server_counts {
+        for algo in &algos {
+            let config = bench::synthetic_config(algo.clone().into(), servers, bench_args.requests);
+            rows.push(bench::bench_config(&config, bench_args.iterations)?);
+        }
+    }
+    match bench_args.format {
+        FormatArg::Json => println!("{}", bench_report_to_json(&rows)),
+        FormatArg::Human | FormatArg::Summary => print!("{}", bench_report_to_text(&rows)),
+    }
+    Ok(())
+}
+
+fn replay_trace(replay_args: ReplayArgs) -> Result<()> {
+    let contents = std::fs::read_to_string(&replay_args.trace_file).map_err(|err| Error::Io {
+        what: "event log",
+        path: replay_args.trace_file.clone(),
+        source: err,
+    })?;
+    let report = replay::replay_event_log(&contents)?;
+    println!(
+        "{}: replay matches ({} events)",
+        replay_args.trace_file.display(),
+        report.events
+    );
+    Ok(())
+}
+
+fn validate_config(validate_args: ValidateArgs) -> Result<()> {
+    let config = config::load_config_profile(
+        &validate_args.config,
+        validate_args.profile.as_deref(),
+        validate_args.lenient,
+    )?;
+    let problems = validation::config_problems(&config);
+    if problems.is_empty() {
+        println!("{}: ok", validate_args.config.display());
+        return Ok(());
+    }
+    for problem in &problems {
+        println!("{}: {}", validate_args.config.display(), problem);
+    }
+    Err(Error::InvalidConfig(problems.len()))
+}
+
+fn write_output_file(path: &Path, contents: &str, label: &str) -> Result<()> {
+    std::fs::write(path, contents).map_err(|err| {
+        Error::Output(format!(
+            "failed to write {} '{}': {}",
+            label,
+            path.display(),
+            err
+        ))
+    })
+}
+
+fn open_trace(run_args: &RunArgs) -> Result<Option<Box<dyn Write>>> {
+    if let Some(path) = run_args.trace_file.as_ref() {
+        let file = File::create(path).map_err(|err| {
+            Error::Output(format!(
+                "failed to create trace file '{}': {}",
+                path.display(),
+                err
+            ))
+        })?;
+        return Ok(Some(Box::new(BufWriter::new(file))));
+    }
+    if run_args.trace {
+        return Ok(Some(Box::new(BufWriter::new(std::io::stderr()))));
+    }
+    Ok(None)
+}
+
 fn list_algorithms() -> Result<()> {
-    println!("round-robin");
-    println!("weighted-round-robin");
-    println!("least-connections");
-    println!("least-response-time");
+    for algo in algorithms::available() {
+        println!("{}", algo);
+    }
     Ok(())
 }
 
 fn show_config(run_args: RunArgs) -> Result<()> {
-    let (config, _) = config::build_config_from_run_args(run_args)?;
-    let output = format_config(&config);
+    let (config, format) = config::build_config_from_run_args(run_args)?;
+    let output = match format {
+        FormatArg::Json => config::config_to_json(&config)?,
+        FormatArg::Human | FormatArg::Summary => format_config(&config),
+    };
     print!("{}", output);
     Ok(())
 }
 
-fn formatter_for(format: &FormatArg) -> Box<dyn Formatter> {
+fn formatter_for(format: &FormatArg, options: OutputOptions) -> Box<dyn Formatter> {
     match format {
-        FormatArg::Human => Box::new(HumanFormatter),
-        FormatArg::Summary => Box::new(SummaryFormatter),
-        FormatArg::Json => Box::new(JsonFormatter),
+        FormatArg::Human => Box::new(HumanFormatter { options }),
+        FormatArg::Summary => Box::new(SummaryFormatter { options }),
+        FormatArg::Json => Box::new(JsonFormatter { options }),
     }
 }
@@ -0,0 +1,241 @@
+use crate::error::{Error, Result};
+use crate::models::{
+    AlgoConfig, CapacityConfig, EventOrder, LatencyDistribution, RandomnessConfig, RequestProfile,
+    RngKind, ServerConfig, ServerModel, SimConfig, SloConfig,
+};
+use crate::validation;
+
+impl SimConfig {
+    /// Starts a config built in code, e.g.
+    /// `SimConfig::builder().server("api", 10).weight(2).requests(100).algo(AlgoConfig::RoundRobin).build()`.
+    pub fn builder() -> SimConfigBuilder {
+        SimConfigBuilder::default()
+    }
+}
+
+/// Fluent `SimConfig` construction. Per-server methods (`weight`,
+/// `concurrency`, `latency`, `zone`) apply to the most recent `server`.
+/// `build` runs the same validation as `run_simulation`.
+#[derive(Clone, Debug, Default)]
+pub struct SimConfigBuilder {
+    servers: Vec<ServerConfig>,
+    requests: Option<RequestProfile>,
+    algo: Option<AlgoConfig>,
+    randomness: RandomnessConfig,
+    rng: RngKind,
+    event_order: EventOrder,
+    allow_large: bool,
+    timeseries_bucket_ms: Option<u64>,
+    record_timelines: bool,
+    slos: Vec<SloConfig>,
+    slowest_requests: usize,
+    explain: bool,
+    max_virtual_time_ms: Option<u64>,
+    max_events: Option<u64>,
+    misuse: Option<String>,
+}
+
+impl SimConfigBuilder {
+    pub fn server(mut self, name: &str, base_latency_ms: u64) -> Self {
+        self.servers.push(ServerConfig {
+            name: name.to_string(),
+            base_latency_ms,
+            weight: 1,
+            model: ServerModel::default(),
+        });
+        self
+    }
+
+    pub fn weight(self, weight: u32) -> Self {
+        self.with_last_server("weight", |server| server.weight = weight)
+    }
+
+    pub fn concurrency(self, concurrency: u32) -> Self {
+        self.with_last_server("concurrency", |server| {
+            server.model.capacity = Some(CapacityConfig { concurrency })
+        })
+    }
+
+    pub fn latency(self, distribution: LatencyDistribution) -> Self {
+        self.with_last_server("latency", |server| {
+            server.model.latency = Some(distribution)
+        })
+    }
+
+    pub fn zone(self, zone: &str) -> Self {
+        self.with_last_server("zone", |server| server.model.zone = Some(zone.to_string()))
+    }
+
+    pub fn requests(mut self, count: usize) -> Self {
+        self.requests = Some(RequestProfile::FixedCount(count));
+        self
+    }
+
+    pub fn burst(mut self, count: usize, at_ms: u64) -> Self {
+        self.requests = Some(RequestProfile::Burst { count, at_ms });
+        self
+    }
+
+    pub fn poisson(mut self, rate: f64, duration_ms: u64) -> Self {
+        self.requests = Some(RequestProfile::Poisson { rate, duration_ms });
+        self
+    }
+
+    pub fn algo(mut self, algo: AlgoConfig) -> Self {
+        self.algo = Some(algo);
+        self
+    }
+
+    /// Like `--seed`: shorthand for `randomness(RandomnessConfig::Seeded(seed))`.
+    pub fn seed(self, seed: u64) -> Self {
+        self.randomness(RandomnessConfig::Seeded(seed))
+    }
+
+    pub fn rng(mut self, rng: RngKind) -> Self {
+        self.rng = rng;
+        self
+    }
+
+    pub fn event_order(mut self, event_order: EventOrder) -> Self {
+        self.event_order = event_order;
+        self
+    }
+
+    pub fn allow_large(mut self, allow_large: bool) -> Self {
+        self.allow_large = allow_large;
+        self
+    }
+
+    pub fn randomness(mut self, randomness: RandomnessConfig) -> Self {
+        self.randomness = randomness;
+        self
+    }
+
+    pub fn slo(mut self, percentile: f64, threshold_ms: u64) -> Self {
+        self.slos.push(SloConfig {
+            percentile,
+            threshold_ms,
+        });
+        self
+    }
+
+    pub fn timeseries_bucket_ms(mut self, bucket_ms: u64) -> Self {
+        self.timeseries_bucket_ms = Some(bucket_ms);
+        self
+    }
+
+    pub fn record_timelines(mut self, enabled: bool) -> Self {
+        self.record_timelines = enabled;
+        self
+    }
+
+    pub fn slowest_requests(mut self, count: usize) -> Self {
+        self.slowest_requests = count;
+        self
+    }
+
+    pub fn explain(mut self, enabled: bool) -> Self {
+        self.explain = enabled;
+        self
+    }
+
+    pub fn max_virtual_time_ms(mut self, limit_ms: u64) -> Self {
+        self.max_virtual_time_ms = Some(limit_ms);
+        self
+    }
+
+    pub fn max_events(mut self, limit: u64) -> Self {
+        self.max_events = Some(limit);
+        self
+    }
+
+    pub fn build(self) -> Result<SimConfig> {
+        if let Some(misuse) = self.misuse {
+            return Err(Error::InvalidBuilder(misuse));
+        }
+        let config = SimConfig {
+            servers: self.servers,
+            requests: self
+                .requests
+                .ok_or_else(|| Error::InvalidBuilder("no workload set".to_string()))?,
+            algo: self
+                .algo
+                .ok_or_else(|| Error::InvalidBuilder("no algo set".to_string()))?,
+            randomness: self.randomness,
+            rng: self.rng,
+            event_order: self.event_order,
+            allow_large: self.allow_large,
+            timeseries_bucket_ms: self.timeseries_bucket_ms,
+            record_timelines: self.record_timelines,
+            slos: self.slos,
+            slowest_requests: self.slowest_requests,
+            explain: self.explain,
+            max_virtual_time_ms: self.max_virtual_time_ms,
+            max_events: self.max_events,
+        };
+        validation::validate(&config)?;
+        Ok(config)
+    }
+
+    fn with_last_server(mut self, method: &str, apply: impl FnOnce(&mut ServerConfig)) -> Self {
+        match self.servers.last_mut() {
+            Some(server) => apply(server),
+            None => {
+                self.misuse
+                    .get_or_insert_with(|| format!("{}() called before server()", method));
+            }
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_applies_server_settings_to_the_last_server() {
+        let config = SimConfig::builder()
+            .server("api", 10)
+            .weight(2)
+            .concurrency(4)
+            .server("db", 40)
+            .zone("eu")
+            .poisson(100.0, 60_000)
+            .algo(AlgoConfig::WeightedRoundRobin)
+            .seed(7)
+            .build()
+            .expect("config should be valid");
+
+        assert_eq!(config.servers[0].weight, 2);
+        assert_eq!(config.servers[0].model.concurrency(), 4);
+        assert_eq!(config.servers[1].weight, 1);
+        assert_eq!(config.servers[1].model.zone.as_deref(), Some("eu"));
+        assert_eq!(config.randomness, RandomnessConfig::Seeded(7));
+    }
+
+    #[test]
+    fn builder_validates_on_build() {
+        let missing_algo = SimConfig::builder().server("a", 10).requests(5).build();
+        assert!(matches!(missing_algo, Err(Error::InvalidBuilder(_))));
+
+        let early_weight = SimConfig::builder()
+            .weight(2)
+            .server("a", 10)
+            .requests(5)
+            .algo(AlgoConfig::RoundRobin)
+            .build();
+        assert_eq!(
+            early_weight.unwrap_err().to_string(),
+            "invalid config builder: weight() called before server()"
+        );
+
+        let duplicate = SimConfig::builder()
+            .server("a", 10)
+            .server("a", 20)
+            .requests(5)
+            .algo(AlgoConfig::RoundRobin)
+            .build();
+        assert!(matches!(duplicate, Err(Error::DuplicateServerName(_))));
+    }
+}
@@ -0,0 +1,601 @@
+use std::collections::{BTreeMap, HashSet};
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+use crate::models::{CapacityConfig, LatencyDistribution, ServerConfig, ServerModel};
+
+/// Below this coefficient of variation probes are treated as fixed latency.
+const FIXED_MAX_CV: f64 = 0.05;
+/// At or above this coefficient of variation the exponential model (CV = 1)
+/// fits better than a uniform band.
+const EXPONENTIAL_MIN_CV: f64 = 0.75;
+
+/// One backend to probe: `name url` or a bare `url` per line in `--targets`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Target {
+    pub name: String,
+    pub url: String,
+}
+
+/// Latency statistics for one backend, from probes or a histogram.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LatencyStats {
+    pub median_ms: f64,
+    pub p95_ms: f64,
+    pub mean_ms: f64,
+    pub stddev_ms: f64,
+}
+
+/// Observed statistics and the server fitted to them. `source` is the probed
+/// URL or the Prometheus series label.
+#[derive(Clone, Debug)]
+pub struct Calibration {
+    pub source: String,
+    pub probes: Option<usize>,
+    pub rate_rps: Option<f64>,
+    pub stats: LatencyStats,
+    pub server: ServerConfig,
+}
+
+/// Parses a targets file; `#` starts a comment. Unnamed targets are named
+/// after their host (and port).
+pub fn parse_targets(source: &str, contents: &str) -> Result<Vec<Target>> {
+    let mut targets = Vec::new();
+    let mut names = HashSet::new();
+    for (idx, raw) in contents.lines().enumerate() {
+        let line = raw.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let invalid = |reason: &str| Error::Cli(format!("{}:{}: {}", source, idx + 1, reason));
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+        let (name, url) = match fields.as_slice() {
+            [url] => (
+                host_name(url).ok_or_else(|| invalid("expected an http(s) URL"))?,
+                *url,
+            ),
+            [name, url] => (name.to_string(), *url),
+            _ => return Err(invalid("expected `url` or `name url`")),
+        };
+        if host_name(url).is_none() {
+            return Err(invalid("expected an http(s) URL"));
+        }
+        if !names.insert(name.clone()) {
+            return Err(invalid(&format!("duplicate target name '{}'", name)));
+        }
+        targets.push(Target {
+            name,
+            url: url.to_string(),
+        });
+    }
+    if targets.is_empty() {
+        return Err(Error::Cli(format!("{}: no targets found", source)));
+    }
+    Ok(targets)
+}
+
+fn host_name(url: &str) -> Option<String> {
+    let rest = url
+        .strip_prefix("http://")
+        .or_else(|| url.strip_prefix("https://"))?;
+    let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+    (!host.is_empty()).then(|| host.replace(':', "-"))
+}
+
+/// Fits a server to probe latencies; see [`fit_server`].
+pub fn calibrate(target: &Target, samples_ms: &[f64]) -> Calibration {
+    let mut sorted = samples_ms.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let count = sorted.len().max(1) as f64;
+    let mean_ms = sorted.iter().sum::<f64>() / count;
+    let stddev_ms = (sorted
+        .iter()
+        .map(|sample| (sample - mean_ms).powi(2))
+        .sum::<f64>()
+        / count)
+        .sqrt();
+    let stats = LatencyStats {
+        median_ms: percentile(&sorted, 50.0),
+        p95_ms: percentile(&sorted, 95.0),
+        mean_ms,
+        stddev_ms,
+    };
+
+    Calibration {
+        source: target.url.clone(),
+        probes: Some(samples_ms.len()),
+        rate_rps: None,
+        server: fit_server(&target.name, &stats, None),
+        stats,
+    }
+}
+
+/// The mean becomes `base_latency_ms` (matching how every distribution
+/// treats it) and the spread picks fixed, uniform (`jitter_ms` from the
+/// stddev, since a uniform band of ±j has stddev j/√3), or exponential
+/// latency. With an observed request rate the server gets the concurrency
+/// that rate needs at that mean (Little's law), so it is not saturated at
+/// the observed load.
+pub fn fit_server(name: &str, stats: &LatencyStats, rate_rps: Option<f64>) -> ServerConfig {
+    let base_latency_ms = (stats.mean_ms.round() as u64).max(1);
+    let cv = if stats.mean_ms > 0.0 {
+        stats.stddev_ms / stats.mean_ms
+    } else {
+        0.0
+    };
+    let latency = if cv < FIXED_MAX_CV {
+        None
+    } else if cv >= EXPONENTIAL_MIN_CV {
+        Some(LatencyDistribution::Exponential)
+    } else {
+        let jitter_ms = ((stats.stddev_ms * 3f64.sqrt()).round() as u64).min(base_latency_ms - 1);
+        (jitter_ms > 0).then_some(LatencyDistribution::Uniform { jitter_ms })
+    };
+    let concurrency = rate_rps
+        .map(|rate| (rate * base_latency_ms as f64 / 1000.0).ceil())
+        .filter(|in_flight| *in_flight > 1.0)
+        .map(|in_flight| in_flight.min(u32::MAX as f64) as u32);
+
+    ServerConfig {
+        name: name.to_string(),
+        base_latency_ms,
+        weight: 1,
+        model: ServerModel {
+            latency,
+            capacity: concurrency.map(|concurrency| CapacityConfig { concurrency }),
+            ..ServerModel::default()
+        },
+    }
+}
+
+fn percentile(sorted: &[f64], percentile: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((percentile / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// A config fragment with the fitted servers; TOML output carries the probe
+/// statistics as comments so it can be `include`d as-is.
+pub fn calibrations_to_toml(calibrations: &[Calibration]) -> Result<String> {
+    #[derive(Serialize)]
+    struct Fragment<'a> {
+        servers: [&'a ServerConfig; 1],
+    }
+
+    let mut output = String::new();
+    for (idx, calibration) in calibrations.iter().enumerate() {
+        if idx > 0 {
+            output.push('\n');
+        }
+        let stats = &calibration.stats;
+        output.push_str(&format!("# {}: ", calibration.source));
+        if let Some(probes) = calibration.probes {
+            output.push_str(&format!("{} probes, ", probes));
+        }
+        output.push_str(&format!(
+            "median {:.1}ms, p95 {:.1}ms, mean {:.1}ms, stddev {:.1}ms",
+            stats.median_ms, stats.p95_ms, stats.mean_ms, stats.stddev_ms
+        ));
+        if let Some(rate_rps) = calibration.rate_rps {
+            output.push_str(&format!(", {:.1} req/s", rate_rps));
+        }
+        output.push('\n');
+        let block = toml::to_string(&Fragment {
+            servers: [&calibration.server],
+        })
+        .map_err(|err| Error::Output(format!("failed to serialize servers: {}", err)))?;
+        output.push_str(&block);
+    }
+    Ok(output)
+}
+
+pub fn calibrations_to_json(calibrations: &[Calibration]) -> Result<String> {
+    #[derive(Serialize)]
+    struct Fragment<'a> {
+        servers: Vec<&'a ServerConfig>,
+    }
+
+    serde_json::to_string_pretty(&Fragment {
+        servers: calibrations
+            .iter()
+            .map(|calibration| &calibration.server)
+            .collect(),
+    })
+    .map(|json| json + "\n")
+    .map_err(|err| Error::Output(format!("failed to serialize servers: {}", err)))
+}
+
+/// Fits one server per `label` value from a Prometheus instant-vector
+/// response of cumulative latency histogram buckets (an `le` label, bounds in
+/// seconds), e.g. `sum by (le, instance) (rate(..._bucket[5m]))`. Bucket
+/// midpoints approximate the mean and spread; quantiles interpolate within
+/// buckets like `histogram_quantile`. `rates`, a response with one
+/// requests-per-second sample per `label` value, adds capacities. Series with
+/// no observations are skipped.
+pub fn calibrate_prometheus(
+    histogram: &str,
+    rates: Option<&str>,
+    label: &str,
+) -> Result<Vec<Calibration>> {
+    let mut buckets: BTreeMap<String, Vec<(f64, f64)>> = BTreeMap::new();
+    for (labels, value) in parse_vector(histogram)? {
+        let server = series_label(&labels, label)?;
+        let le = labels
+            .get("le")
+            .and_then(|le| le.parse::<f64>().ok())
+            .ok_or_else(|| {
+                Error::Cli(
+                    "histogram query returned a series without a numeric `le` label".to_string(),
+                )
+            })?;
+        buckets.entry(server).or_default().push((le, value));
+    }
+    let rates = match rates {
+        Some(rates) => parse_vector(rates)?
+            .into_iter()
+            .map(|(labels, value)| Ok((series_label(&labels, label)?, value)))
+            .collect::<Result<BTreeMap<_, _>>>()?,
+        None => BTreeMap::new(),
+    };
+
+    let mut calibrations = Vec::new();
+    for (server, mut buckets) in buckets {
+        buckets.sort_by(|left, right| left.0.total_cmp(&right.0));
+        let Some(stats) = histogram_stats(&buckets) else {
+            tracing::warn!(%server, "no observations in the histogram; skipping");
+            continue;
+        };
+        let rate_rps = rates.get(&server).copied();
+        calibrations.push(Calibration {
+            source: format!("prometheus {}=\"{}\"", label, server),
+            probes: None,
+            rate_rps,
+            server: fit_server(&server.replace(':', "-"), &stats, rate_rps),
+            stats,
+        });
+    }
+    if calibrations.is_empty() {
+        return Err(Error::Cli(
+            "histogram query returned no observations".to_string(),
+        ));
+    }
+    Ok(calibrations)
+}
+
+fn parse_vector(response: &str) -> Result<Vec<(BTreeMap<String, String>, f64)>> {
+    let invalid =
+        |reason: String| Error::Cli(format!("unexpected Prometheus response: {}", reason));
+    let document: Value = serde_json::from_str(response).map_err(|err| invalid(err.to_string()))?;
+    if document.get("status").and_then(Value::as_str) != Some("success") {
+        let error = document
+            .get("error")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown error");
+        return Err(Error::Cli(format!("Prometheus query failed: {}", error)));
+    }
+    let result_type = document
+        .pointer("/data/resultType")
+        .and_then(Value::as_str)
+        .unwrap_or("none");
+    if result_type != "vector" {
+        return Err(invalid(format!(
+            "expected an instant vector, got {}",
+            result_type
+        )));
+    }
+    let series = document
+        .pointer("/data/result")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    series
+        .iter()
+        .map(|series| {
+            let labels = series
+                .get("metric")
+                .and_then(Value::as_object)
+                .map(|metric| {
+                    metric
+                        .iter()
+                        .filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_string())))
+                        .collect()
+                })
+                .unwrap_or_default();
+            let value = series
+                .pointer("/value/1")
+                .and_then(Value::as_str)
+                .and_then(|value| value.parse::<f64>().ok())
+                .ok_or_else(|| invalid("series without a sample value".to_string()))?;
+            Ok((labels, if value.is_nan() { 0.0 } else { value }))
+        })
+        .collect()
+}
+
+fn series_label(labels: &BTreeMap<String, String>, label: &str) -> Result<String> {
+    labels.get(label).cloned().ok_or_else(|| {
+        Error::Cli(format!(
+            "query returned a series without a `{}` label; aggregate with `sum by ({}, ...)`",
+            label, label
+        ))
+    })
+}
+
+/// Statistics from cumulative `(le seconds, count)` buckets sorted by bound.
+/// Observations in the `+Inf` bucket count at the last finite bound.
+fn histogram_stats(buckets: &[(f64, f64)]) -> Option<LatencyStats> {
+    let total = buckets.last()?.1;
+    if total <= 0.0 {
+        return None;
+    }
+    let mut counts = Vec::with_capacity(buckets.len());
+    let mut lower_ms = 0.0;
+    let mut previous = 0.0;
+    for &(le, cumulative) in buckets {
+        let upper_ms = if le.is_finite() {
+            le * 1000.0
+        } else {
+            lower_ms
+        };
+        counts.push((lower_ms, upper_ms, (cumulative - previous).max(0.0)));
+        lower_ms = upper_ms;
+        previous = cumulative;
+    }
+    let mean_ms = counts
+        .iter()
+        .map(|(lower, upper, count)| (lower + upper) / 2.0 * count)
+        .sum::<f64>()
+        / total;
+    let stddev_ms = (counts
+        .iter()
+        .map(|(lower, upper, count)| ((lower + upper) / 2.0 - mean_ms).powi(2) * count)
+        .sum::<f64>()
+        / total)
+        .sqrt();
+    let quantile = |q: f64| {
+        let rank = q * total;
+        let mut seen = 0.0;
+        for &(lower, upper, count) in &counts {
+            if count > 0.0 && seen + count >= rank {
+                return lower + (upper - lower) * (rank - seen) / count;
+            }
+            seen += count;
+        }
+        lower_ms
+    };
+
+    Some(LatencyStats {
+        median_ms: quantile(0.5),
+        p95_ms: quantile(0.95),
+        mean_ms,
+        stddev_ms,
+    })
+}
+
+/// Runs an instant query against the Prometheus HTTP API at `base_url` and
+/// returns the raw JSON response.
+#[cfg(feature = "calibrate")]
+pub fn query_prometheus(base_url: &str, query: &str, timeout: Duration) -> Result<String> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(timeout))
+        .http_status_as_error(false)
+        .build()
+        .into();
+    let url = format!("{}/api/v1/query", base_url.trim_end_matches('/'));
+    agent
+        .get(&url)
+        .query("query", query)
+        .call()
+        .and_then(|mut response| response.body_mut().read_to_string())
+        .map_err(|err| Error::Cli(format!("Prometheus query to '{}' failed: {}", url, err)))
+}
+
+#[cfg(not(feature = "calibrate"))]
+pub fn query_prometheus(base_url: &str, _query: &str, _timeout: Duration) -> Result<String> {
+    Err(Error::Cli(format!(
+        "cannot query '{}': lb-sim was built without the `calibrate` feature",
+        base_url
+    )))
+}
+
+/// Times `probes` sequential GET requests to `url`, in milliseconds. Any
+/// transport error or non-2xx status fails the calibration.
+#[cfg(feature = "calibrate")]
+pub fn probe(url: &str, probes: usize, timeout: Duration) -> Result<Vec<f64>> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(timeout))
+        .build()
+        .into();
+    (0..probes)
+        .map(|_| {
+            let started = std::time::Instant::now();
+            let mut response = agent
+                .get(url)
+                .call()
+                .map_err(|err| Error::Cli(format!("probe of '{}' failed: {}", url, err)))?;
+            response
+                .body_mut()
+                .read_to_vec()
+                .map_err(|err| Error::Cli(format!("probe of '{}' failed: {}", url, err)))?;
+            Ok(started.elapsed().as_secs_f64() * 1000.0)
+        })
+        .collect()
+}
+
+#[cfg(not(feature = "calibrate"))]
+pub fn probe(url: &str, _probes: usize, _timeout: Duration) -> Result<Vec<f64>> {
+    Err(Error::Cli(format!(
+        "cannot probe '{}': lb-sim was built without the `calibrate` feature",
+        url
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(name: &str) -> Target {
+        Target {
+            name: name.to_string(),
+            url: format!("http://{}.internal/health", name),
+        }
+    }
+
+    #[test]
+    fn parse_targets_names_bare_urls_after_their_host() {
+        let targets = parse_targets(
+            "urls.txt",
+            "# fleet\nhttp://10.0.0.1:8080/health\napi https://api.internal/ping  # primary\n",
+        )
+        .expect("targets should parse");
+
+        assert_eq!(
+            targets,
+            vec![
+                Target {
+                    name: "10.0.0.1-8080".to_string(),
+                    url: "http://10.0.0.1:8080/health".to_string(),
+                },
+                Target {
+                    name: "api".to_string(),
+                    url: "https://api.internal/ping".to_string(),
+                },
+            ]
+        );
+
+        let err = parse_targets("urls.txt", "a http://x\na http://y\n")
+            .expect_err("duplicate names should fail");
+        assert_eq!(err.to_string(), "urls.txt:2: duplicate target name 'a'");
+        let err = parse_targets("urls.txt", "ftp://x\n").expect_err("non-http should fail");
+        assert_eq!(err.to_string(), "urls.txt:1: expected an http(s) URL");
+    }
+
+    #[test]
+    fn calibrate_picks_the_distribution_from_the_spread() {
+        let fixed = calibrate(&target("a"), &[20.0, 20.2, 19.8, 20.0]);
+        assert_eq!(fixed.server.base_latency_ms, 20);
+        assert_eq!(fixed.server.model.latency, None);
+
+        let uniform = calibrate(&target("b"), &[10.0, 20.0, 30.0, 40.0]);
+        assert_eq!(uniform.server.base_latency_ms, 25);
+        assert_eq!(
+            uniform.server.model.latency,
+            Some(LatencyDistribution::Uniform { jitter_ms: 19 })
+        );
+        assert_eq!(uniform.stats.median_ms, 20.0);
+        assert_eq!(uniform.stats.p95_ms, 40.0);
+
+        let exponential = calibrate(&target("c"), &[1.0, 2.0, 3.0, 50.0]);
+        assert_eq!(
+            exponential.server.model.latency,
+            Some(LatencyDistribution::Exponential)
+        );
+    }
+
+    #[test]
+    fn toml_output_is_an_includable_servers_fragment() {
+        let toml = calibrations_to_toml(&[
+            calibrate(&target("a"), &[20.0, 20.0]),
+            calibrate(&target("b"), &[10.0, 20.0, 30.0, 40.0]),
+        ])
+        .expect("servers should serialize");
+
+        assert!(toml.starts_with(
+            "# http://a.internal/health: 2 probes, median 20.0ms, p95 20.0ms, mean 20.0ms, stddev 0.0ms\n[[servers]]\nname = \"a\"\n"
+        ));
+        let parsed: toml::Value = toml::from_str(&toml).expect("output is TOML");
+        let servers = parsed["servers"].as_array().expect("servers list");
+        assert_eq!(servers.len(), 2);
+        assert_eq!(
+            servers[1]["latency"]["distribution"].as_str(),
+            Some("uniform")
+        );
+        assert_eq!(servers[1]["latency"]["jitter_ms"].as_integer(), Some(19));
+    }
+
+    fn vector(series: &[(&str, Option<&str>, &str)]) -> String {
+        let result = series
+            .iter()
+            .map(|(instance, le, value)| {
+                let le = le.map_or(String::new(), |le| format!(r#", "le": "{}""#, le));
+                format!(
+                    r#"{{"metric": {{"instance": "{}"{}}}, "value": [1700000000, "{}"]}}"#,
+                    instance, le, value
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            r#"{{"status": "success", "data": {{"resultType": "vector", "result": [{}]}}}}"#,
+            result
+        )
+    }
+
+    #[test]
+    fn prometheus_histograms_fit_latency_and_rates_fit_capacity() {
+        let histogram = vector(&[
+            ("api:9090", Some("0.04"), "100"),
+            ("api:9090", Some("0.01"), "0"),
+            ("api:9090", Some("0.02"), "50"),
+            ("api:9090", Some("+Inf"), "100"),
+            ("idle:9090", Some("0.01"), "0"),
+            ("idle:9090", Some("+Inf"), "0"),
+        ]);
+        let rates = vector(&[("api:9090", None, "400")]);
+        let calibrations = calibrate_prometheus(&histogram, Some(&rates), "instance")
+            .expect("histogram should fit");
+
+        assert_eq!(calibrations.len(), 1);
+        let calibration = &calibrations[0];
+        assert_eq!(
+            calibration.stats,
+            LatencyStats {
+                median_ms: 20.0,
+                p95_ms: 38.0,
+                mean_ms: 22.5,
+                stddev_ms: 7.5,
+            }
+        );
+        assert_eq!(calibration.server.name, "api-9090");
+        assert_eq!(calibration.server.base_latency_ms, 23);
+        assert_eq!(
+            calibration.server.model.latency,
+            Some(LatencyDistribution::Uniform { jitter_ms: 13 })
+        );
+        assert_eq!(calibration.server.model.concurrency(), 10);
+
+        let toml = calibrations_to_toml(&calibrations).expect("servers should serialize");
+        assert!(toml.starts_with(
+            "# prometheus instance=\"api:9090\": median 20.0ms, p95 38.0ms, mean 22.5ms, stddev 7.5ms, 400.0 req/s\n"
+        ));
+    }
+
+    #[test]
+    fn prometheus_errors_name_the_problem() {
+        let err = calibrate_prometheus(
+            r#"{"status": "error", "errorType": "bad_data", "error": "parse error"}"#,
+            None,
+            "instance",
+        )
+        .expect_err("failed query");
+        assert_eq!(err.to_string(), "Prometheus query failed: parse error");
+
+        let err = calibrate_prometheus(&vector(&[("a", Some("0.1"), "1")]), None, "pod")
+            .expect_err("missing label");
+        assert_eq!(
+            err.to_string(),
+            "query returned a series without a `pod` label; aggregate with `sum by (pod, ...)`"
+        );
+
+        let err = calibrate_prometheus(&vector(&[("a", None, "1")]), None, "instance")
+            .expect_err("missing le");
+        assert_eq!(
+            err.to_string(),
+            "histogram query returned a series without a numeric `le` label"
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::state::SimulationResult;
+
+#[cfg(feature = "charts")]
+pub fn render_chart(result: &SimulationResult, path: &Path) -> Result<()> {
+    use plotters::prelude::*;
+
+    let ext = path
+        .extension()
+        .and_then(|value| value.to_str())
+        .unwrap_or("");
+    if ext != "svg" {
+        return Err(Error::Chart(format!(
+            "unsupported chart format '{}': only .svg is supported",
+            if ext.is_empty() { "unknown" } else { ext }
+        )));
+    }
+
+    let chart_err =
+        |err: &dyn std::fmt::Display| Error::Chart(format!("failed to render chart: {}", err));
+
+    let root = SVGBackend::new(path, (1024, 768)).into_drawing_area();
+    root.fill(&WHITE).map_err(|err| chart_err(&err))?;
+    let (top, bottom) = root.split_vertically(448);
+
+    let max_time = result
+        .assignments
+        .iter()
+        .map(|assignment| assignment.arrival_time_ms)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let max_latency = result
+        .assignments
+        .iter()
+        .map(|assignment| assignment.completed_at - assignment.arrival_time_ms)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    let mut latency_chart = ChartBuilder::on(&top)
+        .caption(
+            format!("Response time over time ({})", result.metadata.algo),
+            ("sans-serif", 20),
+        )
+        .margin(12)
+        .x_label_area_size(36)
+        .y_label_area_size(56)
+        .build_cartesian_2d(0..max_time, 0..max_latency)
+        .map_err(|err| chart_err(&err))?;
+    latency_chart
+        .configure_mesh()
+        .x_desc("arrival time (ms)")
+        .y_desc("response time (ms)")
+        .draw()
+        .map_err(|err| chart_err(&err))?;
+
+    for (idx, summary) in result.totals.iter().enumerate() {
+        let color = Palette99::pick(idx).to_rgba();
+        let points = result
+            .assignments
+            .iter()
+            .filter(|assignment| assignment.server_id == idx)
+            .map(|assignment| {
+                (
+                    assignment.arrival_time_ms,
+                    assignment.completed_at - assignment.arrival_time_ms,
+                )
+            });
+        latency_chart
+            .draw_series(LineSeries::new(points, color.stroke_width(2)))
+            .map_err(|err| chart_err(&err))?
+            .label(summary.name.clone())
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 16, y)], color));
+    }
+    latency_chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .map_err(|err| chart_err(&err))?;
+
+    let server_count = result.totals.len();
+    let max_requests = result
+        .totals
+        .iter()
+        .map(|summary| summary.requests)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let names = result
+        .totals
+        .iter()
+        .map(|summary| summary.name.clone())
+        .collect::<Vec<_>>();
+    let mut share_chart = ChartBuilder::on(&bottom)
+        .caption("Requests per server", ("sans-serif", 20))
+        .margin(12)
+        .x_label_area_size(36)
+        .y_label_area_size(56)
+        .build_cartesian_2d(0f64..server_count as f64, 0u64..max_requests)
+        .map_err(|err| chart_err(&err))?;
+    share_chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .x_labels(server_count.max(1) * 2 + 1)
+        .x_label_formatter(&|value| {
+            let offset = value - value.floor();
+            if (offset - 0.5).abs() > f64::EPSILON {
+                return String::new();
+            }
+            names.get(*value as usize).cloned().unwrap_or_default()
+        })
+        .y_desc("requests")
+        .draw()
+        .map_err(|err| chart_err(&err))?;
+    share_chart
+        .draw_series(result.totals.iter().enumerate().map(|(idx, summary)| {
+            let left = idx as f64 + 0.15;
+            let right = idx as f64 + 0.85;
+            Rectangle::new(
+                [(left, 0), (right, summary.requests)],
+                Palette99::pick(idx).filled(),
+            )
+        }))
+        .map_err(|err| chart_err(&err))?;
+
+    root.present().map_err(|err| chart_err(&err))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "charts"))]
+pub fn render_chart(_result: &SimulationResult, path: &Path) -> Result<()> {
+    Err(Error::Chart(format!(
+        "cannot write '{}': lb-sim was built without the `charts` feature",
+        path.display()
+    )))
+}
+
+#[cfg(all(test, feature = "charts"))]
+mod tests {
+    use super::*;
+    use crate::engine::run_simulation;
+    use crate::models::{
+        AlgoConfig, EventOrder, RandomnessConfig, RequestProfile, RngKind, ServerConfig,
+        ServerModel, SimConfig,
+    };
+
+    #[test]
+    fn render_chart_writes_svg() {
+        let config = SimConfig {
+            servers: vec![
+                ServerConfig {
+                    name: "api".to_string(),
+                    base_latency_ms: 10,
+                    weight: 1,
+                    model: ServerModel::default(),
+                },
+                ServerConfig {
+                    name: "db".to_string(),
+                    base_latency_ms: 20,
+                    weight: 1,
+                    model: ServerModel::default(),
+                },
+            ],
+            requests: RequestProfile::FixedCount(10),
+            algo: AlgoConfig::RoundRobin,
+            randomness: RandomnessConfig::Stable,
+            rng: RngKind::Std,
+            event_order: EventOrder::CompletionsFirst,
+            allow_large: false,
+            timeseries_bucket_ms: None,
+            record_timelines: false,
+            slos: Vec::new(),
+            slowest_requests: 0,
+            explain: false,
+            max_virtual_time_ms: None,
+            max_events: None,
+        };
+        let result = run_simulation(&config).expect("simulation should succeed");
+        let path = std::env::temp_dir().join(format!("lb-chart-{}.svg", std::process::id()));
+
+        render_chart(&result, &path).expect("chart should render");
+        let svg = std::fs::read_to_string(&path).expect("chart should be written");
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("Requests per server"));
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn render_chart_rejects_non_svg_paths() {
+        let config = SimConfig {
+            servers: vec![ServerConfig {
+                name: "api".to_string(),
+                base_latency_ms: 10,
+                weight: 1,
+                model: ServerModel::default(),
+            }],
+            requests: RequestProfile::FixedCount(1),
+            algo: AlgoConfig::RoundRobin,
+            randomness: RandomnessConfig::Stable,
+            rng: RngKind::Std,
+            event_order: EventOrder::CompletionsFirst,
+            allow_large: false,
+            timeseries_bucket_ms: None,
+            record_timelines: false,
+            slos: Vec::new(),
+            slowest_requests: 0,
+            explain: false,
+            max_virtual_time_ms: None,
+            max_events: None,
+        };
+        let result = run_simulation(&config).expect("simulation should succeed");
+        let err = render_chart(&result, Path::new("out.png")).unwrap_err();
+        assert!(err.to_string().contains("only .svg"));
+    }
+}
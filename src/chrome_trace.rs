@@ -0,0 +1,132 @@
+use serde::Serialize;
+use serde_json::json;
+
+use crate::state::SimulationResult;
+
+const PID: u32 = 1;
+
+#[derive(Serialize)]
+struct TraceEvent {
+    name: String,
+    cat: &'static str,
+    ph: &'static str,
+    pid: u32,
+    tid: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ts: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dur: Option<u64>,
+    args: serde_json::Value,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TraceFile {
+    trace_events: Vec<TraceEvent>,
+    display_time_unit: &'static str,
+}
+
+/// Renders assignments in the Chrome `trace_event` format: one thread per
+/// server, one complete ("X") span per request covering its service time.
+/// Trace timestamps are microseconds, so virtual milliseconds are scaled by 1000.
+pub fn to_chrome_trace(result: &SimulationResult) -> String {
+    let mut trace_events = result
+        .totals
+        .iter()
+        .enumerate()
+        .map(|(idx, summary)| TraceEvent {
+            name: "thread_name".to_string(),
+            cat: "__metadata",
+            ph: "M",
+            pid: PID,
+            tid: idx,
+            ts: None,
+            dur: None,
+            args: json!({ "name": summary.name }),
+        })
+        .collect::<Vec<_>>();
+
+    trace_events.extend(result.assignments.iter().map(|assignment| TraceEvent {
+        name: format!("request {}", assignment.request_id),
+        cat: "request",
+        ph: "X",
+        pid: PID,
+        tid: assignment.server_id,
+        ts: Some(assignment.started_at * 1000),
+        dur: Some((assignment.completed_at - assignment.started_at) * 1000),
+        args: json!({
+            "arrival_time_ms": assignment.arrival_time_ms,
+            "wait_ms": assignment.started_at - assignment.arrival_time_ms,
+            "score": assignment.score,
+        }),
+    }));
+
+    serde_json::to_string(&TraceFile {
+        trace_events,
+        display_time_unit: "ms",
+    })
+    .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::run_simulation;
+    use crate::models::{
+        AlgoConfig, EventOrder, RandomnessConfig, RequestProfile, RngKind, ServerConfig,
+        ServerModel, SimConfig,
+    };
+
+    #[test]
+    fn chrome_trace_has_one_track_per_server_and_request_spans() {
+        let config = SimConfig {
+            servers: vec![
+                ServerConfig {
+                    name: "api".to_string(),
+                    base_latency_ms: 10,
+                    weight: 1,
+                    model: ServerModel::default(),
+                },
+                ServerConfig {
+                    name: "db".to_string(),
+                    base_latency_ms: 20,
+                    weight: 1,
+                    model: ServerModel::default(),
+                },
+            ],
+            requests: RequestProfile::FixedCount(3),
+            algo: AlgoConfig::RoundRobin,
+            randomness: RandomnessConfig::Stable,
+            rng: RngKind::Std,
+            event_order: EventOrder::CompletionsFirst,
+            allow_large: false,
+            timeseries_bucket_ms: None,
+            record_timelines: false,
+            slos: Vec::new(),
+            slowest_requests: 0,
+            explain: false,
+            max_virtual_time_ms: None,
+            max_events: None,
+        };
+        let result = run_simulation(&config).expect("simulation should succeed");
+        let trace: serde_json::Value =
+            serde_json::from_str(&to_chrome_trace(&result)).expect("trace should be JSON");
+        let events = trace["traceEvents"].as_array().expect("events array");
+
+        let threads = events
+            .iter()
+            .filter(|event| event["ph"] == "M")
+            .map(|event| event["args"]["name"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(threads, vec!["api", "db"]);
+
+        let third = events
+            .iter()
+            .find(|event| event["name"] == "request 3")
+            .expect("request 3 span");
+        assert_eq!(third["tid"], 0);
+        assert_eq!(third["ts"], 10_000);
+        assert_eq!(third["dur"], 10_000);
+        assert_eq!(third["args"]["wait_ms"], 8);
+    }
+}
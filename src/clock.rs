@@ -0,0 +1,104 @@
+use std::time::{Duration, Instant};
+
+/// The engine's notion of "now". The engine only ever moves the clock forward
+/// to the next event's timestamp; the clock decides what that costs, so the
+/// same engine can run as fast as possible or paced against the wall clock.
+pub trait Clock {
+    fn now_ms(&self) -> u64;
+
+    /// Restarts the clock at `time_ms`: 0 when a run starts, the snapshot's
+    /// time when one is restored.
+    fn reset(&mut self, time_ms: u64);
+
+    /// Moves to `time_ms`, which is never earlier than `now_ms`.
+    fn advance_to(&mut self, time_ms: u64);
+}
+
+/// Jumps straight to each event; the default.
+#[derive(Clone, Debug, Default)]
+pub struct SimulatedClock {
+    now_ms: u64,
+}
+
+impl Clock for SimulatedClock {
+    fn now_ms(&self) -> u64 {
+        self.now_ms
+    }
+
+    fn reset(&mut self, time_ms: u64) {
+        self.now_ms = time_ms;
+    }
+
+    fn advance_to(&mut self, time_ms: u64) {
+        self.now_ms = time_ms;
+    }
+}
+
+/// Sleeps until the wall clock catches up with each event, `speed` virtual
+/// milliseconds per real millisecond (1.0 is real time, 10.0 ten times
+/// faster). A run that falls behind does not sleep until it catches up.
+#[derive(Clone, Debug)]
+pub struct RealTimeClock {
+    speed: f64,
+    origin_ms: u64,
+    started: Instant,
+    now_ms: u64,
+}
+
+impl RealTimeClock {
+    /// Non-positive or non-finite speeds fall back to real time.
+    pub fn new(speed: f64) -> Self {
+        Self {
+            speed: if speed.is_finite() && speed > 0.0 {
+                speed
+            } else {
+                1.0
+            },
+            origin_ms: 0,
+            started: Instant::now(),
+            now_ms: 0,
+        }
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+}
+
+impl Clock for RealTimeClock {
+    fn now_ms(&self) -> u64 {
+        self.now_ms
+    }
+
+    fn reset(&mut self, time_ms: u64) {
+        self.origin_ms = time_ms;
+        self.started = Instant::now();
+        self.now_ms = time_ms;
+    }
+
+    fn advance_to(&mut self, time_ms: u64) {
+        let virtual_ms = time_ms.saturating_sub(self.origin_ms) as f64;
+        let due = Duration::from_secs_f64(virtual_ms / self.speed / 1000.0);
+        if let Some(wait) = due.checked_sub(self.started.elapsed()) {
+            std::thread::sleep(wait);
+        }
+        self.now_ms = time_ms;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn real_time_clock_paces_virtual_time_by_speed() {
+        let mut clock = RealTimeClock::new(10.0);
+        clock.reset(1_000);
+        let started = Instant::now();
+        clock.advance_to(1_200);
+
+        assert_eq!(clock.now_ms(), 1_200);
+        assert!(started.elapsed() >= Duration::from_millis(20));
+        assert_eq!(RealTimeClock::new(0.0).speed(), 1.0);
+    }
+}
@@ -1,10 +1,27 @@
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::builder::{EnumValueParser, PossibleValue, TypedValueParser};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use serde::Serialize;
 use std::collections::HashSet;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::io::IsTerminal;
+use std::path::PathBuf;
 
+use crate::algorithms;
+use crate::assertions::AssertionThresholds;
 use crate::error::{Error, Result};
-use crate::models::{AlgoConfig, RequestProfile, ServerConfig, SimConfig, TieBreakConfig};
+use crate::export::ProxyFormat;
+use crate::loadtest::LoadTool;
+use crate::models::{
+    AlgoConfig, EventOrder, LatencyDistribution, RandomnessConfig, RequestProfile, RngKind,
+    ServerConfig, ServerModel, SimConfig, SloConfig,
+};
+use crate::output::{NumberFormat, OutputOptions, SortKey, SummarySort, TimeUnit};
+use crate::recommend::RankCriterion;
+use crate::validation::{latency_problem, slo_problem, weight_problem};
+
+pub use crate::config_file::{
+    apply_config_overrides, config_schema, load_config, load_config_profile,
+};
 
 const SERVER_SPEC_VALUE_NAME: &str = "name:latency[:weight]";
 
@@ -13,12 +30,39 @@ const SERVER_SPEC_VALUE_NAME: &str = "name:latency[:weight]";
 pub struct CliArgs {
     #[command(subcommand)]
     pub command: Option<Command>,
-    #[arg(long, value_enum)]
-    pub algo: Option<AlgoArg>,
+    #[command(flatten)]
+    pub log: LogArgs,
+    #[arg(long, value_parser = AlgoValueParser)]
+    pub algo: Option<AlgoConfig>,
     #[arg(long)]
     pub servers: Option<String>,
     #[arg(long, value_name = SERVER_SPEC_VALUE_NAME)]
     pub server: Vec<String>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Read server specs from a file, one per line or comma-separated; `#` starts a comment"
+    )]
+    pub servers_file: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Warn about and drop malformed or duplicate server entries instead of failing"
+    )]
+    pub skip_invalid_servers: bool,
+    #[arg(
+        long,
+        value_name = "NAME_{i}:LATENCY_MS[:WEIGHT]",
+        requires = "server_count",
+        help = "Server spec repeated --server-count times; `{i}` in the name becomes 1..=N"
+    )]
+    pub server_template: Option<String>,
+    #[arg(
+        long,
+        value_name = "N",
+        requires = "server_template",
+        help = "Number of servers generated from --server-template"
+    )]
+    pub server_count: Option<usize>,
     #[arg(long)]
     pub requests: Option<usize>,
     #[arg(long, help = "Send all requests at once (burst)")]
@@ -35,17 +79,311 @@ pub struct CliArgs {
     pub overload_factor: f64,
     #[arg(long, default_value_t = 1000, help = "Overload duration in ms")]
     pub overload_duration_ms: u64,
+    #[arg(
+        long,
+        value_name = "RPS",
+        requires = "duration_ms",
+        conflicts_with_all = ["requests", "burst", "overload"],
+        help = "Poisson arrival rate in requests per second (with --duration-ms)"
+    )]
+    pub rate: Option<f64>,
+    #[arg(
+        long,
+        visible_alias = "duration",
+        value_name = "MS",
+        requires = "rate",
+        help = "Length of the Poisson arrival window in ms (with --rate)"
+    )]
+    pub duration_ms: Option<u64>,
     #[arg(long)]
     pub summary: bool,
+    #[arg(long, help = "Append ASCII bar charts and sparklines to human output")]
+    pub ascii_charts: bool,
+    #[arg(
+        long,
+        value_name = "MS",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Append an ASCII heatmap of assignments per server and time bucket"
+    )]
+    pub heatmap_bucket_ms: Option<u64>,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "auto",
+        help = "Colorize human output"
+    )]
+    pub color: ColorArg,
+    #[arg(long, value_enum, help = "Sort the summary table by this column")]
+    pub sort_by: Option<SortKeyArg>,
+    #[arg(
+        long,
+        value_enum,
+        requires = "sort_by",
+        help = "Sort direction (default: desc for numeric columns, asc for name)"
+    )]
+    pub sort_order: Option<SortOrderArg>,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "ms",
+        help = "Time unit for durations in human and summary output"
+    )]
+    pub display_unit: DisplayUnitArg,
+    #[arg(
+        long,
+        help = "Group digits with thousands separators in human and summary output"
+    )]
+    pub thousands: bool,
     #[arg(long, value_enum, default_value = "human")]
     pub format: FormatArg,
     #[arg(
         long,
-        help = "Seed tie-breaks for least-connections/response-time; omit for stable input-order tie-breaks"
+        value_name = "SEED|auto",
+        value_parser = parse_seed_arg,
+        help = "Seed the workload and tie-breaks (`auto` draws one from entropy and reports it); omit for seed-0 arrivals and stable input-order tie-breaks"
+    )]
+    pub seed: Option<SeedArg>,
+    #[arg(
+        long,
+        value_enum,
+        help = "Generator for tie-breaks, Poisson arrivals, and latency sampling [default: std]"
+    )]
+    pub rng: Option<RngArg>,
+    #[arg(
+        long,
+        value_enum,
+        help = "Order of events due at the same millisecond [default: completions-first]"
+    )]
+    pub event_order: Option<EventOrderArg>,
+    #[arg(
+        long,
+        help = "Allow more than 10000 servers or 100000000 requests in one run"
+    )]
+    pub allow_large: bool,
+    #[arg(long, help = "Record a timeseries with buckets of this many ms")]
+    pub timeseries_bucket_ms: Option<u64>,
+    #[arg(long, help = "Record per-server queue depth and connection timelines")]
+    pub timelines: bool,
+    #[arg(
+        long,
+        value_name = "PERCENTILE:THRESHOLD_MS",
+        help = "Latency SLO to report compliance for, e.g. 99:250 (repeatable)"
+    )]
+    pub slo: Vec<String>,
+    #[arg(
+        long,
+        value_name = "N",
+        help = "List the N slowest requests with their queue wait"
+    )]
+    pub slowest: Option<usize>,
+    #[arg(
+        long,
+        help = "Record each assignment's candidates, scores and tie-break path (human/json)"
+    )]
+    pub explain: bool,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Write per-server timelines as CSV (implies --timelines)"
+    )]
+    pub timelines_csv: Option<PathBuf>,
+    #[arg(long, help = "Log every engine event to stderr")]
+    pub trace: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["replications", "seed_range", "scenario"],
+        help = "Pause after every engine event, print the server state delta and wait for a command"
+    )]
+    pub step: bool,
+    #[arg(
+        long,
+        value_name = "SPEED",
+        value_parser = parse_speed_arg,
+        conflicts_with_all = ["replications", "seed_range", "scenario", "step", "watch"],
+        help = "Pace the run against the wall clock, SPEED virtual ms per real ms (1 = real time)"
+    )]
+    pub real_time: Option<f64>,
+    #[arg(
+        long,
+        requires = "config",
+        conflicts_with_all = ["replications", "seed_range", "scenario", "step"],
+        help = "Rerun whenever the --config file changes and print how key metrics moved"
+    )]
+    pub watch: bool,
+    #[arg(
+        long,
+        help = "Fail instead of running if the result would depend on entropy or an implicit seed"
+    )]
+    pub deterministic: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["replications", "seed_range", "scenario", "watch"],
+        help = "Run the simulation twice in lockstep first and fail at the first event where the runs differ"
+    )]
+    pub verify_determinism: bool,
+    #[arg(
+        long,
+        help = "Resolve and validate the configuration, print it with derived workload figures, and exit"
+    )]
+    pub dry_run: bool,
+    #[arg(
+        long = "max-virtual-time",
+        value_name = "MS",
+        help = "Abort if the simulation clock passes this many ms"
+    )]
+    pub max_virtual_time_ms: Option<u64>,
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Abort after processing this many engine events"
+    )]
+    pub max_events: Option<u64>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Log every engine event to a file instead of stderr"
+    )]
+    pub trace_file: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["replications", "seed_range", "scenario", "watch"],
+        help = "Stream every processed engine event to a JSONL file"
+    )]
+    pub event_log: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = [
+            "replications", "seed_range", "scenario", "watch", "chart", "chrome_trace",
+            "otel_file", "otel_endpoint", "heatmap_bucket_ms",
+        ],
+        help = "Stream assignments to NDJSON (or CSV for .csv) instead of keeping them; summary percentiles come from histograms"
+    )]
+    pub stream_assignments: Option<PathBuf>,
+    #[arg(
+        long,
+        conflicts_with_all = ["replications", "seed_range", "scenario", "watch"],
+        help = "Print the run's peak queued events and kept assignments/samples to stderr"
+    )]
+    pub stats_memory: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["replications", "seed_range", "scenario", "watch"],
+        help = "Take summary percentiles from latency histograms instead of keeping every response time"
+    )]
+    pub histogram_percentiles: bool,
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with_all = [
+            "replications", "seed_range", "scenario", "watch", "chart", "chrome_trace",
+            "otel_file", "otel_endpoint", "heatmap_bucket_ms",
+        ],
+        help = "Keep a uniform random sample of N assignments for output instead of all of them; totals and percentiles still cover every request"
+    )]
+    pub sample_assignments: Option<usize>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Write request spans per server as a Chrome tracing (Perfetto) JSON file"
+    )]
+    pub chrome_trace: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "URL",
+        help = "Export request spans to an OTLP/HTTP collector (requires the `otel` feature)"
+    )]
+    pub otel_endpoint: Option<String>,
+    #[arg(long, value_name = "PATH", help = "Write request spans as OTLP/JSON")]
+    pub otel_file: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Render latency and request-share charts to an SVG file (requires the `charts` feature)"
+    )]
+    pub chart: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Run N replications with derived seeds and report mean, stddev and 95% CI"
     )]
-    pub seed: Option<u64>,
+    pub replications: Option<usize>,
+    #[arg(
+        long,
+        value_name = "START..END",
+        conflicts_with_all = ["seed", "replications"],
+        help = "Run once per seed in START..END (inclusive) and report metric distributions"
+    )]
+    pub seed_range: Option<String>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Write the formatted result to a file instead of stdout"
+    )]
+    pub output: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Print nothing on success; rely on the exit code and --output"
+    )]
+    pub quiet: bool,
+    #[arg(
+        long,
+        requires = "quiet",
+        help = "With --quiet, print a single one-line summary"
+    )]
+    pub summary_line: bool,
+    #[arg(
+        long,
+        value_name = "RATIO",
+        help = "Fail if any server's request count deviates from an even split by more than this ratio"
+    )]
+    pub assert_max_imbalance: Option<f64>,
+    #[arg(
+        long,
+        value_name = "MS",
+        help = "Fail if the overall p99 exceeds this many ms"
+    )]
+    pub assert_p99_ms: Option<u64>,
+    #[arg(
+        long,
+        value_name = "MS",
+        help = "Fail if the average queue wait exceeds this many ms"
+    )]
+    pub assert_max_avg_wait_ms: Option<u64>,
     #[arg(long)]
     pub config: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Ignore unknown keys in config files instead of rejecting them"
+    )]
+    pub lenient: bool,
+    #[arg(
+        long,
+        value_name = "NAME",
+        requires = "config",
+        help = "Apply the [profiles.NAME] overrides from the config file"
+    )]
+    pub profile: Option<String>,
+    #[arg(
+        long,
+        help = "Prompt for a missing algorithm, servers, or request count (default when run in a terminal)"
+    )]
+    pub interactive: bool,
+    #[arg(
+        long = "set",
+        value_name = "PATH=VALUE",
+        requires = "config",
+        help = "Override one config value after loading, e.g. server.api.weight=3 (repeatable)"
+    )]
+    pub set: Vec<String>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["config", "algo", "servers", "server", "servers_file", "requests", "burst", "overload", "replications", "seed_range"],
+        help = "Run every named run in a scenario file and print a combined comparison"
+    )]
+    pub scenario: Option<PathBuf>,
 }
 
 #[derive(Subcommand, Debug)]
@@ -54,18 +392,444 @@ pub enum Command {
     Run(RunArgs),
     /// List available algorithms
     ListAlgorithms,
+    /// List output formats, the commands that accept them, and their shape
+    ListFormats,
     /// Show the effective configuration
     ShowConfig(RunArgs),
+    /// Print the JSON Schema for `--format json` output, or for config files
+    Schema(SchemaArgs),
+    /// Rerun the simulation and compare it against a stored JSON result
+    Check(CheckArgs),
+    /// Validate a config file and report every problem found
+    Validate(ValidateArgs),
+    /// Re-render a result saved with `--format json` without re-simulating
+    Render(RenderArgs),
+    /// Turn a saved JSON result into a weighted nginx, HAProxy, or Envoy snippet
+    Export(ExportArgs),
+    /// Write the run's workload as a k6 script, Vegeta attack script, or wrk2 command
+    ExportWorkload(ExportWorkloadArgs),
+    /// Re-execute a run recorded with --trace-file and verify every event matches
+    Replay(ReplayArgs),
+    /// Run every algorithm on the workload and recommend one
+    Recommend(RecommendArgs),
+    /// Measure engine throughput (events/sec) on synthetic workloads
+    Bench(BenchArgs),
+    /// Serve simulations over gRPC (needs the `grpc` feature)
+    Serve(ServeArgs),
+    /// Probe real backends and write a fitted server list (needs the `calibrate` feature)
+    Calibrate(CalibrateArgs),
+    /// Convert an Envoy static cluster (YAML or JSON) into servers and an algorithm
+    ImportEnvoy(ImportEnvoyArgs),
+    /// Print a shell completion script
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
 }
 
-#[derive(Parser, Debug)]
-pub struct RunArgs {
+#[derive(Args, Debug)]
+pub struct ValidateArgs {
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Config file to validate (TOML or JSON)"
+    )]
+    pub config: PathBuf,
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Validate with the [profiles.NAME] overrides applied"
+    )]
+    pub profile: Option<String>,
+    #[arg(long, help = "Ignore unknown keys instead of reporting them")]
+    pub lenient: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct RecommendArgs {
+    #[arg(long, value_enum, default_value = "p99", help = "Metric to rank by")]
+    pub rank_by: RankByArg,
+    #[arg(
+        long,
+        value_name = "INDEX",
+        help = "Only recommend algorithms with Jain's fairness at or above this"
+    )]
+    pub min_fairness: Option<f64>,
+    #[arg(
+        long,
+        value_name = "MS",
+        help = "Only recommend algorithms with p99 at or below this"
+    )]
+    pub max_p99_ms: Option<u64>,
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum RankByArg {
+    P99,
+    AvgWait,
+    Fairness,
+    Throughput,
+}
+
+impl From<RankByArg> for RankCriterion {
+    fn from(value: RankByArg) -> Self {
+        match value {
+            RankByArg::P99 => RankCriterion::P99,
+            RankByArg::AvgWait => RankCriterion::AvgWait,
+            RankByArg::Fairness => RankCriterion::Fairness,
+            RankByArg::Throughput => RankCriterion::Throughput,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct BenchArgs {
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        help = "Algorithms to compare (comma-separated or repeated; default: all)"
+    )]
+    pub algo: Vec<AlgoArg>,
+    #[arg(
+        long,
+        value_name = "N,N,...",
+        value_delimiter = ',',
+        default_value = "10,100",
+        help = "Synthetic fleet sizes to run each algorithm against"
+    )]
+    pub server_counts: Vec<usize>,
+    #[arg(long, default_value_t = 100_000, help = "Requests per run")]
+    pub requests: usize,
+    #[arg(
+        long,
+        default_value_t = 3,
+        help = "Runs per case; the fastest is reported"
+    )]
+    pub iterations: usize,
+    #[arg(long, value_enum, default_value = "human")]
+    pub format: FormatArg,
+}
+
+#[derive(Args, Debug)]
+pub struct CalibrateArgs {
+    #[arg(
+        long,
+        value_name = "PATH",
+        required_unless_present = "prometheus",
+        conflicts_with = "prometheus",
+        help = "File with one `url` or `name url` per line (# comments allowed)"
+    )]
+    pub targets: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "URL",
+        requires = "query",
+        help = "Fit servers from a Prometheus server's latency histograms instead of probing"
+    )]
+    pub prometheus: Option<String>,
+    #[arg(
+        long,
+        value_name = "PROMQL",
+        requires = "prometheus",
+        help = "Instant query for cumulative latency buckets (`le` in seconds) per server, e.g. `sum by (le, instance) (rate(http_request_duration_seconds_bucket[5m]))`"
+    )]
+    pub query: Option<String>,
+    #[arg(
+        long,
+        value_name = "PROMQL",
+        requires = "prometheus",
+        help = "Instant query for requests per second per server; sizes each server's concurrency"
+    )]
+    pub rate_query: Option<String>,
+    #[arg(
+        long,
+        value_name = "LABEL",
+        default_value = "instance",
+        help = "Label that identifies the server in Prometheus series"
+    )]
+    pub server_label: String,
+    #[arg(long, default_value_t = 20, value_parser = clap::value_parser!(u64).range(1..), help = "Sequential GET probes per target")]
+    pub probes: u64,
+    #[arg(
+        long,
+        value_name = "MS",
+        default_value_t = 2000,
+        help = "Per-probe (or per-query) timeout"
+    )]
+    pub timeout_ms: u64,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Write the servers to a file (.json for JSON, otherwise TOML) instead of stdout"
+    )]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct ImportEnvoyArgs {
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Envoy bootstrap, clusters list, or single cluster (YAML or JSON)"
+    )]
+    pub input: PathBuf,
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Cluster to import when the file defines several"
+    )]
+    pub cluster: Option<String>,
+    #[arg(
+        long,
+        value_name = "MS",
+        default_value_t = 10,
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "base_latency_ms for every endpoint (Envoy configs carry no latencies)"
+    )]
+    pub latency_ms: u64,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Write the config to a file (.json for JSON, otherwise TOML) instead of stdout"
+    )]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct ServeArgs {
+    #[arg(
+        long,
+        value_name = "HOST:PORT",
+        default_value = "127.0.0.1:50051",
+        help = "Address to listen on"
+    )]
+    pub addr: std::net::SocketAddr,
+}
+
+#[derive(Args, Debug)]
+pub struct SchemaArgs {
+    #[arg(
+        long,
+        help = "Print the schema for config files instead of JSON results"
+    )]
+    pub config: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ReplayArgs {
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Event log written by run --trace-file"
+    )]
+    pub trace_file: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct RenderArgs {
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "JSON result written by --format json"
+    )]
+    pub input: PathBuf,
+    #[arg(long, value_enum, default_value = "human")]
+    pub format: RenderFormatArg,
+    #[arg(
+        long,
+        value_name = "PATH",
+        required_if_eq("format", "chart"),
+        help = "Write to a file instead of stdout (required for chart)"
+    )]
+    pub output: Option<PathBuf>,
+    #[command(flatten)]
+    pub display: DisplayArgs,
+}
+
+/// The run flags that shape how a result is printed, for `render`.
+#[derive(Args, Clone, Debug)]
+pub struct DisplayArgs {
+    #[arg(long, help = "Append ASCII bar charts and sparklines to human output")]
+    pub ascii_charts: bool,
+    #[arg(
+        long,
+        value_name = "MS",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Append an ASCII heatmap of assignments per server and time bucket"
+    )]
+    pub heatmap_bucket_ms: Option<u64>,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "auto",
+        help = "Colorize human output"
+    )]
+    pub color: ColorArg,
+    #[arg(long, value_enum, help = "Sort the summary table by this column")]
+    pub sort_by: Option<SortKeyArg>,
+    #[arg(
+        long,
+        value_enum,
+        requires = "sort_by",
+        help = "Sort direction (default: desc for numeric columns, asc for name)"
+    )]
+    pub sort_order: Option<SortOrderArg>,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "ms",
+        help = "Time unit for durations in human and summary output"
+    )]
+    pub display_unit: DisplayUnitArg,
+    #[arg(
+        long,
+        help = "Group digits with thousands separators in human and summary output"
+    )]
+    pub thousands: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderFormatArg {
+    Human,
+    Summary,
+    Json,
+    Csv,
+    Markdown,
+    Chart,
+}
+
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "JSON result written by --format json"
+    )]
+    pub input: PathBuf,
     #[arg(long, value_enum)]
-    pub algo: Option<AlgoArg>,
+    pub format: ExportFormatArg,
+    #[arg(
+        long,
+        default_value = "lb_sim",
+        help = "Upstream, backend, or cluster name"
+    )]
+    pub name: String,
+    #[arg(
+        long,
+        default_value_t = 80,
+        help = "Port for servers whose names carry no :port"
+    )]
+    pub port: u16,
+    #[arg(long, value_name = "PATH", help = "Write to a file instead of stdout")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct ExportWorkloadArgs {
+    #[arg(long, value_enum, help = "Load-testing tool to target")]
+    pub tool: LoadToolArg,
+    #[arg(long, value_name = "URL", help = "Endpoint every request is sent to")]
+    pub url: String,
+    #[arg(
+        long,
+        value_name = "MS",
+        default_value_t = 1000,
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Rate bucket for Vegeta attacks"
+    )]
+    pub bucket_ms: u64,
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoadToolArg {
+    K6,
+    Vegeta,
+    Wrk2,
+}
+
+impl From<LoadToolArg> for LoadTool {
+    fn from(value: LoadToolArg) -> Self {
+        match value {
+            LoadToolArg::K6 => LoadTool::K6,
+            LoadToolArg::Vegeta => LoadTool::Vegeta,
+            LoadToolArg::Wrk2 => LoadTool::Wrk2,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormatArg {
+    Nginx,
+    Haproxy,
+    Envoy,
+}
+
+impl From<ExportFormatArg> for ProxyFormat {
+    fn from(value: ExportFormatArg) -> Self {
+        match value {
+            ExportFormatArg::Nginx => ProxyFormat::Nginx,
+            ExportFormatArg::Haproxy => ProxyFormat::Haproxy,
+            ExportFormatArg::Envoy => ProxyFormat::Envoy,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct CheckArgs {
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "JSON result written by --format json"
+    )]
+    pub baseline: PathBuf,
+    #[arg(
+        long,
+        default_value_t = 0.05,
+        help = "Allowed relative deviation per metric (0.05 = 5%)"
+    )]
+    pub tolerance: f64,
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+#[derive(Parser, Clone, Debug)]
+pub struct RunArgs {
+    #[arg(long, value_parser = AlgoValueParser)]
+    pub algo: Option<AlgoConfig>,
     #[arg(long)]
     pub servers: Option<String>,
     #[arg(long, value_name = SERVER_SPEC_VALUE_NAME)]
     pub server: Vec<String>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Read server specs from a file, one per line or comma-separated; `#` starts a comment"
+    )]
+    pub servers_file: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Warn about and drop malformed or duplicate server entries instead of failing"
+    )]
+    pub skip_invalid_servers: bool,
+    #[arg(
+        long,
+        value_name = "NAME_{i}:LATENCY_MS[:WEIGHT]",
+        requires = "server_count",
+        help = "Server spec repeated --server-count times; `{i}` in the name becomes 1..=N"
+    )]
+    pub server_template: Option<String>,
+    #[arg(
+        long,
+        value_name = "N",
+        requires = "server_template",
+        help = "Number of servers generated from --server-template"
+    )]
+    pub server_count: Option<usize>,
     #[arg(long)]
     pub requests: Option<usize>,
     #[arg(long, help = "Send all requests at once (burst)")]
@@ -76,33 +840,456 @@ pub struct RunArgs {
     pub overload: bool,
     #[arg(
         long,
-        default_value_t = 1.1,
-        help = "Overload factor applied to total weighted capacity (Poisson rate)"
+        default_value_t = 1.1,
+        help = "Overload factor applied to total weighted capacity (Poisson rate)"
+    )]
+    pub overload_factor: f64,
+    #[arg(long, default_value_t = 1000, help = "Overload duration in ms")]
+    pub overload_duration_ms: u64,
+    #[arg(
+        long,
+        value_name = "RPS",
+        requires = "duration_ms",
+        conflicts_with_all = ["requests", "burst", "overload"],
+        help = "Poisson arrival rate in requests per second (with --duration-ms)"
+    )]
+    pub rate: Option<f64>,
+    #[arg(
+        long,
+        visible_alias = "duration",
+        value_name = "MS",
+        requires = "rate",
+        help = "Length of the Poisson arrival window in ms (with --rate)"
+    )]
+    pub duration_ms: Option<u64>,
+    #[arg(long)]
+    pub summary: bool,
+    #[arg(long, help = "Append ASCII bar charts and sparklines to human output")]
+    pub ascii_charts: bool,
+    #[arg(
+        long,
+        value_name = "MS",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Append an ASCII heatmap of assignments per server and time bucket"
+    )]
+    pub heatmap_bucket_ms: Option<u64>,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "auto",
+        help = "Colorize human output"
+    )]
+    pub color: ColorArg,
+    #[arg(long, value_enum, help = "Sort the summary table by this column")]
+    pub sort_by: Option<SortKeyArg>,
+    #[arg(
+        long,
+        value_enum,
+        requires = "sort_by",
+        help = "Sort direction (default: desc for numeric columns, asc for name)"
+    )]
+    pub sort_order: Option<SortOrderArg>,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "ms",
+        help = "Time unit for durations in human and summary output"
+    )]
+    pub display_unit: DisplayUnitArg,
+    #[arg(
+        long,
+        help = "Group digits with thousands separators in human and summary output"
+    )]
+    pub thousands: bool,
+    #[arg(long, value_enum, default_value = "human")]
+    pub format: FormatArg,
+    #[arg(
+        long,
+        value_name = "SEED|auto",
+        value_parser = parse_seed_arg,
+        help = "Seed the workload and tie-breaks (`auto` draws one from entropy and reports it); omit for seed-0 arrivals and stable input-order tie-breaks"
+    )]
+    pub seed: Option<SeedArg>,
+    #[arg(
+        long,
+        value_enum,
+        help = "Generator for tie-breaks, Poisson arrivals, and latency sampling [default: std]"
+    )]
+    pub rng: Option<RngArg>,
+    #[arg(
+        long,
+        value_enum,
+        help = "Order of events due at the same millisecond [default: completions-first]"
+    )]
+    pub event_order: Option<EventOrderArg>,
+    #[arg(
+        long,
+        help = "Allow more than 10000 servers or 100000000 requests in one run"
+    )]
+    pub allow_large: bool,
+    #[arg(long, help = "Record a timeseries with buckets of this many ms")]
+    pub timeseries_bucket_ms: Option<u64>,
+    #[arg(long, help = "Record per-server queue depth and connection timelines")]
+    pub timelines: bool,
+    #[arg(
+        long,
+        value_name = "PERCENTILE:THRESHOLD_MS",
+        help = "Latency SLO to report compliance for, e.g. 99:250 (repeatable)"
+    )]
+    pub slo: Vec<String>,
+    #[arg(
+        long,
+        value_name = "N",
+        help = "List the N slowest requests with their queue wait"
+    )]
+    pub slowest: Option<usize>,
+    #[arg(
+        long,
+        help = "Record each assignment's candidates, scores and tie-break path (human/json)"
+    )]
+    pub explain: bool,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Write per-server timelines as CSV (implies --timelines)"
+    )]
+    pub timelines_csv: Option<PathBuf>,
+    #[arg(long, help = "Log every engine event to stderr")]
+    pub trace: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["replications", "seed_range", "scenario"],
+        help = "Pause after every engine event, print the server state delta and wait for a command"
+    )]
+    pub step: bool,
+    #[arg(
+        long,
+        value_name = "SPEED",
+        value_parser = parse_speed_arg,
+        conflicts_with_all = ["replications", "seed_range", "scenario", "step", "watch"],
+        help = "Pace the run against the wall clock, SPEED virtual ms per real ms (1 = real time)"
+    )]
+    pub real_time: Option<f64>,
+    #[arg(
+        long,
+        requires = "config",
+        conflicts_with_all = ["replications", "seed_range", "scenario", "step"],
+        help = "Rerun whenever the --config file changes and print how key metrics moved"
+    )]
+    pub watch: bool,
+    #[arg(
+        long,
+        help = "Fail instead of running if the result would depend on entropy or an implicit seed"
+    )]
+    pub deterministic: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["replications", "seed_range", "scenario", "watch"],
+        help = "Run the simulation twice in lockstep first and fail at the first event where the runs differ"
+    )]
+    pub verify_determinism: bool,
+    #[arg(
+        long,
+        help = "Resolve and validate the configuration, print it with derived workload figures, and exit"
+    )]
+    pub dry_run: bool,
+    #[arg(
+        long = "max-virtual-time",
+        value_name = "MS",
+        help = "Abort if the simulation clock passes this many ms"
+    )]
+    pub max_virtual_time_ms: Option<u64>,
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Abort after processing this many engine events"
+    )]
+    pub max_events: Option<u64>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Log every engine event to a file instead of stderr"
+    )]
+    pub trace_file: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["replications", "seed_range", "scenario", "watch"],
+        help = "Stream every processed engine event to a JSONL file"
+    )]
+    pub event_log: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = [
+            "replications", "seed_range", "scenario", "watch", "chart", "chrome_trace",
+            "otel_file", "otel_endpoint", "heatmap_bucket_ms",
+        ],
+        help = "Stream assignments to NDJSON (or CSV for .csv) instead of keeping them; summary percentiles come from histograms"
+    )]
+    pub stream_assignments: Option<PathBuf>,
+    #[arg(
+        long,
+        conflicts_with_all = ["replications", "seed_range", "scenario", "watch"],
+        help = "Print the run's peak queued events and kept assignments/samples to stderr"
+    )]
+    pub stats_memory: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["replications", "seed_range", "scenario", "watch"],
+        help = "Take summary percentiles from latency histograms instead of keeping every response time"
+    )]
+    pub histogram_percentiles: bool,
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with_all = [
+            "replications", "seed_range", "scenario", "watch", "chart", "chrome_trace",
+            "otel_file", "otel_endpoint", "heatmap_bucket_ms",
+        ],
+        help = "Keep a uniform random sample of N assignments for output instead of all of them; totals and percentiles still cover every request"
+    )]
+    pub sample_assignments: Option<usize>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Write request spans per server as a Chrome tracing (Perfetto) JSON file"
+    )]
+    pub chrome_trace: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "URL",
+        help = "Export request spans to an OTLP/HTTP collector (requires the `otel` feature)"
+    )]
+    pub otel_endpoint: Option<String>,
+    #[arg(long, value_name = "PATH", help = "Write request spans as OTLP/JSON")]
+    pub otel_file: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Render latency and request-share charts to an SVG file (requires the `charts` feature)"
+    )]
+    pub chart: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Run N replications with derived seeds and report mean, stddev and 95% CI"
+    )]
+    pub replications: Option<usize>,
+    #[arg(
+        long,
+        value_name = "START..END",
+        conflicts_with_all = ["seed", "replications"],
+        help = "Run once per seed in START..END (inclusive) and report metric distributions"
+    )]
+    pub seed_range: Option<String>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Write the formatted result to a file instead of stdout"
+    )]
+    pub output: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Print nothing on success; rely on the exit code and --output"
+    )]
+    pub quiet: bool,
+    #[arg(
+        long,
+        requires = "quiet",
+        help = "With --quiet, print a single one-line summary"
     )]
-    pub overload_factor: f64,
-    #[arg(long, default_value_t = 1000, help = "Overload duration in ms")]
-    pub overload_duration_ms: u64,
-    #[arg(long)]
-    pub summary: bool,
-    #[arg(long, value_enum, default_value = "human")]
-    pub format: FormatArg,
+    pub summary_line: bool,
+    #[arg(
+        long,
+        value_name = "RATIO",
+        help = "Fail if any server's request count deviates from an even split by more than this ratio"
+    )]
+    pub assert_max_imbalance: Option<f64>,
+    #[arg(
+        long,
+        value_name = "MS",
+        help = "Fail if the overall p99 exceeds this many ms"
+    )]
+    pub assert_p99_ms: Option<u64>,
     #[arg(
         long,
-        help = "Seed tie-breaks for least-connections/response-time; omit for stable input-order tie-breaks"
+        value_name = "MS",
+        help = "Fail if the average queue wait exceeds this many ms"
     )]
-    pub seed: Option<u64>,
+    pub assert_max_avg_wait_ms: Option<u64>,
     #[arg(long)]
     pub config: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Ignore unknown keys in config files instead of rejecting them"
+    )]
+    pub lenient: bool,
+    #[arg(
+        long,
+        value_name = "NAME",
+        requires = "config",
+        help = "Apply the [profiles.NAME] overrides from the config file"
+    )]
+    pub profile: Option<String>,
+    #[arg(
+        long,
+        help = "Prompt for a missing algorithm, servers, or request count (default when run in a terminal)"
+    )]
+    pub interactive: bool,
+    #[arg(
+        long = "set",
+        value_name = "PATH=VALUE",
+        requires = "config",
+        help = "Override one config value after loading, e.g. server.api.weight=3 (repeatable)"
+    )]
+    pub set: Vec<String>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["config", "algo", "servers", "server", "servers_file", "requests", "burst", "overload", "replications", "seed_range"],
+        help = "Run every named run in a scenario file and print a combined comparison"
+    )]
+    pub scenario: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SeedArg {
+    Fixed(u64),
+    Auto,
+}
+
+impl SeedArg {
+    /// Resolves `auto` to a fresh seed from OS entropy.
+    pub fn resolve(self) -> u64 {
+        match self {
+            SeedArg::Fixed(seed) => seed,
+            SeedArg::Auto => rand::random(),
+        }
+    }
+}
+
+fn parse_seed_arg(value: &str) -> std::result::Result<SeedArg, String> {
+    if value.eq_ignore_ascii_case("auto") {
+        return Ok(SeedArg::Auto);
+    }
+    value
+        .parse::<u64>()
+        .map(SeedArg::Fixed)
+        .map_err(|_| format!("expected an unsigned integer or `auto`, got '{}'", value))
+}
+
+fn parse_speed_arg(value: &str) -> std::result::Result<f64, String> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|speed| speed.is_finite() && *speed > 0.0)
+        .ok_or_else(|| format!("expected a positive number, got '{}'", value))
 }
 
 #[derive(ValueEnum, Clone, Debug)]
 pub enum AlgoArg {
+    #[value(alias = "rr")]
     RoundRobin,
+    #[value(alias = "wrr")]
     WeightedRoundRobin,
+    #[value(alias = "lc")]
     LeastConnections,
+    #[value(alias = "lrt")]
     LeastResponseTime,
 }
 
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RngArg {
+    Std,
+    Small,
+    Xoshiro,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventOrderArg {
+    CompletionsFirst,
+    ArrivalsFirst,
+    Fifo,
+}
+
+impl From<EventOrderArg> for EventOrder {
+    fn from(value: EventOrderArg) -> Self {
+        match value {
+            EventOrderArg::CompletionsFirst => EventOrder::CompletionsFirst,
+            EventOrderArg::ArrivalsFirst => EventOrder::ArrivalsFirst,
+            EventOrderArg::Fifo => EventOrder::Fifo,
+        }
+    }
+}
+
+impl From<RngArg> for RngKind {
+    fn from(value: RngArg) -> Self {
+        match value {
+            RngArg::Std => RngKind::Std,
+            RngArg::Small => RngKind::Small,
+            RngArg::Xoshiro => RngKind::Xoshiro,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorArg {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum SortKeyArg {
+    Requests,
+    Avg,
+    P99,
+    Name,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum SortOrderArg {
+    Asc,
+    Desc,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum DisplayUnitArg {
+    Ms,
+    S,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct LogArgs {
+    #[arg(
+        long,
+        value_enum,
+        global = true,
+        default_value = "warn",
+        help = "Diagnostic log verbosity on stderr"
+    )]
+    pub log_level: LogLevelArg,
+    #[arg(long, value_enum, global = true, default_value = "text")]
+    pub log_format: LogFormatArg,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogLevelArg {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormatArg {
+    Text,
+    Json,
+}
+
 #[derive(ValueEnum, Clone, Debug, PartialEq, Eq)]
 pub enum FormatArg {
     Human,
@@ -110,6 +1297,48 @@ pub enum FormatArg {
     Json,
 }
 
+/// `--algo` values: built-in names and aliases (listed in help and
+/// completions) plus anything added with `algorithms::register`.
+#[derive(Clone)]
+struct AlgoValueParser;
+
+impl TypedValueParser for AlgoValueParser {
+    type Value = AlgoConfig;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> std::result::Result<AlgoConfig, clap::Error> {
+        if let Some(name) = value.to_str() {
+            if algorithms::is_registered(name) {
+                return Ok(AlgoConfig::Custom(name.to_string()));
+            }
+        }
+        EnumValueParser::<AlgoArg>::new()
+            .parse_ref(cmd, arg, value)
+            .map(Into::into)
+    }
+
+    /// The built-ins, then every name registered so far, for `--help` and
+    /// shell completions.
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        let registered = algorithms::available()
+            .into_iter()
+            .filter_map(|algo| match algo {
+                AlgoConfig::Custom(name) => Some(PossibleValue::new(name)),
+                _ => None,
+            });
+        Some(Box::new(
+            AlgoArg::value_variants()
+                .iter()
+                .filter_map(ValueEnum::to_possible_value)
+                .chain(registered),
+        ))
+    }
+}
+
 impl From<AlgoArg> for AlgoConfig {
     fn from(value: AlgoArg) -> Self {
         match value {
@@ -121,36 +1350,168 @@ impl From<AlgoArg> for AlgoConfig {
     }
 }
 
+pub fn write_completions(shell: clap_complete::Shell, out: &mut dyn std::io::Write) {
+    clap_complete::generate(shell, &mut CliArgs::command(), "lb-sim", out);
+}
+
 pub fn parse_args() -> Result<CliArgs> {
     CliArgs::try_parse().map_err(|e| Error::Cli(e.to_string()))
 }
 
-pub fn parse_command() -> Result<Command> {
+/// The subcommand to run (a bare invocation is `run`) and the global logging
+/// flags.
+pub fn parse_command() -> Result<(Command, LogArgs)> {
     let args = parse_args()?;
-    match args.command {
-        Some(cmd) => Ok(cmd),
+    let log = args.log.clone();
+    let command = match args.command {
+        Some(cmd) => cmd,
         None => {
             let run_args = RunArgs {
                 algo: args.algo,
                 servers: args.servers,
                 server: args.server,
+                servers_file: args.servers_file,
+                skip_invalid_servers: args.skip_invalid_servers,
+                server_template: args.server_template,
+                server_count: args.server_count,
                 requests: args.requests,
                 burst: args.burst,
                 burst_at: args.burst_at,
                 overload: args.overload,
                 overload_factor: args.overload_factor,
                 overload_duration_ms: args.overload_duration_ms,
+                rate: args.rate,
+                duration_ms: args.duration_ms,
                 summary: args.summary,
+                ascii_charts: args.ascii_charts,
+                heatmap_bucket_ms: args.heatmap_bucket_ms,
+                color: args.color,
+                sort_by: args.sort_by,
+                sort_order: args.sort_order,
+                display_unit: args.display_unit,
+                thousands: args.thousands,
                 format: args.format,
                 seed: args.seed,
+                rng: args.rng,
+                event_order: args.event_order,
+                allow_large: args.allow_large,
+                timeseries_bucket_ms: args.timeseries_bucket_ms,
+                timelines: args.timelines,
+                slo: args.slo,
+                slowest: args.slowest,
+                explain: args.explain,
+                timelines_csv: args.timelines_csv,
+                trace: args.trace,
+                step: args.step,
+                real_time: args.real_time,
+                watch: args.watch,
+                deterministic: args.deterministic,
+                verify_determinism: args.verify_determinism,
+                dry_run: args.dry_run,
+                max_virtual_time_ms: args.max_virtual_time_ms,
+                max_events: args.max_events,
+                trace_file: args.trace_file,
+                event_log: args.event_log,
+                stream_assignments: args.stream_assignments,
+                stats_memory: args.stats_memory,
+                histogram_percentiles: args.histogram_percentiles,
+                sample_assignments: args.sample_assignments,
+                chrome_trace: args.chrome_trace,
+                otel_endpoint: args.otel_endpoint,
+                otel_file: args.otel_file,
+                chart: args.chart,
+                replications: args.replications,
+                seed_range: args.seed_range,
+                output: args.output,
+                quiet: args.quiet,
+                summary_line: args.summary_line,
+                assert_max_imbalance: args.assert_max_imbalance,
+                assert_p99_ms: args.assert_p99_ms,
+                assert_max_avg_wait_ms: args.assert_max_avg_wait_ms,
                 config: args.config,
+                lenient: args.lenient,
+                profile: args.profile,
+                interactive: args.interactive,
+                set: args.set,
+                scenario: args.scenario,
             };
-            Ok(Command::Run(run_args))
+            Command::Run(run_args)
         }
-    }
+    };
+    Ok((command, log))
 }
 
 pub fn build_config_from_run_args(args: RunArgs) -> Result<(SimConfig, FormatArg)> {
+    let strict = args.deterministic.then_some(args.seed);
+    // Each --seed-range run is given an explicit seed from the range.
+    let seeds_supplied = args.seed_range.is_some();
+    let (config, format) = resolve_run_config(args)?;
+    if let Some(seed_arg) = strict {
+        check_deterministic(&config, seed_arg, seeds_supplied)?;
+    }
+    Ok((config, format))
+}
+
+/// `--deterministic`: rejects runs whose outcome depends on entropy or on an
+/// implicit default seed.
+pub fn check_deterministic(
+    config: &SimConfig,
+    seed_arg: Option<SeedArg>,
+    seeds_supplied: bool,
+) -> Result<()> {
+    let has_seed = matches!(config.randomness, RandomnessConfig::Seeded(_)) || seeds_supplied;
+    let mut problems = Vec::new();
+    if seed_arg == Some(SeedArg::Auto) {
+        problems.push("`--seed auto` draws the seed from entropy".to_string());
+    }
+    if config.randomness == RandomnessConfig::Entropy && !seeds_supplied {
+        problems.push("randomness = \"entropy\" draws the seed from entropy".to_string());
+    }
+    if matches!(config.requests, RequestProfile::Poisson { .. }) && !has_seed {
+        problems.push("Poisson arrivals would use the implicit seed 0; pass --seed".to_string());
+    }
+    if !has_seed {
+        for server in &config.servers {
+            if !matches!(
+                server.model.latency,
+                None | Some(LatencyDistribution::Fixed)
+            ) {
+                problems.push(format!(
+                    "server '{}' samples latency with the implicit seed 0; pass --seed",
+                    server.name
+                ));
+            }
+        }
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::NonDeterministic(problems.join("; ")))
+    }
+}
+
+/// Environment variable holding `--set` overrides, separated by `;`.
+pub const ENV_OVERRIDES: &str = "LB_SIM_SET";
+
+/// The `PATH=VALUE` overrides in `LB_SIM_SET`, if it is set.
+fn env_overrides() -> Result<Vec<String>> {
+    match std::env::var(ENV_OVERRIDES) {
+        Ok(value) => Ok(value
+            .split(';')
+            .map(str::trim)
+            .filter(|spec| !spec.is_empty())
+            .map(str::to_string)
+            .collect()),
+        Err(std::env::VarError::NotPresent) => Ok(Vec::new()),
+        Err(std::env::VarError::NotUnicode(_)) => {
+            Err(Error::Cli(format!("{} is not valid UTF-8", ENV_OVERRIDES)))
+        }
+    }
+}
+
+/// Layers a run's config: the `--config` file, then `LB_SIM_SET`, then
+/// `--set` and the other flags, so a flag beats the environment.
+fn resolve_run_config(args: RunArgs) -> Result<(SimConfig, FormatArg)> {
     let format = format_arg_from_run_args(&args);
     if args.requests.is_some() && args.burst.is_some() {
         return Err(Error::Cli(
@@ -172,15 +1533,29 @@ pub fn build_config_from_run_args(args: RunArgs) -> Result<(SimConfig, FormatArg
             "--overload-duration-ms must be greater than 0".to_string(),
         ));
     }
+    let env_overrides = env_overrides()?;
     let mut config = if let Some(path) = args.config.as_ref() {
-        load_config(path)?
+        let config = load_config_profile(path, args.profile.as_deref(), args.lenient)?;
+        let overrides = env_overrides
+            .into_iter()
+            .chain(args.set.iter().cloned())
+            .collect::<Vec<_>>();
+        apply_config_overrides(config, &overrides, args.lenient)?
     } else {
+        if !env_overrides.is_empty() {
+            return Err(Error::Cli(format!(
+                "{} overrides apply to a config file; pass --config",
+                ENV_OVERRIDES
+            )));
+        }
         let algo = args
             .algo
             .clone()
             .ok_or_else(|| Error::Cli("missing required --algo".to_string()))?;
-        let servers = parse_server_args(&args.server, args.servers.as_deref())?;
-        let requests = if args.overload {
+        let servers = parse_run_servers(&args)?;
+        let requests = if let Some(profile) = poisson_from_run_args(&args) {
+            profile
+        } else if args.overload {
             RequestProfile::Poisson {
                 rate: capacity_rps(&servers) * args.overload_factor,
                 duration_ms: args.overload_duration_ms,
@@ -194,7 +1569,7 @@ pub fn build_config_from_run_args(args: RunArgs) -> Result<(SimConfig, FormatArg
                 },
                 (None, None) => {
                     return Err(Error::Cli(
-                        "missing required --requests, --burst, or --overload".to_string(),
+                        "missing required --requests, --burst, --rate, or --overload".to_string(),
                     ))
                 }
                 (Some(_), Some(_)) => {
@@ -204,19 +1579,26 @@ pub fn build_config_from_run_args(args: RunArgs) -> Result<(SimConfig, FormatArg
                 }
             }
         };
-        let tie_break = if args.seed.is_some() {
-            TieBreakConfig::Seeded
-        } else {
-            TieBreakConfig::Stable
-        };
-        return Ok((
-            create_config(servers, requests, algo, tie_break, args.seed),
-            format,
-        ));
+        let randomness = args.seed.map_or(RandomnessConfig::Stable, |seed| {
+            RandomnessConfig::Seeded(seed.resolve())
+        });
+        let mut config = create_config(servers, requests, algo, randomness);
+        if let Some(rng) = args.rng {
+            config.rng = rng.into();
+        }
+        if let Some(event_order) = args.event_order {
+            config.event_order = event_order.into();
+        }
+        if args.allow_large {
+            config.allow_large = true;
+        }
+        apply_metrics_args(&mut config, &args)?;
+        return Ok((config, format));
     };
+    apply_metrics_args(&mut config, &args)?;
 
-    if let Some(algo) = args.algo {
-        config.algo = algo.into();
+    if let Some(algo) = args.algo.clone() {
+        config.algo = algo;
     }
     if let Some(requests) = args.requests {
         config.requests = RequestProfile::FixedCount(requests);
@@ -227,6 +1609,9 @@ pub fn build_config_from_run_args(args: RunArgs) -> Result<(SimConfig, FormatArg
             at_ms: args.burst_at,
         };
     }
+    if let Some(profile) = poisson_from_run_args(&args) {
+        config.requests = profile;
+    }
     if args.overload {
         let rate = capacity_rps(&config.servers) * args.overload_factor;
         config.requests = RequestProfile::Poisson {
@@ -234,86 +1619,275 @@ pub fn build_config_from_run_args(args: RunArgs) -> Result<(SimConfig, FormatArg
             duration_ms: args.overload_duration_ms,
         };
     }
-    if !args.server.is_empty() || args.servers.is_some() {
-        config.servers = parse_server_args(&args.server, args.servers.as_deref())?;
+    if !args.server.is_empty()
+        || args.servers.is_some()
+        || args.servers_file.is_some()
+        || args.server_template.is_some()
+    {
+        config.servers = parse_run_servers(&args)?;
+    }
+    if let Some(seed) = args.seed {
+        config.randomness = RandomnessConfig::Seeded(seed.resolve());
     }
-    if args.seed.is_some() {
-        config.seed = args.seed;
-        config.tie_break = TieBreakConfig::Seeded;
+    if let Some(rng) = args.rng {
+        config.rng = rng.into();
+    }
+    if let Some(event_order) = args.event_order {
+        config.event_order = event_order.into();
+    }
+    if args.allow_large {
+        config.allow_large = true;
     }
 
     Ok((config, format))
 }
 
-pub fn load_config(path: &Path) -> Result<SimConfig> {
-    let contents = fs::read_to_string(path).map_err(|err| {
-        Error::ConfigIo(format!(
-            "failed to read config '{}': {}",
-            path.display(),
-            err
-        ))
-    })?;
-    let ext = path
-        .extension()
-        .and_then(|value| value.to_str())
-        .unwrap_or("");
+fn poisson_from_run_args(args: &RunArgs) -> Option<RequestProfile> {
+    match (args.rate, args.duration_ms) {
+        (Some(rate), Some(duration_ms)) => Some(RequestProfile::Poisson { rate, duration_ms }),
+        _ => None,
+    }
+}
 
-    match ext {
-        "toml" => toml::from_str(&contents)
-            .map_err(|err| Error::ConfigParse(format!("failed to parse TOML: {}", err))),
-        "json" => serde_json::from_str(&contents)
-            .map_err(|err| Error::ConfigParse(format!("failed to parse JSON: {}", err))),
-        "" => Err(Error::UnsupportedConfigFormat("unknown".to_string())),
-        _ => Err(Error::UnsupportedConfigFormat(ext.to_string())),
+fn apply_metrics_args(config: &mut SimConfig, args: &RunArgs) -> Result<()> {
+    if args.timeseries_bucket_ms.is_some() {
+        config.timeseries_bucket_ms = args.timeseries_bucket_ms;
+    }
+    if args.timelines || args.timelines_csv.is_some() {
+        config.record_timelines = true;
+    }
+    if !args.slo.is_empty() {
+        config.slos = args
+            .slo
+            .iter()
+            .map(|entry| parse_slo_spec(entry))
+            .collect::<Result<Vec<_>>>()?;
+    }
+    if let Some(count) = args.slowest {
+        config.slowest_requests = count;
     }
+    if args.explain {
+        config.explain = true;
+    }
+    if args.max_virtual_time_ms.is_some() {
+        config.max_virtual_time_ms = args.max_virtual_time_ms;
+    }
+    if args.max_events.is_some() {
+        config.max_events = args.max_events;
+    }
+    Ok(())
 }
 
-pub fn parse_server_args(
-    server_entries: &[String],
-    servers_csv: Option<&str>,
-) -> Result<Vec<ServerConfig>> {
-    let mut entries: Vec<String> = Vec::new();
-
-    if let Some(csv) = servers_csv {
-        let trimmed = csv.trim();
-        if !trimmed.is_empty() {
-            for entry in trimmed.split(',') {
-                let trimmed_entry = entry.trim();
-                if trimmed_entry.is_empty() {
-                    return Err(Error::EmptyServerEntry);
-                }
-                entries.push(trimmed_entry.to_string());
-            }
+pub fn parse_slo_spec(entry: &str) -> Result<SloConfig> {
+    let trimmed = entry.trim();
+    let (percentile, threshold) = trimmed
+        .split_once(':')
+        .ok_or_else(|| Error::InvalidSlo(trimmed.to_string()))?;
+    let percentile = percentile
+        .trim()
+        .trim_start_matches(['p', 'P'])
+        .parse::<f64>()
+        .map_err(|_| Error::InvalidSlo(trimmed.to_string()))?;
+    let threshold_ms = threshold
+        .trim()
+        .trim_end_matches("ms")
+        .parse::<u64>()
+        .map_err(|_| Error::InvalidSlo(trimmed.to_string()))?;
+    if let Some(err) = slo_problem(percentile, trimmed) {
+        return Err(err);
+    }
+    Ok(SloConfig {
+        percentile,
+        threshold_ms,
+    })
+}
+
+/// Parses an inclusive `START..END` seed range, e.g. `1..100`.
+pub fn parse_seed_range(entry: &str) -> Result<Vec<u64>> {
+    let trimmed = entry.trim();
+    let (start, end) = trimmed
+        .split_once("..")
+        .ok_or_else(|| Error::InvalidSeedRange(trimmed.to_string()))?;
+    let end = end.strip_prefix('=').unwrap_or(end);
+    let start = start
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| Error::InvalidSeedRange(trimmed.to_string()))?;
+    let end = end
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| Error::InvalidSeedRange(trimmed.to_string()))?;
+    if start > end {
+        return Err(Error::InvalidSeedRange(trimmed.to_string()));
+    }
+    Ok((start..=end).collect())
+}
+
+/// Reads the run's server specs from every source, in order: `--servers`,
+/// `--servers-file`, `--server`, then `--server-template`.
+fn parse_run_servers(args: &RunArgs) -> Result<Vec<ServerConfig>> {
+    let inventory = args
+        .servers_file
+        .as_ref()
+        .map(|path| {
+            fs::read_to_string(path)
+                .map(|contents| (path.display().to_string(), contents))
+                .map_err(|err| Error::io("servers file", path, err))
+        })
+        .transpose()?;
+    let templated = match (args.server_template.as_ref(), args.server_count) {
+        (Some(template), Some(count)) => expand_server_template(template, count),
+        _ => Vec::new(),
+    };
+
+    let mut parser = ServerListParser::new(args.skip_invalid_servers);
+    parser.csv(args.servers.as_deref());
+    if let Some((source, contents)) = inventory.as_ref() {
+        parser.list(source, contents);
+    }
+    parser.flags("--server", &args.server);
+    parser.flags("--server-template", &templated);
+    parser.finish()
+}
+
+/// Parses server specs in one pass over borrowed input: names are checked for
+/// duplicates as slices of the specs, each server's name is allocated once,
+/// and an entry's origin (`--server #2`, `hosts.txt:4:1`) is only formatted
+/// when the entry is rejected. Every rejected entry is collected, so one run
+/// reports all of them.
+struct ServerListParser<'a> {
+    skip_invalid: bool,
+    servers: Vec<ServerConfig>,
+    names: HashSet<&'a str>,
+    entries: usize,
+    problems: Vec<(String, Error)>,
+}
+
+impl<'a> ServerListParser<'a> {
+    fn new(skip_invalid: bool) -> Self {
+        Self {
+            skip_invalid,
+            servers: Vec::new(),
+            names: HashSet::new(),
+            entries: 0,
+            problems: Vec::new(),
         }
     }
 
-    for entry in server_entries {
-        let trimmed = entry.trim();
+    fn csv(&mut self, servers_csv: Option<&'a str>) {
+        let trimmed = servers_csv.map(str::trim).unwrap_or_default();
         if trimmed.is_empty() {
-            return Err(Error::EmptyServerEntry);
+            return;
+        }
+        for (idx, spec) in trimmed.split(',').enumerate() {
+            self.push(spec, || format!("--servers item {}", idx + 1));
+        }
+    }
+
+    fn flags(&mut self, flag: &str, specs: &'a [String]) {
+        for (idx, spec) in specs.iter().enumerate() {
+            self.push(spec, || format!("{} #{}", flag, idx + 1));
+        }
+    }
+
+    /// Inventory-file specs: separated by newlines or commas, with blank
+    /// entries and `#` comments skipped.
+    fn list(&mut self, source: &str, contents: &'a str) {
+        for (line_idx, line) in contents.lines().enumerate() {
+            let line = line.split_once('#').map_or(line, |(spec, _)| spec);
+            let mut column = 1;
+            for item in line.split(',') {
+                let spec = item.trim_start();
+                if !spec.trim_end().is_empty() {
+                    let leading = item.len() - spec.len();
+                    self.push(spec, || {
+                        format!("{}:{}:{}", source, line_idx + 1, column + leading)
+                    });
+                }
+                column += item.len() + 1;
+            }
         }
-        entries.push(trimmed.to_string());
     }
 
-    if entries.is_empty() {
-        return Err(Error::EmptyServers);
+    fn push(&mut self, spec: &'a str, origin: impl FnOnce() -> String) {
+        self.entries += 1;
+        let parsed = parse_server_fields(spec).and_then(|fields| {
+            if self.names.insert(fields.0) {
+                Ok(fields)
+            } else {
+                Err(Error::DuplicateServerName(fields.0.to_string()))
+            }
+        });
+        match parsed {
+            Ok((name, base_latency_ms, weight)) => self.servers.push(ServerConfig {
+                name: name.to_string(),
+                base_latency_ms,
+                weight,
+                model: ServerModel::default(),
+            }),
+            Err(err) => {
+                let origin = origin();
+                if self.skip_invalid {
+                    tracing::warn!(origin = %origin, "skipping server entry: {}", err);
+                }
+                self.problems.push((origin, err));
+            }
+        }
     }
 
-    let mut servers = Vec::new();
-    let mut names = HashSet::new();
-    for entry in entries {
-        let server = parse_server_spec(&entry)?;
-        if names.contains(&server.name) {
-            return Err(Error::DuplicateServerName(server.name));
+    /// With `skip_invalid`, rejected entries were logged as warnings and are
+    /// dropped; otherwise any rejected entry fails the parse.
+    fn finish(self) -> Result<Vec<ServerConfig>> {
+        if self.entries == 0 {
+            return Err(Error::EmptyServers);
+        }
+        let skipped = self.problems.len();
+        if self.skip_invalid && skipped > 0 {
+            tracing::warn!(
+                skipped,
+                kept = self.servers.len(),
+                "skipped {} invalid server entr{}",
+                skipped,
+                if skipped == 1 { "y" } else { "ies" }
+            );
+        } else if skipped == 1 {
+            let (_, err) = self.problems.into_iter().next().expect("one problem");
+            return Err(err);
+        } else if skipped > 1 {
+            return Err(Error::InvalidServerEntries(
+                self.problems
+                    .into_iter()
+                    .map(|(origin, err)| format!("{}: {}", origin, err))
+                    .collect(),
+            ));
         }
-        names.insert(server.name.clone());
-        servers.push(server);
+        if self.servers.is_empty() {
+            return Err(Error::EmptyServers);
+        }
+        Ok(self.servers)
     }
+}
+
+/// Expands `{i}` in a server spec to 1..=count, e.g. `api-{i}:10` -> `api-1:10`, `api-2:10`.
+pub fn expand_server_template(template: &str, count: usize) -> Vec<String> {
+    (1..=count)
+        .map(|idx| template.replace("{i}", &idx.to_string()))
+        .collect()
+}
 
-    Ok(servers)
+pub fn parse_server_args(
+    server_entries: &[String],
+    servers_csv: Option<&str>,
+) -> Result<Vec<ServerConfig>> {
+    let mut parser = ServerListParser::new(false);
+    parser.csv(servers_csv);
+    parser.flags("--server", server_entries);
+    parser.finish()
 }
 
-fn parse_server_spec(entry: &str) -> Result<ServerConfig> {
+/// Splits `name:latency_ms[:weight]` without allocating; errors quote the
+/// trimmed spec.
+fn parse_server_fields(entry: &str) -> Result<(&str, u64, u32)> {
     let trimmed = entry.trim();
     if trimmed.is_empty() {
         return Err(Error::EmptyServerEntry);
@@ -333,8 +1907,8 @@ fn parse_server_spec(entry: &str) -> Result<ServerConfig> {
     let latency_ms: u64 = latency_str
         .parse()
         .map_err(|_| Error::InvalidLatency(trimmed.to_string()))?;
-    if latency_ms == 0 {
-        return Err(Error::InvalidLatencyValue(trimmed.to_string()));
+    if let Some(err) = latency_problem(latency_ms, trimmed) {
+        return Err(err);
     }
 
     let weight = match weight_str {
@@ -343,34 +1917,93 @@ fn parse_server_spec(entry: &str) -> Result<ServerConfig> {
             .map_err(|_| Error::InvalidWeight(trimmed.to_string()))?,
         None => 1,
     };
-    if weight == 0 {
-        return Err(Error::InvalidWeightValue(trimmed.to_string()));
+    if let Some(err) = weight_problem(weight, trimmed) {
+        return Err(err);
     }
 
-    Ok(ServerConfig {
-        name: name.to_string(),
-        base_latency_ms: latency_ms,
-        weight,
-    })
+    Ok((name, latency_ms, weight))
 }
 
 fn create_config(
     servers: Vec<ServerConfig>,
     requests: RequestProfile,
-    algo: AlgoArg,
-    tie_break: TieBreakConfig,
-    seed: Option<u64>,
+    algo: AlgoConfig,
+    randomness: RandomnessConfig,
 ) -> SimConfig {
     SimConfig {
         servers,
         requests,
-        algo: algo.into(),
-        tie_break,
-        seed,
+        algo,
+        randomness,
+        rng: RngKind::Std,
+        event_order: EventOrder::CompletionsFirst,
+        allow_large: false,
+        timeseries_bucket_ms: None,
+        record_timelines: false,
+        slos: Vec::new(),
+        slowest_requests: 0,
+        explain: false,
+        max_virtual_time_ms: None,
+        max_events: None,
+    }
+}
+
+pub fn output_options_from_run_args(args: &RunArgs) -> OutputOptions {
+    output_options(&DisplayArgs {
+        ascii_charts: args.ascii_charts,
+        heatmap_bucket_ms: args.heatmap_bucket_ms,
+        color: args.color,
+        sort_by: args.sort_by,
+        sort_order: args.sort_order,
+        display_unit: args.display_unit,
+        thousands: args.thousands,
+    })
+}
+
+pub fn output_options(args: &DisplayArgs) -> OutputOptions {
+    let color = match args.color {
+        ColorArg::Always => true,
+        ColorArg::Never => false,
+        ColorArg::Auto => std::env::var_os("NO_COLOR").is_none() && std::io::stdout().is_terminal(),
+    };
+    let sort = args.sort_by.map(|key| {
+        let key = match key {
+            SortKeyArg::Requests => SortKey::Requests,
+            SortKeyArg::Avg => SortKey::AvgResponse,
+            SortKeyArg::P99 => SortKey::P99,
+            SortKeyArg::Name => SortKey::Name,
+        };
+        let descending = match args.sort_order {
+            Some(SortOrderArg::Asc) => false,
+            Some(SortOrderArg::Desc) => true,
+            None => key != SortKey::Name,
+        };
+        SummarySort { key, descending }
+    });
+    OutputOptions {
+        ascii_charts: args.ascii_charts,
+        heatmap_bucket_ms: args.heatmap_bucket_ms,
+        color,
+        sort,
+        numbers: NumberFormat {
+            unit: match args.display_unit {
+                DisplayUnitArg::Ms => TimeUnit::Ms,
+                DisplayUnitArg::S => TimeUnit::S,
+            },
+            thousands: args.thousands,
+        },
+    }
+}
+
+pub fn assertion_thresholds_from_run_args(args: &RunArgs) -> AssertionThresholds {
+    AssertionThresholds {
+        max_imbalance: args.assert_max_imbalance,
+        p99_ms: args.assert_p99_ms,
+        max_avg_wait_ms: args.assert_max_avg_wait_ms,
     }
 }
 
-fn format_arg_from_run_args(args: &RunArgs) -> FormatArg {
+pub fn format_arg_from_run_args(args: &RunArgs) -> FormatArg {
     if args.summary {
         FormatArg::Summary
     } else {
@@ -394,14 +2027,30 @@ pub fn format_config(config: &SimConfig) -> String {
         }
     };
 
-    let tie_break_label = config.tie_break.label_with_seed(config.seed);
-
     let mut lines = vec![
         format!("Algorithm: {}", algo_label),
         requests_label,
-        format!("Tie-break: {}", tie_break_label),
-        "Servers:".to_string(),
+        format!("Randomness: {}", config.randomness),
     ];
+    if !config.event_order.is_default() {
+        lines.push(format!("Event order: {}", config.event_order));
+    }
+    if let Some(bucket_ms) = config.timeseries_bucket_ms {
+        lines.push(format!("Timeseries bucket: {}ms", bucket_ms));
+    }
+    if config.record_timelines {
+        lines.push("Timelines: enabled".to_string());
+    }
+    for slo in &config.slos {
+        lines.push(format!("SLO: {}", slo));
+    }
+    if config.explain {
+        lines.push("Explain: enabled".to_string());
+    }
+    if config.slowest_requests > 0 {
+        lines.push(format!("Slowest requests: {}", config.slowest_requests));
+    }
+    lines.push("Servers:".to_string());
 
     for server in &config.servers {
         lines.push(format!(
@@ -413,10 +2062,91 @@ pub fn format_config(config: &SimConfig) -> String {
     lines.join("\n") + "\n"
 }
 
+#[derive(Clone, Debug, Serialize)]
+pub struct DerivedWorkload {
+    pub expected_arrivals: f64,
+    pub offered_rps: f64,
+    pub capacity_rps: f64,
+    pub offered_load: f64,
+}
+
+/// Workload figures implied by the resolved config. Capacity is the same
+/// weighted estimate `--overload-factor` scales.
+pub fn derive_workload(config: &SimConfig) -> DerivedWorkload {
+    let (expected_arrivals, offered_rps) = match &config.requests {
+        RequestProfile::FixedCount(count) => (*count as f64, 1000.0),
+        RequestProfile::Poisson { rate, duration_ms } => {
+            (rate * *duration_ms as f64 / 1000.0, *rate)
+        }
+        RequestProfile::Burst { count, .. } => (*count as f64, f64::INFINITY),
+    };
+    let capacity_rps = capacity_rps(&config.servers);
+    let offered_load = if capacity_rps > 0.0 {
+        offered_rps / capacity_rps
+    } else {
+        0.0
+    };
+    DerivedWorkload {
+        expected_arrivals: round_to(expected_arrivals, 2),
+        offered_rps: round_to(offered_rps, 2),
+        capacity_rps: round_to(capacity_rps, 2),
+        offered_load: round_to(offered_load, 4),
+    }
+}
+
+/// The config as JSON that `--config` loads back into an identical `SimConfig`
+/// (`show-config --format json`).
+pub fn config_to_json(config: &SimConfig) -> Result<String> {
+    serde_json::to_string_pretty(config)
+        .map(|json| json + "\n")
+        .map_err(|err| Error::Output(format!("failed to serialize config: {}", err)))
+}
+
+/// `--dry-run` output: the resolved config as TOML (or JSON) plus derived workload.
+pub fn format_resolved_config(config: &SimConfig, format: &FormatArg) -> Result<String> {
+    let derived = derive_workload(config);
+    match format {
+        FormatArg::Json => {
+            #[derive(Serialize)]
+            struct ResolvedConfig<'a> {
+                config: &'a SimConfig,
+                derived: DerivedWorkload,
+            }
+            serde_json::to_string_pretty(&ResolvedConfig { config, derived })
+                .map(|json| json + "\n")
+                .map_err(|err| Error::Output(format!("failed to serialize config: {}", err)))
+        }
+        FormatArg::Human | FormatArg::Summary => {
+            let toml = toml::to_string_pretty(config)
+                .map_err(|err| Error::Output(format!("failed to serialize config: {}", err)))?;
+            Ok(format!(
+                "# expected_arrivals = {}\n# offered_rps = {}\n# capacity_rps = {}\n# offered_load = {}\n{}",
+                derived.expected_arrivals,
+                derived.offered_rps,
+                derived.capacity_rps,
+                derived.offered_load,
+                toml
+            ))
+        }
+    }
+}
+
+fn round_to(value: f64, decimals: i32) -> f64 {
+    if !value.is_finite() {
+        return value;
+    }
+    let factor = 10_f64.powi(decimals);
+    (value * factor).round() / factor
+}
+
 fn capacity_rps(servers: &[ServerConfig]) -> f64 {
     servers
         .iter()
-        .map(|server| (1000.0 / server.base_latency_ms as f64) * server.weight as f64)
+        .map(|server| {
+            (1000.0 / server.base_latency_ms as f64)
+                * server.weight as f64
+                * f64::from(server.model.concurrency())
+        })
         .sum()
 }
 
@@ -424,16 +2154,188 @@ fn capacity_rps(servers: &[ServerConfig]) -> f64 {
 mod tests {
     use super::*;
 
+    fn parse_list(contents: &str, skip_invalid: bool) -> Result<Vec<ServerConfig>> {
+        let mut parser = ServerListParser::new(skip_invalid);
+        parser.list("hosts.txt", contents);
+        parser.finish()
+    }
+
+    fn names(servers: &[ServerConfig]) -> Vec<&str> {
+        servers.iter().map(|server| server.name.as_str()).collect()
+    }
+
+    #[test]
+    fn parse_server_fields_handles_weight_and_default() {
+        assert_eq!(parse_server_fields("api:25:3").unwrap(), ("api", 25, 3));
+        assert_eq!(parse_server_fields(" db : 40 ").unwrap(), ("db", 40, 1));
+    }
+
+    #[test]
+    fn server_lists_skip_comments_and_blank_lines() {
+        let contents = "# inventory\napi-1:10:2\n\napi-2:12  # canary\ndb-1:40, db-2:45\n";
+        let servers = parse_list(contents, false).expect("inventory should parse");
+        assert_eq!(names(&servers), vec!["api-1", "api-2", "db-1", "db-2"]);
+        assert_eq!(servers[0].weight, 2);
+        assert_eq!(servers[3].base_latency_ms, 45);
+    }
+
+    #[test]
+    fn server_lists_report_every_invalid_entry_with_its_position() {
+        let contents = "a:10\nb:x\na:20\n  c:30, d:0\n";
+        assert_eq!(
+            parse_list(contents, false).unwrap_err().to_string(),
+            "3 invalid server entries:\n  \
+             hosts.txt:2:1: invalid latency in 'b:x'\n  \
+             hosts.txt:3:1: duplicate server name 'a'\n  \
+             hosts.txt:4:9: latency must be > 0 in 'd:0'"
+        );
+        assert!(matches!(
+            parse_list("a:10\nb:x\n", false),
+            Err(Error::InvalidLatency(_))
+        ));
+    }
+
+    #[test]
+    fn server_lists_skip_invalid_entries_when_asked() {
+        let servers =
+            parse_list("a:10\nb:x\na:20\n,c:30\n", true).expect("valid entries should be kept");
+        assert_eq!(names(&servers), vec!["a", "c"]);
+        assert!(matches!(
+            parse_list("b:x\n", true),
+            Err(Error::EmptyServers)
+        ));
+        assert!(matches!(
+            parse_list("# none\n", false),
+            Err(Error::EmptyServers)
+        ));
+    }
+
+    #[test]
+    fn registered_algorithms_are_offered_to_help_and_completions() {
+        algorithms::register("completion-test", || {
+            Box::new(algorithms::RoundRobinStrategy::default())
+        })
+        .expect("name should be free");
+        let names = AlgoValueParser
+            .possible_values()
+            .expect("algo values are listed")
+            .map(|value| value.get_name().to_string())
+            .collect::<Vec<_>>();
+        assert!(names.contains(&"least-connections".to_string()));
+        assert!(names.contains(&"completion-test".to_string()));
+
+        let mut script = Vec::new();
+        write_completions(clap_complete::Shell::Bash, &mut script);
+        assert!(String::from_utf8(script)
+            .expect("completions are UTF-8")
+            .contains("completion-test"));
+    }
+
+    #[test]
+    fn list_formats_covers_every_format_value() {
+        let listed = crate::output::FORMATS
+            .iter()
+            .map(|format| format.name)
+            .collect::<Vec<_>>();
+        let values = RenderFormatArg::value_variants()
+            .iter()
+            .filter_map(|format| format.to_possible_value())
+            .chain(
+                FormatArg::value_variants()
+                    .iter()
+                    .filter_map(|format| format.to_possible_value()),
+            )
+            .chain(
+                ExportFormatArg::value_variants()
+                    .iter()
+                    .filter_map(|format| format.to_possible_value()),
+            )
+            .map(|value| value.get_name().to_string())
+            .collect::<Vec<_>>();
+        for value in values {
+            assert!(listed.contains(&value.as_str()), "{} is not listed", value);
+        }
+    }
+
+    #[test]
+    fn parse_slo_spec_accepts_optional_prefix_and_suffix() {
+        let slo = parse_slo_spec("p99.9:250ms").expect("slo should parse");
+        assert_eq!(
+            slo,
+            SloConfig {
+                percentile: 99.9,
+                threshold_ms: 250,
+            }
+        );
+        assert!(parse_slo_spec("99").is_err());
+        assert!(parse_slo_spec("0:10").is_err());
+        assert!(parse_slo_spec("101:10").is_err());
+    }
+
+    #[test]
+    fn parse_seed_arg_accepts_numbers_and_auto() {
+        assert_eq!(parse_seed_arg("42"), Ok(SeedArg::Fixed(42)));
+        assert_eq!(parse_seed_arg("AUTO"), Ok(SeedArg::Auto));
+        assert!(parse_seed_arg("-1").is_err());
+        assert_eq!(SeedArg::Fixed(7).resolve(), 7);
+    }
+
+    #[test]
+    fn check_deterministic_flags_implicit_and_entropy_seeds() {
+        let mut config = SimConfig {
+            servers: vec![ServerConfig {
+                name: "a".to_string(),
+                base_latency_ms: 10,
+                weight: 1,
+                model: ServerModel::default(),
+            }],
+            requests: RequestProfile::Poisson {
+                rate: 10.0,
+                duration_ms: 100,
+            },
+            algo: AlgoConfig::RoundRobin,
+            randomness: RandomnessConfig::Stable,
+            rng: RngKind::Std,
+            event_order: EventOrder::CompletionsFirst,
+            allow_large: false,
+            timeseries_bucket_ms: None,
+            record_timelines: false,
+            slos: Vec::new(),
+            slowest_requests: 0,
+            explain: false,
+            max_virtual_time_ms: None,
+            max_events: None,
+        };
+        let err = check_deterministic(&config, None, false).expect_err("implicit seed");
+        assert!(err.to_string().contains("implicit seed 0"));
+        assert!(check_deterministic(&config, None, true).is_ok());
+
+        config.randomness = RandomnessConfig::Seeded(3);
+        assert!(check_deterministic(&config, Some(SeedArg::Fixed(3)), false).is_ok());
+        assert!(check_deterministic(&config, Some(SeedArg::Auto), false).is_err());
+
+        config.randomness = RandomnessConfig::Entropy;
+        let err = check_deterministic(&config, None, false).expect_err("entropy");
+        assert!(err.to_string().contains("draws the seed from entropy"));
+
+        config.randomness = RandomnessConfig::Stable;
+        config.requests = RequestProfile::FixedCount(5);
+        config.servers[0].model.latency = Some(LatencyDistribution::Exponential);
+        let err = check_deterministic(&config, None, false).expect_err("sampled latency");
+        assert!(err.to_string().contains("server 'a' samples latency"));
+    }
+
     #[test]
-    fn parse_server_spec_handles_weight_and_default() {
-        let weighted = parse_server_spec("api:25:3").expect("weighted server should parse");
-        assert_eq!(weighted.name, "api");
-        assert_eq!(weighted.base_latency_ms, 25);
-        assert_eq!(weighted.weight, 3);
-
-        let defaulted = parse_server_spec("db:40").expect("default weight should parse");
-        assert_eq!(defaulted.name, "db");
-        assert_eq!(defaulted.base_latency_ms, 40);
-        assert_eq!(defaulted.weight, 1);
+    fn parse_seed_range_is_inclusive() {
+        assert_eq!(
+            parse_seed_range("1..3").expect("range should parse"),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            parse_seed_range("5..=5").expect("range should parse"),
+            vec![5]
+        );
+        assert!(parse_seed_range("3..1").is_err());
+        assert!(parse_seed_range("1-3").is_err());
     }
 }
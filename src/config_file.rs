@@ -0,0 +1,858 @@
+//! Loading config files: TOML or JSON, with `include`s, `[profiles.*]`,
+//! `server_template` expansion, unknown-key checks, and `--set` overrides.
+//! None of this needs the CLI, so it builds without the `cli` feature.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use schemars::JsonSchema;
+
+use crate::error::{Error, Result};
+use crate::models::{ServerTemplate, SimConfig};
+
+pub fn load_config(path: &Path) -> Result<SimConfig> {
+    load_config_profile(path, None, false)
+}
+
+/// Loads a config file, resolving `include = [...]` entries relative to the
+/// including file. Included files are merged in order, then the including file
+/// is applied on top: tables merge key by key, arrays and scalars replace.
+/// A selected `[profiles.<name>]` table is merged over the result the same way.
+pub fn load_config_profile(path: &Path, profile: Option<&str>, lenient: bool) -> Result<SimConfig> {
+    let mut stack = Vec::new();
+    let mut merged = load_config_value(path, &mut stack)?;
+    let profiles = merged
+        .as_object_mut()
+        .and_then(|map| map.remove("profiles"));
+    if let Some(name) = profile {
+        let overlay = profiles
+            .as_ref()
+            .and_then(|profiles| profiles.get(name))
+            .cloned()
+            .ok_or_else(|| {
+                let available = profiles
+                    .as_ref()
+                    .and_then(|profiles| profiles.as_object())
+                    .map(|profiles| profiles.keys().cloned().collect::<Vec<_>>().join(", "))
+                    .filter(|names| !names.is_empty())
+                    .unwrap_or_else(|| "none".to_string());
+                Error::UnknownProfile(name.to_string(), available)
+            })?;
+        tracing::debug!(profile = name, "applying config profile");
+        merge_config_values(&mut merged, overlay);
+    }
+    migrate_randomness(&mut merged)?;
+    if !lenient {
+        check_config_keys(&merged)?;
+    }
+    expand_config_templates(&mut merged)?;
+    serde_json::from_value(merged).map_err(|err| Error::json("config", Some(path), err))
+}
+
+const CONFIG_KEYS: &[&str] = &[
+    "servers",
+    "requests",
+    "algo",
+    "randomness",
+    "rng",
+    "event_order",
+    "allow_large",
+    "timeseries_bucket_ms",
+    "record_timelines",
+    "slos",
+    "slowest_requests",
+    "explain",
+    "max_virtual_time_ms",
+    "max_events",
+    "server_template",
+];
+const SERVER_KEYS: &[&str] = &[
+    "name",
+    "base_latency_ms",
+    "weight",
+    "latency",
+    "capacity",
+    "failure",
+    "zone",
+];
+const SERVER_TEMPLATE_KEYS: &[&str] = &[
+    "name",
+    "count",
+    "base_latency_ms",
+    "weight",
+    "latency_jitter_ms",
+    "jitter_seed",
+    "latency",
+    "capacity",
+    "failure",
+    "zone",
+];
+const LATENCY_KEYS: &[&str] = &["distribution", "jitter_ms"];
+const CAPACITY_KEYS: &[&str] = &["concurrency"];
+const FAILURE_KEYS: &[&str] = &["outages"];
+const OUTAGE_KEYS: &[&str] = &["start_ms", "duration_ms"];
+const REQUESTS_KEYS: &[&str] = &["rate", "duration_ms", "count", "at_ms"];
+const SLO_KEYS: &[&str] = &["percentile", "threshold_ms"];
+
+// Top-level layout of a config file: a `SimConfig` plus the file-only keys
+// resolved while loading. Only used to derive the schema.
+#[derive(JsonSchema)]
+#[schemars(rename = "SimConfig")]
+#[allow(dead_code)]
+struct ConfigFileSchema {
+    #[serde(flatten)]
+    config: SimConfig,
+    server_template: Option<OneOrMany<ServerTemplate>>,
+    include: Option<OneOrMany<String>>,
+    profiles: Option<BTreeMap<String, serde_json::Value>>,
+}
+
+#[derive(JsonSchema)]
+#[serde(untagged)]
+#[schemars(inline)]
+#[allow(dead_code)]
+enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+/// JSON Schema for config files (TOML or JSON), for editors and CI.
+pub fn config_schema() -> String {
+    let mut schema = schemars::schema_for!(ConfigFileSchema);
+    // `servers` may be omitted when a `server_template` generates them.
+    if let Some(serde_json::Value::Array(required)) = schema.get_mut("required") {
+        required.retain(|key| key != "servers");
+    }
+    serde_json::to_string_pretty(&schema).unwrap()
+}
+
+/// Applies `--set PATH=VALUE` overrides to a loaded config, in order.
+/// `server.NAME.FIELD` addresses the server with that name; any other path is
+/// a dotted config key. Values parse as TOML (`3`, `true`, `{ concurrency = 2 }`)
+/// and otherwise fall back to a bare string.
+pub fn apply_config_overrides(
+    config: SimConfig,
+    overrides: &[String],
+    lenient: bool,
+) -> Result<SimConfig> {
+    if overrides.is_empty() {
+        return Ok(config);
+    }
+    let mut value = serde_json::to_value(config).map_err(|err| Error::json("config", None, err))?;
+    for spec in overrides {
+        apply_config_override(&mut value, spec)?;
+    }
+    // A legacy `--set seed=N` or `--set tie_break=...` replaces the loaded policy.
+    if let Some(map) = value.as_object_mut() {
+        if map.contains_key("tie_break") || map.contains_key("seed") {
+            map.remove("randomness");
+        }
+    }
+    migrate_randomness(&mut value)?;
+    if !lenient {
+        check_config_keys(&value)?;
+    }
+    serde_json::from_value(value)
+        .map_err(|err| Error::json("config after --set overrides", None, err))
+}
+
+fn apply_config_override(config: &mut serde_json::Value, spec: &str) -> Result<()> {
+    tracing::debug!(spec, "applying --set override");
+    let invalid = |reason: String| Error::InvalidOverride(spec.to_string(), reason);
+    let (path, raw) = spec
+        .split_once('=')
+        .ok_or_else(|| invalid("expected PATH=VALUE".to_string()))?;
+    let segments = path.trim().split('.').collect::<Vec<_>>();
+    if segments.iter().any(|segment| segment.is_empty()) {
+        return Err(invalid("empty key in path".to_string()));
+    }
+
+    let (mut target, fields) = match segments.as_slice() {
+        ["server" | "servers", name, fields @ ..] if !fields.is_empty() => {
+            let server = config
+                .get_mut("servers")
+                .and_then(serde_json::Value::as_array_mut)
+                .and_then(|servers| {
+                    servers.iter_mut().find(|server| {
+                        server.get("name").and_then(serde_json::Value::as_str) == Some(*name)
+                    })
+                })
+                .ok_or_else(|| invalid(format!("no server named '{}'", name)))?;
+            (server, fields)
+        }
+        fields => (config, fields),
+    };
+    let (last, parents) = fields
+        .split_last()
+        .expect("split yields at least one segment");
+    for key in parents {
+        let table = target
+            .as_object_mut()
+            .ok_or_else(|| invalid(format!("'{}' is not a table", key)))?;
+        target = table
+            .entry(key.to_string())
+            .or_insert_with(|| serde_json::Value::Object(Default::default()));
+    }
+    target
+        .as_object_mut()
+        .ok_or_else(|| invalid(format!("cannot set '{}' inside a non-table value", last)))?
+        .insert(last.to_string(), parse_override_value(raw.trim()));
+    Ok(())
+}
+
+fn parse_override_value(raw: &str) -> serde_json::Value {
+    toml::from_str::<toml::Table>(&format!("value = {}", raw))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .and_then(|value| serde_json::to_value(value).ok())
+        .unwrap_or_else(|| serde_json::Value::String(raw.to_string()))
+}
+
+/// Rejects keys the config structs would silently ignore, suggesting the
+/// closest known key for likely typos.
+/// Rewrites the pre-`randomness` keys: `tie_break = "seeded"` with `seed = N`
+/// becomes `randomness = "seeded(N)"`. A `seed` without seeded tie-breaks
+/// used to seed only the workload, which `randomness` cannot express, so it
+/// is rejected rather than silently switching tie-breaks to seeded.
+fn migrate_randomness(value: &mut serde_json::Value) -> Result<()> {
+    let Some(map) = value.as_object_mut() else {
+        return Ok(());
+    };
+    let tie_break = map.remove("tie_break");
+    let seed = map.remove("seed");
+    if tie_break.is_none() && seed.is_none() {
+        return Ok(());
+    }
+    if map.contains_key("randomness") {
+        return Err(Error::LegacyRandomnessKeys);
+    }
+    let seeded = match tie_break.as_ref().and_then(serde_json::Value::as_str) {
+        None | Some("stable") => false,
+        Some("seeded") => true,
+        Some(other) => {
+            return Err(Error::ConfigParse(format!(
+                "invalid tie_break '{}': expected stable or seeded",
+                other
+            )))
+        }
+    };
+    let randomness = match (seeded, seed) {
+        (true, None) => return Err(Error::InvalidTieBreakSeed),
+        (false, None) => serde_json::Value::from("stable"),
+        (false, Some(_)) => return Err(Error::LegacyRandomnessKeys),
+        (true, Some(seed)) => {
+            let seed = seed.as_u64().ok_or_else(|| {
+                Error::ConfigParse(format!(
+                    "invalid seed {}: expected an unsigned integer",
+                    seed
+                ))
+            })?;
+            serde_json::Value::from(format!("seeded({})", seed))
+        }
+    };
+    tracing::debug!(%randomness, "migrated tie_break/seed to randomness");
+    map.insert("randomness".to_string(), randomness);
+    Ok(())
+}
+
+pub(crate) fn check_config_keys(value: &serde_json::Value) -> Result<()> {
+    let mut problems = Vec::new();
+    collect_unknown_keys(value, "", CONFIG_KEYS, &mut problems);
+    for (key, known) in [
+        ("servers", SERVER_KEYS),
+        ("server_template", SERVER_TEMPLATE_KEYS),
+        ("requests", REQUESTS_KEYS),
+        ("slos", SLO_KEYS),
+    ] {
+        for (entry, prefix) in nested_entries(value, "", key) {
+            collect_unknown_keys(entry, &prefix, known, &mut problems);
+            if matches!(key, "servers" | "server_template") {
+                collect_unknown_model_keys(entry, &prefix, &mut problems);
+            }
+        }
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::UnknownConfigKeys(problems.join("; ")))
+    }
+}
+
+fn collect_unknown_model_keys(
+    server: &serde_json::Value,
+    prefix: &str,
+    problems: &mut Vec<String>,
+) {
+    for (key, known) in [
+        ("latency", LATENCY_KEYS),
+        ("capacity", CAPACITY_KEYS),
+        ("failure", FAILURE_KEYS),
+    ] {
+        for (entry, entry_prefix) in nested_entries(server, prefix, key) {
+            collect_unknown_keys(entry, &entry_prefix, known, problems);
+            for (outage, outage_prefix) in nested_entries(entry, &entry_prefix, "outages") {
+                collect_unknown_keys(outage, &outage_prefix, OUTAGE_KEYS, problems);
+            }
+        }
+    }
+}
+
+/// `parent[key]` with its report path; arrays yield one entry per element.
+fn nested_entries<'a>(
+    parent: &'a serde_json::Value,
+    prefix: &str,
+    key: &str,
+) -> Vec<(&'a serde_json::Value, String)> {
+    match parent.get(key) {
+        Some(serde_json::Value::Array(entries)) => entries
+            .iter()
+            .enumerate()
+            .map(|(idx, entry)| (entry, format!("{}{}[{}].", prefix, key, idx)))
+            .collect(),
+        Some(entry) => vec![(entry, format!("{}{}.", prefix, key))],
+        None => Vec::new(),
+    }
+}
+
+fn collect_unknown_keys(
+    value: &serde_json::Value,
+    prefix: &str,
+    known: &[&str],
+    problems: &mut Vec<String>,
+) {
+    let Some(map) = value.as_object() else {
+        return;
+    };
+    for key in map.keys() {
+        if known.contains(&key.as_str()) {
+            continue;
+        }
+        let suggestion = known
+            .iter()
+            .map(|candidate| (edit_distance(key, candidate), *candidate))
+            .filter(|(distance, candidate)| *distance <= candidate.len().max(key.len()) / 3 + 1)
+            .min_by_key(|(distance, _)| *distance);
+        problems.push(match suggestion {
+            Some((_, candidate)) => format!("{}{} (did you mean `{}`?)", prefix, key, candidate),
+            None => format!("{}{}", prefix, key),
+        });
+    }
+}
+
+fn edit_distance(left: &str, right: &str) -> usize {
+    let right = right.chars().collect::<Vec<_>>();
+    let mut previous = (0..=right.len()).collect::<Vec<_>>();
+    for (i, left_char) in left.chars().enumerate() {
+        let mut current = vec![i + 1; right.len() + 1];
+        for (j, right_char) in right.iter().enumerate() {
+            let substitution = previous[j] + usize::from(left_char != *right_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[right.len()]
+}
+
+pub(crate) fn load_config_value(
+    path: &Path,
+    stack: &mut Vec<PathBuf>,
+) -> Result<serde_json::Value> {
+    tracing::debug!(path = %path.display(), depth = stack.len(), "loading config file");
+    let contents = fs::read_to_string(path).map_err(|err| Error::io("config", path, err))?;
+    let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if stack.contains(&canonical) {
+        let chain = stack
+            .iter()
+            .skip_while(|entry| **entry != canonical)
+            .chain(std::iter::once(&canonical))
+            .map(|entry| entry.display().to_string())
+            .collect::<Vec<_>>()
+            .join(" -> ");
+        return Err(Error::ConfigIncludeCycle(chain));
+    }
+
+    let ext = path
+        .extension()
+        .and_then(|value| value.to_str())
+        .unwrap_or("");
+    let mut value = match ext {
+        "toml" => toml::from_str::<toml::Value>(&contents)
+            .map_err(|err| Error::toml("TOML config", Some(path), &contents, err))
+            .and_then(|value| {
+                serde_json::to_value(value)
+                    .map_err(|err| Error::json("TOML config", Some(path), err))
+            })?,
+        "json" => serde_json::from_str::<serde_json::Value>(&contents)
+            .map_err(|err| Error::json("JSON config", Some(path), err))?,
+        "" => return Err(Error::UnsupportedConfigFormat("unknown".to_string())),
+        _ => return Err(Error::UnsupportedConfigFormat(ext.to_string())),
+    };
+
+    let includes = match value.as_object_mut().and_then(|map| map.remove("include")) {
+        None => return Ok(value),
+        Some(serde_json::Value::String(entry)) => vec![entry],
+        Some(serde_json::Value::Array(entries)) => entries
+            .into_iter()
+            .map(|entry| match entry {
+                serde_json::Value::String(entry) => Ok(entry),
+                other => Err(Error::ConfigParse(format!(
+                    "invalid include entry in '{}': expected a path, got {}",
+                    path.display(),
+                    other
+                ))),
+            })
+            .collect::<Result<Vec<_>>>()?,
+        Some(other) => {
+            return Err(Error::ConfigParse(format!(
+                "invalid include in '{}': expected a path or list of paths, got {}",
+                path.display(),
+                other
+            )))
+        }
+    };
+
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    stack.push(canonical);
+    let mut merged = serde_json::Value::Object(serde_json::Map::new());
+    for include in includes {
+        let included = load_config_value(&base_dir.join(include), stack)?;
+        merge_config_values(&mut merged, included);
+    }
+    stack.pop();
+    merge_config_values(&mut merged, value);
+    Ok(merged)
+}
+
+pub(crate) fn merge_config_values(base: &mut serde_json::Value, overlay: serde_json::Value) {
+    match (base, overlay) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_config_values(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Replaces a `server_template` entry (one table or a list) with the servers it
+/// generates, appended after any explicit `servers`.
+pub(crate) fn expand_config_templates(value: &mut serde_json::Value) -> Result<()> {
+    let Some(map) = value.as_object_mut() else {
+        return Ok(());
+    };
+    let templates = match map.remove("server_template") {
+        None => return Ok(()),
+        Some(serde_json::Value::Array(entries)) => entries,
+        Some(entry) => vec![entry],
+    };
+    let mut generated = Vec::new();
+    for entry in templates {
+        let template: ServerTemplate = serde_json::from_value(entry)
+            .map_err(|err| Error::json("server_template", None, err))?;
+        for server in template.expand() {
+            generated.push(
+                serde_json::to_value(server)
+                    .map_err(|err| Error::json("server_template", None, err))?,
+            );
+        }
+    }
+    match map
+        .entry("servers")
+        .or_insert_with(|| serde_json::Value::Array(Vec::new()))
+    {
+        serde_json::Value::Array(servers) => servers.extend(generated),
+        _ => {
+            return Err(Error::ConfigParse(
+                "servers must be a list when server_template is used".to_string(),
+            ))
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        AlgoConfig, CapacityConfig, EventOrder, FailureConfig, LatencyDistribution, OutageWindow,
+        RandomnessConfig, RequestProfile, RngKind, ServerConfig, ServerModel, SloConfig,
+    };
+
+    #[test]
+    fn expand_config_templates_appends_generated_servers() {
+        let mut value = serde_json::json!({
+            "servers": [{ "name": "db", "base_latency_ms": 40 }],
+            "server_template": {
+                "name": "api-{i}",
+                "count": 3,
+                "base_latency_ms": 10,
+                "latency_jitter_ms": 2,
+            },
+        });
+        expand_config_templates(&mut value).expect("template should expand");
+        let servers: Vec<ServerConfig> =
+            serde_json::from_value(value["servers"].clone()).expect("servers should parse");
+        let names = servers
+            .iter()
+            .map(|server| server.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["db", "api-1", "api-2", "api-3"]);
+        assert!(servers[1..]
+            .iter()
+            .all(|server| (8..=12).contains(&server.base_latency_ms)));
+        assert!(value.get("server_template").is_none());
+    }
+
+    #[test]
+    fn merge_config_values_merges_tables_and_replaces_arrays() {
+        let mut base = serde_json::json!({
+            "requests": 10,
+            "servers": [{ "name": "a" }],
+            "algo": { "type": "round-robin" },
+        });
+        let overlay = serde_json::json!({
+            "servers": [{ "name": "b" }],
+            "algo": { "extra": true },
+        });
+        merge_config_values(&mut base, overlay);
+        assert_eq!(
+            base,
+            serde_json::json!({
+                "requests": 10,
+                "servers": [{ "name": "b" }],
+                "algo": { "type": "round-robin", "extra": true },
+            })
+        );
+    }
+
+    fn full_config() -> SimConfig {
+        SimConfig {
+            servers: vec![ServerConfig {
+                name: "a".to_string(),
+                base_latency_ms: 10,
+                weight: 1,
+                model: ServerModel {
+                    latency: Some(LatencyDistribution::Uniform { jitter_ms: 2 }),
+                    capacity: Some(CapacityConfig { concurrency: 2 }),
+                    failure: Some(FailureConfig {
+                        outages: vec![OutageWindow {
+                            start_ms: 5,
+                            duration_ms: 5,
+                        }],
+                    }),
+                    zone: Some("z1".to_string()),
+                },
+            }],
+            requests: RequestProfile::FixedCount(1),
+            algo: AlgoConfig::RoundRobin,
+            randomness: RandomnessConfig::Seeded(1),
+            rng: RngKind::Std,
+            event_order: EventOrder::CompletionsFirst,
+            allow_large: false,
+            timeseries_bucket_ms: Some(1),
+            record_timelines: true,
+            slos: vec![SloConfig {
+                percentile: 99.0,
+                threshold_ms: 1,
+            }],
+            slowest_requests: 1,
+            explain: true,
+            max_virtual_time_ms: Some(1),
+            max_events: Some(1),
+        }
+    }
+
+    #[test]
+    fn config_key_lists_cover_serialized_config() {
+        let config = full_config();
+        let value = serde_json::to_value(&config).expect("config should serialize");
+        check_config_keys(&value).expect("every serialized key should be known");
+    }
+
+    #[test]
+    fn written_configs_load_back_identically() {
+        let minimal = SimConfig::builder()
+            .server("a", 10)
+            .requests(3)
+            .algo(AlgoConfig::RoundRobin)
+            .build()
+            .expect("config should build");
+        let configs = [
+            minimal,
+            full_config(),
+            SimConfig {
+                requests: RequestProfile::Poisson {
+                    rate: 12.345,
+                    duration_ms: 400,
+                },
+                randomness: RandomnessConfig::Seeded(u64::MAX),
+                rng: RngKind::Xoshiro,
+                event_order: EventOrder::Fifo,
+                allow_large: true,
+                ..full_config()
+            },
+            SimConfig {
+                requests: RequestProfile::Burst { count: 4, at_ms: 0 },
+                randomness: RandomnessConfig::Entropy,
+                algo: AlgoConfig::LeastResponseTime,
+                ..full_config()
+            },
+        ];
+        for (idx, config) in configs.iter().enumerate() {
+            let written = [
+                ("json", serde_json::to_string_pretty(config).expect("JSON")),
+                ("toml", toml::to_string_pretty(config).expect("TOML")),
+            ];
+            for (ext, contents) in written {
+                let path = std::env::temp_dir().join(format!(
+                    "lb-sim-round-trip-{}-{}.{}",
+                    std::process::id(),
+                    idx,
+                    ext
+                ));
+                fs::write(&path, &contents).expect("temp file");
+                let loaded = load_config(&path);
+                let _ = fs::remove_file(&path);
+                assert_eq!(
+                    &loaded.expect("written config should load"),
+                    config,
+                    "{}",
+                    contents
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn requests_tables_must_pick_one_workload() {
+        let parse = serde_json::from_value::<RequestProfile>;
+        assert_eq!(
+            parse(serde_json::json!(7)).unwrap(),
+            RequestProfile::FixedCount(7)
+        );
+        assert_eq!(
+            parse(serde_json::json!({ "rate": 10, "duration_ms": 100 })).unwrap(),
+            RequestProfile::Poisson {
+                rate: 10.0,
+                duration_ms: 100
+            }
+        );
+        assert_eq!(
+            parse(serde_json::json!({ "count": 5, "at_ms": 0 })).unwrap(),
+            RequestProfile::Burst { count: 5, at_ms: 0 }
+        );
+        for mixed in [
+            serde_json::json!({ "rate": 10, "duration_ms": 100, "count": 5 }),
+            serde_json::json!({ "count": 5 }),
+        ] {
+            assert!(parse(mixed)
+                .unwrap_err()
+                .to_string()
+                .starts_with("invalid requests: expected a count, { rate, duration_ms }"));
+        }
+    }
+
+    #[test]
+    fn config_overrides_target_named_servers_and_dotted_keys() {
+        let config = SimConfig {
+            servers: vec![
+                ServerConfig {
+                    name: "api".to_string(),
+                    base_latency_ms: 10,
+                    weight: 1,
+                    model: ServerModel::default(),
+                },
+                ServerConfig {
+                    name: "db".to_string(),
+                    base_latency_ms: 20,
+                    weight: 1,
+                    model: ServerModel::default(),
+                },
+            ],
+            requests: RequestProfile::FixedCount(10),
+            algo: AlgoConfig::RoundRobin,
+            randomness: RandomnessConfig::Stable,
+            rng: RngKind::Std,
+            event_order: EventOrder::CompletionsFirst,
+            allow_large: false,
+            timeseries_bucket_ms: None,
+            record_timelines: false,
+            slos: Vec::new(),
+            slowest_requests: 0,
+            explain: false,
+            max_virtual_time_ms: None,
+            max_events: None,
+        };
+        let overrides = [
+            "server.api.weight=3",
+            "server.db.capacity.concurrency=2",
+            "algo=least-connections",
+            "requests = { rate = 50.0, duration_ms = 100 }",
+        ]
+        .map(String::from);
+        let updated =
+            apply_config_overrides(config.clone(), &overrides, false).expect("overrides apply");
+
+        assert_eq!(updated.servers[0].weight, 3);
+        assert_eq!(updated.servers[1].model.concurrency(), 2);
+        assert!(matches!(updated.algo, AlgoConfig::LeastConnections));
+        assert!(matches!(
+            updated.requests,
+            RequestProfile::Poisson {
+                duration_ms: 100,
+                ..
+            }
+        ));
+
+        let err =
+            apply_config_overrides(config.clone(), &["server.web.weight=2".to_string()], false)
+                .expect_err("unknown server");
+        assert!(err.to_string().contains("no server named 'web'"));
+        let err =
+            apply_config_overrides(config.clone(), &["server.api.wieght=2".to_string()], false)
+                .expect_err("misspelled field");
+        assert!(err.to_string().contains("did you mean `weight`"));
+        assert!(apply_config_overrides(config, &["weight".to_string()], false).is_err());
+    }
+
+    #[test]
+    fn check_config_keys_descends_into_server_model_blocks() {
+        let value = serde_json::json!({
+            "servers": [{
+                "name": "a",
+                "base_latency_ms": 10,
+                "capacity": { "concurency": 2 },
+                "failure": { "outages": [{ "start_ms": 1, "duration": 5 }] },
+            }],
+        });
+        let message = check_config_keys(&value)
+            .expect_err("unknown model keys should be rejected")
+            .to_string();
+        assert!(
+            message.contains("servers[0].capacity.concurency (did you mean `concurrency`?)"),
+            "{}",
+            message
+        );
+        assert!(
+            message
+                .contains("servers[0].failure.outages[0].duration (did you mean `duration_ms`?)"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn check_config_keys_suggests_close_matches() {
+        let value = serde_json::json!({
+            "algo": "round-robin",
+            "randomnes": "stable",
+            "servers": [
+                { "name": "a", "base_latency_ms": 10 },
+                { "name": "b", "base_latency_millis": 20, "zzz": 1 },
+            ],
+            "requests": { "rate": 1.0, "duraton_ms": 10 },
+        });
+        let err = check_config_keys(&value).expect_err("unknown keys should be rejected");
+        let message = err.to_string();
+        assert!(
+            message.contains("randomnes (did you mean `randomness`?)"),
+            "{}",
+            message
+        );
+        assert!(
+            message.contains("servers[1].base_latency_millis (did you mean `base_latency_ms`?)"),
+            "{}",
+            message
+        );
+        assert!(message.contains("servers[1].zzz;"), "{}", message);
+        assert!(
+            message.contains("requests.duraton_ms (did you mean `duration_ms`?)"),
+            "{}",
+            message
+        );
+        assert!(message.contains("--lenient"), "{}", message);
+    }
+
+    #[test]
+    fn config_errors_keep_path_line_and_source() {
+        use std::error::Error as _;
+
+        let missing = Path::new("definitely-missing-config.toml");
+        let err = load_config(missing).expect_err("missing file should fail");
+        assert!(matches!(&err, Error::Io { what: "config", path, .. } if path == missing));
+        assert!(err.source().is_some());
+
+        let path = std::env::temp_dir().join(format!("lb-sim-bad-{}.toml", std::process::id()));
+        fs::write(&path, "algo = \"round-robin\"\nservers = [\n").expect("temp file");
+        let err = load_config(&path).expect_err("truncated TOML should fail");
+        let _ = fs::remove_file(&path);
+        match &err {
+            Error::Parse {
+                what: "TOML config",
+                path: Some(err_path),
+                line: Some(3),
+                ..
+            } => assert_eq!(err_path, &path),
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert!(err.source().is_some());
+        assert!(err
+            .to_string()
+            .starts_with(&format!("invalid TOML config '{}': ", path.display())));
+    }
+
+    #[test]
+    fn legacy_tie_break_and_seed_keys_become_randomness() {
+        let migrate = |value: serde_json::Value| {
+            let mut value = value;
+            migrate_randomness(&mut value).map(|()| value["randomness"].clone())
+        };
+        assert_eq!(
+            migrate(serde_json::json!({ "tie_break": "seeded", "seed": 9 })).unwrap(),
+            "seeded(9)"
+        );
+        // A lone seed only seeded the workload; `randomness` can't keep that.
+        for lone_seed in [
+            serde_json::json!({ "seed": 4 }),
+            serde_json::json!({ "tie_break": "stable", "seed": 4 }),
+        ] {
+            assert!(matches!(
+                migrate(lone_seed),
+                Err(Error::LegacyRandomnessKeys)
+            ));
+        }
+        assert_eq!(
+            migrate(serde_json::json!({ "tie_break": "stable" })).unwrap(),
+            "stable"
+        );
+        assert!(matches!(
+            migrate(serde_json::json!({ "tie_break": "seeded" })),
+            Err(Error::InvalidTieBreakSeed)
+        ));
+        assert!(matches!(
+            migrate(serde_json::json!({ "randomness": "entropy", "seed": 1 })),
+            Err(Error::LegacyRandomnessKeys)
+        ));
+
+        let config = SimConfig::builder()
+            .server("a", 10)
+            .requests(2)
+            .algo(AlgoConfig::RoundRobin)
+            .build()
+            .expect("config should build");
+        assert!(matches!(
+            apply_config_overrides(config.clone(), &["seed=5".to_string()], false),
+            Err(Error::LegacyRandomnessKeys)
+        ));
+        let overrides = ["tie_break=seeded".to_string(), "seed=5".to_string()];
+        let config = apply_config_overrides(config, &overrides, false)
+            .expect("legacy override should migrate");
+        assert_eq!(config.randomness, RandomnessConfig::Seeded(5));
+    }
+}
@@ -1,31 +1,157 @@
-use rand::rngs::StdRng;
 use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use rand_pcg::Pcg64Mcg;
+use rand_xoshiro::Xoshiro256PlusPlus;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::cmp::Reverse;
-use std::collections::{BinaryHeap, HashSet};
+use std::collections::BinaryHeap;
+use std::io::Write;
 
-use crate::algorithms::{build_strategy, SelectionContext, SelectionStrategy};
+use crate::algorithms::{build_strategy, Selection, SelectionContext, SelectionStrategy};
+use crate::analytic::mmc_comparison;
+use crate::assignment_sink::AssignmentSink;
+use crate::clock::{Clock, SimulatedClock};
 use crate::error::{Error, Result};
-use crate::events::{Event, Request, ScheduledEvent};
-use crate::models::{RequestProfile, ServerConfig, SimConfig, TieBreakConfig};
+use crate::event_log::EventLogWriter;
+use crate::events::{EngineEvent, Event, Request, ScheduledEvent};
+use crate::histogram::HistogramRecorder;
+use crate::metrics::{MetricsObserver, MetricsSink};
+use crate::models::{
+    EventOrder, LatencyDistribution, OutageWindow, RandomnessConfig, RequestProfile, RngKind,
+    ServerConfig, SimConfig,
+};
+use crate::reservoir::AssignmentReservoir;
+use crate::slo::SloTracker;
+use crate::slowest::SlowestRecorder;
 use crate::state::{
-    Assignment, EngineState, Phase1Metrics, ResponseTimePercentiles, RunMetadata, ServerState,
-    ServerSummary, ServerUtilization, SimulationResult,
+    Assignment, CandidateExplain, EngineState, GlobalStats, LatencyHistograms, LittlesLaw,
+    Phase1Metrics, ResponseTimePercentiles, RunMetadata, SelectionExplain, ServerLatencyHistogram,
+    ServerState, ServerSummary, ServerTail, ServerUtilization, SimulationResult, TailBreakdown,
 };
+use crate::timeline::TimelineRecorder;
+use crate::timeseries::TimeseriesRecorder;
+use crate::validation::{self, request_problems};
+
+/// The default generator: ChaCha12, the one behind rand's `StdRng` (so seeded
+/// runs match it), used directly because its state can be serialized into
+/// snapshots.
+pub type EngineRng = ChaCha12Rng;
+
+/// The generator for `RngKind::Small`.
+pub type PcgRng = Pcg64Mcg;
+
+/// The generator for `RngKind::Xoshiro`.
+pub type XoshiroRng = Xoshiro256PlusPlus;
+
+/// Evaluates `$body` with `$rng` naming the generator `$kind` selects.
+macro_rules! with_rng {
+    ($kind:expr, $rng:ident => $body:expr) => {
+        match $kind {
+            RngKind::Std => {
+                type $rng = EngineRng;
+                $body
+            }
+            RngKind::Small => {
+                type $rng = PcgRng;
+                $body
+            }
+            RngKind::Xoshiro => {
+                type $rng = XoshiroRng;
+                $body
+            }
+        }
+    };
+}
 
-pub struct SimulationEngine {
+/// Drives one simulation. `R` generates tie-breaks, the Poisson workload, and
+/// per-server service times; see `SimulationEngine::seeded`. The engine uses
+/// the `R` it is built with; `config.rng` picks it for the `run_simulation*`
+/// and `run_replications` helpers and the CLI.
+pub struct SimulationEngine<R = EngineRng> {
     pub config: SimConfig,
     pub state: EngineState,
     pub strategy: Box<dyn SelectionStrategy>,
-    pub rng: StdRng,
+    pub rng: R,
+    clock: Box<dyn Clock>,
+    trace: Option<Box<dyn Write>>,
+    event_log: Option<Box<dyn EventLogWriter>>,
+    assignment_sink: Option<Box<dyn AssignmentSink>>,
+    histogram_percentiles: bool,
+    assignment_sample: Option<usize>,
+    step: Option<Box<dyn StepHook>>,
+    observers: Vec<Box<dyn EngineObserver>>,
+    run: Option<RunState<R>>,
+    spare: RunBuffers,
+    memory: MemoryStats,
+    /// Set by `reseed`: the seed a replication's streams derive from, which a
+    /// `Stable` policy has no place to hold.
+    replication_seed: Option<u64>,
+}
+
+/// High-water marks of the buffers that can grow with a run's size, for
+/// checking that a large run stays bounded (`--stats-memory`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    /// Most events queued at once: the next arrival plus pending completions.
+    pub peak_queued_events: usize,
+    /// Assignments kept for the result.
+    pub stored_assignments: usize,
+    /// Response times kept for exact percentiles.
+    pub retained_samples: usize,
+}
+
+/// Everything needed to continue a run elsewhere: config, state, pending
+/// events and accumulators, strategy state, and RNG state. Trace writers,
+/// step hooks, and observers are not part of it.
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EngineSnapshot<R = EngineRng> {
+    pub config: SimConfig,
+    pub state: EngineState,
+    rng: R,
+    strategy: serde_json::Value,
+    run: Option<RunState<R>>,
+}
+
+/// Invoked after every processed event; `--step` uses this to pause the run.
+pub trait StepHook {
+    fn after_event(&mut self, time_ms: u64, servers: &[ServerState]) -> Result<()>;
+}
+
+/// Receives engine events as they are processed, for custom metrics, live
+/// views, or logging without touching the run loop. Every method defaults to
+/// a no-op.
+pub trait EngineObserver {
+    fn on_arrival(&mut self, _time_ms: u64, _request: &Request) {}
+
+    /// `assignment` carries the scheduled start and completion; `servers`
+    /// reflects the pick.
+    fn on_select(&mut self, _time_ms: u64, _assignment: &Assignment, _servers: &[ServerState]) {}
+
+    fn on_complete(&mut self, _time_ms: u64, _request_id: u64, _server: &ServerState) {}
+
+    /// A request left unfinished because a `--max-events` /
+    /// `--max-virtual-time` limit aborted the run.
+    fn on_fail(&mut self, _time_ms: u64, _request_id: u64, _reason: &str) {}
 }
 
 impl SimulationEngine {
     pub fn new(config: SimConfig, strategy: Box<dyn SelectionStrategy>) -> Self {
-        let seed = match config.tie_break {
-            TieBreakConfig::Seeded => config.seed.unwrap_or(0),
-            TieBreakConfig::Stable => 0,
-        };
-        let rng = StdRng::seed_from_u64(seed);
+        Self::seeded(config, strategy)
+    }
+}
+
+impl<R: RngCore + SeedableRng> SimulationEngine<R> {
+    /// An engine on generator `R`, e.g. `SimulationEngine::<ChaCha8Rng>::seeded`.
+    /// Tie-breaks, the workload, and each server's service times get their own
+    /// `R` stream derived from the seed in `config.randomness` (see
+    /// `stream_seed`), exactly as `new` seeds the default generator. `Entropy`
+    /// draws its seed here, once per engine.
+    pub fn seeded(mut config: SimConfig, strategy: Box<dyn SelectionStrategy>) -> Self {
+        config.randomness = config.randomness.resolve();
+        let seed = config.randomness.seed().unwrap_or(0);
+        let rng = R::seed_from_u64(stream_seed(seed, TIE_BREAK_STREAM));
         let state = EngineState {
             time_ms: 0,
             servers: Vec::new(),
@@ -37,102 +163,618 @@ impl SimulationEngine {
             state,
             strategy,
             rng,
+            clock: Box::new(SimulatedClock::default()),
+            trace: None,
+            event_log: None,
+            assignment_sink: None,
+            histogram_percentiles: false,
+            assignment_sample: None,
+            step: None,
+            observers: Vec::new(),
+            run: None,
+            spare: RunBuffers::default(),
+            memory: MemoryStats::default(),
+            replication_seed: None,
+        }
+    }
+
+    /// Switches to `seed` for the next run, reseeding the tie-break generator
+    /// as `seeded` would. The policy keeps its kind: a `Stable` run still
+    /// breaks ties in input order and only its workload and service times
+    /// move to the new seed. Buffers from earlier runs are kept for reuse.
+    fn reseed(&mut self, seed: u64) {
+        if self.config.randomness.seeded_tie_breaks() {
+            self.config.randomness = RandomnessConfig::Seeded(seed);
+        }
+        self.replication_seed = Some(seed);
+        self.rng = R::seed_from_u64(stream_seed(seed, TIE_BREAK_STREAM));
+    }
+
+    /// The seed this run's streams derive from.
+    fn run_seed(&self) -> u64 {
+        self.replication_seed
+            .or_else(|| self.config.randomness.seed())
+            .unwrap_or(0)
+    }
+
+    /// Replaces the tie-break generator, e.g. with a scripted one in tests.
+    pub fn with_rng(mut self, rng: R) -> Self {
+        self.rng = rng;
+        self
+    }
+
+    /// Streams one line per processed event (arrival, selection, completion)
+    /// to `writer`.
+    pub fn with_trace(mut self, writer: Box<dyn Write>) -> Self {
+        self.trace = Some(writer);
+        self
+    }
+
+    /// Streams every processed `ScheduledEvent` to `writer`.
+    pub fn with_event_log(mut self, writer: Box<dyn EventLogWriter>) -> Self {
+        self.event_log = Some(writer);
+        self
+    }
+
+    /// Streams each assignment to `sink` as it is made. The run then keeps no
+    /// per-request samples: summary percentiles and the p99 tail come from the
+    /// latency histograms (within about 1/8 of the exact value, never below
+    /// it). Pair with `run(false)` for memory that does not grow with the
+    /// request count.
+    pub fn with_assignment_sink(mut self, sink: Box<dyn AssignmentSink>) -> Self {
+        self.assignment_sink = Some(sink);
+        self
+    }
+
+    /// Takes summary percentiles from the latency histograms, as streaming
+    /// runs do, instead of keeping every response time for exact ones.
+    pub fn with_histogram_percentiles(mut self) -> Self {
+        self.histogram_percentiles = true;
+        self
+    }
+
+    /// Keeps a uniform random sample of at most `limit` assignments instead of
+    /// all of them; totals and percentiles still cover every request. The
+    /// sample is reproducible for a given seed.
+    pub fn with_assignment_sample(mut self, limit: usize) -> Self {
+        self.assignment_sample = Some(limit);
+        self
+    }
+
+    /// Replaces the default `SimulatedClock`, e.g. with a `RealTimeClock` to
+    /// pace the run against the wall clock.
+    pub fn with_clock(mut self, clock: Box<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_step_hook(mut self, hook: Box<dyn StepHook>) -> Self {
+        self.step = Some(hook);
+        self
+    }
+
+    /// Attaches an observer; observers are notified in the order added.
+    pub fn with_observer(mut self, observer: Box<dyn EngineObserver>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    /// Pushes selection/completion counters, in-flight and queue-depth
+    /// gauges, and response/wait histograms into `sink` as the run goes; see
+    /// `MetricsSink` for the metric names.
+    pub fn with_metrics(self, sink: Box<dyn MetricsSink>) -> Self {
+        let observer = MetricsObserver::new(&self.config, sink);
+        self.with_observer(Box::new(observer))
+    }
+
+    /// Builds an engine from a snapshot with a fresh strategy for its algorithm
+    /// (registered ones included), then restores the snapshot into it.
+    #[cfg(feature = "serde")]
+    pub fn from_snapshot(snapshot: EngineSnapshot<R>) -> Result<Self>
+    where
+        R: Clone,
+    {
+        let strategy = build_strategy(snapshot.config.algo.clone())?;
+        let mut engine = Self::seeded(snapshot.config.clone(), strategy);
+        engine.restore(snapshot)?;
+        Ok(engine)
+    }
+
+    /// Captures the engine between steps, for checkpointing or branching a
+    /// run. Restoring it and continuing gives the same result as never
+    /// stopping.
+    #[cfg(feature = "serde")]
+    pub fn snapshot(&self) -> EngineSnapshot<R>
+    where
+        R: Clone,
+    {
+        EngineSnapshot {
+            config: self.config.clone(),
+            state: self.state.clone(),
+            rng: self.rng.clone(),
+            strategy: self.strategy.save_state(),
+            run: self.run.clone(),
+        }
+    }
+
+    /// Replaces config, state, and any run in progress with `snapshot`. The
+    /// engine's strategy must be for the snapshot's algorithm.
+    #[cfg(feature = "serde")]
+    pub fn restore(&mut self, snapshot: EngineSnapshot<R>) -> Result<()> {
+        if snapshot.config.algo != self.config.algo {
+            return Err(Error::Snapshot(format!(
+                "taken with algorithm {}, but this engine runs {}",
+                snapshot.config.algo, self.config.algo
+            )));
+        }
+        if let Some(run) = &snapshot.run {
+            if run.runtimes.len() != snapshot.state.servers.len() {
+                return Err(Error::Snapshot(format!(
+                    "{} server runtimes for {} servers",
+                    run.runtimes.len(),
+                    snapshot.state.servers.len()
+                )));
+            }
         }
+        self.strategy.restore_state(snapshot.strategy)?;
+        self.config = snapshot.config;
+        self.state = snapshot.state;
+        self.rng = snapshot.rng;
+        self.run = snapshot.run;
+        self.clock.reset(self.state.time_ms);
+        self.strategy.sync(&self.state.servers);
+        Ok(())
     }
 
+    /// Runs to completion; equivalent to `start` followed by `finish`. When
+    /// the strategy does not need completions and no trace, event log, step
+    /// hook, observer, timeseries, explain, or run limit would see them, the
+    /// run schedules no completion events; the result is the same.
     pub fn run(&mut self, store_assignments: bool) -> Result<SimulationResult> {
-        validate_config(&self.config)?;
-        let requests = build_requests(&self.config.requests, self.config.seed)?;
+        let skip_completions = self.completions_unobserved();
+        self.start_run(store_assignments, skip_completions)?;
+        self.finish()
+    }
+
+    /// Generates the workload and resets server state so the run can be
+    /// driven with `step`. Replaces any run already in progress.
+    pub fn start(&mut self, store_assignments: bool) -> Result<()> {
+        self.start_run(store_assignments, false)
+    }
+
+    fn completions_unobserved(&self) -> bool {
+        !self.strategy.needs_completions()
+            && self.trace.is_none()
+            && self.event_log.is_none()
+            && self.step.is_none()
+            && self.observers.is_empty()
+            && self.config.timeseries_bucket_ms.is_none()
+            && !self.config.explain
+            && self.config.max_events.is_none()
+            && self.config.max_virtual_time_ms.is_none()
+    }
+
+    fn start_run(&mut self, store_assignments: bool, skip_completions: bool) -> Result<()> {
+        validation::validate(&self.config)?;
+        let seed = self.run_seed();
+        let mut workload = Workload::<R>::new(&self.config.requests, Some(seed))?;
+        let first = workload.next().ok_or(Error::RequestsZero)?;
+        tracing::info!(
+            algo = %self.config.algo,
+            servers = self.config.servers.len(),
+            requests = ?self.config.requests,
+            "workload ready"
+        );
 
+        self.clock.reset(0);
+        self.state.time_ms = self.clock.now_ms();
         self.state.servers = init_server_state(&self.config.servers);
+        self.strategy.sync(&self.state.servers);
+        let store_assignments = store_assignments && self.assignment_sample.is_none();
         if store_assignments {
-            self.state.assignments = Vec::with_capacity(requests.len());
+            self.state.assignments = Vec::with_capacity(workload.size_hint().0 + 1);
         } else {
             self.state.assignments = Vec::new();
         }
+        self.run = Some(RunState::new(
+            &self.config,
+            seed,
+            workload,
+            first,
+            store_assignments,
+            self.assignment_sink.is_none() && !self.histogram_percentiles,
+            std::mem::take(&mut self.spare),
+        ));
+        if let Some(run) = self.run.as_mut() {
+            run.skip_completions = skip_completions;
+        }
+        if let (Some(run), Some(limit)) = (self.run.as_mut(), self.assignment_sample) {
+            run.sample = Some(AssignmentReservoir::new(
+                limit,
+                stream_seed(seed, SAMPLE_STREAM),
+            ));
+        }
+        Ok(())
+    }
 
-        let mut counts = vec![0u32; self.state.servers.len()];
-        let mut total_response_ms = vec![0u64; self.state.servers.len()];
-        let mut total_service_ms = vec![0u64; self.state.servers.len()];
-        let mut response_times = Vec::with_capacity(requests.len());
-        let mut total_wait_ms = 0u64;
-        let mut duration_ms = 0;
-        let mut first_arrival_ms: Option<u64> = None;
+    /// Current time, server state, and (when stored) assignments so far.
+    pub fn state(&self) -> &EngineState {
+        &self.state
+    }
 
-        let mut events: BinaryHeap<Reverse<ScheduledEvent>> = BinaryHeap::new();
-        for request in requests {
-            first_arrival_ms = Some(match first_arrival_ms {
-                Some(current) => current.min(request.arrival_time_ms),
-                None => request.arrival_time_ms,
-            });
-            events.push(Reverse(ScheduledEvent::new(
-                request.arrival_time_ms,
-                Event::RequestArrival(request),
-            )));
-        }
+    /// Events processed by the run in progress.
+    pub fn processed_events(&self) -> u64 {
+        self.run.as_ref().map_or(0, |run| run.processed_events)
+    }
 
-        let mut stable_rng = StableRng;
+    /// Buffer high-water marks of the last finished run.
+    pub fn memory_stats(&self) -> MemoryStats {
+        self.memory
+    }
 
-        while let Some(Reverse(scheduled)) = events.pop() {
-            self.state.time_ms = scheduled.time_ms;
-            match scheduled.event {
-                Event::RequestComplete { server_id, .. } => {
-                    let server = &mut self.state.servers[server_id];
-                    server.active_connections -= 1;
-                    server.in_flight -= 1;
+    /// Processes the next event, starting a run (with assignments stored) if
+    /// none is in progress. `None` once every event has been processed.
+    pub fn step(&mut self) -> Result<Option<EngineEvent>> {
+        if self.run.is_none() {
+            self.start(true)?;
+        }
+        let Some(run) = self.run.as_mut() else {
+            return Ok(None);
+        };
+        run.peak_queued_events = run.peak_queued_events.max(run.events.len());
+        let Some(Reverse(scheduled)) = run.events.pop() else {
+            return Ok(None);
+        };
+        let exceeded = match (self.config.max_events, self.config.max_virtual_time_ms) {
+            (Some(limit), _) if run.processed_events >= limit => {
+                Some(format!("--max-events {}", limit))
+            }
+            (_, Some(limit)) if scheduled.time_ms > limit => {
+                Some(format!("--max-virtual-time {}ms", limit))
+            }
+            _ => None,
+        };
+        if let Some(limit) = exceeded {
+            tracing::warn!(
+                %limit,
+                processed_events = run.processed_events,
+                "simulation limit exceeded"
+            );
+            let pending = std::mem::take(&mut run.events);
+            if self.observers.is_empty() {
+                run.total_arrivals += run.workload.by_ref().count() as u64;
+            } else {
+                let unarrived = run
+                    .workload
+                    .by_ref()
+                    .map(|request| request.id)
+                    .collect::<Vec<_>>();
+                run.total_arrivals += unarrived.len() as u64;
+                let mut unfinished = std::iter::once(scheduled)
+                    .chain(pending.into_iter().map(|Reverse(event)| event))
+                    .map(|scheduled| match scheduled.event {
+                        Event::RequestArrival(request) => request.id,
+                        Event::RequestComplete { request_id, .. } => request_id,
+                    })
+                    .chain(unarrived)
+                    .collect::<Vec<_>>();
+                unfinished.sort_unstable();
+                for request_id in unfinished {
+                    for observer in &mut self.observers {
+                        observer.on_fail(self.state.time_ms, request_id, &limit);
+                    }
+                }
+            }
+            let error = Error::SimulationLimit {
+                limit,
+                algo: self.config.algo.to_string(),
+                time_ms: self.state.time_ms,
+                events: run.processed_events,
+                completed: run.completed_requests,
+                total: run.total_arrivals,
+            };
+            self.run = None;
+            return Err(error);
+        }
+        run.processed_events += 1;
+        if let Some(log) = self.event_log.as_mut() {
+            log.write_event(&scheduled)?;
+        }
+        if let Some(last_ms) = run.last_event_ms {
+            run.in_system_area +=
+                u128::from(run.in_system) * u128::from(scheduled.time_ms - last_ms);
+        }
+        run.last_event_ms = Some(scheduled.time_ms);
+        self.clock.advance_to(scheduled.time_ms);
+        self.state.time_ms = self.clock.now_ms();
+        let event = match scheduled.event {
+            Event::RequestComplete {
+                server_id,
+                request_id,
+            } => {
+                let server = &mut self.state.servers[server_id];
+                server.active_connections -= 1;
+                server.in_flight -= 1;
+                self.strategy.server_updated(server);
+                run.in_system -= 1;
+                run.completed_requests += 1;
+                tracing::trace!(request_id, server = %server.name, "request completed");
+                for observer in &mut self.observers {
+                    observer.on_complete(self.state.time_ms, request_id, server);
+                }
+                if let Some(trace) = self.trace.as_mut() {
+                    writeln!(
+                        trace,
+                        "t={} complete request={} server={} in_flight={}",
+                        self.state.time_ms, request_id, server.name, server.in_flight
+                    )
+                    .map_err(trace_error)?;
+                }
+                if let Some(recorder) = run.timeseries.as_mut() {
+                    recorder.record_release(self.state.time_ms, server_id, server.in_flight);
+                }
+                EngineEvent::Completed {
+                    time_ms: self.state.time_ms,
+                    request_id,
+                    server_id,
+                }
+            }
+            Event::RequestArrival(request) => {
+                let _request = tracing::debug_span!("request", id = request.id).entered();
+                if let Some(next) = run.workload.next() {
+                    run.total_arrivals += 1;
+                    run.schedule(next.arrival_time_ms, Event::RequestArrival(next));
+                }
+                for observer in &mut self.observers {
+                    observer.on_arrival(self.state.time_ms, &request);
+                }
+                if let Some(trace) = self.trace.as_mut() {
+                    writeln!(
+                        trace,
+                        "t={} arrival request={}",
+                        self.state.time_ms, request.id
+                    )
+                    .map_err(trace_error)?;
+                }
+                let rng: &mut dyn RngCore = if self.config.randomness.seeded_tie_breaks() {
+                    &mut self.rng
+                } else {
+                    &mut StableRng
+                };
+                let mut ctx = SelectionContext {
+                    servers: &self.state.servers,
+                    time_ms: self.state.time_ms,
+                    rng,
+                };
+                let selection = self.strategy.select(&mut ctx);
+                let server_idx = selection.server_id;
+                if self.config.explain && run.store_assignments {
+                    run.explanations.push(explain_selection(
+                        self.strategy.as_ref(),
+                        &self.state.servers,
+                        self.state.time_ms,
+                        &self.config.randomness,
+                    ));
+                }
+                if let Some(trace) = self.trace.as_mut() {
+                    write_selection_trace(
+                        trace.as_mut(),
+                        self.state.time_ms,
+                        request.id,
+                        &selection,
+                        self.strategy.last_candidates(),
+                        &self.state.servers,
+                    )?;
                 }
-                Event::RequestArrival(request) => {
-                    let rng: &mut dyn RngCore = match self.config.tie_break {
-                        TieBreakConfig::Stable => &mut stable_rng,
-                        TieBreakConfig::Seeded => &mut self.rng,
-                    };
-                    let mut ctx = SelectionContext {
-                        servers: &self.state.servers,
-                        time_ms: self.state.time_ms,
-                        rng,
-                    };
-                    let selection = self.strategy.select(&mut ctx);
-                    let server_idx = selection.server_id;
 
-                    let server = &mut self.state.servers[server_idx];
+                let server = &mut self.state.servers[server_idx];
+                server.pick_count += 1;
+                if !run.skip_completions {
                     server.active_connections += 1;
-                    server.pick_count += 1;
                     server.in_flight += 1;
+                    self.strategy.server_updated(server);
+                    run.in_system += 1;
+                }
 
-                    let started_at = self.state.time_ms.max(server.next_available_ms);
-                    let completed_at = started_at + server.base_latency_ms;
-                    server.next_available_ms = completed_at;
-                    let response_time = completed_at - request.arrival_time_ms;
-                    let service_time = completed_at - started_at;
-                    let wait_time = started_at.saturating_sub(request.arrival_time_ms);
-                    counts[server_idx] += 1;
-                    total_response_ms[server_idx] += response_time;
-                    total_service_ms[server_idx] += service_time;
-                    response_times.push(response_time);
-                    total_wait_ms += wait_time;
-                    duration_ms = duration_ms.max(completed_at);
-                    events.push(Reverse(ScheduledEvent::new(
+                let runtime = &mut run.runtimes[server_idx];
+                let (started_at, completed_at) = runtime
+                    .schedule(self.state.time_ms, server.base_latency_ms)
+                    .ok_or_else(|| Error::TimeOverflow {
+                        server: server.name.clone(),
+                        started_at: self.state.time_ms.max(runtime.next_available_ms()),
+                        service_ms: server.base_latency_ms,
+                    })?;
+                server.next_available_ms = runtime.next_available_ms();
+                tracing::debug!(
+                    server = %server.name,
+                    started_at,
+                    completed_at,
+                    "request assigned"
+                );
+                let response_time = completed_at - request.arrival_time_ms;
+                let service_time = completed_at - started_at;
+                let wait_time = started_at.saturating_sub(request.arrival_time_ms);
+                run.counts[server_idx] += 1;
+                // Saturate rather than wrap on multi-billion-request runs.
+                let totals = [
+                    (&mut run.total_response_ms, response_time),
+                    (&mut run.total_service_ms, service_time),
+                    (&mut run.total_queued_ms, wait_time),
+                ];
+                for (total, value) in totals {
+                    total[server_idx] = total[server_idx].saturating_add(value);
+                }
+                if let Some(samples) = run.server_response_times.as_mut() {
+                    samples[server_idx].push(response_time);
+                }
+                run.overall_histogram.record(response_time);
+                run.server_histograms[server_idx].record(response_time);
+                if let Some(recorder) = run.timeseries.as_mut() {
+                    recorder.record_arrival(self.state.time_ms, server_idx, server.in_flight);
+                    recorder.record_completion(completed_at, response_time);
+                }
+                run.slo_tracker
+                    .record(server_idx, completed_at, response_time);
+                run.slowest.record(
+                    request.id,
+                    server_idx,
+                    request.arrival_time_ms,
+                    started_at,
+                    completed_at,
+                    selection.score,
+                );
+                if let Some(recorder) = run.timelines.as_mut() {
+                    recorder.record(
+                        server_idx,
+                        request.arrival_time_ms,
+                        started_at,
+                        completed_at,
+                    );
+                }
+                run.total_wait_ms = run.total_wait_ms.saturating_add(wait_time);
+                run.duration_ms = run.duration_ms.max(completed_at);
+                if run.skip_completions {
+                    // Equivalent to integrating this request's time in system
+                    // across the completion events it would have produced.
+                    run.in_system_area += u128::from(response_time);
+                    run.completed_requests += 1;
+                } else {
+                    run.schedule(
                         completed_at,
                         Event::RequestComplete {
                             server_id: server_idx,
                             request_id: request.id,
                         },
-                    )));
+                    );
+                }
 
-                    if store_assignments {
-                        self.state.assignments.push(Assignment {
-                            request_id: request.id,
-                            server_id: server_idx,
-                            arrival_time_ms: request.arrival_time_ms,
-                            started_at,
-                            completed_at,
-                            score: selection.score,
-                        });
-                    }
+                let assignment = Assignment {
+                    request_id: request.id,
+                    server_id: server_idx,
+                    arrival_time_ms: request.arrival_time_ms,
+                    started_at,
+                    completed_at,
+                    queued_ms: wait_time,
+                    service_ms: service_time,
+                    score: selection.score,
+                };
+                for observer in &mut self.observers {
+                    observer.on_select(self.state.time_ms, &assignment, &self.state.servers);
+                }
+                if let Some(sink) = self.assignment_sink.as_mut() {
+                    sink.write_assignment(&assignment, &self.state.servers[server_idx])?;
+                }
+                if run.store_assignments {
+                    self.state.assignments.push(assignment.clone());
+                } else if let Some(sample) = run.sample.as_mut() {
+                    sample.record(&assignment);
                 }
+                EngineEvent::Assigned {
+                    time_ms: self.state.time_ms,
+                    assignment,
+                }
+            }
+        };
+        if let Some(hook) = self.step.as_mut() {
+            if let Some(trace) = self.trace.as_mut() {
+                trace.flush().map_err(trace_error)?;
+            }
+            hook.after_event(self.state.time_ms, &self.state.servers)?;
+        }
+        Ok(Some(event))
+    }
+
+    /// Like `run`, but yields to the tokio scheduler every `yield_every` events
+    /// so a huge run does not monopolize an executor thread.
+    #[cfg(feature = "tokio")]
+    pub async fn run_async(
+        &mut self,
+        store_assignments: bool,
+        yield_every: u64,
+    ) -> Result<SimulationResult> {
+        self.start(store_assignments)?;
+        let yield_every = yield_every.max(1);
+        while self.step()?.is_some() {
+            if self.processed_events().is_multiple_of(yield_every) {
+                tokio::task::yield_now().await;
             }
         }
+        self.finish()
+    }
+
+    /// Processes any remaining events and builds the result. Starts a run (with
+    /// assignments stored) if none is in progress.
+    pub fn finish(&mut self) -> Result<SimulationResult> {
+        if self.run.is_none() {
+            self.start(true)?;
+        }
+        let _simulation = tracing::info_span!(
+            "simulation",
+            algo = %self.config.algo,
+            servers = self.config.servers.len()
+        )
+        .entered();
+        while self.step()?.is_some() {}
+        let run = self.run.take().expect("step keeps the run until it ends");
+        let RunState {
+            store_assignments,
+            events,
+            counts,
+            total_response_ms,
+            total_service_ms,
+            total_queued_ms,
+            mut server_response_times,
+            overall_histogram,
+            server_histograms,
+            timeseries,
+            timelines,
+            slo_tracker,
+            slowest,
+            explanations,
+            total_wait_ms,
+            duration_ms,
+            first_arrival_ms,
+            processed_events,
+            completed_requests,
+            in_system_area,
+            peak_queued_events,
+            sample,
+            skip_completions,
+            ..
+        } = run;
+        if skip_completions {
+            self.clock.advance_to(duration_ms);
+            self.state.time_ms = self.clock.now_ms();
+        }
+        self.memory = MemoryStats {
+            peak_queued_events,
+            stored_assignments: self.state.assignments.len()
+                + sample.as_ref().map_or(0, AssignmentReservoir::len),
+            retained_samples: server_response_times
+                .as_ref()
+                .map_or(0, |samples| samples.iter().map(Vec::len).sum()),
+        };
+
+        if let Some(trace) = self.trace.as_mut() {
+            trace.flush().map_err(trace_error)?;
+        }
+        if let Some(log) = self.event_log.as_mut() {
+            log.flush()?;
+        }
+        if let Some(sink) = self.assignment_sink.as_mut() {
+            sink.flush()?;
+        }
+        tracing::info!(
+            completed = completed_requests,
+            events = processed_events,
+            duration_ms,
+            "simulation finished"
+        );
 
+        let percentiles = match server_response_times.as_mut() {
+            Some(samples) => Percentiles::exact(samples),
+            None => Percentiles::from_histograms(&overall_histogram, &server_histograms),
+        };
         let totals = self
             .state
             .servers
@@ -140,22 +782,43 @@ impl SimulationEngine {
             .enumerate()
             .map(|(idx, server)| {
                 let count = counts[idx];
-                let avg_response_ms = if count == 0 {
-                    0
-                } else {
-                    total_response_ms[idx] / count as u64
-                };
+                let average = |total: u64| total.checked_div(count).unwrap_or(0);
                 ServerSummary {
                     name: server.name.clone(),
+                    zone: self.config.servers[idx].model.zone.clone(),
                     requests: count,
-                    avg_response_ms,
+                    avg_response_ms: average(total_response_ms[idx]),
+                    avg_queued_ms: average(total_queued_ms[idx]),
+                    avg_service_ms: average(total_service_ms[idx]),
+                    p99_ms: percentiles.per_server_p99[idx],
                 }
             })
             .collect();
 
-        response_times.sort_unstable();
-        let p95_ms = nearest_rank_percentile(&response_times, 95.0);
-        let p99_ms = nearest_rank_percentile(&response_times, 99.0);
+        let (p95_ms, p99_ms) = (percentiles.p95_ms, percentiles.p99_ms);
+        let tail = TailBreakdown {
+            threshold_ms: p99_ms,
+            per_server: self
+                .state
+                .servers
+                .iter()
+                .enumerate()
+                .map(|(idx, server)| {
+                    let tail_requests = percentiles.tail_requests[idx];
+                    let tail_pct = if counts[idx] == 0 {
+                        0.0
+                    } else {
+                        tail_requests as f64 / counts[idx] as f64 * 100.0
+                    };
+                    ServerTail {
+                        name: server.name.clone(),
+                        tail_requests,
+                        tail_pct: round_to(tail_pct, 2),
+                        p99_ms: percentiles.per_server_p99[idx],
+                    }
+                })
+                .collect(),
+        };
         let active_duration_ms = match self.config.requests {
             RequestProfile::Burst { at_ms, .. } if at_ms > 0 => {
                 duration_ms.saturating_sub(first_arrival_ms.unwrap_or(0))
@@ -170,10 +833,11 @@ impl SimulationEngine {
             .enumerate()
             .map(|(idx, server)| {
                 let busy_time_ms = total_service_ms[idx];
+                let slots = self.config.servers[idx].model.concurrency();
                 let utilization_pct = if active_duration_ms == 0 {
                     0.0
                 } else {
-                    (busy_time_ms as f64 / active_duration_ms as f64) * 100.0
+                    (busy_time_ms as f64 / (active_duration_ms as f64 * f64::from(slots))) * 100.0
                 };
                 ServerUtilization {
                     name: server.name.clone(),
@@ -181,22 +845,43 @@ impl SimulationEngine {
                 }
             })
             .collect::<Vec<_>>();
-        let total_requests = counts.iter().copied().map(u64::from).sum::<u64>();
+        let total_requests = counts.iter().sum::<u64>();
         let throughput_rps = if active_duration_ms == 0 {
             0.0
         } else {
             (total_requests as f64 / active_duration_ms as f64) * 1000.0
         };
-        let avg_wait_ms = if total_requests == 0 {
-            0
+        let avg_wait_ms = total_wait_ms.checked_div(total_requests).unwrap_or(0);
+        let time_in_system_ms = total_response_ms.iter().sum::<u64>();
+        let avg_concurrency = if active_duration_ms == 0 {
+            0.0
         } else {
-            total_wait_ms / total_requests
+            time_in_system_ms as f64 / active_duration_ms as f64
         };
-        let sum = counts.iter().copied().map(f64::from).sum::<f64>();
+        let littles_law = littles_law(
+            in_system_area,
+            total_requests,
+            time_in_system_ms,
+            active_duration_ms,
+        );
+        let analytic = mmc_comparison(
+            &self.config,
+            if total_requests == 0 {
+                0.0
+            } else {
+                total_wait_ms as f64 / total_requests as f64
+            },
+            if active_duration_ms == 0 || self.state.servers.is_empty() {
+                0.0
+            } else {
+                total_service_ms.iter().sum::<u64>() as f64
+                    / (active_duration_ms as f64 * self.state.servers.len() as f64)
+            },
+        );
+        let sum = counts.iter().map(|&count| count as f64).sum::<f64>();
         let sum_sq = counts
             .iter()
-            .copied()
-            .map(f64::from)
+            .map(|&count| count as f64)
             .map(|value| value * value)
             .sum::<f64>();
         let jain_fairness = if sum == 0.0 || sum_sq == 0.0 {
@@ -204,19 +889,63 @@ impl SimulationEngine {
         } else {
             (sum * sum) / (counts.len() as f64 * sum_sq)
         };
+        let latency_histograms = LatencyHistograms {
+            overall: overall_histogram.to_histogram(),
+            per_server: self
+                .state
+                .servers
+                .iter()
+                .zip(&server_histograms)
+                .map(|(server, recorder)| ServerLatencyHistogram {
+                    name: server.name.clone(),
+                    histogram: recorder.to_histogram(),
+                })
+                .collect(),
+        };
+
+        self.spare = RunBuffers {
+            events,
+            counts,
+            total_response_ms,
+            total_service_ms,
+            total_queued_ms,
+            samples: server_response_times.unwrap_or_default(),
+        };
 
+        let server_names = || {
+            self.state
+                .servers
+                .iter()
+                .map(|server| server.name.clone())
+                .collect::<Vec<_>>()
+        };
         Ok(SimulationResult {
-            assignments: if store_assignments {
-                std::mem::take(&mut self.state.assignments)
-            } else {
-                Vec::new()
+            assignments: match sample {
+                Some(sample) => sample.finish(),
+                None if store_assignments => std::mem::take(&mut self.state.assignments),
+                None => Vec::new(),
             },
             totals,
             metadata: RunMetadata {
                 algo: self.config.algo.to_string(),
-                tie_break: self.config.tie_break.label_with_seed(self.config.seed),
+                tie_break: self.config.randomness.to_string(),
+                seed: Some(self.run_seed()),
+                rng: Some(self.config.rng.to_string()),
+                event_order: (!self.config.event_order.is_default())
+                    .then(|| self.config.event_order.to_string()),
+                score_metric: self
+                    .strategy
+                    .score_metric()
+                    .map(|metric| metric.name().to_string()),
+                assignment_sample: self.assignment_sample,
                 duration_ms: active_duration_ms,
             },
+            global: GlobalStats {
+                total_requests,
+                makespan_ms: active_duration_ms,
+                throughput_rps: round_to(throughput_rps, 2),
+                avg_concurrency: round_to(avg_concurrency, 2),
+            },
             phase1_metrics: Phase1Metrics {
                 response_time: ResponseTimePercentiles { p95_ms, p99_ms },
                 per_server_utilization,
@@ -224,6 +953,15 @@ impl SimulationEngine {
                 throughput_rps: round_to(throughput_rps, 2),
                 avg_wait_ms,
             },
+            latency_histograms,
+            littles_law,
+            tail,
+            analytic,
+            timeseries: timeseries.map(|recorder| recorder.finish(server_names())),
+            timelines: timelines.map(|recorder| recorder.finish(server_names())),
+            slo: slo_tracker.finish(&server_names()),
+            slowest: slowest.finish(&server_names()),
+            explanations,
         })
     }
 }
@@ -236,192 +974,1611 @@ pub fn run_simulation_summary(config: &SimConfig) -> Result<SimulationResult> {
     run_simulation_with_options(config, false)
 }
 
+pub fn run_simulation_with_trace(
+    config: &SimConfig,
+    store_assignments: bool,
+    trace: Box<dyn Write>,
+) -> Result<SimulationResult> {
+    let strategy = build_strategy(config.algo.clone())?;
+    with_rng!(config.rng, R => {
+        let mut engine = SimulationEngine::<R>::seeded(config.clone(), strategy).with_trace(trace);
+        engine.run(store_assignments)
+    })
+}
+
+pub fn run_simulation_stepped(
+    config: &SimConfig,
+    store_assignments: bool,
+    trace: Box<dyn Write>,
+    hook: Box<dyn StepHook>,
+) -> Result<SimulationResult> {
+    let strategy = build_strategy(config.algo.clone())?;
+    with_rng!(config.rng, R => {
+        let mut engine = SimulationEngine::<R>::seeded(config.clone(), strategy)
+            .with_trace(trace)
+            .with_step_hook(hook);
+        engine.run(store_assignments)
+    })
+}
+
 pub fn run_simulation_with_options(
     config: &SimConfig,
     store_assignments: bool,
 ) -> Result<SimulationResult> {
-    let strategy = build_strategy(config.algo.clone());
-    let mut engine = SimulationEngine::new(config.clone(), strategy);
-    engine.run(store_assignments)
+    let strategy = build_strategy(config.algo.clone())?;
+    with_rng!(config.rng, R => {
+        SimulationEngine::<R>::seeded(config.clone(), strategy).run(store_assignments)
+    })
 }
 
-fn validate_config(config: &SimConfig) -> Result<()> {
-    if config.servers.is_empty() {
-        return Err(Error::EmptyServers);
-    }
-    let mut names = HashSet::new();
-    for server in &config.servers {
-        if server.name.trim().is_empty() {
-            return Err(Error::InvalidServerEntry(server.name.clone()));
-        }
-        if server.base_latency_ms == 0 {
-            return Err(Error::InvalidLatencyValue(server.name.clone()));
-        }
-        if server.weight == 0 {
-            return Err(Error::InvalidWeightValue(server.name.clone()));
+/// Runs `config` twice in lockstep and fails with `Error::Diverged` at the
+/// first event where the runs disagree (`--verify-determinism`). Entropy is
+/// resolved once, so both runs share a seed.
+pub fn verify_determinism(config: &SimConfig) -> Result<u64> {
+    let mut config = config.clone();
+    config.randomness = config.randomness.resolve();
+    with_rng!(config.rng, R => verify_determinism_with::<R>(&config))
+}
+
+fn verify_determinism_with<R: RngCore + SeedableRng>(config: &SimConfig) -> Result<u64> {
+    let mut first =
+        SimulationEngine::<R>::seeded(config.clone(), build_strategy(config.algo.clone())?);
+    let mut second =
+        SimulationEngine::<R>::seeded(config.clone(), build_strategy(config.algo.clone())?);
+    first.start(false)?;
+    second.start(false)?;
+    let mut index = 0;
+    loop {
+        let (ours, theirs) = (first.step()?, second.step()?);
+        if ours != theirs {
+            return Err(Error::Diverged {
+                index,
+                first: describe_step(ours.as_ref(), &config.servers),
+                second: describe_step(theirs.as_ref(), &config.servers),
+            });
         }
-        if names.contains(&server.name) {
-            return Err(Error::DuplicateServerName(server.name.clone()));
+        if ours.is_none() {
+            return Ok(index);
         }
-        names.insert(server.name.clone());
+        index += 1;
     }
+}
 
-    match config.requests {
-        RequestProfile::FixedCount(0) => return Err(Error::RequestsZero),
-        RequestProfile::FixedCount(_) => {}
-        RequestProfile::Poisson { rate, duration_ms } => {
-            if rate <= 0.0 {
-                return Err(Error::InvalidRequestRate(rate));
-            }
-            if duration_ms == 0 {
-                return Err(Error::InvalidRequestDuration(duration_ms));
-            }
-        }
-        RequestProfile::Burst { count, .. } => {
-            if count == 0 {
-                return Err(Error::RequestsZero);
+fn describe_step(event: Option<&EngineEvent>, servers: &[ServerConfig]) -> String {
+    let name = |server_id: usize| servers.get(server_id).map_or("?", |server| &server.name);
+    match event {
+        Some(EngineEvent::Assigned {
+            time_ms,
+            assignment,
+        }) => format!(
+            "t={}ms assigned request {} to {} (start {}ms, complete {}ms, score {})",
+            time_ms,
+            assignment.request_id,
+            name(assignment.server_id),
+            assignment.started_at,
+            assignment.completed_at,
+            assignment
+                .score
+                .map_or_else(|| "none".to_string(), |score| score.to_string())
+        ),
+        Some(EngineEvent::Completed {
+            time_ms,
+            request_id,
+            server_id,
+        }) => format!(
+            "t={}ms completed request {} on {}",
+            time_ms,
+            request_id,
+            name(*server_id)
+        ),
+        None => "ended".to_string(),
+    }
+}
+
+/// Runs the simulation on tokio's blocking pool; unlike
+/// `SimulationEngine::run_async` the returned future is `Send`, so it can be
+/// awaited from spawned tasks such as request handlers.
+#[cfg(feature = "tokio")]
+pub async fn run_simulation_async(
+    config: SimConfig,
+    store_assignments: bool,
+) -> Result<SimulationResult> {
+    tokio::task::spawn_blocking(move || run_simulation_with_options(&config, store_assignments))
+        .await
+        .map_err(|err| Error::Cli(format!("simulation task failed: {}", err)))?
+}
+
+/// Runs one summary-only simulation per seed. Each seed replaces the one in
+/// `config.randomness`, so the workload, service times, and (under a seeded
+/// policy) tie-breaks vary between replications; a `Stable` policy keeps
+/// breaking ties in input order. With the
+/// `parallel` feature the seeds run across rayon's thread pool; results keep
+/// the order of `seeds` either way.
+pub fn run_replications(config: &SimConfig, seeds: &[u64]) -> Result<Vec<SimulationResult>> {
+    with_rng!(config.rng, R => replications_with::<R>(config, seeds))
+}
+
+fn replications_with<R>(config: &SimConfig, seeds: &[u64]) -> Result<Vec<SimulationResult>>
+where
+    R: RngCore + SeedableRng,
+{
+    // Each worker keeps one engine so its run buffers are reused; only the
+    // strategy is rebuilt, so every replication starts from fresh state.
+    let run = |engine: &mut Option<SimulationEngine<R>>, seed: &u64| {
+        let strategy = build_strategy(config.algo.clone())?;
+        let engine = match engine {
+            Some(engine) => {
+                engine.strategy = strategy;
+                engine
             }
-        }
+            None => engine.insert(SimulationEngine::seeded(config.clone(), strategy)),
+        };
+        engine.reseed(*seed);
+        engine.run(false)
+    };
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        seeds.par_iter().map_init(|| None, run).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        let mut engine = None;
+        seeds.iter().map(|seed| run(&mut engine, seed)).collect()
+    }
+}
+
+/// Runs each config as a summary-only simulation, across rayon's thread pool
+/// with the `parallel` feature. Results keep the order of `configs`, and the
+/// error reported is the first failing config's either way.
+pub fn run_summaries(configs: &[SimConfig]) -> Result<Vec<SimulationResult>> {
+    #[cfg(feature = "parallel")]
+    let results = {
+        use rayon::prelude::*;
+        configs
+            .par_iter()
+            .map(run_simulation_summary)
+            .collect::<Vec<_>>()
+    };
+    #[cfg(not(feature = "parallel"))]
+    let results = configs.iter().map(run_simulation_summary);
+    results.into_iter().collect()
+}
+
+fn explain_selection(
+    strategy: &dyn SelectionStrategy,
+    servers: &[ServerState],
+    time_ms: u64,
+    randomness: &RandomnessConfig,
+) -> SelectionExplain {
+    let tied = strategy.last_candidates();
+    let tie_break = match tied.len() {
+        0 => "rotation",
+        1 => "unique-best",
+        _ if randomness.seeded_tie_breaks() => "seeded-random",
+        _ => "stable-first",
+    };
+    SelectionExplain {
+        candidates: servers
+            .iter()
+            .map(|server| CandidateExplain {
+                server: server.name.clone(),
+                active_connections: server.active_connections,
+                score: strategy.score(server, time_ms),
+            })
+            .collect(),
+        tied: tied.iter().map(|idx| servers[*idx].name.clone()).collect(),
+        tie_break: tie_break.to_string(),
+    }
+}
+
+fn write_selection_trace(
+    trace: &mut dyn Write,
+    time_ms: u64,
+    request_id: u64,
+    selection: &Selection,
+    candidates: &[usize],
+    servers: &[ServerState],
+) -> Result<()> {
+    let candidates = if candidates.is_empty() {
+        std::slice::from_ref(&selection.server_id)
+    } else {
+        candidates
+    };
+    let candidate_names = candidates
+        .iter()
+        .map(|idx| servers[*idx].name.as_str())
+        .collect::<Vec<_>>()
+        .join(",");
+    write!(
+        trace,
+        "t={} select request={} server={} candidates=[{}]",
+        time_ms, request_id, servers[selection.server_id].name, candidate_names
+    )
+    .map_err(trace_error)?;
+    if let Some(score) = selection.score {
+        write!(trace, " score={}", score).map_err(trace_error)?;
     }
+    writeln!(trace).map_err(trace_error)
+}
+
+fn trace_error(err: std::io::Error) -> Error {
+    Error::Output(format!("failed to write trace: {}", err))
+}
+
+/// The workload a run would see: requests in arrival order, with the same
+/// seeded Poisson draws `run` uses.
+pub fn build_requests(profile: &RequestProfile, seed: Option<u64>) -> Result<Vec<Request>> {
+    build_requests_with::<EngineRng>(profile, seed)
+}
 
-    if matches!(config.tie_break, TieBreakConfig::Seeded) && config.seed.is_none() {
-        return Err(Error::InvalidTieBreakSeed);
+/// `build_requests` with the generator `config.rng` selects, as a run of
+/// `config` through `run_simulation` sees it.
+pub fn build_config_requests(config: &SimConfig) -> Result<Vec<Request>> {
+    with_rng!(config.rng, R => build_requests_with::<R>(&config.requests, config.randomness.seed()))
+}
+
+/// `build_requests` with Poisson arrivals drawn from generator `R`.
+pub fn build_requests_with<R: RngCore + SeedableRng>(
+    profile: &RequestProfile,
+    seed: Option<u64>,
+) -> Result<Vec<Request>> {
+    let requests = Workload::<R>::new(profile, seed)?.collect::<Vec<_>>();
+    if requests.is_empty() {
+        return Err(Error::RequestsZero);
     }
+    Ok(requests)
+}
 
-    Ok(())
+/// Yields a profile's requests in arrival order, one at a time, so a run
+/// keeps only the next arrival in its event queue.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+enum Workload<R> {
+    /// Request `id` arrives at `at_ms`, or at `id - 1` when that is unset.
+    Sequence {
+        next_id: u64,
+        count: u64,
+        at_ms: Option<u64>,
+    },
+    Poisson {
+        rng: R,
+        lambda_ms: f64,
+        duration_ms: u64,
+        /// The f64 arrival clock as raw bits, so snapshots restore it exactly.
+        time_bits: u64,
+        next_id: u64,
+    },
 }
 
-fn build_requests(profile: &RequestProfile, seed: Option<u64>) -> Result<Vec<Request>> {
-    match profile {
-        RequestProfile::FixedCount(count) => {
-            if *count == 0 {
-                return Err(Error::RequestsZero);
-            }
-            Ok((0..*count)
-                .map(|idx| Request {
-                    id: idx + 1,
-                    arrival_time_ms: idx as u64,
-                })
-                .collect())
+impl<R: RngCore + SeedableRng> Workload<R> {
+    fn new(profile: &RequestProfile, seed: Option<u64>) -> Result<Self> {
+        if let Some(err) = request_problems(profile).into_iter().next() {
+            return Err(err);
         }
-        RequestProfile::Poisson { rate, duration_ms } => {
-            if *rate <= 0.0 {
-                return Err(Error::InvalidRequestRate(*rate));
-            }
-            if *duration_ms == 0 {
-                return Err(Error::InvalidRequestDuration(*duration_ms));
-            }
+        Ok(match profile {
+            RequestProfile::FixedCount(count) => Self::Sequence {
+                next_id: 1,
+                count: *count as u64,
+                at_ms: None,
+            },
+            RequestProfile::Poisson { rate, duration_ms } => Self::Poisson {
+                rng: R::seed_from_u64(seed.unwrap_or(0)),
+                lambda_ms: rate / 1000.0,
+                duration_ms: *duration_ms,
+                time_bits: 0f64.to_bits(),
+                next_id: 1,
+            },
+            RequestProfile::Burst { count, at_ms } => Self::Sequence {
+                next_id: 1,
+                count: *count as u64,
+                at_ms: Some(*at_ms),
+            },
+        })
+    }
+}
+
+impl<R: RngCore> Iterator for Workload<R> {
+    type Item = Request;
 
-            let mut rng = StdRng::seed_from_u64(seed.unwrap_or(0));
-            let lambda_ms = rate / 1000.0;
-            let mut requests = Vec::new();
-            let mut time = 0.0;
-            let mut id = 1usize;
-            while time < *duration_ms as f64 {
+    fn next(&mut self) -> Option<Request> {
+        match self {
+            Self::Sequence {
+                next_id,
+                count,
+                at_ms,
+            } => {
+                if *next_id > *count {
+                    return None;
+                }
+                let id = *next_id;
+                *next_id += 1;
+                Some(Request {
+                    id,
+                    arrival_time_ms: at_ms.unwrap_or(id - 1),
+                })
+            }
+            Self::Poisson {
+                rng,
+                lambda_ms,
+                duration_ms,
+                time_bits,
+                next_id,
+            } => {
+                let mut time = f64::from_bits(*time_bits);
+                if time >= *duration_ms as f64 {
+                    return None;
+                }
                 let mut u = rng.gen::<f64>();
                 if u <= f64::MIN_POSITIVE {
                     u = f64::MIN_POSITIVE;
                 }
-                let inter_arrival = -u.ln() / lambda_ms;
-                time += inter_arrival;
+                time += -u.ln() / *lambda_ms;
+                *time_bits = time.to_bits();
                 if time >= *duration_ms as f64 {
-                    break;
+                    return None;
                 }
-                requests.push(Request {
+                let id = *next_id;
+                *next_id += 1;
+                Some(Request {
                     id,
                     arrival_time_ms: time.floor() as u64,
-                });
-                id += 1;
+                })
             }
+        }
+    }
 
-            if requests.is_empty() {
-                return Err(Error::RequestsZero);
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Self::Sequence { next_id, count, .. } => {
+                let remaining =
+                    usize::try_from((*count + 1).saturating_sub(*next_id)).unwrap_or(usize::MAX);
+                (remaining, Some(remaining))
             }
-
-            Ok(requests)
+            Self::Poisson { .. } => (0, None),
         }
-        RequestProfile::Burst { count, at_ms } => {
-            if *count == 0 {
-                return Err(Error::RequestsZero);
+    }
+}
+
+fn init_server_state(servers: &[ServerConfig]) -> Vec<ServerState> {
+    servers
+        .iter()
+        .enumerate()
+        .map(|(id, server)| ServerState {
+            id,
+            name: server.name.clone(),
+            base_latency_ms: server.base_latency_ms,
+            weight: server.weight,
+            active_connections: 0,
+            pick_count: 0,
+            in_flight: 0,
+            next_available_ms: 0,
+        })
+        .collect()
+}
+
+/// Accumulators for the run in progress, kept between `step` calls.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+struct RunState<R> {
+    store_assignments: bool,
+    events: BinaryHeap<Reverse<ScheduledEvent>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    event_order: EventOrder,
+    /// Events queued so far, which rank `EventOrder::Fifo` events.
+    #[cfg_attr(feature = "serde", serde(default))]
+    scheduled_events: u64,
+    runtimes: Vec<ServerRuntime<R>>,
+    counts: Vec<u64>,
+    total_response_ms: Vec<u64>,
+    total_service_ms: Vec<u64>,
+    total_queued_ms: Vec<u64>,
+    /// Raw response times per server for exact percentiles; `None` when
+    /// assignments are streamed and percentiles come from the histograms.
+    server_response_times: Option<Vec<Vec<u64>>>,
+    overall_histogram: HistogramRecorder,
+    server_histograms: Vec<HistogramRecorder>,
+    timeseries: Option<TimeseriesRecorder>,
+    timelines: Option<TimelineRecorder>,
+    slo_tracker: SloTracker,
+    slowest: SlowestRecorder,
+    /// Set by `with_assignment_sample`, in place of storing every assignment.
+    sample: Option<AssignmentReservoir>,
+    explanations: Vec<SelectionExplain>,
+    total_wait_ms: u64,
+    duration_ms: u64,
+    first_arrival_ms: Option<u64>,
+    /// Generates the arrivals still to come; only the next one is queued in
+    /// `events`.
+    workload: Workload<R>,
+    /// Arrivals generated so far.
+    total_arrivals: u64,
+    processed_events: u64,
+    completed_requests: u64,
+    in_system: u64,
+    in_system_area: u128,
+    last_event_ms: Option<u64>,
+    peak_queued_events: usize,
+    /// Completions are accounted for at assignment instead of being
+    /// scheduled; see `SimulationEngine::run`.
+    skip_completions: bool,
+}
+
+impl<R: RngCore + SeedableRng> RunState<R> {
+    fn new(
+        config: &SimConfig,
+        seed: u64,
+        workload: Workload<R>,
+        first: Request,
+        store_assignments: bool,
+        keep_samples: bool,
+        buffers: RunBuffers,
+    ) -> Self {
+        let server_count = config.servers.len();
+        let first_arrival_ms = Some(first.arrival_time_ms);
+        let RunBuffers {
+            mut events,
+            counts,
+            total_response_ms,
+            total_service_ms,
+            total_queued_ms,
+            mut samples,
+        } = buffers;
+        events.clear();
+        samples.truncate(server_count);
+        samples.iter_mut().for_each(Vec::clear);
+        samples.resize_with(server_count, Vec::new);
+        let runtimes = config
+            .servers
+            .iter()
+            .enumerate()
+            .map(|(idx, server)| ServerRuntime::new(server, seed, idx))
+            .collect();
+
+        let mut run = Self {
+            store_assignments,
+            workload,
+            total_arrivals: 1,
+            events,
+            event_order: config.event_order,
+            scheduled_events: 0,
+            runtimes,
+            counts: zeroed(counts, server_count),
+            total_response_ms: zeroed(total_response_ms, server_count),
+            total_service_ms: zeroed(total_service_ms, server_count),
+            total_queued_ms: zeroed(total_queued_ms, server_count),
+            server_response_times: keep_samples.then_some(samples),
+            overall_histogram: HistogramRecorder::default(),
+            server_histograms: vec![HistogramRecorder::default(); server_count],
+            timeseries: config
+                .timeseries_bucket_ms
+                .map(|bucket_ms| TimeseriesRecorder::new(bucket_ms, server_count)),
+            timelines: config
+                .record_timelines
+                .then(|| TimelineRecorder::new(server_count)),
+            slo_tracker: SloTracker::new(&config.slos, server_count),
+            slowest: SlowestRecorder::new(config.slowest_requests),
+            sample: None,
+            explanations: Vec::new(),
+            total_wait_ms: 0,
+            duration_ms: 0,
+            first_arrival_ms,
+            processed_events: 0,
+            completed_requests: 0,
+            in_system: 0,
+            in_system_area: 0,
+            last_event_ms: None,
+            peak_queued_events: 0,
+            skip_completions: false,
+        };
+        run.schedule(first.arrival_time_ms, Event::RequestArrival(first));
+        run
+    }
+
+    /// Queues `event`, ranked so events due at the same time follow
+    /// `event_order`.
+    fn schedule(&mut self, time_ms: u64, event: Event) {
+        let rank = match self.event_order {
+            EventOrder::CompletionsFirst => 0,
+            EventOrder::ArrivalsFirst => u64::from(matches!(event, Event::RequestComplete { .. })),
+            EventOrder::Fifo => self.scheduled_events,
+        };
+        self.scheduled_events += 1;
+        self.events
+            .push(Reverse(ScheduledEvent::new(time_ms, event).with_rank(rank)));
+    }
+}
+
+/// Allocations a finished run hands to the next `start`, so replications on
+/// one engine refill buffers instead of growing new ones.
+#[derive(Debug, Default)]
+struct RunBuffers {
+    events: BinaryHeap<Reverse<ScheduledEvent>>,
+    counts: Vec<u64>,
+    total_response_ms: Vec<u64>,
+    total_service_ms: Vec<u64>,
+    total_queued_ms: Vec<u64>,
+    samples: Vec<Vec<u64>>,
+}
+
+fn zeroed<T: Copy + Default>(mut buffer: Vec<T>, len: usize) -> Vec<T> {
+    buffer.clear();
+    buffer.resize(len, T::default());
+    buffer
+}
+
+/// Streams for the run-wide sources. The workload draws from stream 0 and
+/// server `idx` from stream `idx + 1`, so these count down from the top.
+const TIE_BREAK_STREAM: u64 = u64::MAX;
+const SAMPLE_STREAM: u64 = u64::MAX - 1;
+
+/// The seed of one stochastic source's stream under a run's `seed`, so no two
+/// sources replay each other's draws. Stream 0 is `seed` itself, which keeps
+/// `build_requests` matching the workload a run generates.
+fn stream_seed(seed: u64, stream: u64) -> u64 {
+    seed ^ stream.wrapping_mul(0x9E37_79B9_7F4A_7C15)
+}
+
+/// Per-server model state strategies don't see: when each concurrency slot
+/// frees up, outage windows, and the service time sampler.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+struct ServerRuntime<R> {
+    slots: Vec<u64>,
+    outages: Vec<OutageWindow>,
+    latency: LatencyDistribution,
+    rng: R,
+}
+
+impl<R: RngCore + SeedableRng> ServerRuntime<R> {
+    fn new(server: &ServerConfig, seed: u64, idx: usize) -> Self {
+        let mut outages = server
+            .model
+            .failure
+            .as_ref()
+            .map(|failure| failure.outages.clone())
+            .unwrap_or_default();
+        outages.sort_by_key(|outage| outage.start_ms);
+        Self {
+            slots: vec![0; server.model.concurrency() as usize],
+            outages,
+            latency: server
+                .model
+                .latency
+                .clone()
+                .unwrap_or(LatencyDistribution::Fixed),
+            rng: R::seed_from_u64(stream_seed(seed, idx as u64 + 1)),
+        }
+    }
+
+    /// Books the earliest free slot, pushed past any outage it would start
+    /// in, and returns `(started_at, completed_at)`, or `None` when the
+    /// completion would overflow the clock.
+    fn schedule(&mut self, now_ms: u64, base_latency_ms: u64) -> Option<(u64, u64)> {
+        let (slot, free_at) = self
+            .slots
+            .iter()
+            .copied()
+            .enumerate()
+            .min_by_key(|(_, free_at)| *free_at)
+            .expect("servers have at least one slot");
+        let mut started_at = now_ms.max(free_at);
+        for outage in &self.outages {
+            if (outage.start_ms..outage.end_ms()).contains(&started_at) {
+                started_at = outage.end_ms();
+            }
+        }
+        let completed_at = started_at.checked_add(self.sample_latency(base_latency_ms))?;
+        self.slots[slot] = completed_at;
+        Some((started_at, completed_at))
+    }
+
+    fn next_available_ms(&self) -> u64 {
+        self.slots.iter().copied().min().unwrap_or(0)
+    }
+
+    fn sample_latency(&mut self, base_latency_ms: u64) -> u64 {
+        match self.latency {
+            LatencyDistribution::Fixed => base_latency_ms,
+            LatencyDistribution::Uniform { jitter_ms } => self
+                .rng
+                .gen_range(
+                    base_latency_ms.saturating_sub(jitter_ms)
+                        ..=base_latency_ms.saturating_add(jitter_ms),
+                )
+                .max(1),
+            LatencyDistribution::Exponential => {
+                let uniform: f64 = self.rng.gen();
+                let sample = -(1.0 - uniform).ln() * base_latency_ms as f64;
+                (sample.round() as u64).max(1)
+            }
+        }
+    }
+}
+
+struct StableRng;
+
+impl RngCore for StableRng {
+    fn next_u32(&mut self) -> u32 {
+        0
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        0
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        dest.fill(0);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> std::result::Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Checks L = λW using an event-driven integral of requests in the system for
+/// L, so any drift from the per-request latency accounting shows up as residual.
+fn littles_law(
+    in_system_area: u128,
+    total_requests: u64,
+    time_in_system_ms: u64,
+    window_ms: u64,
+) -> LittlesLaw {
+    if window_ms == 0 || total_requests == 0 {
+        return LittlesLaw {
+            avg_in_system: 0.0,
+            arrival_rate_rps: 0.0,
+            avg_latency_ms: 0.0,
+            predicted_in_system: 0.0,
+            residual: 0.0,
+        };
+    }
+    let avg_in_system = in_system_area as f64 / window_ms as f64;
+    let arrival_rate_per_ms = total_requests as f64 / window_ms as f64;
+    let avg_latency_ms = time_in_system_ms as f64 / total_requests as f64;
+    let predicted_in_system = arrival_rate_per_ms * avg_latency_ms;
+    LittlesLaw {
+        avg_in_system: round_to(avg_in_system, 4),
+        arrival_rate_rps: round_to(arrival_rate_per_ms * 1000.0, 4),
+        avg_latency_ms: round_to(avg_latency_ms, 4),
+        predicted_in_system: round_to(predicted_in_system, 4),
+        residual: round_to(avg_in_system - predicted_in_system, 4),
+    }
+}
+
+/// Summary percentiles and p99 tail counts, from raw samples or histograms.
+struct Percentiles {
+    per_server_p99: Vec<Option<u64>>,
+    p95_ms: Option<u64>,
+    p99_ms: Option<u64>,
+    tail_requests: Vec<u64>,
+}
+
+impl Percentiles {
+    fn exact(samples: &mut [Vec<u64>]) -> Self {
+        for server in samples.iter_mut() {
+            server.sort_unstable();
+        }
+        let p99_ms = merged_percentile(samples, 99.0);
+        Self {
+            per_server_p99: samples
+                .iter()
+                .map(|server| nearest_rank_percentile(server, 99.0))
+                .collect(),
+            p95_ms: merged_percentile(samples, 95.0),
+            p99_ms,
+            tail_requests: samples
+                .iter()
+                .map(|server| {
+                    p99_ms.map_or(0, |threshold| {
+                        (server.len() - server.partition_point(|value| *value < threshold)) as u64
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    fn from_histograms(overall: &HistogramRecorder, servers: &[HistogramRecorder]) -> Self {
+        let p99_ms = overall.percentile(99.0);
+        Self {
+            per_server_p99: servers
+                .iter()
+                .map(|server| server.percentile(99.0))
+                .collect(),
+            p95_ms: overall.percentile(95.0),
+            p99_ms,
+            tail_requests: servers
+                .iter()
+                .map(|server| p99_ms.map_or(0, |threshold| server.count_at_least(threshold)))
+                .collect(),
+        }
+    }
+}
+
+fn nearest_rank_percentile(sorted: &[u64], percentile: f64) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((percentile / 100.0) * sorted.len() as f64).ceil() as usize;
+    let idx = rank.saturating_sub(1).min(sorted.len() - 1);
+    Some(sorted[idx])
+}
+
+/// `nearest_rank_percentile` over the union of several sorted sample sets,
+/// found by bisecting on the value so the sets are never merged.
+fn merged_percentile(sorted: &[Vec<u64>], percentile: f64) -> Option<u64> {
+    let total = sorted.iter().map(Vec::len).sum::<usize>();
+    if total == 0 {
+        return None;
+    }
+    let rank = ((percentile / 100.0) * total as f64).ceil() as usize;
+    let idx = rank.saturating_sub(1).min(total - 1);
+    let at_most = |value: u64| {
+        sorted
+            .iter()
+            .map(|samples| samples.partition_point(|sample| *sample <= value))
+            .sum::<usize>()
+    };
+    let mut low = sorted
+        .iter()
+        .filter_map(|samples| samples.first())
+        .min()
+        .copied()?;
+    let mut high = sorted
+        .iter()
+        .filter_map(|samples| samples.last())
+        .max()
+        .copied()?;
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if at_most(mid) > idx {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    Some(low)
+}
+
+fn round_to(value: f64, decimals: u32) -> f64 {
+    if decimals == 0 {
+        return value.round();
+    }
+    let factor = 10_f64.powi(decimals as i32);
+    (value * factor).round() / factor
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AlgoConfig, CapacityConfig, FailureConfig, ServerModel};
+    use crate::validation::tests::{config_with_servers, modeled_server};
+
+    #[derive(Default)]
+    struct EventLog(std::rc::Rc<std::cell::RefCell<Vec<String>>>);
+
+    impl EngineObserver for EventLog {
+        fn on_arrival(&mut self, time_ms: u64, request: &Request) {
+            self.0
+                .borrow_mut()
+                .push(format!("t={} arrival {}", time_ms, request.id));
+        }
+
+        fn on_select(&mut self, time_ms: u64, assignment: &Assignment, servers: &[ServerState]) {
+            self.0.borrow_mut().push(format!(
+                "t={} select {} -> {} (in_flight {})",
+                time_ms,
+                assignment.request_id,
+                servers[assignment.server_id].name,
+                servers[assignment.server_id].in_flight
+            ));
+        }
+
+        fn on_complete(&mut self, time_ms: u64, request_id: u64, server: &ServerState) {
+            self.0.borrow_mut().push(format!(
+                "t={} complete {} on {}",
+                time_ms, request_id, server.name
+            ));
+        }
+
+        fn on_fail(&mut self, time_ms: u64, request_id: u64, reason: &str) {
+            self.0
+                .borrow_mut()
+                .push(format!("t={} fail {} ({})", time_ms, request_id, reason));
+        }
+    }
+
+    #[test]
+    fn observers_see_every_event_in_order() {
+        let mut config = config_with_servers(vec![ServerConfig {
+            name: "a".to_string(),
+            base_latency_ms: 10,
+            weight: 1,
+            model: ServerModel::default(),
+        }]);
+        config.requests = RequestProfile::FixedCount(2);
+        let first = EventLog::default();
+        let second = EventLog::default();
+        let (first_log, second_log) = (first.0.clone(), second.0.clone());
+        let strategy = build_strategy(config.algo.clone()).expect("built-in algorithm");
+        SimulationEngine::new(config, strategy)
+            .with_observer(Box::new(first))
+            .with_observer(Box::new(second))
+            .run(false)
+            .expect("simulation should succeed");
+
+        assert_eq!(
+            *first_log.borrow(),
+            vec![
+                "t=0 arrival 1",
+                "t=0 select 1 -> a (in_flight 1)",
+                "t=1 arrival 2",
+                "t=1 select 2 -> a (in_flight 2)",
+                "t=10 complete 1 on a",
+                "t=20 complete 2 on a",
+            ]
+        );
+        assert_eq!(*first_log.borrow(), *second_log.borrow());
+    }
+
+    #[test]
+    fn observers_hear_about_requests_left_by_an_aborted_run() {
+        let mut config = config_with_servers(vec![ServerConfig {
+            name: "a".to_string(),
+            base_latency_ms: 10,
+            weight: 1,
+            model: ServerModel::default(),
+        }]);
+        config.requests = RequestProfile::FixedCount(3);
+        config.max_events = Some(2);
+        let log = EventLog::default();
+        let events = log.0.clone();
+        let strategy = build_strategy(config.algo.clone()).expect("built-in algorithm");
+        let result = SimulationEngine::new(config, strategy)
+            .with_observer(Box::new(log))
+            .run(false);
+
+        assert!(matches!(result, Err(Error::SimulationLimit { .. })));
+        let fails = events
+            .borrow()
+            .iter()
+            .filter(|event| event.contains("fail"))
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fails,
+            vec![
+                "t=1 fail 1 (--max-events 2)",
+                "t=1 fail 2 (--max-events 2)",
+                "t=1 fail 3 (--max-events 2)",
+            ]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn step_drives_the_run_one_event_at_a_time() {
+        let mut config = config_with_servers(vec![ServerConfig {
+            name: "a".to_string(),
+            base_latency_ms: 10,
+            weight: 1,
+            model: ServerModel::default(),
+        }]);
+        config.requests = RequestProfile::FixedCount(2);
+        let strategy = build_strategy(config.algo.clone()).expect("built-in algorithm");
+        let mut engine = SimulationEngine::new(config.clone(), strategy);
+
+        match engine.step().expect("step should succeed") {
+            Some(EngineEvent::Assigned {
+                time_ms: 0,
+                assignment,
+            }) => assert_eq!(assignment.request_id, 1),
+            other => panic!("expected the first assignment, got {:?}", other),
+        }
+        assert_eq!(engine.state().servers[0].in_flight, 1);
+        assert_eq!(engine.processed_events(), 1);
+
+        let mut rest = Vec::new();
+        while let Some(event) = engine.step().expect("step should succeed") {
+            rest.push(event);
+        }
+        assert!(matches!(
+            rest.as_slice(),
+            [
+                EngineEvent::Assigned { time_ms: 1, .. },
+                EngineEvent::Completed {
+                    time_ms: 10,
+                    request_id: 1,
+                    ..
+                },
+                EngineEvent::Completed {
+                    time_ms: 20,
+                    request_id: 2,
+                    ..
+                },
+            ]
+        ));
+        assert_eq!(engine.state().time_ms, 20);
+        assert_eq!(engine.state().servers[0].in_flight, 0);
+
+        let stepped = engine.finish().expect("finish should succeed");
+        let ran = run_simulation(&config).expect("simulation should succeed");
+        assert_eq!(
+            serde_json::to_string(&stepped).expect("result serializes"),
+            serde_json::to_string(&ran).expect("result serializes")
+        );
+    }
+
+    /// Always yields 2^63, which `gen_range(0..2)` maps to 1.
+    #[cfg(feature = "serde")]
+    #[derive(Clone)]
+    struct ScriptedRng;
+
+    #[cfg(feature = "serde")]
+    impl RngCore for ScriptedRng {
+        fn next_u32(&mut self) -> u32 {
+            1 << 31
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            1 << 63
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            dest.fill(0);
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> std::result::Result<(), rand::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl SeedableRng for ScriptedRng {
+        type Seed = [u8; 8];
+
+        fn from_seed(_seed: Self::Seed) -> Self {
+            ScriptedRng
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn engines_run_on_any_seedable_rng() {
+        let server = |name: &str| ServerConfig {
+            name: name.to_string(),
+            base_latency_ms: 10,
+            weight: 1,
+            model: ServerModel {
+                latency: Some(LatencyDistribution::Exponential),
+                ..ServerModel::default()
+            },
+        };
+        let mut config = config_with_servers(vec![server("a"), server("b")]);
+        config.requests = RequestProfile::Poisson {
+            rate: 200.0,
+            duration_ms: 200,
+        };
+        config.algo = AlgoConfig::LeastConnections;
+        config.randomness = RandomnessConfig::Seeded(3);
+
+        let run = || {
+            let strategy = build_strategy(config.algo.clone()).expect("built-in algorithm");
+            SimulationEngine::<rand_chacha::ChaCha8Rng>::seeded(config.clone(), strategy)
+                .run(true)
+                .expect("simulation should succeed")
+        };
+        let first = serde_json::to_string(&run()).expect("result serializes");
+        assert_eq!(
+            first,
+            serde_json::to_string(&run()).expect("result serializes")
+        );
+        let default = run_simulation(&config).expect("simulation should succeed");
+        assert_ne!(
+            first,
+            serde_json::to_string(&default).expect("result serializes")
+        );
+        assert_ne!(
+            build_requests_with::<rand_chacha::ChaCha8Rng>(
+                &config.requests,
+                config.randomness.seed()
+            )
+            .expect("workload"),
+            build_requests(&config.requests, config.randomness.seed()).expect("workload")
+        );
+
+        config.requests = RequestProfile::FixedCount(1);
+        let strategy = build_strategy(config.algo.clone()).expect("built-in algorithm");
+        let result = SimulationEngine::<ScriptedRng>::seeded(config, strategy)
+            .with_rng(ScriptedRng)
+            .run(true)
+            .expect("simulation should succeed");
+        assert_eq!(result.assignments[0].server_id, 1);
+    }
+
+    #[test]
+    fn metrics_sinks_see_selections_completions_and_queue_depth() {
+        let mut config = config_with_servers(vec![ServerConfig {
+            name: "a".to_string(),
+            base_latency_ms: 10,
+            weight: 1,
+            model: ServerModel::default(),
+        }]);
+        config.requests = RequestProfile::Burst { count: 3, at_ms: 0 };
+        let metrics = crate::metrics::InMemoryMetrics::default();
+        let strategy = build_strategy(config.algo.clone()).expect("built-in algorithm");
+        SimulationEngine::new(config, strategy)
+            .with_metrics(Box::new(metrics.clone()))
+            .run(false)
+            .expect("simulation should succeed");
+
+        let recorded = metrics.recorded();
+        assert_eq!(recorded.counters["lb_sim.selections{server=a}"], 3);
+        assert_eq!(recorded.counters["lb_sim.completions{server=a}"], 3);
+        assert_eq!(recorded.gauges["lb_sim.in_flight{server=a}"], 0.0);
+        assert_eq!(
+            recorded.histograms["lb_sim.response_ms{server=a}"],
+            vec![10.0, 20.0, 30.0]
+        );
+        assert_eq!(
+            recorded.histograms["lb_sim.wait_ms{server=a}"],
+            vec![0.0, 10.0, 20.0]
+        );
+    }
+
+    #[test]
+    fn event_logs_receive_every_processed_event() {
+        let mut config = config_with_servers(vec![ServerConfig {
+            name: "a".to_string(),
+            base_latency_ms: 10,
+            weight: 1,
+            model: ServerModel::default(),
+        }]);
+        config.requests = RequestProfile::FixedCount(2);
+        let log = crate::event_log::InMemoryEventLog::default();
+        let strategy = build_strategy(config.algo.clone()).expect("built-in algorithm");
+        SimulationEngine::new(config, strategy)
+            .with_event_log(Box::new(log.clone()))
+            .run(false)
+            .expect("simulation should succeed");
+
+        let events = log
+            .events()
+            .into_iter()
+            .map(|scheduled| match scheduled.event {
+                Event::RequestArrival(request) => {
+                    format!("{} arrive {}", scheduled.time_ms, request.id)
+                }
+                Event::RequestComplete { request_id, .. } => {
+                    format!("{} complete {}", scheduled.time_ms, request_id)
+                }
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            vec!["0 arrive 1", "1 arrive 2", "10 complete 1", "20 complete 2"]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn restored_snapshots_continue_the_run_exactly() {
+        let server = |name: &str, base_latency_ms: u64| ServerConfig {
+            name: name.to_string(),
+            base_latency_ms,
+            weight: 1,
+            model: ServerModel {
+                latency: Some(LatencyDistribution::Uniform { jitter_ms: 4 }),
+                ..ServerModel::default()
+            },
+        };
+        let mut config = config_with_servers(vec![server("a", 10), server("b", 12)]);
+        config.requests = RequestProfile::Poisson {
+            rate: 150.0,
+            duration_ms: 500,
+        };
+        config.algo = AlgoConfig::LeastConnections;
+        config.randomness = RandomnessConfig::Seeded(7);
+        config.timeseries_bucket_ms = Some(100);
+        let uninterrupted = serde_json::to_string(&run_simulation(&config).expect("run"))
+            .expect("result serializes");
+
+        let strategy = build_strategy(config.algo.clone()).expect("built-in algorithm");
+        let mut engine = SimulationEngine::new(config.clone(), strategy);
+        for _ in 0..40 {
+            engine.step().expect("step should succeed");
+        }
+        let json = serde_json::to_string(&engine.snapshot()).expect("snapshot serializes");
+        let snapshot: EngineSnapshot = serde_json::from_str(&json).expect("snapshot parses");
+
+        let mut resumed = SimulationEngine::from_snapshot(snapshot.clone()).expect("restores");
+        assert_eq!(resumed.processed_events(), 40);
+        let resumed = resumed.finish().expect("resumed run should finish");
+        assert_eq!(
+            serde_json::to_string(&resumed).expect("result serializes"),
+            uninterrupted
+        );
+        let original = engine.finish().expect("original run should finish");
+        assert_eq!(
+            serde_json::to_string(&original).expect("result serializes"),
+            uninterrupted
+        );
+
+        let strategy = build_strategy(AlgoConfig::RoundRobin).expect("built-in algorithm");
+        let mut other = SimulationEngine::new(config_with_servers(Vec::new()), strategy);
+        assert_eq!(
+            other.restore(snapshot).unwrap_err().to_string(),
+            "invalid engine snapshot: taken with algorithm least-connections, but this engine runs round-robin"
+        );
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn async_runs_match_the_blocking_run() {
+        let mut config = config_with_servers(vec![
+            ServerConfig {
+                name: "a".to_string(),
+                base_latency_ms: 10,
+                weight: 1,
+                model: ServerModel::default(),
+            },
+            ServerConfig {
+                name: "b".to_string(),
+                base_latency_ms: 20,
+                weight: 1,
+                model: ServerModel::default(),
+            },
+        ]);
+        config.requests = RequestProfile::FixedCount(50);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .expect("runtime should build");
+        let strategy = build_strategy(config.algo.clone()).expect("built-in algorithm");
+        let mut engine = SimulationEngine::new(config.clone(), strategy);
+        let yielding = runtime
+            .block_on(engine.run_async(true, 7))
+            .expect("simulation should succeed");
+        let blocking_pool = runtime
+            .block_on(run_simulation_async(config.clone(), true))
+            .expect("simulation should succeed");
+        let expected = serde_json::to_string(&run_simulation(&config).expect("simulation"))
+            .expect("result serializes");
+
+        for result in [yielding, blocking_pool] {
+            assert_eq!(
+                serde_json::to_string(&result).expect("result serializes"),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn replications_keep_seed_order() {
+        let mut config = config_with_servers(vec![
+            ServerConfig {
+                name: "a".to_string(),
+                base_latency_ms: 10,
+                weight: 1,
+                model: ServerModel::default(),
+            },
+            ServerConfig {
+                name: "b".to_string(),
+                base_latency_ms: 30,
+                weight: 1,
+                model: ServerModel::default(),
+            },
+        ]);
+        config.requests = RequestProfile::Poisson {
+            rate: 50.0,
+            duration_ms: 1_000,
+        };
+        let seeds = [9, 1, 5, 3];
+        let results = run_replications(&config, &seeds).expect("replications should succeed");
+
+        assert_eq!(results.len(), seeds.len());
+        for (seed, result) in seeds.iter().zip(&results) {
+            let mut replica = config.clone();
+            replica.randomness = RandomnessConfig::Seeded(*seed);
+            let expected = run_simulation_summary(&replica).expect("simulation should succeed");
+            assert_eq!(result.metadata.seed, Some(*seed));
+            assert_eq!(result.global.total_requests, expected.global.total_requests);
+            assert_eq!(result.global.makespan_ms, expected.global.makespan_ms);
+        }
+    }
+
+    #[test]
+    fn stable_replications_keep_breaking_ties_in_input_order() {
+        let server = |name: &str| ServerConfig {
+            name: name.to_string(),
+            base_latency_ms: 10,
+            weight: 1,
+            model: ServerModel::default(),
+        };
+        let mut config = config_with_servers(vec![server("a"), server("b")]);
+        config.algo = AlgoConfig::LeastConnections;
+        config.requests = RequestProfile::Burst { count: 1, at_ms: 0 };
+        let seeds = (1..=16).collect::<Vec<_>>();
+        let results = run_replications(&config, &seeds).expect("replications should succeed");
+
+        for (seed, result) in seeds.iter().zip(&results) {
+            assert_eq!(result.metadata.tie_break, "stable");
+            assert_eq!(result.metadata.seed, Some(*seed));
+            let requests = result
+                .totals
+                .iter()
+                .map(|server| server.requests)
+                .collect::<Vec<_>>();
+            assert_eq!(requests, vec![1, 0], "seed {}", seed);
+        }
+
+        config.randomness = RandomnessConfig::Seeded(0);
+        let results = run_replications(&config, &seeds).expect("replications should succeed");
+        assert!(results.iter().any(|result| result.totals[1].requests == 1));
+    }
+
+    #[test]
+    fn summaries_keep_config_order_and_report_the_first_failure() {
+        let configs = [3, 1, 2]
+            .map(|count| {
+                let mut config = config_with_servers(vec![ServerConfig {
+                    name: "a".to_string(),
+                    base_latency_ms: 10,
+                    weight: 1,
+                    model: ServerModel::default(),
+                }]);
+                config.requests = RequestProfile::FixedCount(count);
+                config
+            })
+            .to_vec();
+        let totals = run_summaries(&configs)
+            .expect("simulations should succeed")
+            .iter()
+            .map(|result| result.global.total_requests)
+            .collect::<Vec<_>>();
+        assert_eq!(totals, [3, 1, 2]);
+
+        let mut failing = configs;
+        failing[1].requests = RequestProfile::FixedCount(0);
+        failing[2].servers.clear();
+        assert!(matches!(run_summaries(&failing), Err(Error::RequestsZero)));
+    }
+
+    #[test]
+    fn arrivals_are_generated_as_the_run_reaches_them() {
+        let mut config = config_with_servers(vec![ServerConfig {
+            name: "a".to_string(),
+            base_latency_ms: 5,
+            weight: 1,
+            model: ServerModel::default(),
+        }]);
+        config.requests = RequestProfile::Poisson {
+            rate: 500.0,
+            duration_ms: 2_000,
+        };
+        config.randomness = RandomnessConfig::Seeded(3);
+        let expected =
+            build_requests(&config.requests, config.randomness.seed()).expect("workload");
+        let mut engine =
+            SimulationEngine::new(config, build_strategy(AlgoConfig::RoundRobin).unwrap());
+        engine.start(true).expect("run should start");
+        let mut arrivals = Vec::new();
+        while let Some(event) = engine.step().expect("step should succeed") {
+            let in_flight = engine.state().servers[0].in_flight as usize;
+            let queued = engine.run.as_ref().map_or(0, |run| run.events.len());
+            assert!(
+                queued <= in_flight + 1,
+                "{} queued, {} in flight",
+                queued,
+                in_flight
+            );
+            if let EngineEvent::Assigned { assignment, .. } = event {
+                arrivals.push((assignment.request_id, assignment.arrival_time_ms));
+            }
+        }
+        let expected = expected
+            .iter()
+            .map(|request| (request.id, request.arrival_time_ms))
+            .collect::<Vec<_>>();
+        assert_eq!(arrivals, expected);
+    }
+
+    #[test]
+    fn rng_kind_picks_the_generator_and_is_reported() {
+        let mut config = config_with_servers(vec![ServerConfig {
+            name: "a".to_string(),
+            base_latency_ms: 5,
+            weight: 1,
+            model: ServerModel::default(),
+        }]);
+        config.requests = RequestProfile::Poisson {
+            rate: 200.0,
+            duration_ms: 1_000,
+        };
+        config.randomness = RandomnessConfig::Seeded(9);
+        let arrivals = |config: &SimConfig| {
+            run_simulation(config)
+                .expect("simulation should run")
+                .assignments
+                .iter()
+                .map(|assignment| assignment.arrival_time_ms)
+                .collect::<Vec<_>>()
+        };
+        let std_arrivals = arrivals(&config);
+        assert_eq!(
+            run_simulation(&config).unwrap().metadata.rng.as_deref(),
+            Some("std")
+        );
+
+        for kind in [RngKind::Small, RngKind::Xoshiro] {
+            config.rng = kind;
+            let result = run_simulation(&config).expect("simulation should run");
+            assert_eq!(result.metadata.rng, Some(kind.to_string()));
+            assert_eq!(arrivals(&config), arrivals(&config));
+            assert_ne!(arrivals(&config), std_arrivals);
+            assert_eq!(
+                build_config_requests(&config).unwrap().len(),
+                result.assignments.len()
+            );
+        }
+    }
+
+    #[test]
+    fn event_order_decides_whether_arrivals_see_simultaneous_completions() {
+        let picks = |event_order: EventOrder| {
+            let config = SimConfig::builder()
+                .server("a", 1)
+                .server("b", 1)
+                .requests(2)
+                .algo(AlgoConfig::LeastConnections)
+                .event_order(event_order)
+                .build()
+                .expect("config should build");
+            let result = run_simulation(&config).expect("simulation should run");
+            let servers = result
+                .assignments
+                .iter()
+                .map(|assignment| assignment.server_id)
+                .collect::<Vec<_>>();
+            (servers, result.metadata.event_order)
+        };
+        // Request 1 finishes on `a` at t=1, exactly when request 2 arrives.
+        assert_eq!(picks(EventOrder::CompletionsFirst), (vec![0, 0], None));
+        assert_eq!(
+            picks(EventOrder::ArrivalsFirst),
+            (vec![0, 1], Some("arrivals-first".to_string()))
+        );
+        // Request 2's arrival is queued before request 1's completion.
+        assert_eq!(
+            picks(EventOrder::Fifo),
+            (vec![0, 1], Some("fifo".to_string()))
+        );
+    }
+
+    #[test]
+    fn completions_past_the_end_of_the_clock_fail_instead_of_wrapping() {
+        let config = SimConfig::builder()
+            .server("slow", u64::MAX)
+            .burst(1, 5)
+            .algo(AlgoConfig::RoundRobin)
+            .build()
+            .expect("config should build");
+        let err = run_simulation(&config).expect_err("completion overflows u64");
+        assert!(matches!(
+            err,
+            Error::TimeOverflow {
+                started_at: 5,
+                service_ms: u64::MAX,
+                ..
             }
-            Ok((0..*count)
-                .map(|idx| Request {
-                    id: idx + 1,
-                    arrival_time_ms: *at_ms,
-                })
-                .collect())
-        }
+        ));
+        assert!(err
+            .to_string()
+            .starts_with("simulated time overflowed on 'slow'"));
     }
-}
 
-fn init_server_state(servers: &[ServerConfig]) -> Vec<ServerState> {
-    servers
-        .iter()
-        .enumerate()
-        .map(|(id, server)| ServerState {
-            id,
-            name: server.name.clone(),
-            base_latency_ms: server.base_latency_ms,
-            weight: server.weight,
-            active_connections: 0,
-            pick_count: 0,
-            in_flight: 0,
-            next_available_ms: 0,
-        })
-        .collect()
-}
+    #[test]
+    fn metadata_records_the_effective_seed_of_every_policy() {
+        let mut config = config_with_servers(vec![ServerConfig {
+            name: "a".to_string(),
+            base_latency_ms: 5,
+            weight: 1,
+            model: ServerModel::default(),
+        }]);
+        config.requests = RequestProfile::Poisson {
+            rate: 200.0,
+            duration_ms: 500,
+        };
+        let stable = run_simulation(&config).expect("simulation should run");
+        assert_eq!(
+            (stable.metadata.tie_break.as_str(), stable.metadata.seed),
+            ("stable", Some(0))
+        );
 
-struct StableRng;
+        config.randomness = RandomnessConfig::Entropy;
+        let entropy = run_simulation(&config).expect("simulation should run");
+        let seed = entropy
+            .metadata
+            .seed
+            .expect("entropy runs record their seed");
+        assert_eq!(entropy.metadata.tie_break, format!("seeded({})", seed));
 
-impl RngCore for StableRng {
-    fn next_u32(&mut self) -> u32 {
-        0
+        config.randomness = RandomnessConfig::Seeded(seed);
+        let replayed = run_simulation(&config).expect("simulation should run");
+        let arrivals = |result: &SimulationResult| {
+            result
+                .assignments
+                .iter()
+                .map(|assignment| assignment.arrival_time_ms)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(arrivals(&replayed), arrivals(&entropy));
     }
 
-    fn next_u64(&mut self) -> u64 {
-        0
+    #[test]
+    fn randomness_parses_and_round_trips_its_labels() {
+        for (label, expected) in [
+            ("stable", RandomnessConfig::Stable),
+            ("entropy", RandomnessConfig::Entropy),
+            ("seeded(42)", RandomnessConfig::Seeded(42)),
+            ("42", RandomnessConfig::Seeded(42)),
+        ] {
+            assert_eq!(label.parse::<RandomnessConfig>(), Ok(expected));
+        }
+        assert!("seeded(x)".parse::<RandomnessConfig>().is_err());
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&RandomnessConfig::Seeded(3)).unwrap();
+            assert_eq!(json, r#""seeded(3)""#);
+            assert_eq!(
+                serde_json::from_str::<RandomnessConfig>("3").unwrap(),
+                RandomnessConfig::Seeded(3)
+            );
+        }
     }
 
-    fn fill_bytes(&mut self, dest: &mut [u8]) {
-        dest.fill(0);
-    }
+    #[test]
+    fn sampled_runs_keep_a_subset_and_exact_aggregates() {
+        let config = SimConfig::builder()
+            .server("a", 10)
+            .server("b", 30)
+            .requests(1_000)
+            .algo(AlgoConfig::LeastConnections)
+            .build()
+            .expect("config should build");
+        let full = run_simulation(&config).expect("simulation should run");
+        let sampled_run = || {
+            let mut engine =
+                SimulationEngine::new(config.clone(), build_strategy(config.algo.clone()).unwrap())
+                    .with_assignment_sample(20);
+            let result = engine.run(true).expect("simulation should run");
+            (result, engine.memory_stats())
+        };
+        let (sampled, memory) = sampled_run();
 
-    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> std::result::Result<(), rand::Error> {
-        self.fill_bytes(dest);
-        Ok(())
+        assert_eq!(sampled.assignments.len(), 20);
+        assert_eq!(memory.stored_assignments, 20);
+        assert_eq!(sampled.metadata.assignment_sample, Some(20));
+        assert!(sampled
+            .assignments
+            .windows(2)
+            .all(|pair| pair[0].request_id < pair[1].request_id));
+        for assignment in &sampled.assignments {
+            let original = &full.assignments[assignment.request_id as usize - 1];
+            assert_eq!(assignment.server_id, original.server_id);
+            assert_eq!(assignment.completed_at, original.completed_at);
+        }
+        let ids = |result: &SimulationResult| {
+            result
+                .assignments
+                .iter()
+                .map(|assignment| assignment.request_id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&sampled_run().0), ids(&sampled));
+        for (sampled, full) in sampled.totals.iter().zip(&full.totals) {
+            assert_eq!(sampled.requests, full.requests);
+            assert_eq!(sampled.avg_response_ms, full.avg_response_ms);
+            assert_eq!(sampled.p99_ms, full.p99_ms);
+        }
     }
-}
 
-fn nearest_rank_percentile(sorted: &[u64], percentile: f64) -> Option<u64> {
-    if sorted.is_empty() {
-        return None;
+    #[test]
+    fn round_robin_runs_skip_completion_events_with_the_same_result() {
+        struct Silent;
+        impl EngineObserver for Silent {}
+
+        for algo in [AlgoConfig::RoundRobin, AlgoConfig::WeightedRoundRobin] {
+            let config = SimConfig::builder()
+                .server("a", 10)
+                .server("b", 25)
+                .weight(3)
+                .concurrency(2)
+                .latency(LatencyDistribution::Exponential)
+                .poisson(400.0, 2_000)
+                .seed(11)
+                .algo(algo.clone())
+                .record_timelines(true)
+                .build()
+                .expect("config should build");
+            let run = |observed: bool| {
+                let mut engine =
+                    SimulationEngine::new(config.clone(), build_strategy(algo.clone()).unwrap());
+                if observed {
+                    engine = engine.with_observer(Box::new(Silent));
+                }
+                let result = engine.run(true).expect("simulation should run");
+                (
+                    format!("{:?}", result),
+                    engine.state().clone(),
+                    engine.memory_stats(),
+                )
+            };
+            let (fast, fast_state, fast_memory) = run(false);
+            let (full, full_state, full_memory) = run(true);
+
+            assert_eq!(fast, full);
+            assert_eq!(format!("{:?}", fast_state), format!("{:?}", full_state));
+            assert_eq!(fast_memory.peak_queued_events, 1);
+            assert!(full_memory.peak_queued_events > 1);
+        }
     }
-    let rank = ((percentile / 100.0) * sorted.len() as f64).ceil() as usize;
-    let idx = rank.saturating_sub(1).min(sorted.len() - 1);
-    Some(sorted[idx])
-}
 
-fn round_to(value: f64, decimals: u32) -> f64 {
-    if decimals == 0 {
-        return value.round();
+    #[test]
+    fn merged_percentiles_match_percentiles_of_the_concatenation() {
+        let samples = vec![
+            vec![3, 9, 9, 40],
+            Vec::new(),
+            vec![1, 2, 9, 15, 16, 100],
+            vec![7],
+        ];
+        let mut all = samples.concat();
+        all.sort_unstable();
+        for percentile in [0.0, 10.0, 50.0, 90.0, 95.0, 99.0, 100.0] {
+            assert_eq!(
+                merged_percentile(&samples, percentile),
+                nearest_rank_percentile(&all, percentile),
+                "p{}",
+                percentile
+            );
+        }
+        assert_eq!(merged_percentile(&[Vec::new()], 99.0), None);
     }
-    let factor = 10_f64.powi(decimals as i32);
-    (value * factor).round() / factor
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::models::AlgoConfig;
+    #[test]
+    fn rerunning_an_engine_reuses_its_buffers_without_leaking_state() {
+        let mut config = config_with_servers(vec![
+            ServerConfig {
+                name: "a".to_string(),
+                base_latency_ms: 10,
+                weight: 1,
+                model: ServerModel::default(),
+            },
+            ServerConfig {
+                name: "b".to_string(),
+                base_latency_ms: 30,
+                weight: 1,
+                model: ServerModel::default(),
+            },
+        ]);
+        config.requests = RequestProfile::FixedCount(200);
+        config.algo = AlgoConfig::LeastConnections;
+        let mut engine =
+            SimulationEngine::new(config.clone(), build_strategy(config.algo.clone()).unwrap());
+        let first = format!(
+            "{:?}",
+            engine.run(false).expect("simulation should succeed")
+        );
+        let capacity = engine.spare.events.capacity();
+        assert!(capacity >= 200);
 
-    fn config_with_servers(servers: Vec<ServerConfig>) -> SimConfig {
-        SimConfig {
-            servers,
-            requests: RequestProfile::FixedCount(1),
-            algo: AlgoConfig::RoundRobin,
-            tie_break: TieBreakConfig::Stable,
-            seed: None,
-        }
+        let second = format!(
+            "{:?}",
+            engine.run(false).expect("simulation should succeed")
+        );
+        assert_eq!(engine.spare.events.capacity(), capacity);
+        assert_eq!(second, first);
     }
 
     #[test]
@@ -432,17 +2589,28 @@ mod tests {
                     name: "fast".to_string(),
                     base_latency_ms: 1,
                     weight: 1,
+                    model: ServerModel::default(),
                 },
                 ServerConfig {
                     name: "slow".to_string(),
                     base_latency_ms: 100,
                     weight: 1,
+                    model: ServerModel::default(),
                 },
             ],
             requests: RequestProfile::FixedCount(2),
             algo: AlgoConfig::LeastConnections,
-            tie_break: TieBreakConfig::Stable,
-            seed: None,
+            randomness: RandomnessConfig::Stable,
+            rng: RngKind::Std,
+            event_order: EventOrder::CompletionsFirst,
+            allow_large: false,
+            timeseries_bucket_ms: None,
+            record_timelines: false,
+            slos: Vec::new(),
+            slowest_requests: 0,
+            explain: false,
+            max_virtual_time_ms: None,
+            max_events: None,
         };
         let result = run_simulation(&config).expect("simulation should succeed");
         let assigned = result
@@ -453,6 +2621,36 @@ mod tests {
         assert_eq!(assigned, vec![0, 0]);
     }
 
+    #[test]
+    fn littles_law_holds_for_poisson_overload() {
+        let mut config = config_with_servers(vec![
+            ServerConfig {
+                name: "a".to_string(),
+                base_latency_ms: 7,
+                weight: 1,
+                model: ServerModel::default(),
+            },
+            ServerConfig {
+                name: "b".to_string(),
+                base_latency_ms: 13,
+                weight: 1,
+                model: ServerModel::default(),
+            },
+        ]);
+        config.algo = AlgoConfig::LeastConnections;
+        config.requests = RequestProfile::Poisson {
+            rate: 250.0,
+            duration_ms: 500,
+        };
+        config.randomness = RandomnessConfig::Seeded(11);
+        let result = run_simulation_summary(&config).expect("simulation should succeed");
+
+        let law = &result.littles_law;
+        assert!(law.avg_in_system > 0.0);
+        assert_eq!(law.avg_in_system, law.predicted_in_system);
+        assert_eq!(law.residual, 0.0);
+    }
+
     #[test]
     fn seeded_tiebreak_is_deterministic_in_engine() {
         let config = SimConfig {
@@ -461,22 +2659,34 @@ mod tests {
                     name: "a".to_string(),
                     base_latency_ms: 1,
                     weight: 1,
+                    model: ServerModel::default(),
                 },
                 ServerConfig {
                     name: "b".to_string(),
                     base_latency_ms: 1,
                     weight: 1,
+                    model: ServerModel::default(),
                 },
                 ServerConfig {
                     name: "c".to_string(),
                     base_latency_ms: 1,
                     weight: 1,
+                    model: ServerModel::default(),
                 },
             ],
             requests: RequestProfile::FixedCount(3),
             algo: AlgoConfig::LeastConnections,
-            tie_break: TieBreakConfig::Seeded,
-            seed: Some(42),
+            randomness: RandomnessConfig::Seeded(42),
+            rng: RngKind::Std,
+            event_order: EventOrder::CompletionsFirst,
+            allow_large: false,
+            timeseries_bucket_ms: None,
+            record_timelines: false,
+            slos: Vec::new(),
+            slowest_requests: 0,
+            explain: false,
+            max_virtual_time_ms: None,
+            max_events: None,
         };
         let result_a = run_simulation(&config).expect("simulation should succeed");
         let result_b = run_simulation(&config).expect("simulation should succeed");
@@ -502,11 +2712,21 @@ mod tests {
                 name: "api".to_string(),
                 base_latency_ms: 5,
                 weight: 1,
+                model: ServerModel::default(),
             }],
             requests: RequestProfile::FixedCount(2),
             algo: AlgoConfig::RoundRobin,
-            tie_break: TieBreakConfig::Stable,
-            seed: None,
+            randomness: RandomnessConfig::Stable,
+            rng: RngKind::Std,
+            event_order: EventOrder::CompletionsFirst,
+            allow_large: false,
+            timeseries_bucket_ms: None,
+            record_timelines: false,
+            slos: Vec::new(),
+            slowest_requests: 0,
+            explain: false,
+            max_virtual_time_ms: None,
+            max_events: None,
         };
         let result = run_simulation(&config).expect("simulation should succeed");
 
@@ -532,6 +2752,15 @@ mod tests {
         assert_eq!(arrivals, vec![0, 1]);
 
         assert_eq!(result.totals[0].avg_response_ms, 7);
+
+        let split: Vec<(u64, u64)> = result
+            .assignments
+            .iter()
+            .map(|assignment| (assignment.queued_ms, assignment.service_ms))
+            .collect();
+        assert_eq!(split, vec![(0, 5), (4, 5)]);
+        assert_eq!(result.totals[0].avg_queued_ms, 2);
+        assert_eq!(result.totals[0].avg_service_ms, 5);
     }
 
     #[test]
@@ -542,22 +2771,34 @@ mod tests {
                     name: "api".to_string(),
                     base_latency_ms: 10,
                     weight: 1,
+                    model: ServerModel::default(),
                 },
                 ServerConfig {
                     name: "db".to_string(),
                     base_latency_ms: 20,
                     weight: 1,
+                    model: ServerModel::default(),
                 },
                 ServerConfig {
                     name: "cache".to_string(),
                     base_latency_ms: 30,
                     weight: 1,
+                    model: ServerModel::default(),
                 },
             ],
             requests: RequestProfile::FixedCount(2),
             algo: AlgoConfig::RoundRobin,
-            tie_break: TieBreakConfig::Stable,
-            seed: None,
+            randomness: RandomnessConfig::Stable,
+            rng: RngKind::Std,
+            event_order: EventOrder::CompletionsFirst,
+            allow_large: false,
+            timeseries_bucket_ms: None,
+            record_timelines: false,
+            slos: Vec::new(),
+            slowest_requests: 0,
+            explain: false,
+            max_virtual_time_ms: None,
+            max_events: None,
         };
         let result = run_simulation(&config).expect("simulation should succeed");
         let names: Vec<&str> = result
@@ -575,25 +2816,65 @@ mod tests {
                 name: "a".to_string(),
                 base_latency_ms: 10,
                 weight: 1,
+                model: ServerModel::default(),
             },
             ServerConfig {
                 name: "a".to_string(),
                 base_latency_ms: 20,
                 weight: 1,
+                model: ServerModel::default(),
             },
         ]);
         let result = run_simulation(&config);
         assert!(result.is_err());
     }
 
+    #[test]
+    fn max_events_aborts_with_partial_progress() {
+        let mut config = config_with_servers(vec![ServerConfig {
+            name: "a".to_string(),
+            base_latency_ms: 10,
+            weight: 1,
+            model: ServerModel::default(),
+        }]);
+        config.requests = RequestProfile::FixedCount(100);
+        config.max_events = Some(50);
+        match run_simulation(&config) {
+            Err(Error::SimulationLimit {
+                events,
+                completed,
+                total,
+                ..
+            }) => {
+                assert_eq!(events, 50);
+                assert_eq!(total, 100);
+                assert!(completed < total);
+            }
+            other => panic!("expected SimulationLimit, got {:?}", other.map(|_| ())),
+        }
+
+        config.max_events = None;
+        config.max_virtual_time_ms = Some(10_000);
+        assert!(run_simulation(&config).is_ok());
+    }
+
     #[test]
     fn empty_servers_error() {
         let config = SimConfig {
             servers: Vec::new(),
             requests: RequestProfile::FixedCount(1),
             algo: AlgoConfig::RoundRobin,
-            tie_break: TieBreakConfig::Stable,
-            seed: None,
+            randomness: RandomnessConfig::Stable,
+            rng: RngKind::Std,
+            event_order: EventOrder::CompletionsFirst,
+            allow_large: false,
+            timeseries_bucket_ms: None,
+            record_timelines: false,
+            slos: Vec::new(),
+            slowest_requests: 0,
+            explain: false,
+            max_virtual_time_ms: None,
+            max_events: None,
         };
         let result = run_simulation(&config);
         assert!(result.is_err());
@@ -607,17 +2888,28 @@ mod tests {
                     name: "a".to_string(),
                     base_latency_ms: 1,
                     weight: 1,
+                    model: ServerModel::default(),
                 },
                 ServerConfig {
                     name: "b".to_string(),
                     base_latency_ms: 1,
                     weight: 1,
+                    model: ServerModel::default(),
                 },
             ],
             requests: RequestProfile::FixedCount(2),
             algo: AlgoConfig::RoundRobin,
-            tie_break: TieBreakConfig::Stable,
-            seed: None,
+            randomness: RandomnessConfig::Stable,
+            rng: RngKind::Std,
+            event_order: EventOrder::CompletionsFirst,
+            allow_large: false,
+            timeseries_bucket_ms: None,
+            record_timelines: false,
+            slos: Vec::new(),
+            slowest_requests: 0,
+            explain: false,
+            max_virtual_time_ms: None,
+            max_events: None,
         };
         let result = run_simulation(&config).expect("simulation should succeed");
 
@@ -636,4 +2928,382 @@ mod tests {
         assert_eq!(result.phase1_metrics.throughput_rps, 1000.0);
         assert_eq!(result.phase1_metrics.avg_wait_ms, 0);
     }
+
+    #[test]
+    fn timeseries_buckets_track_completions_and_in_flight() {
+        let mut config = config_with_servers(vec![
+            ServerConfig {
+                name: "a".to_string(),
+                base_latency_ms: 4,
+                weight: 1,
+                model: ServerModel::default(),
+            },
+            ServerConfig {
+                name: "b".to_string(),
+                base_latency_ms: 4,
+                weight: 1,
+                model: ServerModel::default(),
+            },
+        ]);
+        config.requests = RequestProfile::Burst { count: 4, at_ms: 0 };
+        config.timeseries_bucket_ms = Some(5);
+        let result = run_simulation(&config).expect("simulation should succeed");
+        let timeseries = result.timeseries.expect("timeseries should be recorded");
+
+        assert_eq!(timeseries.servers, vec!["a", "b"]);
+        let completions = timeseries
+            .buckets
+            .iter()
+            .map(|bucket| bucket.completions)
+            .collect::<Vec<_>>();
+        assert_eq!(completions, vec![2, 2]);
+        assert_eq!(timeseries.buckets[0].arrivals, 4);
+        assert_eq!(timeseries.buckets[0].max_in_flight, vec![2, 2]);
+        assert_eq!(timeseries.buckets[1].avg_latency_ms, Some(8));
+    }
+
+    #[test]
+    fn timelines_track_queue_depth_per_server() {
+        let mut config = config_with_servers(vec![ServerConfig {
+            name: "a".to_string(),
+            base_latency_ms: 5,
+            weight: 1,
+            model: ServerModel::default(),
+        }]);
+        config.requests = RequestProfile::Burst { count: 3, at_ms: 0 };
+        config.record_timelines = true;
+        let result = run_simulation_summary(&config).expect("simulation should succeed");
+        let timelines = result.timelines.expect("timelines should be recorded");
+        let points = timelines[0]
+            .points
+            .iter()
+            .map(|point| (point.time_ms, point.queue_depth, point.active_connections))
+            .collect::<Vec<_>>();
+        assert_eq!(points, vec![(0, 2, 3), (5, 1, 2), (10, 0, 1), (15, 0, 0)]);
+    }
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(std::rc::Rc<std::cell::RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn trace_logs_arrivals_selections_and_completions() {
+        let config = SimConfig {
+            algo: AlgoConfig::LeastConnections,
+            requests: RequestProfile::Burst { count: 2, at_ms: 0 },
+            ..config_with_servers(vec![
+                ServerConfig {
+                    name: "a".to_string(),
+                    base_latency_ms: 5,
+                    weight: 1,
+                    model: ServerModel::default(),
+                },
+                ServerConfig {
+                    name: "b".to_string(),
+                    base_latency_ms: 5,
+                    weight: 1,
+                    model: ServerModel::default(),
+                },
+            ])
+        };
+        let buffer = SharedBuffer::default();
+        run_simulation_with_trace(&config, false, Box::new(buffer.clone()))
+            .expect("simulation should succeed");
+
+        let trace = String::from_utf8(buffer.0.borrow().clone()).expect("trace is utf-8");
+        assert_eq!(
+            trace,
+            concat!(
+                "t=0 arrival request=1\n",
+                "t=0 select request=1 server=a candidates=[a,b] score=0\n",
+                "t=0 arrival request=2\n",
+                "t=0 select request=2 server=b candidates=[b] score=0\n",
+                "t=5 complete request=1 server=a in_flight=0\n",
+                "t=5 complete request=2 server=b in_flight=0\n",
+            )
+        );
+    }
+
+    #[test]
+    fn zero_timeseries_bucket_errors() {
+        let mut config = config_with_servers(vec![ServerConfig {
+            name: "a".to_string(),
+            base_latency_ms: 1,
+            weight: 1,
+            model: ServerModel::default(),
+        }]);
+        config.timeseries_bucket_ms = Some(0);
+        assert!(matches!(
+            run_simulation(&config),
+            Err(Error::InvalidTimeseriesBucket(0))
+        ));
+    }
+
+    #[test]
+    fn global_stats_summarize_the_run() {
+        let config = config_with_servers(vec![
+            ServerConfig {
+                name: "a".to_string(),
+                base_latency_ms: 10,
+                weight: 1,
+                model: ServerModel::default(),
+            },
+            ServerConfig {
+                name: "b".to_string(),
+                base_latency_ms: 10,
+                weight: 1,
+                model: ServerModel::default(),
+            },
+        ]);
+        let config = SimConfig {
+            requests: RequestProfile::Burst { count: 4, at_ms: 0 },
+            ..config
+        };
+        let result = run_simulation_summary(&config).expect("simulation should succeed");
+
+        assert_eq!(result.global.total_requests, 4);
+        assert_eq!(result.global.makespan_ms, 20);
+        assert_eq!(result.global.throughput_rps, 200.0);
+        assert_eq!(result.global.avg_concurrency, 3.0);
+    }
+
+    #[test]
+    fn slo_report_uses_configured_thresholds() {
+        let mut config = config_with_servers(vec![
+            ServerConfig {
+                name: "fast".to_string(),
+                base_latency_ms: 5,
+                weight: 1,
+                model: ServerModel::default(),
+            },
+            ServerConfig {
+                name: "slow".to_string(),
+                base_latency_ms: 50,
+                weight: 1,
+                model: ServerModel::default(),
+            },
+        ]);
+        config.requests = RequestProfile::FixedCount(4);
+        config.slos = vec![crate::models::SloConfig {
+            percentile: 90.0,
+            threshold_ms: 20,
+        }];
+        let result = run_simulation_summary(&config).expect("simulation should succeed");
+        let report = &result.slo[0];
+
+        assert_eq!(report.overall.compliance_pct, 50.0);
+        assert!(!report.overall.met);
+        assert_eq!(report.overall.first_violation_ms, Some(51));
+        assert!(report.per_server[0].compliance.met);
+        assert_eq!(report.per_server[1].compliance.compliance_pct, 0.0);
+    }
+
+    #[test]
+    fn latency_histograms_cover_every_request() {
+        let config = SimConfig {
+            servers: vec![
+                ServerConfig {
+                    name: "a".to_string(),
+                    base_latency_ms: 10,
+                    weight: 1,
+                    model: ServerModel::default(),
+                },
+                ServerConfig {
+                    name: "b".to_string(),
+                    base_latency_ms: 20,
+                    weight: 1,
+                    model: ServerModel::default(),
+                },
+            ],
+            requests: RequestProfile::FixedCount(5),
+            algo: AlgoConfig::RoundRobin,
+            randomness: RandomnessConfig::Stable,
+            rng: RngKind::Std,
+            event_order: EventOrder::CompletionsFirst,
+            allow_large: false,
+            timeseries_bucket_ms: None,
+            record_timelines: false,
+            slos: Vec::new(),
+            slowest_requests: 0,
+            explain: false,
+            max_virtual_time_ms: None,
+            max_events: None,
+        };
+        let result = run_simulation_summary(&config).expect("simulation should succeed");
+        let histograms = &result.latency_histograms;
+
+        assert_eq!(histograms.overall.count, 5);
+        let per_server = histograms
+            .per_server
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.histogram.count))
+            .collect::<Vec<_>>();
+        assert_eq!(per_server, vec![("a", 3), ("b", 2)]);
+        assert_eq!(histograms.per_server[0].histogram.min_ms, Some(10));
+        assert_eq!(
+            histograms.overall.max_ms,
+            result.phase1_metrics.response_time.p99_ms
+        );
+    }
+
+    fn started_and_completed(config: &SimConfig) -> Vec<(u64, u64)> {
+        run_simulation(config)
+            .expect("simulation should succeed")
+            .assignments
+            .iter()
+            .map(|assignment| (assignment.started_at, assignment.completed_at))
+            .collect()
+    }
+
+    #[test]
+    fn capacity_serves_requests_in_parallel_slots() {
+        let mut config = config_with_servers(vec![modeled_server(ServerModel {
+            capacity: Some(CapacityConfig { concurrency: 2 }),
+            ..ServerModel::default()
+        })]);
+        config.requests = RequestProfile::Burst { count: 4, at_ms: 0 };
+
+        assert_eq!(
+            started_and_completed(&config),
+            vec![(0, 10), (0, 10), (10, 20), (10, 20)]
+        );
+        let result = run_simulation_summary(&config).expect("simulation should succeed");
+        assert_eq!(
+            result.phase1_metrics.per_server_utilization[0].utilization_pct,
+            100.0
+        );
+    }
+
+    #[test]
+    fn outages_delay_work_until_the_window_ends() {
+        let mut config = config_with_servers(vec![modeled_server(ServerModel {
+            failure: Some(FailureConfig {
+                outages: vec![OutageWindow {
+                    start_ms: 5,
+                    duration_ms: 20,
+                }],
+            }),
+            ..ServerModel::default()
+        })]);
+        config.requests = RequestProfile::Burst { count: 2, at_ms: 0 };
+
+        assert_eq!(started_and_completed(&config), vec![(0, 10), (25, 35)]);
+    }
+
+    #[test]
+    fn latency_distributions_are_bounded_and_reproducible() {
+        let mut config = config_with_servers(vec![modeled_server(ServerModel {
+            latency: Some(LatencyDistribution::Uniform { jitter_ms: 3 }),
+            ..ServerModel::default()
+        })]);
+        config.requests = RequestProfile::FixedCount(50);
+        config.randomness = RandomnessConfig::Seeded(7);
+
+        let result = run_simulation(&config).expect("simulation should succeed");
+        assert!(result
+            .assignments
+            .iter()
+            .all(|assignment| (7..=13).contains(&assignment.service_ms)));
+        assert!(result
+            .assignments
+            .iter()
+            .any(|assignment| assignment.service_ms != 10));
+        assert_eq!(
+            started_and_completed(&config),
+            started_and_completed(&config)
+        );
+    }
+
+    #[test]
+    fn every_builtin_strategy_reports_its_score_metric() {
+        let servers = ["a", "b"]
+            .into_iter()
+            .map(|name| ServerConfig {
+                name: name.to_string(),
+                base_latency_ms: 10,
+                weight: 1,
+                model: ServerModel::default(),
+            })
+            .collect();
+        let mut config = config_with_servers(servers);
+        config.requests = RequestProfile::FixedCount(3);
+        let cases = [
+            (AlgoConfig::RoundRobin, "rotation_slot", vec![0, 1, 0]),
+            (
+                AlgoConfig::WeightedRoundRobin,
+                "rotation_slot",
+                vec![0, 1, 0],
+            ),
+            (
+                AlgoConfig::LeastConnections,
+                "active_connections",
+                vec![0, 0, 1],
+            ),
+            (
+                AlgoConfig::LeastResponseTime,
+                "expected_completion_ms",
+                vec![10, 11, 20],
+            ),
+        ];
+        for (algo, metric, scores) in cases {
+            config.algo = algo;
+            let result = run_simulation(&config).expect("simulation should succeed");
+            assert_eq!(result.metadata.score_metric.as_deref(), Some(metric));
+            assert_eq!(
+                result
+                    .assignments
+                    .iter()
+                    .map(|assignment| assignment.score)
+                    .collect::<Vec<_>>(),
+                scores.into_iter().map(Some).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn verify_determinism_reports_the_first_diverging_event() {
+        use crate::algorithms::{register, Selection, SelectionContext};
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        static CALLS: AtomicU64 = AtomicU64::new(0);
+        struct Flaky;
+        impl SelectionStrategy for Flaky {
+            fn select(&mut self, _ctx: &mut SelectionContext) -> Selection {
+                // The runs alternate, so call 3 is the second run's request 2.
+                let call = CALLS.fetch_add(1, Ordering::SeqCst);
+                Selection {
+                    server_id: usize::from(call == 3),
+                    score: None,
+                }
+            }
+        }
+
+        let mut config = SimConfig::builder()
+            .server("a", 10)
+            .server("b", 10)
+            .requests(3)
+            .algo(AlgoConfig::LeastConnections)
+            .build()
+            .expect("config should build");
+        assert_eq!(verify_determinism(&config).expect("runs should agree"), 6);
+
+        register("flaky-determinism-test", || Box::new(Flaky)).expect("name should be free");
+        config.algo = AlgoConfig::Custom("flaky-determinism-test".to_string());
+        assert_eq!(
+            verify_determinism(&config).unwrap_err().to_string(),
+            "two identical runs diverged at event 1: first run t=1ms assigned request 2 to a \
+             (start 10ms, complete 20ms, score none), second run t=1ms assigned request 2 to b \
+             (start 1ms, complete 11ms, score none)"
+        );
+    }
 }
@@ -0,0 +1,337 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+use crate::models::{AlgoConfig, ServerConfig, ServerModel};
+
+/// Servers and the closest algorithm for one Envoy cluster. Envoy configs
+/// carry no latencies, so every server gets the same `base_latency_ms`.
+#[derive(Clone, Debug, Serialize)]
+pub struct EnvoyImport {
+    #[serde(skip)]
+    pub cluster: String,
+    pub algo: AlgoConfig,
+    pub servers: Vec<ServerConfig>,
+    /// Parts of the cluster the simulator cannot represent exactly.
+    #[serde(skip)]
+    pub notes: Vec<String>,
+}
+
+/// Imports a static cluster from Envoy YAML or JSON: a bootstrap with
+/// `static_resources.clusters`, a bare `clusters` list, or a single cluster.
+/// `cluster` picks one when the document defines several.
+pub fn import_envoy(
+    contents: &str,
+    cluster: Option<&str>,
+    base_latency_ms: u64,
+) -> Result<EnvoyImport> {
+    let document: Value =
+        serde_yaml::from_str(contents).map_err(|err| Error::yaml("Envoy config", None, err))?;
+    let clusters = find_clusters(&document);
+    let names = clusters
+        .iter()
+        .map(|cluster| cluster_name(cluster))
+        .collect::<Vec<_>>();
+    let selected = match cluster {
+        Some(name) => clusters
+            .iter()
+            .find(|candidate| cluster_name(candidate) == name)
+            .ok_or_else(|| {
+                Error::ConfigParse(format!(
+                    "no Envoy cluster named '{}' (available: {})",
+                    name,
+                    names.join(", ")
+                ))
+            })?,
+        None => match clusters.as_slice() {
+            [cluster] => *cluster,
+            [] => return Err(Error::ConfigParse("no Envoy clusters found".to_string())),
+            _ => {
+                return Err(Error::ConfigParse(format!(
+                    "Envoy config defines {} clusters; pick one with --cluster (available: {})",
+                    clusters.len(),
+                    names.join(", ")
+                )))
+            }
+        },
+    };
+    import_cluster(selected, base_latency_ms)
+}
+
+fn find_clusters(document: &Value) -> Vec<&Value> {
+    let list = document
+        .pointer("/static_resources/clusters")
+        .or_else(|| document.get("clusters"))
+        .and_then(Value::as_array);
+    match list {
+        Some(clusters) => clusters.iter().collect(),
+        None if document.get("load_assignment").is_some() => vec![document],
+        None => Vec::new(),
+    }
+}
+
+fn cluster_name(cluster: &Value) -> String {
+    cluster
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or("unnamed")
+        .to_string()
+}
+
+fn import_cluster(cluster: &Value, base_latency_ms: u64) -> Result<EnvoyImport> {
+    let name = cluster_name(cluster);
+    let mut notes = Vec::new();
+    let mut servers: Vec<ServerConfig> = Vec::new();
+    let localities = cluster
+        .pointer("/load_assignment/endpoints")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for locality in localities {
+        let locality_weight = weight(locality, &name)?;
+        let zone = locality
+            .pointer("/locality/zone")
+            .or_else(|| locality.pointer("/locality/region"))
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let endpoints = locality
+            .get("lb_endpoints")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        for endpoint in endpoints {
+            let server_name = endpoint_name(endpoint).ok_or_else(|| {
+                Error::ConfigParse(format!(
+                    "cluster '{}': endpoint without a hostname or socket_address",
+                    name
+                ))
+            })?;
+            if servers.iter().any(|server| server.name == server_name) {
+                return Err(Error::DuplicateServerName(server_name));
+            }
+            servers.push(ServerConfig {
+                name: server_name,
+                base_latency_ms,
+                weight: locality_weight.saturating_mul(weight(endpoint, &name)?),
+                model: ServerModel {
+                    zone: zone.clone(),
+                    ..ServerModel::default()
+                },
+            });
+        }
+    }
+    if servers.is_empty() {
+        return Err(Error::ConfigParse(format!(
+            "cluster '{}' has no load_assignment endpoints",
+            name
+        )));
+    }
+    if localities.len() > 1 {
+        notes.push(
+            "locality weights are folded into endpoint weights; Envoy's two-level locality pick is not modeled".to_string(),
+        );
+    }
+
+    let weighted = servers.iter().any(|server| server.weight != 1);
+    let policy = lb_policy(cluster);
+    let algo = match policy.as_deref() {
+        None | Some("ROUND_ROBIN") if weighted => AlgoConfig::WeightedRoundRobin,
+        None | Some("ROUND_ROBIN") => AlgoConfig::RoundRobin,
+        Some("LEAST_REQUEST") => AlgoConfig::LeastConnections,
+        Some(other) => {
+            notes.push(format!(
+                "lb policy {} has no simulator equivalent; using round-robin",
+                other
+            ));
+            if weighted {
+                AlgoConfig::WeightedRoundRobin
+            } else {
+                AlgoConfig::RoundRobin
+            }
+        }
+    };
+    if matches!(algo, AlgoConfig::LeastConnections) && weighted {
+        notes.push("least-connections ignores endpoint weights".to_string());
+    }
+
+    Ok(EnvoyImport {
+        cluster: name,
+        algo,
+        servers,
+        notes,
+    })
+}
+
+/// `lb_policy`, or the first `load_balancing_policy` extension mapped to its
+/// `lb_policy` spelling (e.g. `...least_request.v3.LeastRequest` ->
+/// `LEAST_REQUEST`).
+fn lb_policy(cluster: &Value) -> Option<String> {
+    if let Some(policy) = cluster.get("lb_policy").and_then(Value::as_str) {
+        return Some(policy.to_ascii_uppercase());
+    }
+    let policies = cluster
+        .pointer("/load_balancing_policy/policies")
+        .and_then(Value::as_array)?;
+    policies.iter().find_map(|policy| {
+        let extension = policy.get("typed_extension_config")?;
+        let type_url = extension
+            .pointer("/typed_config/@type")
+            .and_then(Value::as_str)
+            .or_else(|| extension.get("name").and_then(Value::as_str))?;
+        let package = type_url
+            .split("load_balancing_policies.")
+            .nth(1)
+            .unwrap_or(type_url);
+        Some(package.split('.').next()?.to_ascii_uppercase())
+    })
+}
+
+fn endpoint_name(endpoint: &Value) -> Option<String> {
+    let endpoint = endpoint.get("endpoint")?;
+    if let Some(hostname) = endpoint.get("hostname").and_then(Value::as_str) {
+        return Some(hostname.to_string());
+    }
+    let socket = endpoint.pointer("/address/socket_address")?;
+    let address = socket.get("address")?.as_str()?;
+    Some(match socket.get("port_value").and_then(Value::as_u64) {
+        Some(port) => format!("{}:{}", address, port),
+        None => address.to_string(),
+    })
+}
+
+fn weight(entry: &Value, cluster: &str) -> Result<u32> {
+    match entry.get("load_balancing_weight") {
+        None => Ok(1),
+        Some(value) => value
+            .as_u64()
+            .or_else(|| value.get("value").and_then(Value::as_u64))
+            .and_then(|weight| u32::try_from(weight).ok())
+            .filter(|weight| *weight > 0)
+            .ok_or_else(|| {
+                Error::ConfigParse(format!(
+                    "cluster '{}': invalid load_balancing_weight {}",
+                    cluster, value
+                ))
+            }),
+    }
+}
+
+/// TOML config fragment (`algo` plus `[[servers]]`) with the cluster and any
+/// approximations noted as comments; add `requests` or `include` it.
+pub fn import_to_toml(import: &EnvoyImport) -> Result<String> {
+    let mut output = format!("# imported from Envoy cluster '{}'\n", import.cluster);
+    for note in &import.notes {
+        output.push_str(&format!("# note: {}\n", note));
+    }
+    let body = toml::to_string(import)
+        .map_err(|err| Error::Output(format!("failed to serialize config: {}", err)))?;
+    output.push_str(&body);
+    Ok(output)
+}
+
+pub fn import_to_json(import: &EnvoyImport) -> Result<String> {
+    serde_json::to_string_pretty(import)
+        .map(|json| json + "\n")
+        .map_err(|err| Error::Output(format!("failed to serialize config: {}", err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOOTSTRAP: &str = r#"
+static_resources:
+  clusters:
+  - name: api
+    connect_timeout: 0.25s
+    lb_policy: LEAST_REQUEST
+    load_assignment:
+      cluster_name: api
+      endpoints:
+      - locality: { region: us-east-1, zone: us-east-1a }
+        load_balancing_weight: 2
+        lb_endpoints:
+        - endpoint: { address: { socket_address: { address: 10.0.0.1, port_value: 8080 } } }
+        - endpoint: { hostname: api-2.internal, address: { socket_address: { address: 10.0.0.2, port_value: 8080 } } }
+          load_balancing_weight: 3
+      - locality: { region: us-west-2 }
+        lb_endpoints:
+        - endpoint: { address: { socket_address: { address: 10.1.0.1, port_value: 8080 } } }
+  - name: static
+    load_assignment:
+      endpoints:
+      - lb_endpoints:
+        - endpoint: { address: { socket_address: { address: 10.2.0.1, port_value: 80 } } }
+"#;
+
+    #[test]
+    fn imports_endpoints_weights_zones_and_policy() {
+        let import = import_envoy(BOOTSTRAP, Some("api"), 15).expect("cluster should import");
+
+        assert_eq!(import.algo, AlgoConfig::LeastConnections);
+        let servers = import
+            .servers
+            .iter()
+            .map(|server| {
+                (
+                    server.name.as_str(),
+                    server.weight,
+                    server.model.zone.as_deref(),
+                    server.base_latency_ms,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            servers,
+            vec![
+                ("10.0.0.1:8080", 2, Some("us-east-1a"), 15),
+                ("api-2.internal", 6, Some("us-east-1a"), 15),
+                ("10.1.0.1:8080", 1, Some("us-west-2"), 15),
+            ]
+        );
+        assert_eq!(import.notes.len(), 2);
+
+        let toml = import_to_toml(&import).expect("import should serialize");
+        assert!(toml.starts_with("# imported from Envoy cluster 'api'\n# note: locality weights"));
+        assert!(toml.contains("algo = \"least-connections\"\n"));
+    }
+
+    #[test]
+    fn maps_typed_load_balancing_policies_and_weighted_round_robin() {
+        let cluster = r#"{
+            "name": "web",
+            "load_balancing_policy": { "policies": [{ "typed_extension_config": {
+                "name": "envoy.load_balancing_policies.round_robin",
+                "typed_config": { "@type": "type.googleapis.com/envoy.extensions.load_balancing_policies.round_robin.v3.RoundRobin" }
+            } }] },
+            "load_assignment": { "endpoints": [{ "lb_endpoints": [
+                { "endpoint": { "hostname": "a" }, "load_balancing_weight": 2 },
+                { "endpoint": { "hostname": "b" } }
+            ] }] }
+        }"#;
+        let import = import_envoy(cluster, None, 10).expect("cluster should import");
+        assert_eq!(import.algo, AlgoConfig::WeightedRoundRobin);
+        assert!(import.notes.is_empty());
+
+        let ring_hash = cluster.replace("round_robin.v3.RoundRobin", "ring_hash.v3.RingHash");
+        let import = import_envoy(&ring_hash, None, 10).expect("cluster should import");
+        assert_eq!(
+            import.notes,
+            vec!["lb policy RING_HASH has no simulator equivalent; using round-robin"]
+        );
+    }
+
+    #[test]
+    fn multiple_clusters_need_a_selection() {
+        let err = import_envoy(BOOTSTRAP, None, 10).expect_err("two clusters should fail");
+        assert_eq!(
+            err.to_string(),
+            "Envoy config defines 2 clusters; pick one with --cluster (available: api, static)"
+        );
+        let err = import_envoy(BOOTSTRAP, Some("db"), 10).expect_err("unknown cluster");
+        assert_eq!(
+            err.to_string(),
+            "no Envoy cluster named 'db' (available: api, static)"
+        );
+    }
+}
@@ -1,5 +1,9 @@
+use std::path::{Path, PathBuf};
+
 use thiserror::Error;
 
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("servers must not be empty")]
@@ -12,6 +16,9 @@ pub enum Error {
     DuplicateServerName(String),
     #[error("invalid server entry '{0}': expected name:latency_ms[:weight]")]
     InvalidServerEntry(String),
+    /// Every rejected entry, each prefixed with where it came from.
+    #[error("{} invalid server entries:\n  {}", .0.len(), .0.join("\n  "))]
+    InvalidServerEntries(Vec<String>),
     #[error("invalid latency in '{0}'")]
     InvalidLatency(String),
     #[error("latency must be > 0 in '{0}'")]
@@ -20,20 +27,196 @@ pub enum Error {
     InvalidWeight(String),
     #[error("weight must be > 0 in '{0}'")]
     InvalidWeightValue(String),
+    #[error(
+        "total server weight {0} (after dividing by the weights' common factor) is too large for weighted round-robin (max {max}); scale the weights down",
+        max = crate::validation::MAX_TOTAL_WEIGHT
+    )]
+    InvalidTotalWeight(u64),
+    #[error(
+        "{0} servers is over the limit of {max}; pass --allow-large (allow_large = true) to run it anyway",
+        max = crate::validation::MAX_SERVERS
+    )]
+    TooManyServers(usize),
+    #[error(
+        "{0} requests is over the limit of {max}; pass --allow-large (allow_large = true), and --summary or --stream-assignments to avoid storing every assignment",
+        max = crate::validation::MAX_REQUESTS
+    )]
+    TooManyRequests(u64),
+    #[error("invalid server model for '{0}': {1}")]
+    InvalidServerModel(String, String),
     #[error("request rate must be > 0 (got {0})")]
     InvalidRequestRate(f64),
     #[error("request duration must be > 0 (got {0}ms)")]
     InvalidRequestDuration(u64),
+    #[error("timeseries bucket must be > 0 (got {0}ms)")]
+    InvalidTimeseriesBucket(u64),
+    #[error("invalid SLO '{0}': expected percentile:threshold_ms with 0 < percentile <= 100")]
+    InvalidSlo(String),
+    #[error("invalid seed range '{0}': expected START..END with START <= END")]
+    InvalidSeedRange(String),
+    #[error("replications must be greater than 0")]
+    InvalidReplications,
     #[error("tie-break seed required when tie_break is seeded")]
     InvalidTieBreakSeed,
-    #[error("{0}")]
-    ConfigIo(String),
+    #[error(
+        "`randomness` replaces `tie_break` and `seed`; set only `randomness` (\"stable\", or \"seeded(N)\", which seeds tie-breaks too)"
+    )]
+    LegacyRandomnessKeys,
+    #[error("failed to read {what} '{}': {source}", .path.display())]
+    Io {
+        what: &'static str,
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// A document that does not parse or deserialize. `line` is 1-based, when
+    /// the underlying parser reports one.
+    #[error("invalid {what}{}: {source}", quoted_path(.path.as_deref()))]
+    Parse {
+        what: &'static str,
+        path: Option<PathBuf>,
+        line: Option<usize>,
+        #[source]
+        source: BoxError,
+    },
     #[error("{0}")]
     ConfigParse(String),
     #[error("unsupported config format '{0}'")]
     UnsupportedConfigFormat(String),
+    #[error("{0} problem(s) found in config")]
+    InvalidConfig(usize),
+    #[error("invalid scenario: {0}")]
+    InvalidScenario(String),
+    #[error("unknown profile '{0}' (available: {1})")]
+    UnknownProfile(String, String),
+    #[error("invalid --set override '{0}': {1}")]
+    InvalidOverride(String, String),
+    #[error("unknown algorithm '{0}' (see list-algorithms)")]
+    UnknownAlgorithm(String),
+    #[error("cannot register algorithm: {0}")]
+    AlgorithmRegistration(String),
+    #[error("invalid config builder: {0}")]
+    InvalidBuilder(String),
+    #[error("invalid event log: {0}")]
+    InvalidEventLog(String),
+    #[error("replay diverged at event {event}: recorded `{recorded}`, replayed `{replayed}`")]
+    ReplayDivergence {
+        event: usize,
+        recorded: String,
+        replayed: String,
+    },
+    #[error("unknown config key(s): {0} (use --lenient to ignore)")]
+    UnknownConfigKeys(String),
+    #[error("config include cycle: {0}")]
+    ConfigIncludeCycle(String),
+    #[error("{0}")]
+    Chart(String),
+    #[error("{0}")]
+    Grpc(String),
+    #[error("invariant violated: {0}")]
+    Invariant(String),
+    #[error("invalid engine snapshot: {0}")]
+    Snapshot(String),
+    #[error("{0}")]
+    Output(String),
+    #[error("assertion failed: {0}")]
+    AssertionFailed(String),
+    #[error("{0} metric(s) deviate from the baseline beyond tolerance")]
+    BaselineMismatch(usize),
+    #[error("--deterministic: {0}")]
+    NonDeterministic(String),
+    #[error(
+        "two identical runs diverged at event {index}: first run {first}, second run {second}"
+    )]
+    Diverged {
+        index: u64,
+        first: String,
+        second: String,
+    },
+    #[error(
+        "simulated time overflowed on '{server}': a {service_ms}ms service starting at {started_at}ms ends past u64::MAX ms"
+    )]
+    TimeOverflow {
+        server: String,
+        started_at: u64,
+        service_ms: u64,
+    },
+    #[error(
+        "simulation aborted: {limit} exceeded (algo {algo}, t={time_ms}ms, {events} events processed, {completed} of {total} requests completed)"
+    )]
+    SimulationLimit {
+        limit: String,
+        algo: String,
+        time_ms: u64,
+        events: u64,
+        completed: u64,
+        total: u64,
+    },
+    #[error("simulation stopped at t={0}ms")]
+    Stopped(u64),
     #[error("{0}")]
     Cli(String),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    #[cfg(feature = "serde")]
+    pub(crate) fn io(what: &'static str, path: &Path, source: std::io::Error) -> Self {
+        Error::Io {
+            what,
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn json(what: &'static str, path: Option<&Path>, source: serde_json::Error) -> Self {
+        // serde_json reports line 0 for errors that have no position, such as
+        // `from_value` failures.
+        let line = (source.line() > 0).then(|| source.line());
+        Self::parse(what, path, line, source)
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn toml(
+        what: &'static str,
+        path: Option<&Path>,
+        contents: &str,
+        source: toml::de::Error,
+    ) -> Self {
+        let line = source.span().map(|span| {
+            contents[..span.start.min(contents.len())]
+                .matches('\n')
+                .count()
+                + 1
+        });
+        Self::parse(what, path, line, source)
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn yaml(what: &'static str, path: Option<&Path>, source: serde_yaml::Error) -> Self {
+        let line = source.location().map(|location| location.line());
+        Self::parse(what, path, line, source)
+    }
+
+    #[cfg(feature = "serde")]
+    fn parse(
+        what: &'static str,
+        path: Option<&Path>,
+        line: Option<usize>,
+        source: impl Into<BoxError>,
+    ) -> Self {
+        Error::Parse {
+            what,
+            path: path.map(Path::to_path_buf),
+            line,
+            source: source.into(),
+        }
+    }
+}
+
+fn quoted_path(path: Option<&Path>) -> String {
+    path.map(|path| format!(" '{}'", path.display()))
+        .unwrap_or_default()
+}
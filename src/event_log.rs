@@ -0,0 +1,159 @@
+#[cfg(feature = "serde")]
+use std::fs::File;
+#[cfg(feature = "serde")]
+use std::io::{BufWriter, Write};
+#[cfg(feature = "serde")]
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+
+#[cfg(feature = "serde")]
+use crate::error::Error;
+use crate::error::Result;
+use crate::events::ScheduledEvent;
+
+/// Receives every event the engine processes, in processing order
+/// (`SimulationEngine::with_event_log`). Unlike `--trace` lines these are the
+/// engine's own `ScheduledEvent`s, so they can be deserialized again.
+pub trait EventLogWriter {
+    fn write_event(&mut self, event: &ScheduledEvent) -> Result<()>;
+
+    /// Called once the run finishes.
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Discards every event.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NullEventLog;
+
+impl EventLogWriter for NullEventLog {
+    fn write_event(&mut self, _event: &ScheduledEvent) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Keeps events in memory. Clones share storage, so keep one to read the
+/// events after handing another to the engine.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryEventLog {
+    events: Arc<Mutex<Vec<ScheduledEvent>>>,
+}
+
+impl InMemoryEventLog {
+    pub fn events(&self) -> Vec<ScheduledEvent> {
+        self.events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+impl EventLogWriter for InMemoryEventLog {
+    fn write_event(&mut self, event: &ScheduledEvent) -> Result<()> {
+        self.events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(event.clone());
+        Ok(())
+    }
+}
+
+/// One JSON object per line.
+#[cfg(feature = "serde")]
+pub struct JsonlEventLog<W: Write> {
+    writer: W,
+}
+
+#[cfg(feature = "serde")]
+impl<W: Write> JsonlEventLog<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl JsonlEventLog<BufWriter<File>> {
+    pub fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path).map_err(|err| {
+            Error::Output(format!(
+                "failed to create event log '{}': {}",
+                path.display(),
+                err
+            ))
+        })?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<W: Write> EventLogWriter for JsonlEventLog<W> {
+    fn write_event(&mut self, event: &ScheduledEvent) -> Result<()> {
+        serde_json::to_writer(&mut self.writer, event).map_err(event_log_error)?;
+        self.writer.write_all(b"\n").map_err(event_log_error)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.writer.flush().map_err(event_log_error)
+    }
+}
+
+#[cfg(feature = "serde")]
+fn event_log_error(err: impl std::fmt::Display) -> Error {
+    Error::Output(format!("failed to write event log: {}", err))
+}
+
+/// Parses a JSONL event log back into events.
+#[cfg(feature = "serde")]
+pub fn read_jsonl(contents: &str) -> Result<Vec<ScheduledEvent>> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            serde_json::from_str(line)
+                .map_err(|err| Error::InvalidEventLog(format!("line {}: {}", idx + 1, err)))
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::events::{Event, Request};
+
+    #[test]
+    fn jsonl_round_trips_events() {
+        let events = vec![
+            ScheduledEvent::new(
+                5,
+                Event::RequestArrival(Request {
+                    id: 1,
+                    arrival_time_ms: 5,
+                }),
+            ),
+            ScheduledEvent::new(
+                15,
+                Event::RequestComplete {
+                    server_id: 0,
+                    request_id: 1,
+                },
+            ),
+        ];
+        let mut log = JsonlEventLog::new(Vec::new());
+        for event in &events {
+            log.write_event(event).expect("event should serialize");
+        }
+        let contents = String::from_utf8(log.writer).expect("JSONL is UTF-8");
+
+        assert_eq!(
+            contents.lines().next(),
+            Some(r#"{"time_ms":5,"event":{"RequestArrival":{"id":1,"arrival_time_ms":5}}}"#)
+        );
+        assert_eq!(read_jsonl(&contents).expect("log should parse"), events);
+        assert_eq!(
+            read_jsonl("{}\n").unwrap_err().to_string(),
+            "invalid event log: line 1: missing field `time_ms` at line 1 column 2"
+        );
+    }
+}
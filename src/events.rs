@@ -1,33 +1,75 @@
 use std::cmp::Ordering;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::state::Assignment;
+
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Request {
-    pub id: usize,
+    pub id: u64,
     pub arrival_time_ms: u64,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum Event {
     RequestArrival(Request),
-    RequestComplete { server_id: usize, request_id: usize },
+    RequestComplete { server_id: usize, request_id: u64 },
+}
+
+/// What one `SimulationEngine::step` processed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EngineEvent {
+    /// A request arrived and was assigned to a server.
+    Assigned {
+        time_ms: u64,
+        assignment: Assignment,
+    },
+    Completed {
+        time_ms: u64,
+        request_id: u64,
+        server_id: usize,
+    },
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct ScheduledEvent {
     pub time_ms: u64,
     pub event: Event,
+    /// Orders events due at the same time ahead of the built-in
+    /// completions-first rule; the engine sets it from `SimConfig::event_order`.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_zero"))]
+    pub rank: u64,
 }
 
 impl ScheduledEvent {
     pub fn new(time_ms: u64, event: Event) -> Self {
-        Self { time_ms, event }
+        Self {
+            time_ms,
+            event,
+            rank: 0,
+        }
+    }
+
+    pub fn with_rank(mut self, rank: u64) -> Self {
+        self.rank = rank;
+        self
     }
 }
 
+#[cfg(feature = "serde")]
+fn is_zero(rank: &u64) -> bool {
+    *rank == 0
+}
+
 impl Ord for ScheduledEvent {
     fn cmp(&self, other: &Self) -> Ordering {
         self.time_ms
             .cmp(&other.time_ms)
+            .then_with(|| self.rank.cmp(&other.rank))
             .then_with(|| self.event.priority().cmp(&other.event.priority()))
             .then_with(|| self.event.tiebreaker().cmp(&other.event.tiebreaker()))
     }
@@ -47,7 +89,7 @@ impl Event {
         }
     }
 
-    fn tiebreaker(&self) -> usize {
+    fn tiebreaker(&self) -> u64 {
         match self {
             Event::RequestComplete { request_id, .. } => *request_id,
             Event::RequestArrival(request) => request.id,
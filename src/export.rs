@@ -0,0 +1,260 @@
+use crate::state::{ServerSummary, SimulationResult};
+
+/// Largest suggested weight; the rest scale down from it (minimum 1), which
+/// keeps every format within its weight range (HAProxy allows 0-256).
+const MAX_WEIGHT: u64 = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProxyFormat {
+    Nginx,
+    Haproxy,
+    Envoy,
+}
+
+/// A server's proxy address and the weight the run suggests for it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WeightedServer {
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    pub zone: Option<String>,
+    pub weight: u64,
+}
+
+/// Weights inversely proportional to each server's observed average service
+/// time, scaled so the fastest server gets `MAX_WEIGHT` and reduced by their
+/// common divisor. Servers that served nothing get weight 1. Names of the
+/// form `host:port` keep their port; others use `default_port`.
+pub fn suggested_weights(result: &SimulationResult, default_port: u16) -> Vec<WeightedServer> {
+    let fastest = result
+        .totals
+        .iter()
+        .map(|server| server.avg_service_ms)
+        .filter(|service_ms| *service_ms > 0)
+        .min();
+    let raw = result
+        .totals
+        .iter()
+        .map(|server| match (fastest, server.avg_service_ms) {
+            (Some(fastest), service_ms) if service_ms > 0 => {
+                let scaled = (MAX_WEIGHT * fastest) as f64 / service_ms as f64;
+                scaled.round().max(1.0) as u64
+            }
+            _ => 1,
+        })
+        .collect::<Vec<_>>();
+    let divisor = raw.iter().copied().fold(0, gcd).max(1);
+    result
+        .totals
+        .iter()
+        .zip(raw)
+        .map(|(server, weight)| {
+            let (host, port) = split_address(server, default_port);
+            WeightedServer {
+                name: server.name.clone(),
+                host,
+                port,
+                zone: server.zone.clone(),
+                weight: weight / divisor,
+            }
+        })
+        .collect()
+}
+
+fn split_address(server: &ServerSummary, default_port: u16) -> (String, u16) {
+    match server.name.rsplit_once(':') {
+        Some((host, port)) if !host.is_empty() => match port.parse() {
+            Ok(port) => (host.to_string(), port),
+            Err(_) => (server.name.clone(), default_port),
+        },
+        _ => (server.name.clone(), default_port),
+    }
+}
+
+fn gcd(left: u64, right: u64) -> u64 {
+    if right == 0 {
+        left
+    } else {
+        gcd(right, left % right)
+    }
+}
+
+/// Renders an nginx `upstream`, HAProxy `backend`, or Envoy cluster named
+/// `name` with the suggested weights, headed by a comment naming the source.
+pub fn export_snippet(
+    result: &SimulationResult,
+    format: ProxyFormat,
+    name: &str,
+    default_port: u16,
+) -> String {
+    let servers = suggested_weights(result, default_port);
+    let header = format!(
+        "# weights from lb-sim ({}, {} requests): inverse of avg service time\n",
+        result.metadata.algo, result.global.total_requests
+    );
+    match format {
+        ProxyFormat::Nginx => {
+            let mut output = format!("{}upstream {} {{\n", header, name);
+            for server in &servers {
+                output.push_str(&format!(
+                    "    server {}:{} weight={};\n",
+                    server.host, server.port, server.weight
+                ));
+            }
+            output.push_str("}\n");
+            output
+        }
+        ProxyFormat::Haproxy => {
+            let mut output = format!("{}backend {}\n    balance roundrobin\n", header, name);
+            for server in &servers {
+                output.push_str(&format!(
+                    "    server {} {}:{} weight {}\n",
+                    server.name.replace(':', "-"),
+                    server.host,
+                    server.port,
+                    server.weight
+                ));
+            }
+            output
+        }
+        ProxyFormat::Envoy => {
+            let mut output = format!(
+                "{}name: {}\nlb_policy: ROUND_ROBIN\nload_assignment:\n  cluster_name: {}\n  endpoints:\n",
+                header, name, name
+            );
+            let mut zones: Vec<Option<&str>> = Vec::new();
+            for server in &servers {
+                if !zones.contains(&server.zone.as_deref()) {
+                    zones.push(server.zone.as_deref());
+                }
+            }
+            for zone in zones {
+                match zone {
+                    Some(zone) => output.push_str(&format!(
+                        "  - locality: {{ zone: {} }}\n    lb_endpoints:\n",
+                        zone
+                    )),
+                    None => output.push_str("  - lb_endpoints:\n"),
+                }
+                for server in servers
+                    .iter()
+                    .filter(|server| server.zone.as_deref() == zone)
+                {
+                    output.push_str(&format!(
+                        "    - endpoint: {{ address: {{ socket_address: {{ address: {}, port_value: {} }} }} }}\n      load_balancing_weight: {}\n",
+                        server.host, server.port, server.weight
+                    ));
+                }
+            }
+            output
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::run_simulation;
+    #[cfg(feature = "serde")]
+    use crate::envoy::import_envoy;
+    use crate::models::{
+        AlgoConfig, EventOrder, RequestProfile, RngKind, ServerConfig, ServerModel, SimConfig,
+    };
+
+    fn result() -> SimulationResult {
+        let server = |name: &str, base_latency_ms: u64, zone: Option<&str>| ServerConfig {
+            name: name.to_string(),
+            base_latency_ms,
+            weight: 1,
+            model: ServerModel {
+                zone: zone.map(str::to_string),
+                ..ServerModel::default()
+            },
+        };
+        let config = SimConfig {
+            servers: vec![
+                server("10.0.0.1:8080", 10, Some("a")),
+                server("api-2", 20, Some("b")),
+                server("api-3", 40, Some("a")),
+            ],
+            requests: RequestProfile::FixedCount(30),
+            algo: AlgoConfig::RoundRobin,
+            randomness: Default::default(),
+            rng: RngKind::Std,
+            event_order: EventOrder::CompletionsFirst,
+            allow_large: false,
+            timeseries_bucket_ms: None,
+            record_timelines: false,
+            slos: Vec::new(),
+            slowest_requests: 0,
+            explain: false,
+            max_virtual_time_ms: None,
+            max_events: None,
+        };
+        run_simulation(&config).expect("simulation should succeed")
+    }
+
+    #[test]
+    fn weights_are_inverse_to_service_time_and_reduced() {
+        let weights = suggested_weights(&result(), 80)
+            .into_iter()
+            .map(|server| (server.host, server.port, server.weight))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            weights,
+            vec![
+                ("10.0.0.1".to_string(), 8080, 4),
+                ("api-2".to_string(), 80, 2),
+                ("api-3".to_string(), 80, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn nginx_and_haproxy_snippets_list_weighted_servers() {
+        let result = result();
+        assert_eq!(
+            export_snippet(&result, ProxyFormat::Nginx, "backend", 80),
+            concat!(
+                "# weights from lb-sim (round-robin, 30 requests): inverse of avg service time\n",
+                "upstream backend {\n",
+                "    server 10.0.0.1:8080 weight=4;\n",
+                "    server api-2:80 weight=2;\n",
+                "    server api-3:80 weight=1;\n",
+                "}\n",
+            )
+        );
+        let haproxy = export_snippet(&result, ProxyFormat::Haproxy, "backend", 80);
+        assert!(haproxy.contains(
+            "backend backend\n    balance roundrobin\n    server 10.0.0.1-8080 10.0.0.1:8080 weight 4\n"
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn envoy_snippet_round_trips_through_import_envoy() {
+        let snippet = export_snippet(&result(), ProxyFormat::Envoy, "backend", 80);
+        let import = import_envoy(&snippet, None, 10).expect("snippet should import");
+
+        assert_eq!(import.algo, AlgoConfig::WeightedRoundRobin);
+        let servers = import
+            .servers
+            .iter()
+            .map(|server| {
+                (
+                    server.name.as_str(),
+                    server.weight,
+                    server.model.zone.as_deref(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            servers,
+            vec![
+                ("10.0.0.1:8080", 4, Some("a")),
+                ("api-3:80", 1, Some("a")),
+                ("api-2:80", 2, Some("b")),
+            ]
+        );
+    }
+}
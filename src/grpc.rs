@@ -0,0 +1,228 @@
+use std::net::SocketAddr;
+
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+
+use crate::algorithms::build_strategy;
+use crate::engine::{run_simulation, SimulationEngine};
+use crate::error::{Error, Result};
+use crate::events::EngineEvent;
+use crate::models::SimConfig;
+use crate::output::{Formatter, JsonFormatter};
+use crate::validation;
+
+pub mod proto {
+    tonic::include_proto!("lb_sim.v1");
+}
+
+use proto::simulator_server::{Simulator, SimulatorServer};
+use proto::{simulation_event, RunRequest, RunResponse, SimulationEvent};
+
+/// Events buffered per `RunStream` call before the engine waits for the client.
+const STREAM_BUFFER: usize = 256;
+
+#[derive(Debug, Default)]
+pub struct SimulatorService;
+
+#[tonic::async_trait]
+impl Simulator for SimulatorService {
+    async fn run(
+        &self,
+        request: Request<RunRequest>,
+    ) -> std::result::Result<Response<RunResponse>, Status> {
+        let config = parse_config(&request.into_inner().config_json)?;
+        let result = tokio::task::spawn_blocking(move || run_simulation(&config))
+            .await
+            .map_err(|err| Status::internal(format!("simulation task failed: {}", err)))?
+            .map_err(status)?;
+        Ok(Response::new(RunResponse {
+            result_json: JsonFormatter::default().write(&result),
+        }))
+    }
+
+    type RunStreamStream = ReceiverStream<std::result::Result<SimulationEvent, Status>>;
+
+    async fn run_stream(
+        &self,
+        request: Request<RunRequest>,
+    ) -> std::result::Result<Response<Self::RunStreamStream>, Status> {
+        let config = parse_config(&request.into_inner().config_json)?;
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        tokio::task::spawn_blocking(move || {
+            if let Err(err) = stream_events(config, &tx) {
+                // The client may already be gone; nothing else to report to.
+                let _ = tx.blocking_send(Err(status(err)));
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+/// Serves `Simulator` on `addr` until the process is stopped.
+pub fn serve(addr: SocketAddr) -> Result<()> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .map_err(|err| Error::Grpc(format!("failed to start runtime: {}", err)))?;
+    tracing::info!(%addr, "serving gRPC");
+    runtime.block_on(async {
+        tonic::transport::Server::builder()
+            .add_service(SimulatorServer::new(SimulatorService))
+            .serve(addr)
+            .await
+            .map_err(|err| Error::Grpc(format!("gRPC server on {} failed: {}", addr, err)))
+    })
+}
+
+fn parse_config(config_json: &str) -> std::result::Result<SimConfig, Status> {
+    let config: SimConfig = serde_json::from_str(config_json)
+        .map_err(|err| Status::invalid_argument(format!("invalid config JSON: {}", err)))?;
+    validation::validate(&config).map_err(|err| Status::invalid_argument(err.to_string()))?;
+    Ok(config)
+}
+
+fn status(err: Error) -> Status {
+    match err {
+        Error::SimulationLimit { .. } => Status::resource_exhausted(err.to_string()),
+        other => Status::internal(other.to_string()),
+    }
+}
+
+/// Steps the engine, forwarding each event; stops early if the client hangs up.
+fn stream_events(
+    config: SimConfig,
+    tx: &mpsc::Sender<std::result::Result<SimulationEvent, Status>>,
+) -> Result<()> {
+    let strategy = build_strategy(config.algo.clone())?;
+    let mut engine = SimulationEngine::new(config, strategy);
+    engine.start(false)?;
+    while let Some(event) = engine.step()? {
+        let servers = &engine.state().servers;
+        let event = match event {
+            EngineEvent::Assigned {
+                time_ms,
+                assignment,
+            } => SimulationEvent {
+                time_ms,
+                event: Some(simulation_event::Event::Assigned(proto::Assigned {
+                    request_id: assignment.request_id,
+                    server_name: servers[assignment.server_id].name.clone(),
+                    arrival_time_ms: assignment.arrival_time_ms,
+                    started_at: assignment.started_at,
+                    completed_at: assignment.completed_at,
+                    queued_ms: assignment.queued_ms,
+                    service_ms: assignment.service_ms,
+                    score: assignment.score,
+                })),
+            },
+            EngineEvent::Completed {
+                time_ms,
+                request_id,
+                server_id,
+            } => SimulationEvent {
+                time_ms,
+                event: Some(simulation_event::Event::Completed(proto::Completed {
+                    request_id,
+                    server_name: servers[server_id].name.clone(),
+                })),
+            },
+        };
+        if tx.blocking_send(Ok(event)).is_err() {
+            return Ok(());
+        }
+    }
+    let time_ms = engine.state().time_ms;
+    let result = engine.finish()?;
+    let _ = tx.blocking_send(Ok(SimulationEvent {
+        time_ms,
+        event: Some(simulation_event::Event::ResultJson(
+            JsonFormatter::default().write(&result),
+        )),
+    }));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_stream::StreamExt;
+
+    const CONFIG: &str = r#"{
+        "servers": [{ "name": "a", "base_latency_ms": 10 }, { "name": "b", "base_latency_ms": 20 }],
+        "requests": 2,
+        "algo": "round-robin"
+    }"#;
+
+    fn run_request(config_json: &str) -> Request<RunRequest> {
+        Request::new(RunRequest {
+            config_json: config_json.to_string(),
+        })
+    }
+
+    #[tokio::test]
+    async fn run_returns_the_json_result() {
+        let response = SimulatorService
+            .run(run_request(CONFIG))
+            .await
+            .expect("run should succeed")
+            .into_inner();
+        let result: serde_json::Value =
+            serde_json::from_str(&response.result_json).expect("result is JSON");
+        assert_eq!(result["global"]["total_requests"], 2);
+    }
+
+    #[tokio::test]
+    async fn run_stream_sends_each_event_then_the_result() {
+        let stream = SimulatorService
+            .run_stream(run_request(CONFIG))
+            .await
+            .expect("stream should start")
+            .into_inner();
+        let events = stream
+            .map(|event| event.expect("event should succeed"))
+            .collect::<Vec<_>>()
+            .await;
+
+        let kinds = events
+            .iter()
+            .map(|event| match event.event.as_ref().expect("event is set") {
+                simulation_event::Event::Assigned(assigned) => {
+                    format!(
+                        "t={} assign {} -> {}",
+                        event.time_ms, assigned.request_id, assigned.server_name
+                    )
+                }
+                simulation_event::Event::Completed(completed) => {
+                    format!(
+                        "t={} complete {} on {}",
+                        event.time_ms, completed.request_id, completed.server_name
+                    )
+                }
+                simulation_event::Event::ResultJson(_) => format!("t={} result", event.time_ms),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                "t=0 assign 1 -> a",
+                "t=1 assign 2 -> b",
+                "t=10 complete 1 on a",
+                "t=21 complete 2 on b",
+                "t=21 result",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_configs_are_rejected_as_invalid_argument() {
+        let err = SimulatorService
+            .run(run_request(
+                r#"{ "servers": [], "requests": 2, "algo": "round-robin" }"#,
+            ))
+            .await
+            .expect_err("empty servers should fail");
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+        assert_eq!(err.message(), "servers must not be empty");
+    }
+}
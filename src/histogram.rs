@@ -0,0 +1,170 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::state::{HistogramBucket, LatencyHistogram};
+
+const SUB_BUCKET_BITS: u32 = 4;
+const SUB_BUCKETS: u64 = 1 << SUB_BUCKET_BITS;
+const HALF_SUB_BUCKETS: u64 = SUB_BUCKETS / 2;
+
+/// Log-linear latency recorder in the spirit of HdrHistogram: values below
+/// `SUB_BUCKETS` are exact, larger values keep roughly 1/8 relative precision.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct HistogramRecorder {
+    counts: Vec<u64>,
+    total: u64,
+    min: Option<u64>,
+    max: Option<u64>,
+}
+
+impl HistogramRecorder {
+    pub fn record(&mut self, value: u64) {
+        let idx = bucket_index(value);
+        if idx >= self.counts.len() {
+            self.counts.resize(idx + 1, 0);
+        }
+        self.counts[idx] += 1;
+        self.total += 1;
+        self.min = Some(self.min.map_or(value, |current| current.min(value)));
+        self.max = Some(self.max.map_or(value, |current| current.max(value)));
+    }
+
+    /// Nearest-rank percentile, reported as the top of the bucket it falls in
+    /// (clamped to the recorded extremes), so it never understates a tail.
+    pub fn percentile(&self, percentile: f64) -> Option<u64> {
+        if self.total == 0 {
+            return None;
+        }
+        let rank = ((percentile / 100.0) * self.total as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        let idx = self
+            .counts
+            .iter()
+            .position(|count| {
+                seen += count;
+                seen >= rank
+            })
+            .unwrap_or(self.counts.len() - 1);
+        let (_, upper) = bucket_bounds(idx);
+        Some((upper - 1).clamp(self.min?, self.max?))
+    }
+
+    /// Values recorded in buckets that can hold `threshold` or more.
+    pub fn count_at_least(&self, threshold: u64) -> u64 {
+        self.counts
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| bucket_bounds(*idx).1 > threshold)
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    pub fn to_histogram(&self) -> LatencyHistogram {
+        let buckets = self
+            .counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(idx, count)| {
+                let (lower_ms, upper_ms) = bucket_bounds(idx);
+                HistogramBucket {
+                    lower_ms,
+                    upper_ms,
+                    count: *count,
+                }
+            })
+            .collect();
+        LatencyHistogram {
+            count: self.total,
+            min_ms: self.min,
+            max_ms: self.max,
+            buckets,
+        }
+    }
+}
+
+fn bucket_index(value: u64) -> usize {
+    if value < SUB_BUCKETS {
+        return value as usize;
+    }
+    let msb = 63 - value.leading_zeros();
+    let shift = msb - (SUB_BUCKET_BITS - 1);
+    let mantissa = value >> shift;
+    let octave = (msb - SUB_BUCKET_BITS) as u64;
+    (SUB_BUCKETS + octave * HALF_SUB_BUCKETS + (mantissa - HALF_SUB_BUCKETS)) as usize
+}
+
+/// Returns the inclusive lower and exclusive upper bound of a bucket.
+fn bucket_bounds(idx: usize) -> (u64, u64) {
+    let idx = idx as u64;
+    if idx < SUB_BUCKETS {
+        return (idx, idx + 1);
+    }
+    let offset = idx - SUB_BUCKETS;
+    let octave = offset / HALF_SUB_BUCKETS;
+    let mantissa = HALF_SUB_BUCKETS + offset % HALF_SUB_BUCKETS;
+    let shift = octave as u32 + 1;
+    let lower = mantissa << shift;
+    let upper = u64::try_from(u128::from(mantissa + 1) << shift).unwrap_or(u64::MAX);
+    (lower, upper)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_values_get_exact_buckets() {
+        for value in 0..SUB_BUCKETS {
+            let idx = bucket_index(value);
+            assert_eq!(bucket_bounds(idx), (value, value + 1));
+        }
+    }
+
+    #[test]
+    fn large_values_fall_within_their_bucket_bounds() {
+        for value in [16, 17, 31, 32, 100, 250, 1_000, 65_535, 1 << 40, u64::MAX] {
+            let (lower, upper) = bucket_bounds(bucket_index(value));
+            assert!(
+                lower <= value,
+                "{} below bucket [{}, {})",
+                value,
+                lower,
+                upper
+            );
+            assert!(
+                value < upper || upper == u64::MAX,
+                "{} above bucket [{}, {})",
+                value,
+                lower,
+                upper
+            );
+            assert!((upper - lower) * 8 <= lower);
+        }
+    }
+
+    #[test]
+    fn recorder_reports_sparse_buckets_and_extremes() {
+        let mut recorder = HistogramRecorder::default();
+        for value in [3, 3, 40, 41, 900] {
+            recorder.record(value);
+        }
+        let histogram = recorder.to_histogram();
+        assert_eq!(histogram.count, 5);
+        assert_eq!(histogram.min_ms, Some(3));
+        assert_eq!(histogram.max_ms, Some(900));
+        let counts = histogram
+            .buckets
+            .iter()
+            .map(|bucket| (bucket.lower_ms, bucket.count))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, vec![(3, 2), (40, 2), (896, 1)]);
+
+        assert_eq!(recorder.percentile(40.0), Some(3));
+        assert_eq!(recorder.percentile(80.0), Some(43));
+        assert_eq!(recorder.percentile(99.0), Some(900));
+        assert_eq!(recorder.count_at_least(41), 3);
+        assert_eq!(HistogramRecorder::default().percentile(99.0), None);
+    }
+}
@@ -1,8 +1,56 @@
 pub mod algorithms;
+pub mod analytic;
+pub mod assertions;
+pub mod assignment_sink;
+#[cfg(feature = "serde")]
+pub mod baseline;
+pub mod bench;
+pub mod builder;
+#[cfg(feature = "serde")]
+pub mod calibrate;
+pub mod chart;
+#[cfg(feature = "serde")]
+pub mod chrome_trace;
+pub mod clock;
+#[cfg(feature = "cli")]
 pub mod config;
+#[cfg(feature = "serde")]
+pub mod config_file;
 pub mod engine;
+#[cfg(feature = "serde")]
+pub mod envoy;
 pub mod error;
+pub mod event_log;
 pub mod events;
+pub mod export;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod histogram;
+pub mod loadtest;
+#[cfg(feature = "cli")]
+pub mod logging;
+pub mod metrics;
 pub mod models;
+#[cfg(feature = "serde")]
+pub mod otel;
 pub mod output;
+#[cfg(feature = "cli")]
+pub mod prompt;
+pub mod recommend;
+#[cfg(feature = "serde")]
+pub mod replay;
+pub mod replication;
+pub mod reservoir;
+#[cfg(feature = "serde")]
+pub mod scenario;
+pub mod slo;
+pub mod slowest;
 pub mod state;
+pub mod step;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod timeline;
+pub mod timeseries;
+pub mod validation;
+#[cfg(feature = "serde")]
+pub mod wasm;
@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoadTool {
+    K6,
+    Vegeta,
+    Wrk2,
+}
+
+/// Renders a load-testing setup that replays `arrivals_ms` (sorted, relative
+/// to the start) against `url` with GET requests. k6 keeps every arrival time;
+/// Vegeta replays the per-`bucket_ms` rate, since it paces at a constant rate
+/// within each attack; wrk2 only supports one constant rate, so it gets the
+/// mean.
+pub fn export_workload(arrivals_ms: &[u64], tool: LoadTool, url: &str, bucket_ms: u64) -> String {
+    let duration_ms = arrivals_ms.last().map_or(0, |last| last + 1);
+    let header = format!(
+        "lb-sim workload: {} requests over {}ms",
+        arrivals_ms.len(),
+        duration_ms
+    );
+    match tool {
+        LoadTool::K6 => k6_script(&header, arrivals_ms, url),
+        LoadTool::Vegeta => vegeta_script(&header, arrivals_ms, url, bucket_ms.max(1)),
+        LoadTool::Wrk2 => {
+            let duration_s = duration_ms.div_ceil(1000).max(1);
+            let rate = (arrivals_ms.len() as f64 / duration_s as f64)
+                .ceil()
+                .max(1.0) as u64;
+            format!(
+                "# {}\n# wrk2 paces at one constant rate; bursts and rate changes are averaged out\nwrk -t2 -c{} -d{}s -R{} {}\n",
+                header,
+                rate.clamp(2, 100),
+                duration_s,
+                rate,
+                url
+            )
+        }
+    }
+}
+
+fn k6_script(header: &str, arrivals_ms: &[u64], url: &str) -> String {
+    let offsets = arrivals_ms
+        .iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let vus = arrivals_ms.len().clamp(1, 100);
+    format!(
+        r#"// {header}
+// Each iteration claims the next arrival and waits for its offset, so
+// requests start at the simulated times as long as enough VUs are free.
+import http from "k6/http";
+import exec from "k6/execution";
+import {{ sleep }} from "k6";
+
+const ARRIVALS_MS = [{offsets}];
+const START = Date.now() + 1000;
+
+export const options = {{
+  scenarios: {{
+    replay: {{
+      executor: "shared-iterations",
+      vus: {vus},
+      iterations: ARRIVALS_MS.length,
+      maxDuration: "{max_duration}s",
+    }},
+  }},
+}};
+
+export default function () {{
+  const due = START + ARRIVALS_MS[exec.scenario.iterationInTest];
+  const wait = due - Date.now();
+  if (wait > 0) {{
+    sleep(wait / 1000);
+  }}
+  http.get("{url}");
+}}
+"#,
+        header = header,
+        offsets = offsets,
+        vus = vus,
+        max_duration = arrivals_ms.last().map_or(0, |last| last / 1000) + 60,
+        url = url
+    )
+}
+
+fn vegeta_script(header: &str, arrivals_ms: &[u64], url: &str, bucket_ms: u64) -> String {
+    let mut buckets = BTreeMap::new();
+    for arrival in arrivals_ms {
+        *buckets.entry(arrival / bucket_ms).or_insert(0u64) += 1;
+    }
+    let last_bucket = buckets.keys().next_back().copied().unwrap_or(0);
+    let mut output = format!(
+        "#!/bin/sh\n# {}\n# One attack per {}ms bucket at that bucket's rate.\nset -e\nprintf 'GET %s\\n' '{}' > targets.txt\n",
+        header, bucket_ms, url
+    );
+    for bucket in 0..=last_bucket {
+        match buckets.get(&bucket) {
+            Some(count) => output.push_str(&format!(
+                "vegeta attack -targets=targets.txt -rate={}/{}ms -duration={}ms -output=results-{:05}.bin\n",
+                count, bucket_ms, bucket_ms, bucket
+            )),
+            None => output.push_str(&format!("sleep {}\n", bucket_ms as f64 / 1000.0)),
+        }
+    }
+    output.push_str("vegeta report results-*.bin\n");
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn k6_script_embeds_every_arrival_time() {
+        let script = export_workload(&[0, 5, 1200], LoadTool::K6, "http://staging/api", 1000);
+        assert!(script.starts_with("// lb-sim workload: 3 requests over 1201ms\n"));
+        assert!(script.contains("const ARRIVALS_MS = [0, 5, 1200];\n"));
+        assert!(script.contains("      vus: 3,\n"));
+        assert!(script.contains("  http.get(\"http://staging/api\");\n"));
+    }
+
+    #[test]
+    fn vegeta_script_replays_the_rate_per_bucket() {
+        let script = export_workload(
+            &[0, 10, 20, 2500],
+            LoadTool::Vegeta,
+            "http://staging/",
+            1000,
+        );
+        assert_eq!(
+            script,
+            concat!(
+                "#!/bin/sh\n",
+                "# lb-sim workload: 4 requests over 2501ms\n",
+                "# One attack per 1000ms bucket at that bucket's rate.\n",
+                "set -e\n",
+                "printf 'GET %s\\n' 'http://staging/' > targets.txt\n",
+                "vegeta attack -targets=targets.txt -rate=3/1000ms -duration=1000ms -output=results-00000.bin\n",
+                "sleep 1\n",
+                "vegeta attack -targets=targets.txt -rate=1/1000ms -duration=1000ms -output=results-00002.bin\n",
+                "vegeta report results-*.bin\n",
+            )
+        );
+    }
+
+    #[test]
+    fn wrk2_gets_the_mean_rate() {
+        let arrivals = (0..500).map(|idx| idx * 4).collect::<Vec<_>>();
+        let command = export_workload(&arrivals, LoadTool::Wrk2, "http://staging/", 1000);
+        assert!(command.ends_with("wrk -t2 -c100 -d2s -R250 http://staging/\n"));
+    }
+}
@@ -0,0 +1,28 @@
+use std::io::IsTerminal;
+
+use tracing::level_filters::LevelFilter;
+
+use crate::config::{LogArgs, LogFormatArg, LogLevelArg};
+use crate::error::{Error, Result};
+
+/// Installs the global `tracing` subscriber for `--log-level`/`--log-format`.
+/// Logs go to stderr so they never mix with formatted results on stdout.
+pub fn init(args: &LogArgs) -> Result<()> {
+    let level = match args.log_level {
+        LogLevelArg::Off => LevelFilter::OFF,
+        LogLevelArg::Error => LevelFilter::ERROR,
+        LogLevelArg::Warn => LevelFilter::WARN,
+        LogLevelArg::Info => LevelFilter::INFO,
+        LogLevelArg::Debug => LevelFilter::DEBUG,
+        LogLevelArg::Trace => LevelFilter::TRACE,
+    };
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_ansi(std::io::stderr().is_terminal())
+        .with_writer(std::io::stderr);
+    let installed = match args.log_format {
+        LogFormatArg::Text => builder.try_init(),
+        LogFormatArg::Json => builder.json().try_init(),
+    };
+    installed.map_err(|err| Error::Output(format!("failed to initialize logging: {}", err)))
+}
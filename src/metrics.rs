@@ -0,0 +1,220 @@
+use std::collections::BTreeMap;
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::engine::EngineObserver;
+use crate::error::{Error, Result};
+use crate::models::SimConfig;
+use crate::state::{Assignment, ServerState};
+
+/// Where the engine pushes live metrics (see `SimulationEngine::with_metrics`).
+/// Every metric is tagged with `server`:
+///
+/// - `lb_sim.selections` / `lb_sim.completions` counters
+/// - `lb_sim.in_flight` / `lb_sim.queue_depth` gauges after each change
+/// - `lb_sim.response_ms` / `lb_sim.wait_ms` histograms per assignment
+pub trait MetricsSink {
+    fn counter(&mut self, name: &str, value: u64, tags: &[(&str, &str)]);
+    fn gauge(&mut self, name: &str, value: f64, tags: &[(&str, &str)]);
+    fn histogram(&mut self, name: &str, value: f64, tags: &[(&str, &str)]);
+}
+
+/// Discards everything.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopMetrics;
+
+impl MetricsSink for NoopMetrics {
+    fn counter(&mut self, _name: &str, _value: u64, _tags: &[(&str, &str)]) {}
+    fn gauge(&mut self, _name: &str, _value: f64, _tags: &[(&str, &str)]) {}
+    fn histogram(&mut self, _name: &str, _value: f64, _tags: &[(&str, &str)]) {}
+}
+
+/// Collected values keyed like `lb_sim.selections{server=a}`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RecordedMetrics {
+    pub counters: BTreeMap<String, u64>,
+    /// Last value set.
+    pub gauges: BTreeMap<String, f64>,
+    /// Every observation, in order.
+    pub histograms: BTreeMap<String, Vec<f64>>,
+}
+
+/// Keeps metrics in memory. Clones share storage, so keep one to read the
+/// values after handing another to the engine.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryMetrics {
+    recorded: Arc<Mutex<RecordedMetrics>>,
+}
+
+impl InMemoryMetrics {
+    pub fn recorded(&self) -> RecordedMetrics {
+        self.lock().clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RecordedMetrics> {
+        self.recorded.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl MetricsSink for InMemoryMetrics {
+    fn counter(&mut self, name: &str, value: u64, tags: &[(&str, &str)]) {
+        *self
+            .lock()
+            .counters
+            .entry(metric_key(name, tags))
+            .or_default() += value;
+    }
+
+    fn gauge(&mut self, name: &str, value: f64, tags: &[(&str, &str)]) {
+        self.lock().gauges.insert(metric_key(name, tags), value);
+    }
+
+    fn histogram(&mut self, name: &str, value: f64, tags: &[(&str, &str)]) {
+        self.lock()
+            .histograms
+            .entry(metric_key(name, tags))
+            .or_default()
+            .push(value);
+    }
+}
+
+fn metric_key(name: &str, tags: &[(&str, &str)]) -> String {
+    if tags.is_empty() {
+        return name.to_string();
+    }
+    let tags = tags
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("{}{{{}}}", name, tags)
+}
+
+/// Sends each metric as a StatsD datagram with DogStatsD-style `|#key:value`
+/// tags. Like any StatsD client it never blocks the run: send errors are
+/// dropped.
+#[derive(Debug)]
+pub struct StatsdMetrics {
+    socket: UdpSocket,
+}
+
+impl StatsdMetrics {
+    pub fn connect(addr: impl ToSocketAddrs) -> Result<Self> {
+        let connect = || -> std::io::Result<UdpSocket> {
+            let socket = UdpSocket::bind(("0.0.0.0", 0))?;
+            socket.connect(addr)?;
+            Ok(socket)
+        };
+        let socket = connect()
+            .map_err(|err| Error::Output(format!("failed to open StatsD socket: {}", err)))?;
+        Ok(Self { socket })
+    }
+
+    fn send(&self, name: &str, value: &str, kind: &str, tags: &[(&str, &str)]) {
+        let _ = self
+            .socket
+            .send(statsd_line(name, value, kind, tags).as_bytes());
+    }
+}
+
+impl MetricsSink for StatsdMetrics {
+    fn counter(&mut self, name: &str, value: u64, tags: &[(&str, &str)]) {
+        self.send(name, &value.to_string(), "c", tags);
+    }
+
+    fn gauge(&mut self, name: &str, value: f64, tags: &[(&str, &str)]) {
+        self.send(name, &value.to_string(), "g", tags);
+    }
+
+    fn histogram(&mut self, name: &str, value: f64, tags: &[(&str, &str)]) {
+        self.send(name, &value.to_string(), "h", tags);
+    }
+}
+
+fn statsd_line(name: &str, value: &str, kind: &str, tags: &[(&str, &str)]) -> String {
+    let mut line = format!("{}:{}|{}", name, value, kind);
+    if !tags.is_empty() {
+        let tags = tags
+            .iter()
+            .map(|(key, value)| format!("{}:{}", key, value))
+            .collect::<Vec<_>>()
+            .join(",");
+        line.push_str("|#");
+        line.push_str(&tags);
+    }
+    line
+}
+
+/// Feeds a `MetricsSink` from engine events; `with_metrics` attaches one.
+pub(crate) struct MetricsObserver {
+    sink: Box<dyn MetricsSink>,
+    slots: Vec<u32>,
+}
+
+impl MetricsObserver {
+    pub(crate) fn new(config: &SimConfig, sink: Box<dyn MetricsSink>) -> Self {
+        Self {
+            sink,
+            slots: config
+                .servers
+                .iter()
+                .map(|server| server.model.concurrency())
+                .collect(),
+        }
+    }
+
+    fn occupancy(&mut self, server: &ServerState) {
+        let tags = [("server", server.name.as_str())];
+        let queued = server
+            .in_flight
+            .saturating_sub(self.slots.get(server.id).copied().unwrap_or(1));
+        self.sink
+            .gauge("lb_sim.in_flight", f64::from(server.in_flight), &tags);
+        self.sink
+            .gauge("lb_sim.queue_depth", f64::from(queued), &tags);
+    }
+}
+
+impl EngineObserver for MetricsObserver {
+    fn on_select(&mut self, _time_ms: u64, assignment: &Assignment, servers: &[ServerState]) {
+        let server = &servers[assignment.server_id];
+        let tags = [("server", server.name.as_str())];
+        self.sink.counter("lb_sim.selections", 1, &tags);
+        self.sink.histogram(
+            "lb_sim.response_ms",
+            (assignment.completed_at - assignment.arrival_time_ms) as f64,
+            &tags,
+        );
+        self.sink
+            .histogram("lb_sim.wait_ms", assignment.queued_ms as f64, &tags);
+        self.occupancy(server);
+    }
+
+    fn on_complete(&mut self, _time_ms: u64, _request_id: u64, server: &ServerState) {
+        self.sink
+            .counter("lb_sim.completions", 1, &[("server", server.name.as_str())]);
+        self.occupancy(server);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statsd_lines_carry_kind_and_tags() {
+        assert_eq!(
+            statsd_line("lb_sim.wait_ms", "2.5", "h", &[("server", "a")]),
+            "lb_sim.wait_ms:2.5|h|#server:a"
+        );
+        assert_eq!(statsd_line("runs", "1", "c", &[]), "runs:1|c");
+
+        let receiver = UdpSocket::bind("127.0.0.1:0").expect("receiver should bind");
+        let mut sink = StatsdMetrics::connect(receiver.local_addr().expect("bound address"))
+            .expect("socket should open");
+        sink.counter("lb_sim.selections", 1, &[("server", "a")]);
+        let mut buf = [0u8; 128];
+        let len = receiver.recv(&mut buf).expect("datagram should arrive");
+        assert_eq!(&buf[..len], b"lb_sim.selections:1|c|#server:a");
+    }
+}
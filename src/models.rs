@@ -1,40 +1,314 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+#[cfg(feature = "serde")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
 pub struct SimConfig {
     pub servers: Vec<ServerConfig>,
     pub requests: RequestProfile,
     pub algo: AlgoConfig,
-    #[serde(default)]
-    pub tie_break: TieBreakConfig,
-    #[serde(default)]
-    pub seed: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub randomness: RandomnessConfig,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "RngKind::is_std")
+    )]
+    pub rng: RngKind,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "EventOrder::is_default")
+    )]
+    pub event_order: EventOrder,
+    /// Lifts the server and request count limits (`--allow-large`).
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "std::ops::Not::not")
+    )]
+    pub allow_large: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub timeseries_bucket_ms: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub record_timelines: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub slos: Vec<SloConfig>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub slowest_requests: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub explain: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_virtual_time_ms: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_events: Option<u64>,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
 pub struct ServerConfig {
     pub name: String,
     pub base_latency_ms: u64,
-    #[serde(default = "default_weight")]
+    #[cfg_attr(feature = "serde", serde(default = "default_weight"))]
     pub weight: u32,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub model: ServerModel,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
-#[serde(untagged)]
+/// Optional per-server blocks beyond the `name:latency[:weight]` short form.
+/// Absent blocks keep the classic model: fixed latency, one request at a
+/// time, always up.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct ServerModel {
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub latency: Option<LatencyDistribution>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub capacity: Option<CapacityConfig>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub failure: Option<FailureConfig>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub zone: Option<String>,
+}
+
+impl ServerModel {
+    pub fn concurrency(&self) -> u32 {
+        self.capacity
+            .as_ref()
+            .map_or(1, |capacity| capacity.concurrency)
+    }
+
+    /// True when service times are exactly `base_latency_ms` on a single
+    /// always-available slot, i.e. the model `analytic` assumes.
+    pub fn is_deterministic_single_slot(&self) -> bool {
+        matches!(self.latency, None | Some(LatencyDistribution::Fixed))
+            && self.concurrency() == 1
+            && self
+                .failure
+                .as_ref()
+                .is_none_or(|failure| failure.outages.is_empty())
+    }
+}
+
+/// Service time distribution around `base_latency_ms`, which stays the mean
+/// and the estimate latency-aware strategies use.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "distribution", rename_all = "kebab-case")
+)]
+pub enum LatencyDistribution {
+    Fixed,
+    /// Uniform within `base_latency_ms ± jitter_ms`, floored at 1ms.
+    Uniform {
+        jitter_ms: u64,
+    },
+    /// Exponential with mean `base_latency_ms`, floored at 1ms.
+    Exponential,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct CapacityConfig {
+    /// Requests the server works on in parallel; extra requests queue.
+    pub concurrency: u32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct FailureConfig {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub outages: Vec<OutageWindow>,
+}
+
+/// The server starts no new work in `[start_ms, start_ms + duration_ms)`;
+/// requests routed to it wait for the window to end.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct OutageWindow {
+    pub start_ms: u64,
+    pub duration_ms: u64,
+}
+
+impl OutageWindow {
+    pub fn end_ms(&self) -> u64 {
+        self.start_ms.saturating_add(self.duration_ms)
+    }
+}
+
+/// Generates `count` servers named by replacing `{i}` (1-based) in `name`.
+/// `latency_jitter_ms` spreads each latency uniformly within ±jitter,
+/// reproducibly for a given `jitter_seed`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct ServerTemplate {
+    pub name: String,
+    pub count: usize,
+    pub base_latency_ms: u64,
+    #[cfg_attr(feature = "serde", serde(default = "default_weight"))]
+    pub weight: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub latency_jitter_ms: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub jitter_seed: u64,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub model: ServerModel,
+}
+
+impl ServerTemplate {
+    pub fn expand(&self) -> Vec<ServerConfig> {
+        let mut rng = StdRng::seed_from_u64(self.jitter_seed);
+        (1..=self.count)
+            .map(|idx| {
+                let jitter = self.latency_jitter_ms as i64;
+                let offset = if jitter > 0 {
+                    rng.gen_range(-jitter..=jitter)
+                } else {
+                    0
+                };
+                ServerConfig {
+                    name: self.name.replace("{i}", &idx.to_string()),
+                    base_latency_ms: (self.base_latency_ms as i64 + offset).max(1) as u64,
+                    weight: self.weight,
+                    model: self.model.clone(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct SloConfig {
+    pub percentile: f64,
+    pub threshold_ms: u64,
+}
+
+/// Written as a bare count, `{ rate, duration_ms }`, or `{ count, at_ms }`.
+/// Deserializing checks the table's keys as a set, so a table mixing the
+/// Poisson and burst keys is rejected rather than read as whichever matches
+/// first.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, JsonSchema))]
+#[cfg_attr(feature = "serde", serde(untagged))]
 pub enum RequestProfile {
     FixedCount(usize),
     Poisson { rate: f64, duration_ms: u64 },
     Burst { count: usize, at_ms: u64 },
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
-#[serde(rename_all = "kebab-case")]
+/// How `RequestProfile` is read from config files: a count, or a table whose
+/// keys pick the workload.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RequestsRepr {
+    Count(usize),
+    Table {
+        rate: Option<f64>,
+        duration_ms: Option<u64>,
+        count: Option<usize>,
+        at_ms: Option<u64>,
+    },
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RequestsRepr> for RequestProfile {
+    type Error = String;
+
+    fn try_from(repr: RequestsRepr) -> std::result::Result<Self, Self::Error> {
+        match repr {
+            RequestsRepr::Count(count) => Ok(RequestProfile::FixedCount(count)),
+            RequestsRepr::Table {
+                rate: Some(rate),
+                duration_ms: Some(duration_ms),
+                count: None,
+                at_ms: None,
+            } => Ok(RequestProfile::Poisson { rate, duration_ms }),
+            RequestsRepr::Table {
+                rate: None,
+                duration_ms: None,
+                count: Some(count),
+                at_ms: Some(at_ms),
+            } => Ok(RequestProfile::Burst { count, at_ms }),
+            RequestsRepr::Table { .. } => Err(
+                "invalid requests: expected a count, { rate, duration_ms } for Poisson \
+                 arrivals, or { count, at_ms } for a burst"
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for RequestProfile {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        RequestsRepr::deserialize(deserializer)?
+            .try_into()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Written as a name in configs; unknown names become `Custom` and must be
+/// registered with `algorithms::register` before the run is validated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+#[cfg_attr(feature = "serde", serde(from = "String", into = "String"))]
 pub enum AlgoConfig {
     RoundRobin,
     WeightedRoundRobin,
     LeastConnections,
     LeastResponseTime,
+    Custom(String),
+}
+
+impl AlgoConfig {
+    pub const BUILTIN: [AlgoConfig; 4] = [
+        AlgoConfig::RoundRobin,
+        AlgoConfig::WeightedRoundRobin,
+        AlgoConfig::LeastConnections,
+        AlgoConfig::LeastResponseTime,
+    ];
+
+    /// The built-in algorithm for a canonical name or short alias.
+    pub fn builtin(name: &str) -> Option<AlgoConfig> {
+        match name {
+            "round-robin" | "rr" => Some(AlgoConfig::RoundRobin),
+            "weighted-round-robin" | "wrr" => Some(AlgoConfig::WeightedRoundRobin),
+            "least-connections" | "lc" => Some(AlgoConfig::LeastConnections),
+            "least-response-time" | "lrt" => Some(AlgoConfig::LeastResponseTime),
+            _ => None,
+        }
+    }
+}
+
+impl From<String> for AlgoConfig {
+    fn from(name: String) -> Self {
+        AlgoConfig::builtin(&name).unwrap_or(AlgoConfig::Custom(name))
+    }
+}
+
+impl From<AlgoConfig> for String {
+    fn from(algo: AlgoConfig) -> Self {
+        algo.to_string()
+    }
 }
 
 impl fmt::Display for AlgoConfig {
@@ -44,31 +318,199 @@ impl fmt::Display for AlgoConfig {
             AlgoConfig::WeightedRoundRobin => "weighted-round-robin",
             AlgoConfig::LeastConnections => "least-connections",
             AlgoConfig::LeastResponseTime => "least-response-time",
+            AlgoConfig::Custom(name) => name,
         };
         write!(f, "{}", label)
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize, Default)]
-#[serde(rename_all = "kebab-case")]
-pub enum TieBreakConfig {
+/// Where a run's randomness comes from. One policy covers tie-breaks, the
+/// Poisson workload, and sampled service times. In config files it is written
+/// `"stable"`, `"seeded(42)"` (or just `42`), or `"entropy"`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(try_from = "RandomnessRepr", into = "RandomnessRepr")
+)]
+pub enum RandomnessConfig {
+    /// Ties go to the first candidate in input order; the workload and
+    /// service times draw from seed 0.
     #[default]
     Stable,
-    Seeded,
+    /// Ties are broken at random, and everything draws from this seed.
+    Seeded(u64),
+    /// Like `Seeded`, with a fresh seed drawn from OS entropy. `resolve` draws
+    /// it before a run, so the seed is reported and can be replayed.
+    Entropy,
+}
+
+impl RandomnessConfig {
+    /// The seed the workload, service times, and seeded tie-breaks draw from.
+    /// An unresolved `Entropy` has none yet.
+    pub fn seed(&self) -> Option<u64> {
+        match self {
+            RandomnessConfig::Stable => Some(0),
+            RandomnessConfig::Seeded(seed) => Some(*seed),
+            RandomnessConfig::Entropy => None,
+        }
+    }
+
+    /// Whether ties are broken at random rather than by input order.
+    pub fn seeded_tie_breaks(&self) -> bool {
+        !matches!(self, RandomnessConfig::Stable)
+    }
+
+    /// Replaces `Entropy` with `Seeded` on a freshly drawn seed.
+    pub fn resolve(self) -> Self {
+        match self {
+            RandomnessConfig::Entropy => RandomnessConfig::Seeded(rand::random()),
+            other => other,
+        }
+    }
 }
 
-impl TieBreakConfig {
-    pub fn label_with_seed(&self, seed: Option<u64>) -> String {
+impl fmt::Display for RandomnessConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            TieBreakConfig::Stable => "stable".to_string(),
-            TieBreakConfig::Seeded => match seed {
-                Some(value) => format!("seeded({})", value),
-                None => "seeded".to_string(),
-            },
+            RandomnessConfig::Stable => f.write_str("stable"),
+            RandomnessConfig::Seeded(seed) => write!(f, "seeded({})", seed),
+            RandomnessConfig::Entropy => f.write_str("entropy"),
+        }
+    }
+}
+
+impl std::str::FromStr for RandomnessConfig {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        let trimmed = value.trim();
+        let seed = trimmed
+            .strip_prefix("seeded(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .unwrap_or(trimmed);
+        match trimmed {
+            "stable" => Ok(RandomnessConfig::Stable),
+            "entropy" => Ok(RandomnessConfig::Entropy),
+            _ => seed.parse().map(RandomnessConfig::Seeded).map_err(|_| {
+                format!(
+                    "invalid randomness '{}': expected stable, seeded(SEED), SEED, or entropy",
+                    value
+                )
+            }),
         }
     }
 }
 
+/// How `RandomnessConfig` appears in config files: its label, or a bare seed.
+#[cfg(feature = "serde")]
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[serde(untagged)]
+enum RandomnessRepr {
+    Seed(u64),
+    Label(String),
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RandomnessRepr> for RandomnessConfig {
+    type Error = String;
+
+    fn try_from(repr: RandomnessRepr) -> std::result::Result<Self, Self::Error> {
+        match repr {
+            RandomnessRepr::Seed(seed) => Ok(RandomnessConfig::Seeded(seed)),
+            RandomnessRepr::Label(label) => label.parse(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<RandomnessConfig> for RandomnessRepr {
+    fn from(randomness: RandomnessConfig) -> Self {
+        RandomnessRepr::Label(randomness.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl JsonSchema for RandomnessConfig {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "RandomnessConfig".into()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        RandomnessRepr::json_schema(generator)
+    }
+}
+
+/// Generator behind tie-breaks, Poisson arrivals, and latency sampling. Each
+/// gives its own reproducible sequence for a seed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum RngKind {
+    /// ChaCha12, the generator behind rand's `StdRng`.
+    #[default]
+    Std,
+    /// PCG64 (MCG): 16 bytes of state and a multiply per draw.
+    Small,
+    /// Xoshiro256++.
+    Xoshiro,
+}
+
+impl RngKind {
+    pub fn is_std(&self) -> bool {
+        *self == RngKind::Std
+    }
+}
+
+impl fmt::Display for RngKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RngKind::Std => "std",
+            RngKind::Small => "small",
+            RngKind::Xoshiro => "xoshiro",
+        })
+    }
+}
+
+/// Which of several events due at the same millisecond is processed first.
+/// The order decides whether an arrival sees a connection that frees up at
+/// that instant, which matters for least-connections.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum EventOrder {
+    /// Completions before arrivals, then by request id.
+    #[default]
+    CompletionsFirst,
+    /// Arrivals before completions, then by request id.
+    ArrivalsFirst,
+    /// In the order the events were scheduled.
+    Fifo,
+}
+
+impl EventOrder {
+    pub fn is_default(&self) -> bool {
+        *self == EventOrder::CompletionsFirst
+    }
+}
+
+impl fmt::Display for EventOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            EventOrder::CompletionsFirst => "completions-first",
+            EventOrder::ArrivalsFirst => "arrivals-first",
+            EventOrder::Fifo => "fifo",
+        })
+    }
+}
+
+impl fmt::Display for SloConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "p{} <= {}ms", self.percentile, self.threshold_ms)
+    }
+}
+
+#[cfg(feature = "serde")]
 fn default_weight() -> u32 {
     1
 }
@@ -0,0 +1,192 @@
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::error::{Error, Result};
+use crate::state::SimulationResult;
+
+const SPAN_KIND_SERVER: u8 = 2;
+const SPAN_KIND_CLIENT: u8 = 3;
+const NANOS_PER_MS: u64 = 1_000_000;
+
+/// Where OTLP spans are sent: an OTLP/HTTP collector base URL (spans are
+/// posted to `<endpoint>/v1/traces`) or a local JSON file.
+#[derive(Clone, Debug)]
+pub enum OtlpTarget<'a> {
+    Endpoint(&'a str),
+    File(&'a Path),
+}
+
+/// Builds an OTLP/JSON `ExportTraceServiceRequest` with one trace per
+/// request: a client span on the load balancer covering arrival to
+/// completion, and a child server span on the chosen backend covering
+/// service time. Virtual milliseconds are offset from `epoch_unix_nanos`.
+pub fn to_otlp_json(result: &SimulationResult, epoch_unix_nanos: u64) -> Value {
+    let trace_prefix = u128::from(epoch_unix_nanos) << 64;
+    let timestamp = |time_ms: u64| (epoch_unix_nanos + time_ms * NANOS_PER_MS).to_string();
+
+    let mut client_spans = Vec::with_capacity(result.assignments.len());
+    let mut server_spans = vec![Vec::new(); result.totals.len()];
+    for assignment in &result.assignments {
+        let request_id = assignment.request_id;
+        let trace_id = format!("{:032x}", trace_prefix | u128::from(request_id));
+        let client_span_id = format!("{:016x}", request_id << 1);
+        let server_span_id = format!("{:016x}", (request_id << 1) | 1);
+        let server_name = result
+            .totals
+            .get(assignment.server_id)
+            .map(|summary| summary.name.as_str())
+            .unwrap_or("unknown");
+        let wait_ms = assignment.started_at - assignment.arrival_time_ms;
+
+        let mut client_attributes = vec![
+            string_attribute("lb.algorithm", &result.metadata.algo),
+            string_attribute("lb.server", server_name),
+            int_attribute("lb.request_id", request_id),
+            int_attribute("lb.queue_wait_ms", wait_ms),
+        ];
+        if let Some(score) = assignment.score {
+            client_attributes.push(int_attribute("lb.score", score));
+        }
+        client_spans.push(json!({
+            "traceId": trace_id,
+            "spanId": client_span_id,
+            "name": "lb.route",
+            "kind": SPAN_KIND_CLIENT,
+            "startTimeUnixNano": timestamp(assignment.arrival_time_ms),
+            "endTimeUnixNano": timestamp(assignment.completed_at),
+            "attributes": client_attributes,
+        }));
+        if let Some(spans) = server_spans.get_mut(assignment.server_id) {
+            spans.push(json!({
+                "traceId": trace_id,
+                "spanId": server_span_id,
+                "parentSpanId": client_span_id,
+                "name": "backend.handle",
+                "kind": SPAN_KIND_SERVER,
+                "startTimeUnixNano": timestamp(assignment.started_at),
+                "endTimeUnixNano": timestamp(assignment.completed_at),
+                "attributes": [int_attribute("lb.request_id", request_id)],
+            }));
+        }
+    }
+
+    let mut resources = vec![resource_spans("lb-sim", client_spans)];
+    resources.extend(
+        result
+            .totals
+            .iter()
+            .zip(server_spans)
+            .filter(|(_, spans)| !spans.is_empty())
+            .map(|(summary, spans)| resource_spans(&summary.name, spans)),
+    );
+    json!({ "resourceSpans": resources })
+}
+
+pub fn export_spans(result: &SimulationResult, target: OtlpTarget) -> Result<()> {
+    let epoch_unix_nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or(0);
+    let payload = to_otlp_json(result, epoch_unix_nanos).to_string();
+    match target {
+        OtlpTarget::File(path) => std::fs::write(path, payload).map_err(|err| {
+            Error::Output(format!(
+                "failed to write OTLP spans '{}': {}",
+                path.display(),
+                err
+            ))
+        }),
+        OtlpTarget::Endpoint(endpoint) => post_spans(endpoint, payload),
+    }
+}
+
+#[cfg(feature = "otel")]
+fn post_spans(endpoint: &str, payload: String) -> Result<()> {
+    let url = format!("{}/v1/traces", endpoint.trim_end_matches('/'));
+    ureq::post(&url)
+        .header("Content-Type", "application/json")
+        .send(payload)
+        .map(|_| ())
+        .map_err(|err| Error::Output(format!("failed to export spans to '{}': {}", url, err)))
+}
+
+#[cfg(not(feature = "otel"))]
+fn post_spans(endpoint: &str, _payload: String) -> Result<()> {
+    Err(Error::Output(format!(
+        "cannot export spans to '{}': lb-sim was built without the `otel` feature",
+        endpoint
+    )))
+}
+
+fn resource_spans(service_name: &str, spans: Vec<Value>) -> Value {
+    json!({
+        "resource": {
+            "attributes": [string_attribute("service.name", service_name)],
+        },
+        "scopeSpans": [{
+            "scope": { "name": "lb-sim", "version": env!("CARGO_PKG_VERSION") },
+            "spans": spans,
+        }],
+    })
+}
+
+fn string_attribute(key: &str, value: &str) -> Value {
+    json!({ "key": key, "value": { "stringValue": value } })
+}
+
+fn int_attribute(key: &str, value: u64) -> Value {
+    json!({ "key": key, "value": { "intValue": value.to_string() } })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::run_simulation;
+    use crate::models::{
+        AlgoConfig, EventOrder, RandomnessConfig, RequestProfile, RngKind, ServerConfig,
+        ServerModel, SimConfig,
+    };
+
+    #[test]
+    fn otlp_json_links_backend_spans_to_lb_spans() {
+        let config = SimConfig {
+            servers: vec![ServerConfig {
+                name: "api".to_string(),
+                base_latency_ms: 10,
+                weight: 1,
+                model: ServerModel::default(),
+            }],
+            requests: RequestProfile::FixedCount(2),
+            algo: AlgoConfig::RoundRobin,
+            randomness: RandomnessConfig::Stable,
+            rng: RngKind::Std,
+            event_order: EventOrder::CompletionsFirst,
+            allow_large: false,
+            timeseries_bucket_ms: None,
+            record_timelines: false,
+            slos: Vec::new(),
+            slowest_requests: 0,
+            explain: false,
+            max_virtual_time_ms: None,
+            max_events: None,
+        };
+        let result = run_simulation(&config).expect("simulation should succeed");
+        let otlp = to_otlp_json(&result, 1_000_000_000);
+
+        let resources = otlp["resourceSpans"].as_array().expect("resource spans");
+        assert_eq!(resources.len(), 2);
+        let client = &resources[0]["scopeSpans"][0]["spans"][1];
+        let server = &resources[1]["scopeSpans"][0]["spans"][1];
+        assert_eq!(
+            resources[1]["resource"]["attributes"][0]["value"]["stringValue"],
+            "api"
+        );
+        assert_eq!(client["kind"], SPAN_KIND_CLIENT);
+        assert_eq!(client["startTimeUnixNano"], "1001000000");
+        assert_eq!(client["endTimeUnixNano"], "1020000000");
+        assert_eq!(server["parentSpanId"], client["spanId"]);
+        assert_eq!(server["traceId"], client["traceId"]);
+        assert_eq!(server["startTimeUnixNano"], "1010000000");
+    }
+}
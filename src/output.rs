@@ -1,99 +1,1198 @@
-use crate::state::{Assignment, Phase1Metrics, RunMetadata, ServerSummary, SimulationResult};
-use serde::Serialize;
+use crate::algorithms::ScoreMetric;
+#[cfg(feature = "serde")]
+use crate::baseline::MetricComparison;
+use crate::bench::BenchRow;
+#[cfg(feature = "serde")]
+use crate::error::{Error, Result};
+use crate::recommend::Recommendation;
+use crate::replication::{key_metrics, ReplicationReport, SeedSweepReport};
+#[cfg(feature = "serde")]
+use crate::scenario::ScenarioReport;
+use crate::state::{
+    AnalyticComparison, Assignment, GlobalStats, LittlesLaw, SelectionExplain, ServerSummary,
+    ServerTimeline, SimulationResult, SloCompliance, SloReport, SlowRequest, TailBreakdown,
+};
+#[cfg(feature = "serde")]
+use crate::state::{LatencyHistograms, Phase1Metrics, RunMetadata, Timeseries};
+#[cfg(feature = "serde")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer};
+use std::collections::HashMap;
+use std::io;
+
+const CHART_WIDTH: usize = 40;
+const ANSI_DIM: &str = "2";
+const ANSI_HIGHLIGHT: &str = "1;33";
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const HEATMAP_SHADES: [char; 10] = [' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
+const HEATMAP_MAX_COLUMNS: u64 = 80;
 
 pub trait Formatter {
     fn write(&self, result: &SimulationResult) -> String;
+
+    /// Writes the output to `writer`. The default renders `write` first;
+    /// formatters whose output grows with the request count override it to
+    /// stream instead.
+    fn write_to(&self, result: &SimulationResult, writer: &mut dyn io::Write) -> io::Result<()> {
+        writer.write_all(self.write(result).as_bytes())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct OutputOptions {
+    pub ascii_charts: bool,
+    pub heatmap_bucket_ms: Option<u64>,
+    pub color: bool,
+    pub sort: Option<SummarySort>,
+    pub numbers: NumberFormat,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimeUnit {
+    #[default]
+    Ms,
+    S,
+}
+
+/// How the human and summary formatters print counts and durations. Seconds
+/// always carry three decimals, so values stay exact and line up.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NumberFormat {
+    pub unit: TimeUnit,
+    pub thousands: bool,
+}
+
+impl NumberFormat {
+    pub fn count(&self, value: u64) -> String {
+        if self.thousands {
+            group_thousands(value)
+        } else {
+            value.to_string()
+        }
+    }
+
+    /// `1234567ms`, `1,234,567ms`, `1234.567s`, or `1,234.567s`.
+    pub fn duration(&self, ms: u64) -> String {
+        match self.unit {
+            TimeUnit::Ms => format!("{}ms", self.count(ms)),
+            TimeUnit::S => format!("{}.{:03}s", self.count(ms / 1000), ms % 1000),
+        }
+    }
+
+    /// A `key_ms: value` line field, renamed to `key_s` when printing seconds.
+    fn field(&self, key: &str, ms: u64) -> String {
+        match self.unit {
+            TimeUnit::Ms => format!("{}_ms: {}", key, self.count(ms)),
+            TimeUnit::S => format!("{}_s: {}.{:03}", key, self.count(ms / 1000), ms % 1000),
+        }
+    }
+}
+
+fn group_thousands(value: u64) -> String {
+    let digits = value.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (idx, digit) in digits.chars().enumerate() {
+        if idx > 0 && (digits.len() - idx).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
 }
 
-pub struct HumanFormatter;
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortKey {
+    Requests,
+    AvgResponse,
+    P99,
+    Name,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SummarySort {
+    pub key: SortKey,
+    pub descending: bool,
+}
+
+impl SummarySort {
+    /// Orders summaries by `key`; ties keep input order so output stays stable.
+    pub fn apply<'a>(&self, totals: &'a [ServerSummary]) -> Vec<&'a ServerSummary> {
+        let mut sorted = totals.iter().collect::<Vec<_>>();
+        sorted.sort_by(|left, right| {
+            let ordering = match self.key {
+                SortKey::Requests => left.requests.cmp(&right.requests),
+                SortKey::AvgResponse => left.avg_response_ms.cmp(&right.avg_response_ms),
+                SortKey::P99 => left.p99_ms.cmp(&right.p99_ms),
+                SortKey::Name => left.name.cmp(&right.name),
+            };
+            if self.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+        sorted
+    }
+}
+
+fn sorted_totals<'a>(
+    totals: &'a [ServerSummary],
+    sort: Option<&SummarySort>,
+) -> Vec<&'a ServerSummary> {
+    match sort {
+        Some(sort) => sort.apply(totals),
+        None => totals.iter().collect(),
+    }
+}
+
+#[derive(Default)]
+pub struct HumanFormatter {
+    pub options: OutputOptions,
+}
 
 impl Formatter for HumanFormatter {
     fn write(&self, result: &SimulationResult) -> String {
+        let numbers = &self.options.numbers;
         let mut output = String::new();
-        write_metadata(&mut output, result);
+        write_metadata(&mut output, result, numbers);
         output.push_str("Assignments:\n");
-        for assignment in &result.assignments {
-            write_assignment_with_totals(&mut output, assignment, &result.totals);
+        let id_width = result
+            .assignments
+            .iter()
+            .map(|assignment| assignment.request_id.to_string().len())
+            .max()
+            .unwrap_or(0);
+        let name_width = name_width(&result.totals);
+        for (idx, assignment) in result.assignments.iter().enumerate() {
+            write_assignment_with_totals(
+                &mut output,
+                assignment,
+                result.metadata.score_metric.as_deref(),
+                &result.totals,
+                (id_width, name_width),
+                self.options.color,
+                numbers,
+            );
+            if let Some(explain) = result.explanations.get(idx) {
+                write_explain(&mut output, explain);
+            }
+        }
+        write_aligned_summary(
+            &mut output,
+            &sorted_totals(&result.totals, self.options.sort.as_ref()),
+            self.options.color,
+            numbers,
+        );
+        write_global(&mut output, &result.global, numbers);
+        write_littles_law(&mut output, &result.littles_law);
+        write_tail(&mut output, &result.tail, numbers);
+        if let Some(analytic) = result.analytic.as_ref() {
+            write_analytic(&mut output, analytic);
+        }
+        write_slo(&mut output, &result.slo, numbers);
+        write_slowest(
+            &mut output,
+            &result.slowest,
+            result.metadata.score_metric.as_deref(),
+            numbers,
+        );
+        if self.options.ascii_charts {
+            write_ascii_charts(&mut output, result);
+        }
+        if let Some(bucket_ms) = self.options.heatmap_bucket_ms {
+            write_heatmap(&mut output, result, bucket_ms);
         }
-        write_summary(&mut output, &result.totals);
         output
     }
 }
 
-pub struct SummaryFormatter;
+#[derive(Default)]
+pub struct SummaryFormatter {
+    pub options: OutputOptions,
+}
 
 impl Formatter for SummaryFormatter {
     fn write(&self, result: &SimulationResult) -> String {
+        let numbers = &self.options.numbers;
         let mut output = String::new();
-        write_metadata(&mut output, result);
-        write_summary(&mut output, &result.totals);
+        write_metadata(&mut output, result, numbers);
+        write_summary(
+            &mut output,
+            &sorted_totals(&result.totals, self.options.sort.as_ref()),
+            numbers,
+        );
+        write_slo(&mut output, &result.slo, numbers);
+        write_slowest(
+            &mut output,
+            &result.slowest,
+            result.metadata.score_metric.as_deref(),
+            numbers,
+        );
+        if let Some(bucket_ms) = self.options.heatmap_bucket_ms {
+            write_heatmap(&mut output, result, bucket_ms);
+        }
         output
     }
 }
 
-pub struct JsonFormatter;
+#[cfg(feature = "serde")]
+#[derive(Default)]
+pub struct JsonFormatter {
+    pub options: OutputOptions,
+}
 
+#[cfg(feature = "serde")]
 impl Formatter for JsonFormatter {
     fn write(&self, result: &SimulationResult) -> String {
-        let assignments = result
-            .assignments
-            .iter()
-            .map(|assignment| JsonAssignment {
-                request_id: assignment.request_id,
-                server_id: assignment.server_id,
-                server_name: server_name_for(assignment, &result.totals),
-                arrival_time_ms: assignment.arrival_time_ms,
-                started_at: assignment.started_at,
-                completed_at: assignment.completed_at,
-                score: assignment.score,
-            })
-            .collect::<Vec<_>>();
+        let mut output = Vec::new();
+        self.write_to(result, &mut output)
+            .expect("writing to a Vec cannot fail");
+        String::from_utf8(output).expect("serde_json writes UTF-8")
+    }
+
+    /// Serializes straight into `writer`, one assignment at a time, so no
+    /// copy of the document is built in memory first.
+    fn write_to(&self, result: &SimulationResult, writer: &mut dyn io::Write) -> io::Result<()> {
         let json = JsonSimulationResult {
-            assignments,
-            totals: &result.totals,
+            assignments: JsonAssignments(result),
+            totals: sorted_totals(&result.totals, self.options.sort.as_ref()),
             metadata: &result.metadata,
+            global: &result.global,
             phase1_metrics: &result.phase1_metrics,
+            latency_histograms: &result.latency_histograms,
+            littles_law: &result.littles_law,
+            tail: &result.tail,
+            analytic: result.analytic.as_ref(),
+            timeseries: result.timeseries.as_ref(),
+            timelines: result.timelines.as_deref(),
+            slo: &result.slo,
+            slowest: &result.slowest,
         };
-        serde_json::to_string_pretty(&json).unwrap()
+        serde_json::to_writer_pretty(writer, &json).map_err(io::Error::from)
+    }
+}
+
+/// One CSV row per server: its summary plus utilization. Only `options.sort`
+/// applies; the columns stay plain milliseconds and counts.
+#[derive(Default)]
+pub struct CsvFormatter {
+    pub options: OutputOptions,
+}
+
+impl Formatter for CsvFormatter {
+    fn write(&self, result: &SimulationResult) -> String {
+        let mut output = String::from(
+            "server,requests,avg_response_ms,avg_queued_ms,avg_service_ms,p99_ms,utilization_pct\n",
+        );
+        let utilization = utilization_by_server(result);
+        for summary in sorted_totals(&result.totals, self.options.sort.as_ref()) {
+            output.push_str(&format!(
+                "{},{},{},{},{},{},{}\n",
+                summary.name,
+                summary.requests,
+                summary.avg_response_ms,
+                summary.avg_queued_ms,
+                summary.avg_service_ms,
+                summary
+                    .p99_ms
+                    .map(|p99| p99.to_string())
+                    .unwrap_or_default(),
+                utilization
+                    .get(summary.name.as_str())
+                    .map(|pct| pct.to_string())
+                    .unwrap_or_default(),
+            ));
+        }
+        output
+    }
+}
+
+/// Per-server and headline metrics as Markdown tables, for reports and PRs.
+/// Only `options.sort` applies, as with `CsvFormatter`.
+#[derive(Default)]
+pub struct MarkdownFormatter {
+    pub options: OutputOptions,
+}
+
+impl Formatter for MarkdownFormatter {
+    fn write(&self, result: &SimulationResult) -> String {
+        let mut output = format!(
+            "**{}** (tie-break: {}, duration: {}ms)\n\n",
+            result.metadata.algo, result.metadata.tie_break, result.metadata.duration_ms
+        );
+        output.push_str("| Server | Requests | Avg response (ms) | Avg queued (ms) | Avg service (ms) | p99 (ms) | Utilization (%) |\n");
+        output.push_str("| --- | ---: | ---: | ---: | ---: | ---: | ---: |\n");
+        let utilization = utilization_by_server(result);
+        for summary in sorted_totals(&result.totals, self.options.sort.as_ref()) {
+            output.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} | {} |\n",
+                summary.name,
+                summary.requests,
+                summary.avg_response_ms,
+                summary.avg_queued_ms,
+                summary.avg_service_ms,
+                format_optional_ms(summary.p99_ms),
+                utilization
+                    .get(summary.name.as_str())
+                    .map(|pct| pct.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+            ));
+        }
+        let metrics = &result.phase1_metrics;
+        output.push_str("\n| Metric | Value |\n| --- | ---: |\n");
+        output.push_str(&format!(
+            "| p95 (ms) | {} |\n",
+            format_optional_ms(metrics.response_time.p95_ms)
+        ));
+        output.push_str(&format!(
+            "| p99 (ms) | {} |\n",
+            format_optional_ms(metrics.response_time.p99_ms)
+        ));
+        output.push_str(&format!("| Avg wait (ms) | {} |\n", metrics.avg_wait_ms));
+        output.push_str(&format!(
+            "| Throughput (rps) | {} |\n",
+            metrics.throughput_rps
+        ));
+        output.push_str(&format!("| Jain fairness | {} |\n", metrics.jain_fairness));
+        output
     }
 }
 
-fn write_metadata(output: &mut String, result: &SimulationResult) {
+/// Utilization by server name, since sorted rows no longer line up with
+/// `per_server_utilization`.
+fn utilization_by_server(result: &SimulationResult) -> HashMap<&str, f64> {
+    result
+        .phase1_metrics
+        .per_server_utilization
+        .iter()
+        .map(|entry| (entry.name.as_str(), entry.utilization_pct))
+        .collect()
+}
+
+#[cfg(feature = "serde")]
+/// Reads a document written by `JsonFormatter` back into a result, e.g. for
+/// `render`. Inline `explain` entries are collected into `explanations`.
+pub fn parse_json_result(contents: &str) -> Result<SimulationResult> {
+    let invalid = |err: serde_json::Error| Error::json("result JSON", None, err);
+    let value: serde_json::Value = serde_json::from_str(contents).map_err(invalid)?;
+    let explanations = value
+        .get("assignments")
+        .and_then(serde_json::Value::as_array)
+        .map(|assignments| {
+            assignments
+                .iter()
+                .filter_map(|assignment| assignment.get("explain"))
+                .map(|explain| serde_json::from_value(explain.clone()))
+                .collect::<std::result::Result<Vec<SelectionExplain>, _>>()
+        })
+        .transpose()
+        .map_err(invalid)?
+        .unwrap_or_default();
+    let mut result: SimulationResult = serde_json::from_value(value).map_err(invalid)?;
+    result.explanations = explanations;
+    Ok(result)
+}
+
+#[cfg(feature = "serde")]
+/// JSON Schema for the document `JsonFormatter` emits.
+pub fn json_output_schema() -> String {
+    let schema = schemars::schema_for!(JsonSimulationResult<'static>);
+    serde_json::to_string_pretty(&schema).unwrap()
+}
+
+pub fn timelines_to_csv(timelines: &[ServerTimeline]) -> String {
+    let mut output = String::from("time_ms,server,queue_depth,active_connections\n");
+    for timeline in timelines {
+        for point in &timeline.points {
+            output.push_str(&format!(
+                "{},{},{},{}\n",
+                point.time_ms, timeline.name, point.queue_depth, point.active_connections
+            ));
+        }
+    }
+    output
+}
+
+pub fn summary_line(result: &SimulationResult) -> String {
+    let p99 = result
+        .phase1_metrics
+        .response_time
+        .p99_ms
+        .map(|value| value.to_string())
+        .unwrap_or_else(|| "-".to_string());
+    format!(
+        "ok algo={} requests={} makespan_ms={} p99_ms={} throughput_rps={}",
+        result.metadata.algo,
+        result.global.total_requests,
+        result.global.makespan_ms,
+        p99,
+        result.global.throughput_rps
+    )
+}
+
+/// Lists the key metrics and per-server request counts that changed between two
+/// runs, e.g. `p99_ms: 120 -> 98 (-18.33%)`.
+pub fn metric_diff_to_text(previous: &SimulationResult, current: &SimulationResult) -> String {
+    let mut output = String::new();
+    for ((name, before), (_, after)) in key_metrics(previous).into_iter().zip(key_metrics(current))
+    {
+        if before == after {
+            continue;
+        }
+        let change = if before == 0.0 {
+            String::new()
+        } else {
+            format!(" ({:+.2}%)", (after - before) / before * 100.0)
+        };
+        output.push_str(&format!("{}: {} -> {}{}\n", name, before, after, change));
+    }
+    for summary in &current.totals {
+        let before = previous
+            .totals
+            .iter()
+            .find(|candidate| candidate.name == summary.name)
+            .map(|candidate| candidate.requests);
+        if before != Some(summary.requests) {
+            let before = before.map_or_else(|| "-".to_string(), |value| value.to_string());
+            output.push_str(&format!(
+                "requests[{}]: {} -> {}\n",
+                summary.name, before, summary.requests
+            ));
+        }
+    }
+    if output.is_empty() {
+        output.push_str("no metric changes\n");
+    }
+    output
+}
+
+#[cfg(feature = "serde")]
+pub fn baseline_comparison_to_text(comparisons: &[MetricComparison]) -> String {
+    let metric_width = comparisons
+        .iter()
+        .map(|comparison| comparison.metric.len())
+        .max()
+        .unwrap_or(0);
+    let value = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |v| v.to_string());
+    let mut output = String::from("Baseline check:\n");
+    for comparison in comparisons {
+        let change = match (comparison.baseline, comparison.actual) {
+            (Some(expected), Some(actual)) if expected != 0.0 => {
+                format!(" ({:+.2}%)", (actual - expected) / expected.abs() * 100.0)
+            }
+            _ => String::new(),
+        };
+        output.push_str(&format!(
+            "{:<metric_width$} baseline {} actual {}{} {}\n",
+            comparison.metric,
+            value(comparison.baseline),
+            value(comparison.actual),
+            change,
+            if comparison.within_tolerance {
+                "ok"
+            } else {
+                "FAIL"
+            }
+        ));
+    }
+    output
+}
+
+pub fn recommendation_to_text(report: &Recommendation) -> String {
+    let algo_width = report
+        .candidates
+        .iter()
+        .map(|row| row.algo.len())
+        .chain(["algo".len()])
+        .max()
+        .unwrap_or(0);
+    let mut output = format!(
+        "{:<4} {:<algo_width$} {:>8} {:>12} {:>15} {:>9}\n",
+        "rank", "algo", "p99_ms", "avg_wait_ms", "throughput_rps", "fairness"
+    );
+    for row in &report.candidates {
+        output.push_str(&format!(
+            "{:<4} {:<algo_width$} {:>8} {:>12} {:>15.2} {:>9.3}{}\n",
+            row.rank,
+            row.algo,
+            format_optional_ms(row.p99_ms),
+            row.avg_wait_ms,
+            row.throughput_rps,
+            row.jain_fairness,
+            if row.eligible { "" } else { "  (excluded)" }
+        ));
+    }
+    match report.recommended.as_deref() {
+        Some(algo) => output.push_str(&format!("Recommendation: {} — {}\n", algo, report.reason)),
+        None => output.push_str(&format!("Recommendation: none — {}\n", report.reason)),
+    }
+    output
+}
+
+#[cfg(feature = "serde")]
+pub fn recommendation_to_json(report: &Recommendation) -> String {
+    serde_json::to_string_pretty(report).unwrap()
+}
+
+/// One entry in `list-formats`: a `--format` value, the commands that accept
+/// it, and what the output looks like.
+pub struct FormatInfo {
+    pub name: &'static str,
+    pub commands: &'static str,
+    pub shape: &'static str,
+}
+
+pub const FORMATS: &[FormatInfo] = &[
+    FormatInfo {
+        name: "human",
+        commands: "run, recommend, bench, render",
+        shape: "aligned text report: metadata, assignments, summary, global stats",
+    },
+    FormatInfo {
+        name: "summary",
+        commands: "run, render",
+        shape: "metadata plus one line per server",
+    },
+    FormatInfo {
+        name: "json",
+        commands: "run, recommend, bench, render",
+        shape: "pretty-printed JSON; `lb-sim schema` describes the run result",
+    },
+    FormatInfo {
+        name: "csv",
+        commands: "render",
+        shape: "header row plus one row per server with utilization_pct",
+    },
+    FormatInfo {
+        name: "markdown",
+        commands: "render",
+        shape: "per-server and headline metric tables",
+    },
+    FormatInfo {
+        name: "chart",
+        commands: "render --output PATH",
+        shape: "SVG request/latency chart (charts feature)",
+    },
+    FormatInfo {
+        name: "nginx",
+        commands: "export",
+        shape: "upstream block with weights inverse to avg service time",
+    },
+    FormatInfo {
+        name: "haproxy",
+        commands: "export",
+        shape: "backend section with weighted server lines",
+    },
+    FormatInfo {
+        name: "envoy",
+        commands: "export",
+        shape: "static cluster YAML with endpoint weights and zone localities",
+    },
+];
+
+pub fn formats_to_text(formats: &[FormatInfo]) -> String {
+    let name_width = formats
+        .iter()
+        .map(|format| format.name.len())
+        .max()
+        .unwrap_or(0);
+    let commands_width = formats
+        .iter()
+        .map(|format| format.commands.len())
+        .max()
+        .unwrap_or(0);
+    formats
+        .iter()
+        .map(|format| {
+            format!(
+                "{:<name_width$}  {:<commands_width$}  {}\n",
+                format.name, format.commands, format.shape
+            )
+        })
+        .collect()
+}
+
+pub fn bench_report_to_text(rows: &[BenchRow]) -> String {
+    let algo_width = rows
+        .iter()
+        .map(|row| row.algo.len())
+        .chain(["algo".len()])
+        .max()
+        .unwrap_or(0);
+    let mut output = format!(
+        "{:<algo_width$} {:>8} {:>10} {:>10} {:>10} {:>14}\n",
+        "algo", "servers", "requests", "events", "best_ms", "events/sec"
+    );
+    for row in rows {
+        output.push_str(&format!(
+            "{:<algo_width$} {:>8} {:>10} {:>10} {:>10.2} {:>14.0}\n",
+            row.algo, row.servers, row.requests, row.events, row.best_ms, row.events_per_sec
+        ));
+    }
+    output
+}
+
+#[cfg(feature = "serde")]
+pub fn bench_report_to_json(rows: &[BenchRow]) -> String {
+    serde_json::to_string_pretty(rows).unwrap()
+}
+
+pub fn replication_report_to_text(report: &ReplicationReport) -> String {
+    let mut output = String::from("Replications:\n");
+    output.push_str(&format!("algo: {}\n", report.algo));
+    output.push_str(&format!("runs: {}\n", report.replications));
+    let metric_width = report
+        .metrics
+        .iter()
+        .map(|summary| summary.metric.len())
+        .max()
+        .unwrap_or(0);
+    output.push_str(&format!(
+        "{:<metric_width$} {:>12} {:>12}   95% CI\n",
+        "metric", "mean", "stddev"
+    ));
+    for summary in &report.metrics {
+        output.push_str(&format!(
+            "{:<metric_width$} {:>12.2} {:>12.2}   [{:.2}, {:.2}]\n",
+            summary.metric, summary.mean, summary.stddev, summary.ci95_low, summary.ci95_high
+        ));
+    }
+    output
+}
+
+#[cfg(feature = "serde")]
+pub fn replication_report_to_json(report: &ReplicationReport) -> String {
+    serde_json::to_string_pretty(report).unwrap()
+}
+
+pub fn seed_sweep_report_to_text(report: &SeedSweepReport) -> String {
+    let mut output = String::from("Seed sweep:\n");
+    output.push_str(&format!("algo: {}\n", report.algo));
+    output.push_str(&format!(
+        "seeds: {}..={} ({} runs)\n",
+        report.first_seed, report.last_seed, report.runs
+    ));
+    let metric_width = report
+        .metrics
+        .iter()
+        .map(|distribution| distribution.metric.len())
+        .max()
+        .unwrap_or(0);
+    output.push_str(&format!(
+        "{:<metric_width$} {:>10} {:>10} {:>10} {:>10} {:>10}\n",
+        "metric", "min", "p50", "mean", "stddev", "max"
+    ));
+    for distribution in &report.metrics {
+        output.push_str(&format!(
+            "{:<metric_width$} {:>10.2} {:>10.2} {:>10.2} {:>10.2} {:>10.2}\n",
+            distribution.metric,
+            distribution.min,
+            distribution.p50,
+            distribution.mean,
+            distribution.stddev,
+            distribution.max
+        ));
+    }
+    output
+}
+
+#[cfg(feature = "serde")]
+pub fn seed_sweep_report_to_json(report: &SeedSweepReport) -> String {
+    serde_json::to_string_pretty(report).unwrap()
+}
+
+#[cfg(feature = "serde")]
+pub fn scenario_report_to_text(report: &ScenarioReport) -> String {
+    let mut output = String::from("Scenario:\n");
+    let name_width = report
+        .runs
+        .iter()
+        .map(|row| row.name.len())
+        .chain(std::iter::once("run".len()))
+        .max()
+        .unwrap_or(0);
+    let algo_width = report
+        .runs
+        .iter()
+        .map(|row| row.algo.len())
+        .chain(std::iter::once("algo".len()))
+        .max()
+        .unwrap_or(0);
+    output.push_str(&format!(
+        "{:<name_width$}  {:<algo_width$} {:>8} {:>8} {:>8} {:>8} {:>10} {:>8}\n",
+        "run", "algo", "requests", "p95_ms", "p99_ms", "wait_ms", "rps", "fairness"
+    ));
+    for row in &report.runs {
+        output.push_str(&format!(
+            "{:<name_width$}  {:<algo_width$} {:>8} {:>8} {:>8} {:>8} {:>10.2} {:>8.3}\n",
+            row.name,
+            row.algo,
+            row.requests,
+            format_optional_ms(row.p95_ms),
+            format_optional_ms(row.p99_ms),
+            row.avg_wait_ms,
+            row.throughput_rps,
+            row.jain_fairness
+        ));
+    }
+    output
+}
+
+#[cfg(feature = "serde")]
+pub fn scenario_report_to_json(report: &ScenarioReport) -> String {
+    serde_json::to_string_pretty(report).unwrap()
+}
+
+fn format_optional_ms(value: Option<u64>) -> String {
+    value.map_or_else(|| "-".to_string(), |value| value.to_string())
+}
+
+fn write_metadata(output: &mut String, result: &SimulationResult, numbers: &NumberFormat) {
     output.push_str("Metadata:\n");
     output.push_str(&format!("algo: {}\n", result.metadata.algo));
     output.push_str(&format!("tie_break: {}\n", result.metadata.tie_break));
-    output.push_str(&format!("duration_ms: {}\n", result.metadata.duration_ms));
+    // The default generator is left to JSON, as the default event order is.
+    if let Some(rng) = result.metadata.rng.as_deref().filter(|rng| *rng != "std") {
+        output.push_str(&format!("rng: {}\n", rng));
+    }
+    if let Some(event_order) = &result.metadata.event_order {
+        output.push_str(&format!("event_order: {}\n", event_order));
+    }
+    if result.metadata.assignment_sample.is_some() {
+        output.push_str(&format!(
+            "assignments: sampled {} of {}\n",
+            result.assignments.len(),
+            result.global.total_requests
+        ));
+    }
+    output.push_str(&format!(
+        "{}\n",
+        numbers.field("duration", result.metadata.duration_ms)
+    ));
 }
 
-fn write_summary(output: &mut String, totals: &[ServerSummary]) {
+fn write_summary(output: &mut String, totals: &[&ServerSummary], numbers: &NumberFormat) {
     output.push_str("Summary:\n");
     for summary in totals {
         output.push_str(&format!(
-            "{}: {} requests (avg response: {}ms)\n",
-            summary.name, summary.requests, summary.avg_response_ms
+            "{}: {} requests (avg response: {}, queued: {}, service: {})\n",
+            summary.name,
+            numbers.count(summary.requests),
+            numbers.duration(summary.avg_response_ms),
+            numbers.duration(summary.avg_queued_ms),
+            numbers.duration(summary.avg_service_ms)
+        ));
+    }
+}
+
+fn write_aligned_summary(
+    output: &mut String,
+    totals: &[&ServerSummary],
+    color: bool,
+    numbers: &NumberFormat,
+) {
+    output.push_str("Summary:\n");
+    let label_width = name_width(totals.iter().copied()) + 1;
+    let requests_width = totals
+        .iter()
+        .map(|summary| numbers.count(summary.requests).len())
+        .max()
+        .unwrap_or(0);
+    let max_requests = totals.iter().map(|summary| summary.requests).max();
+    for summary in totals {
+        let line = format!(
+            "{:<label_width$} {:>requests_width$} requests (avg response: {}, queued: {}, service: {})",
+            format!("{}:", summary.name),
+            numbers.count(summary.requests),
+            numbers.duration(summary.avg_response_ms),
+            numbers.duration(summary.avg_queued_ms),
+            numbers.duration(summary.avg_service_ms),
+        );
+        let line = if totals.len() > 1 && Some(summary.requests) == max_requests {
+            paint(&line, ANSI_HIGHLIGHT, color)
+        } else {
+            line
+        };
+        output.push_str(&line);
+        output.push('\n');
+    }
+}
+
+fn write_global(output: &mut String, global: &GlobalStats, numbers: &NumberFormat) {
+    output.push_str("Global:\n");
+    output.push_str(&format!(
+        "total_requests: {}\n",
+        numbers.count(global.total_requests)
+    ));
+    output.push_str(&format!(
+        "{}\n",
+        numbers.field("makespan", global.makespan_ms)
+    ));
+    output.push_str(&format!("throughput_rps: {}\n", global.throughput_rps));
+    output.push_str(&format!("avg_concurrency: {}\n", global.avg_concurrency));
+}
+
+fn write_littles_law(output: &mut String, law: &LittlesLaw) {
+    output.push_str("Little's law:\n");
+    output.push_str(&format!("L (avg in system): {}\n", law.avg_in_system));
+    output.push_str(&format!(
+        "lambda (arrival rate): {} rps\n",
+        law.arrival_rate_rps
+    ));
+    output.push_str(&format!("W (avg latency): {}ms\n", law.avg_latency_ms));
+    output.push_str(&format!(
+        "lambda * W: {} (residual: {})\n",
+        law.predicted_in_system, law.residual
+    ));
+}
+
+fn write_analytic(output: &mut String, analytic: &AnalyticComparison) {
+    let wait = |value: Option<f64>| {
+        value.map_or_else(|| "unstable".to_string(), |wait| format!("{}ms", wait))
+    };
+    output.push_str(&format!(
+        "Analytic ({}, c={}, lambda={} rps, S={}ms):\n",
+        analytic.model, analytic.servers, analytic.arrival_rate_rps, analytic.service_time_ms
+    ));
+    output.push_str(&format!(
+        "utilization: theory {}, simulated {}\n",
+        analytic.utilization, analytic.simulated_utilization
+    ));
+    output.push_str(&format!(
+        "avg wait: M/M/c {}, M/D/c approx {}, simulated {}ms\n",
+        wait(analytic.mmc_wait_ms),
+        wait(analytic.approx_wait_ms),
+        analytic.simulated_wait_ms
+    ));
+}
+
+fn write_tail(output: &mut String, tail: &TailBreakdown, numbers: &NumberFormat) {
+    let Some(threshold_ms) = tail.threshold_ms else {
+        return;
+    };
+    output.push_str(&format!(
+        "Tail (>= p99 {}):\n",
+        numbers.duration(threshold_ms)
+    ));
+    for server in &tail.per_server {
+        let p99 = server
+            .p99_ms
+            .map(|value| numbers.duration(value))
+            .unwrap_or_else(|| "-".to_string());
+        output.push_str(&format!(
+            "{}: {} tail requests ({}% of its requests), p99: {}\n",
+            server.name, server.tail_requests, server.tail_pct, p99
         ));
     }
 }
 
+fn write_slo(output: &mut String, reports: &[SloReport], numbers: &NumberFormat) {
+    if reports.is_empty() {
+        return;
+    }
+    output.push_str("SLO:\n");
+    for report in reports {
+        output.push_str(&format!(
+            "p{} <= {}: {}\n",
+            report.percentile,
+            numbers.duration(report.threshold_ms),
+            slo_status(&report.overall, numbers)
+        ));
+        for server in &report.per_server {
+            output.push_str(&format!(
+                "  {}: {}\n",
+                server.name,
+                slo_status(&server.compliance, numbers)
+            ));
+        }
+    }
+}
+
+fn write_slowest(
+    output: &mut String,
+    slowest: &[SlowRequest],
+    score_metric: Option<&str>,
+    numbers: &NumberFormat,
+) {
+    if slowest.is_empty() {
+        return;
+    }
+    output.push_str("Slowest requests:\n");
+    for request in slowest {
+        output.push_str(&format!(
+            "Request {} -> {}: {} (arrival: {}, start: {}, queue wait: {}",
+            request.request_id,
+            request.server,
+            numbers.duration(request.response_ms),
+            numbers.duration(request.arrival_time_ms),
+            numbers.duration(request.started_at),
+            numbers.duration(request.queue_wait_ms)
+        ));
+        let label = request
+            .score
+            .and_then(|score| score_label(score, score_metric, numbers));
+        if let Some(label) = label {
+            output.push_str(&format!(", {}", label));
+        }
+        output.push_str(")\n");
+    }
+}
+
+fn slo_status(compliance: &SloCompliance, numbers: &NumberFormat) -> String {
+    let verdict = if compliance.met { "met" } else { "violated" };
+    match compliance.first_violation_ms {
+        Some(time_ms) => format!(
+            "{}% {} (first violation at {})",
+            compliance.compliance_pct,
+            verdict,
+            numbers.duration(time_ms)
+        ),
+        None => format!("{}% {}", compliance.compliance_pct, verdict),
+    }
+}
+
+fn write_ascii_charts(output: &mut String, result: &SimulationResult) {
+    let name_width = name_width(&result.totals);
+
+    output.push_str("Charts:\n");
+    output.push_str("requests\n");
+    let max_requests = result
+        .totals
+        .iter()
+        .map(|summary| summary.requests)
+        .max()
+        .unwrap_or(0);
+    for summary in &result.totals {
+        write_bar(
+            output,
+            &summary.name,
+            name_width,
+            summary.requests,
+            max_requests,
+        );
+    }
+
+    output.push_str("avg response (ms)\n");
+    let max_response = result
+        .totals
+        .iter()
+        .map(|summary| summary.avg_response_ms)
+        .max()
+        .unwrap_or(0);
+    for summary in &result.totals {
+        write_bar(
+            output,
+            &summary.name,
+            name_width,
+            summary.avg_response_ms,
+            max_response,
+        );
+    }
+
+    if result.assignments.is_empty() {
+        return;
+    }
+    output.push_str("response time trend\n");
+    for (idx, summary) in result.totals.iter().enumerate() {
+        let samples = result
+            .assignments
+            .iter()
+            .filter(|assignment| assignment.server_id == idx)
+            .map(|assignment| assignment.completed_at - assignment.arrival_time_ms)
+            .collect::<Vec<_>>();
+        output.push_str(&format!(
+            "  {:<width$} |{}\n",
+            summary.name,
+            sparkline(&samples, CHART_WIDTH),
+            width = name_width
+        ));
+    }
+}
+
+/// Bins assignments by arrival time into one row per server. Buckets are
+/// widened by whole multiples when the run would exceed the column budget.
+fn write_heatmap(output: &mut String, result: &SimulationResult, bucket_ms: u64) {
+    let Some(last_arrival_ms) = result
+        .assignments
+        .iter()
+        .map(|assignment| assignment.arrival_time_ms)
+        .max()
+    else {
+        return;
+    };
+    let columns = last_arrival_ms / bucket_ms + 1;
+    let bucket_ms = bucket_ms * columns.div_ceil(HEATMAP_MAX_COLUMNS);
+    let columns = (last_arrival_ms / bucket_ms + 1) as usize;
+    let mut cells = vec![vec![0u64; columns]; result.totals.len()];
+    for assignment in &result.assignments {
+        if let Some(row) = cells.get_mut(assignment.server_id) {
+            row[(assignment.arrival_time_ms / bucket_ms) as usize] += 1;
+        }
+    }
+    let max = cells.iter().flatten().copied().max().unwrap_or(0);
+    let name_width = name_width(&result.totals);
+
+    output.push_str(&format!(
+        "Heatmap (assignments per {}ms, 0..{}ms, max {}):\n",
+        bucket_ms,
+        columns as u64 * bucket_ms,
+        max
+    ));
+    for (summary, row) in result.totals.iter().zip(&cells) {
+        let shades = row
+            .iter()
+            .map(|count| {
+                if *count == 0 || max == 0 {
+                    return HEATMAP_SHADES[0];
+                }
+                let top = (HEATMAP_SHADES.len() - 1) as u64;
+                HEATMAP_SHADES[(1 + (count * (top - 1)) / max) as usize]
+            })
+            .collect::<String>();
+        output.push_str(&format!(
+            "  {:<width$} |{}|\n",
+            summary.name,
+            shades,
+            width = name_width
+        ));
+    }
+}
+
+fn write_explain(output: &mut String, explain: &SelectionExplain) {
+    let candidates = explain
+        .candidates
+        .iter()
+        .map(|candidate| match candidate.score {
+            Some(score) => format!(
+                "{}(conn={}, score={})",
+                candidate.server, candidate.active_connections, score
+            ),
+            None => format!(
+                "{}(conn={})",
+                candidate.server, candidate.active_connections
+            ),
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    output.push_str(&format!("    candidates: {}\n", candidates));
+    if explain.tied.len() <= 1 {
+        output.push_str(&format!("    tie-break: {}\n", explain.tie_break));
+    } else {
+        output.push_str(&format!(
+            "    tie-break: {} among [{}]\n",
+            explain.tie_break,
+            explain.tied.join(", ")
+        ));
+    }
+}
+
+fn write_bar(output: &mut String, name: &str, name_width: usize, value: u64, max: u64) {
+    let filled = if max == 0 {
+        0
+    } else {
+        ((value as f64 / max as f64) * CHART_WIDTH as f64).round() as usize
+    };
+    output.push_str(&format!(
+        "  {:<width$} |{} {}\n",
+        name,
+        "#".repeat(filled),
+        value,
+        width = name_width
+    ));
+}
+
+/// Averages `samples` into at most `width` columns and maps each column onto
+/// eight block heights scaled between the smallest and largest column.
+fn sparkline(samples: &[u64], width: usize) -> String {
+    if samples.is_empty() {
+        return String::new();
+    }
+    let columns = samples.len().min(width);
+    let averages = (0..columns)
+        .map(|column| {
+            let start = column * samples.len() / columns;
+            let end = ((column + 1) * samples.len() / columns).max(start + 1);
+            let chunk = &samples[start..end];
+            chunk.iter().sum::<u64>() as f64 / chunk.len() as f64
+        })
+        .collect::<Vec<_>>();
+    let min = averages.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = averages.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    averages
+        .iter()
+        .map(|value| {
+            if max <= min {
+                return SPARK_LEVELS[0];
+            }
+            let level = ((value - min) / (max - min) * (SPARK_LEVELS.len() - 1) as f64).round();
+            SPARK_LEVELS[level as usize]
+        })
+        .collect()
+}
+
+/// `score: 12ms` for time-based scores, or the metric and its count.
+/// Rotation slots only say where the cursor was, so human output leaves them
+/// to JSON, CSV, and traces. A metric this build doesn't know (a result from
+/// a newer version) is shown by name.
+fn score_label(score: u64, score_metric: Option<&str>, numbers: &NumberFormat) -> Option<String> {
+    let metric = score_metric.map(|name| (name, ScoreMetric::from_name(name)));
+    match metric {
+        None | Some((_, Some(ScoreMetric::ExpectedCompletionMs))) => {
+            Some(format!("score: {}", numbers.duration(score)))
+        }
+        Some((_, Some(ScoreMetric::ActiveConnections))) => {
+            Some(format!("connections: {}", numbers.count(score)))
+        }
+        Some((_, Some(ScoreMetric::RotationSlot))) => None,
+        Some((name, None)) => Some(format!("{}: {}", name, numbers.count(score))),
+    }
+}
+
 fn write_assignment_with_totals(
     output: &mut String,
     assignment: &Assignment,
+    score_metric: Option<&str>,
     totals: &[ServerSummary],
+    (id_width, name_width): (usize, usize),
+    color: bool,
+    numbers: &NumberFormat,
 ) {
     let server_name = server_name_for(assignment, totals);
-    if let Some(score) = assignment.score {
+    let label = assignment
+        .score
+        .and_then(|score| score_label(score, score_metric, numbers));
+    if let Some(label) = label {
         output.push_str(&format!(
-            "Request {} -> {} (score: {}ms)\n",
-            assignment.request_id, server_name, score
+            "Request {:>id_width$} -> {:<name_width$} {}\n",
+            assignment.request_id,
+            server_name,
+            paint(&format!("({})", label), ANSI_DIM, color),
         ));
     } else {
         output.push_str(&format!(
-            "Request {} -> {}\n",
+            "Request {:>id_width$} -> {}\n",
             assignment.request_id, server_name
         ));
     }
 }
 
+fn name_width<'a>(totals: impl IntoIterator<Item = &'a ServerSummary>) -> usize {
+    totals
+        .into_iter()
+        .map(|summary| summary.name.chars().count())
+        .max()
+        .unwrap_or(0)
+}
+
+fn paint(text: &str, code: &str, enabled: bool) -> String {
+    if enabled {
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    } else {
+        text.to_string()
+    }
+}
+
 fn server_name_for<'a>(assignment: &Assignment, totals: &'a [ServerSummary]) -> &'a str {
     totals
         .get(assignment.server_id)
@@ -101,29 +1200,97 @@ fn server_name_for<'a>(assignment: &Assignment, totals: &'a [ServerSummary]) ->
         .unwrap_or("unknown")
 }
 
-#[derive(Serialize)]
+#[cfg(feature = "serde")]
+#[derive(Serialize, JsonSchema)]
+#[schemars(rename = "Assignment")]
 struct JsonAssignment<'a> {
-    request_id: usize,
+    request_id: u64,
     server_id: usize,
     server_name: &'a str,
     arrival_time_ms: u64,
     started_at: u64,
     completed_at: u64,
+    queued_ms: u64,
+    service_ms: u64,
     score: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    explain: Option<&'a SelectionExplain>,
 }
 
-#[derive(Serialize)]
+/// A result's assignments, serialized lazily as `JsonAssignment`s.
+#[cfg(feature = "serde")]
+struct JsonAssignments<'a>(&'a SimulationResult);
+
+#[cfg(feature = "serde")]
+impl Serialize for JsonAssignments<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let result = self.0;
+        serializer.collect_seq(
+            result
+                .assignments
+                .iter()
+                .enumerate()
+                .map(|(idx, assignment)| JsonAssignment {
+                    request_id: assignment.request_id,
+                    server_id: assignment.server_id,
+                    server_name: server_name_for(assignment, &result.totals),
+                    arrival_time_ms: assignment.arrival_time_ms,
+                    started_at: assignment.started_at,
+                    completed_at: assignment.completed_at,
+                    queued_ms: assignment.queued_ms,
+                    service_ms: assignment.service_ms,
+                    score: assignment.score,
+                    explain: result.explanations.get(idx),
+                }),
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize, JsonSchema)]
+#[schemars(rename = "SimulationResult")]
 struct JsonSimulationResult<'a> {
-    assignments: Vec<JsonAssignment<'a>>,
-    totals: &'a [ServerSummary],
+    #[schemars(with = "Vec<JsonAssignment<'a>>")]
+    assignments: JsonAssignments<'a>,
+    totals: Vec<&'a ServerSummary>,
     metadata: &'a RunMetadata,
+    global: &'a GlobalStats,
     phase1_metrics: &'a Phase1Metrics,
+    latency_histograms: &'a LatencyHistograms,
+    littles_law: &'a LittlesLaw,
+    tail: &'a TailBreakdown,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    analytic: Option<&'a AnalyticComparison>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timeseries: Option<&'a Timeseries>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timelines: Option<&'a [ServerTimeline]>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    slo: &'a [SloReport],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    slowest: &'a [SlowRequest],
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::state::{Assignment, RunMetadata, ServerSummary, SimulationResult};
+    use crate::state::{
+        Assignment, HistogramBucket, LatencyHistogram, LatencyHistograms, Phase1Metrics,
+        RunMetadata, ServerLatencyHistogram, ServerSummary, SimulationResult,
+    };
+
+    fn sample_histogram() -> LatencyHistogram {
+        LatencyHistogram {
+            count: 1,
+            min_ms: Some(10),
+            max_ms: Some(10),
+            buckets: vec![HistogramBucket {
+                lower_ms: 10,
+                upper_ms: 11,
+                count: 1,
+            }],
+        }
+    }
 
     fn sample_result() -> SimulationResult {
         SimulationResult {
@@ -134,16 +1301,33 @@ mod tests {
                 score: Some(10),
                 started_at: 0,
                 completed_at: 10,
+                queued_ms: 0,
+                service_ms: 10,
             }],
             totals: vec![ServerSummary {
                 name: "api".to_string(),
+                zone: None,
                 requests: 1,
                 avg_response_ms: 10,
+                avg_queued_ms: 0,
+                avg_service_ms: 10,
+                p99_ms: Some(10),
             }],
             metadata: RunMetadata {
                 algo: "round-robin".to_string(),
                 tie_break: "stable".to_string(),
                 duration_ms: 10,
+                seed: None,
+                rng: None,
+                event_order: None,
+                score_metric: None,
+                assignment_sample: None,
+            },
+            global: GlobalStats {
+                total_requests: 1,
+                makespan_ms: 10,
+                throughput_rps: 100.0,
+                avg_concurrency: 1.0,
             },
             phase1_metrics: Phase1Metrics {
                 response_time: crate::state::ResponseTimePercentiles {
@@ -158,12 +1342,41 @@ mod tests {
                 throughput_rps: 100.0,
                 avg_wait_ms: 0,
             },
+            latency_histograms: LatencyHistograms {
+                overall: sample_histogram(),
+                per_server: vec![ServerLatencyHistogram {
+                    name: "api".to_string(),
+                    histogram: sample_histogram(),
+                }],
+            },
+            littles_law: LittlesLaw {
+                avg_in_system: 1.0,
+                arrival_rate_rps: 100.0,
+                avg_latency_ms: 10.0,
+                predicted_in_system: 1.0,
+                residual: 0.0,
+            },
+            analytic: None,
+            tail: TailBreakdown {
+                threshold_ms: Some(10),
+                per_server: vec![crate::state::ServerTail {
+                    name: "api".to_string(),
+                    tail_requests: 1,
+                    tail_pct: 100.0,
+                    p99_ms: Some(10),
+                }],
+            },
+            timeseries: None,
+            timelines: None,
+            slo: Vec::new(),
+            slowest: Vec::new(),
+            explanations: Vec::new(),
         }
     }
 
     #[test]
     fn human_formatter_includes_assignments_and_summary() {
-        let formatter = HumanFormatter;
+        let formatter = HumanFormatter::default();
         let output = formatter.write(&sample_result());
         let expected = concat!(
             "Metadata:\n",
@@ -173,14 +1386,242 @@ mod tests {
             "Assignments:\n",
             "Request 1 -> api (score: 10ms)\n",
             "Summary:\n",
-            "api: 1 requests (avg response: 10ms)\n",
+            "api: 1 requests (avg response: 10ms, queued: 0ms, service: 10ms)\n",
+            "Global:\n",
+            "total_requests: 1\n",
+            "makespan_ms: 10\n",
+            "throughput_rps: 100\n",
+            "avg_concurrency: 1\n",
+            "Little's law:\n",
+            "L (avg in system): 1\n",
+            "lambda (arrival rate): 100 rps\n",
+            "W (avg latency): 10ms\n",
+            "lambda * W: 1 (residual: 0)\n",
+            "Tail (>= p99 10ms):\n",
+            "api: 1 tail requests (100% of its requests), p99: 10ms\n",
         );
         assert_eq!(output, expected);
     }
 
+    #[test]
+    fn number_format_groups_digits_and_converts_units() {
+        let plain = NumberFormat::default();
+        assert_eq!(plain.duration(1234567), "1234567ms");
+        assert_eq!(plain.field("duration", 21), "duration_ms: 21");
+
+        let grouped = NumberFormat {
+            unit: TimeUnit::Ms,
+            thousands: true,
+        };
+        assert_eq!(grouped.count(999), "999");
+        assert_eq!(grouped.duration(1234567), "1,234,567ms");
+
+        let seconds = NumberFormat {
+            unit: TimeUnit::S,
+            thousands: true,
+        };
+        assert_eq!(seconds.duration(1234567), "1,234.567s");
+        assert_eq!(seconds.duration(5), "0.005s");
+        assert_eq!(seconds.field("makespan", 21), "makespan_s: 0.021");
+    }
+
+    #[test]
+    fn human_formatter_appends_ascii_charts_when_enabled() {
+        let formatter = HumanFormatter {
+            options: OutputOptions {
+                ascii_charts: true,
+                ..OutputOptions::default()
+            },
+        };
+        let output = formatter.write(&sample_result());
+        let expected_tail = concat!(
+            "Charts:\n",
+            "requests\n",
+            "  api |######################################## 1\n",
+            "avg response (ms)\n",
+            "  api |######################################## 10\n",
+            "response time trend\n",
+            "  api |▁\n",
+        );
+        assert!(output.ends_with(expected_tail), "got:\n{}", output);
+    }
+
+    #[test]
+    fn timelines_csv_lists_points_per_server() {
+        let timelines = vec![ServerTimeline {
+            name: "api".to_string(),
+            points: vec![
+                crate::state::TimelinePoint {
+                    time_ms: 0,
+                    queue_depth: 1,
+                    active_connections: 2,
+                },
+                crate::state::TimelinePoint {
+                    time_ms: 10,
+                    queue_depth: 0,
+                    active_connections: 0,
+                },
+            ],
+        }];
+        assert_eq!(
+            timelines_to_csv(&timelines),
+            "time_ms,server,queue_depth,active_connections\n0,api,1,2\n10,api,0,0\n"
+        );
+    }
+
+    #[test]
+    fn summary_formatter_appends_heatmap_when_requested() {
+        let mut result = sample_result();
+        for (request_id, arrival_time_ms) in [(2, 4), (3, 5), (4, 25)] {
+            result.assignments.push(Assignment {
+                request_id,
+                server_id: 0,
+                arrival_time_ms,
+                started_at: arrival_time_ms,
+                completed_at: arrival_time_ms + 10,
+                queued_ms: 0,
+                service_ms: 10,
+                score: None,
+            });
+        }
+        let formatter = SummaryFormatter {
+            options: OutputOptions {
+                heatmap_bucket_ms: Some(10),
+                ..OutputOptions::default()
+            },
+        };
+        let output = formatter.write(&result);
+        assert!(output.ends_with(concat!(
+            "Heatmap (assignments per 10ms, 0..30ms, max 3):\n",
+            "  api |@ -|\n",
+        )));
+    }
+
+    #[test]
+    fn summary_formatter_sorts_totals_when_requested() {
+        let mut result = sample_result();
+        result.totals.push(ServerSummary {
+            name: "cache".to_string(),
+            zone: None,
+            requests: 4,
+            avg_response_ms: 2,
+            avg_queued_ms: 0,
+            avg_service_ms: 2,
+            p99_ms: Some(5),
+        });
+        let formatter = SummaryFormatter {
+            options: OutputOptions {
+                sort: Some(SummarySort {
+                    key: SortKey::Requests,
+                    descending: true,
+                }),
+                ..OutputOptions::default()
+            },
+        };
+        let output = formatter.write(&result);
+        assert!(output.ends_with(concat!(
+            "Summary:\n",
+            "cache: 4 requests (avg response: 2ms, queued: 0ms, service: 2ms)\n",
+            "api: 1 requests (avg response: 10ms, queued: 0ms, service: 10ms)\n",
+        )));
+
+        let by_p99 = SummarySort {
+            key: SortKey::P99,
+            descending: false,
+        };
+        let names = by_p99
+            .apply(&result.totals)
+            .iter()
+            .map(|summary| summary.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["cache", "api"]);
+    }
+
+    #[test]
+    fn human_formatter_aligns_columns_and_colors_when_enabled() {
+        let mut result = sample_result();
+        result.totals.push(ServerSummary {
+            name: "database".to_string(),
+            zone: None,
+            requests: 12,
+            avg_response_ms: 20,
+            avg_queued_ms: 0,
+            avg_service_ms: 20,
+            p99_ms: Some(20),
+        });
+        result.assignments[0].request_id = 7;
+        result.assignments.push(Assignment {
+            request_id: 12,
+            server_id: 1,
+            arrival_time_ms: 1,
+            started_at: 1,
+            completed_at: 21,
+            queued_ms: 0,
+            service_ms: 20,
+            score: Some(20),
+        });
+
+        let plain = HumanFormatter::default().write(&result);
+        assert!(plain.contains(concat!(
+            "Request  7 -> api      (score: 10ms)\n",
+            "Request 12 -> database (score: 20ms)\n",
+        )));
+        assert!(plain.contains(concat!(
+            "api:       1 requests (avg response: 10ms, queued: 0ms, service: 10ms)\n",
+            "database: 12 requests (avg response: 20ms, queued: 0ms, service: 20ms)\n",
+        )));
+
+        let colored = HumanFormatter {
+            options: OutputOptions {
+                color: true,
+                ..OutputOptions::default()
+            },
+        }
+        .write(&result);
+        assert!(colored.contains("api      \x1b[2m(score: 10ms)\x1b[0m\n"));
+        assert!(colored.contains("\x1b[1;33mdatabase: 12 requests (avg response: 20ms, queued: 0ms, service: 20ms)\x1b[0m\n"));
+        assert!(colored
+            .contains("api:       1 requests (avg response: 10ms, queued: 0ms, service: 10ms)\n"));
+    }
+
+    #[test]
+    fn sparkline_scales_between_extremes() {
+        assert_eq!(sparkline(&[1, 2, 3, 4, 5, 6, 7, 8], 8), "▁▂▃▄▅▆▇█");
+        assert_eq!(sparkline(&[5, 5, 10, 10], 2), "▁█");
+        assert_eq!(sparkline(&[], 8), "");
+    }
+
+    #[test]
+    fn summary_formatter_reports_slo_compliance() {
+        let mut result = sample_result();
+        result.slo = vec![SloReport {
+            percentile: 99.0,
+            threshold_ms: 5,
+            overall: SloCompliance {
+                compliance_pct: 0.0,
+                met: false,
+                first_violation_ms: Some(10),
+            },
+            per_server: vec![crate::state::ServerSloCompliance {
+                name: "api".to_string(),
+                compliance: SloCompliance {
+                    compliance_pct: 0.0,
+                    met: false,
+                    first_violation_ms: Some(10),
+                },
+            }],
+        }];
+        let output = SummaryFormatter::default().write(&result);
+        assert!(output.ends_with(concat!(
+            "SLO:\n",
+            "p99 <= 5ms: 0% violated (first violation at 10ms)\n",
+            "  api: 0% violated (first violation at 10ms)\n",
+        )));
+    }
+
     #[test]
     fn summary_formatter_includes_metadata_and_summary_only() {
-        let formatter = SummaryFormatter;
+        let formatter = SummaryFormatter::default();
         let output = formatter.write(&sample_result());
         let expected = concat!(
             "Metadata:\n",
@@ -188,14 +1629,115 @@ mod tests {
             "tie_break: stable\n",
             "duration_ms: 10\n",
             "Summary:\n",
-            "api: 1 requests (avg response: 10ms)\n",
+            "api: 1 requests (avg response: 10ms, queued: 0ms, service: 10ms)\n",
         );
         assert_eq!(output, expected);
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_schema_describes_every_emitted_field() {
+        let schema: serde_json::Value =
+            serde_json::from_str(&json_output_schema()).expect("schema should be JSON");
+        assert_eq!(schema["title"], "SimulationResult");
+        let output: serde_json::Value =
+            serde_json::from_str(&JsonFormatter::default().write(&sample_result()))
+                .expect("output should be JSON");
+        for key in output
+            .as_object()
+            .expect("output should be an object")
+            .keys()
+        {
+            assert!(
+                schema["properties"].get(key).is_some(),
+                "schema is missing '{}'",
+                key
+            );
+        }
+        let required = schema["required"].as_array().expect("required list");
+        assert!(required.contains(&serde_json::json!("assignments")));
+        assert!(!required.contains(&serde_json::json!("timeseries")));
+        let assignment = &schema["$defs"]["Assignment"]["properties"];
+        assert!(assignment.get("queued_ms").is_some());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_formatter_streams_the_rendered_document() {
+        struct Closed;
+        impl io::Write for Closed {
+            fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::BrokenPipe, "closed"))
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let formatter = JsonFormatter::default();
+        let mut streamed = Vec::new();
+        formatter
+            .write_to(&sample_result(), &mut streamed)
+            .expect("writing to a Vec succeeds");
+        assert_eq!(
+            String::from_utf8(streamed).unwrap(),
+            formatter.write(&sample_result())
+        );
+        assert_eq!(
+            formatter
+                .write_to(&sample_result(), &mut Closed)
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::BrokenPipe
+        );
+    }
+
+    #[test]
+    fn metric_diff_lists_only_changed_metrics() {
+        let previous = sample_result();
+        assert_eq!(
+            metric_diff_to_text(&previous, &previous),
+            "no metric changes\n"
+        );
+
+        let mut current = sample_result();
+        current.phase1_metrics.response_time.p99_ms = Some(15);
+        current.totals[0].requests = 2;
+        assert_eq!(
+            metric_diff_to_text(&previous, &current),
+            "p99_ms: 10 -> 15 (+50.00%)\nrequests[api]: 1 -> 2\n"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn parse_json_result_round_trips_json_output() {
+        let json = JsonFormatter::default().write(&sample_result());
+        let parsed = parse_json_result(&json).expect("json output should parse");
+
+        assert_eq!(JsonFormatter::default().write(&parsed), json);
+        assert!(parse_json_result("{}").is_err());
+    }
+
+    #[test]
+    fn csv_formatter_writes_one_row_per_server() {
+        let output = CsvFormatter::default().write(&sample_result());
+        let mut lines = output.lines();
+
+        assert_eq!(
+            lines.next(),
+            Some("server,requests,avg_response_ms,avg_queued_ms,avg_service_ms,p99_ms,utilization_pct")
+        );
+        assert!(lines
+            .next()
+            .is_some_and(|row| row.starts_with("api,1,10,0,10,10,")));
+        assert_eq!(lines.next(), None);
+    }
+
+    #[cfg(feature = "serde")]
     #[test]
     fn json_formatter_emits_structured_output() {
-        let formatter = JsonFormatter;
+        let formatter = JsonFormatter::default();
         let output = formatter.write(&sample_result());
         let expected = r#"{
   "assignments": [
@@ -206,6 +1748,8 @@ mod tests {
       "arrival_time_ms": 0,
       "started_at": 0,
       "completed_at": 10,
+      "queued_ms": 0,
+      "service_ms": 10,
       "score": 10
     }
   ],
@@ -213,7 +1757,10 @@ mod tests {
     {
       "name": "api",
       "requests": 1,
-      "avg_response_ms": 10
+      "avg_response_ms": 10,
+      "avg_queued_ms": 0,
+      "avg_service_ms": 10,
+      "p99_ms": 10
     }
   ],
   "metadata": {
@@ -221,6 +1768,12 @@ mod tests {
     "tie_break": "stable",
     "duration_ms": 10
   },
+  "global": {
+    "total_requests": 1,
+    "makespan_ms": 10,
+    "throughput_rps": 100.0,
+    "avg_concurrency": 1.0
+  },
   "phase1_metrics": {
     "response_time": {
       "p95_ms": 10,
@@ -235,6 +1788,55 @@ mod tests {
     "jain_fairness": 1.0,
     "throughput_rps": 100.0,
     "avg_wait_ms": 0
+  },
+  "latency_histograms": {
+    "overall": {
+      "count": 1,
+      "min_ms": 10,
+      "max_ms": 10,
+      "buckets": [
+        {
+          "lower_ms": 10,
+          "upper_ms": 11,
+          "count": 1
+        }
+      ]
+    },
+    "per_server": [
+      {
+        "name": "api",
+        "histogram": {
+          "count": 1,
+          "min_ms": 10,
+          "max_ms": 10,
+          "buckets": [
+            {
+              "lower_ms": 10,
+              "upper_ms": 11,
+              "count": 1
+            }
+          ]
+        }
+      }
+    ]
+  },
+  "littles_law": {
+    "avg_in_system": 1.0,
+    "arrival_rate_rps": 100.0,
+    "avg_latency_ms": 10.0,
+    "predicted_in_system": 1.0,
+    "residual": 0.0
+  },
+  "tail": {
+    "threshold_ms": 10,
+    "per_server": [
+      {
+        "name": "api",
+        "tail_requests": 1,
+        "tail_pct": 100.0,
+        "p99_ms": 10
+      }
+    ]
   }
 }"#;
         assert_eq!(output, expected);
@@ -0,0 +1,166 @@
+use std::io::{BufRead, IsTerminal, Write};
+
+use crate::algorithms;
+use crate::config::{parse_server_args, RunArgs};
+use crate::error::{Error, Result};
+use crate::models::AlgoConfig;
+
+/// True when a run without a config file is missing its algorithm, servers,
+/// or workload and we may ask for them: `--interactive`, or stdin and stderr
+/// are both terminals.
+pub fn should_prompt(args: &RunArgs) -> bool {
+    if args.config.is_some() || args.scenario.is_some() || missing_fields(args).is_empty() {
+        return false;
+    }
+    args.interactive || (std::io::stdin().is_terminal() && std::io::stderr().is_terminal())
+}
+
+/// Asks for each missing required value on `output` and fills it into `args`.
+/// Invalid answers are re-asked; end of input fails like the missing flag.
+pub fn prompt_missing<R: BufRead, W: Write>(
+    args: &mut RunArgs,
+    mut input: R,
+    mut output: W,
+) -> Result<()> {
+    for field in missing_fields(args) {
+        match field {
+            Field::Algo => {
+                let algos = algorithms::available();
+                for (idx, algo) in algos.iter().enumerate() {
+                    prompt_io(writeln!(output, "  {}) {}", idx + 1, algo))?;
+                }
+                let algo = ask(
+                    &mut input,
+                    &mut output,
+                    &format!("Algorithm [1-{} or name]", algos.len()),
+                    |answer| {
+                        let by_number = answer
+                            .parse::<usize>()
+                            .ok()
+                            .and_then(|idx| idx.checked_sub(1))
+                            .and_then(|idx| algos.get(idx).cloned());
+                        by_number
+                            .or_else(|| AlgoConfig::builtin(answer))
+                            .or_else(|| {
+                                algorithms::is_registered(answer)
+                                    .then(|| AlgoConfig::Custom(answer.to_string()))
+                            })
+                            .ok_or_else(|| format!("unknown algorithm '{}'", answer))
+                    },
+                )?;
+                args.algo = Some(algo.ok_or_else(|| missing("--algo"))?);
+            }
+            Field::Servers => {
+                let servers = ask(
+                    &mut input,
+                    &mut output,
+                    "Servers (name:latency[:weight], comma-separated)",
+                    |answer| {
+                        parse_server_args(&[], Some(answer))
+                            .map(|_| answer.to_string())
+                            .map_err(|err| err.to_string())
+                    },
+                )?;
+                args.servers = Some(servers.ok_or_else(|| missing("--servers"))?);
+            }
+            Field::Requests => {
+                let requests = ask(&mut input, &mut output, "Requests", |answer| {
+                    match answer.parse::<usize>() {
+                        Ok(count) if count > 0 => Ok(count),
+                        _ => Err("enter a whole number greater than 0".to_string()),
+                    }
+                })?;
+                args.requests = Some(requests.ok_or_else(|| missing("--requests"))?);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    Algo,
+    Servers,
+    Requests,
+}
+
+fn missing_fields(args: &RunArgs) -> Vec<Field> {
+    let mut fields = Vec::new();
+    if args.algo.is_none() {
+        fields.push(Field::Algo);
+    }
+    if args.servers.is_none()
+        && args.server.is_empty()
+        && args.servers_file.is_none()
+        && args.server_template.is_none()
+    {
+        fields.push(Field::Servers);
+    }
+    if args.requests.is_none() && args.burst.is_none() && args.rate.is_none() && !args.overload {
+        fields.push(Field::Requests);
+    }
+    fields
+}
+
+/// Re-asks until `parse` accepts the answer; `None` on end of input.
+fn ask<T, R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    label: &str,
+    parse: impl Fn(&str) -> std::result::Result<T, String>,
+) -> Result<Option<T>> {
+    loop {
+        prompt_io(write!(output, "{}: ", label))?;
+        prompt_io(output.flush())?;
+        let mut line = String::new();
+        if prompt_io(input.read_line(&mut line))? == 0 {
+            prompt_io(writeln!(output))?;
+            return Ok(None);
+        }
+        match parse(line.trim()) {
+            Ok(value) => return Ok(Some(value)),
+            Err(reason) => prompt_io(writeln!(output, "  {}", reason))?,
+        }
+    }
+}
+
+fn missing(flag: &str) -> Error {
+    Error::Cli(format!("missing required {}", flag))
+}
+
+fn prompt_io<T>(result: std::io::Result<T>) -> Result<T> {
+    result.map_err(|err| Error::Output(format!("failed to prompt: {}", err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn run_args(extra: &[&str]) -> RunArgs {
+        RunArgs::parse_from(std::iter::once("run").chain(extra.iter().copied()))
+    }
+
+    #[test]
+    fn prompt_fills_missing_values_and_reasks_invalid_answers() {
+        let mut args = run_args(&["--requests", "5"]);
+        let input = "9\nlc\na:10,b\na:10,b:20\n";
+        let mut output = Vec::new();
+        prompt_missing(&mut args, input.as_bytes(), &mut output).expect("prompt should finish");
+
+        assert_eq!(args.algo, Some(AlgoConfig::LeastConnections));
+        assert_eq!(args.servers.as_deref(), Some("a:10,b:20"));
+        assert_eq!(args.requests, Some(5));
+        let transcript = String::from_utf8(output).expect("prompt output is UTF-8");
+        assert!(transcript.contains("unknown algorithm '9'"));
+        assert!(transcript.contains("invalid server entry 'b'"));
+    }
+
+    #[test]
+    fn prompt_fails_like_the_missing_flag_at_end_of_input() {
+        let mut args = run_args(&["--servers", "a:10"]);
+        let err = prompt_missing(&mut args, "2\n".as_bytes(), Vec::new())
+            .expect_err("missing requests should fail");
+        assert_eq!(err.to_string(), "missing required --requests");
+    }
+}
@@ -0,0 +1,238 @@
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::engine;
+use crate::error::Result;
+use crate::models::{AlgoConfig, SimConfig};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum RankCriterion {
+    #[default]
+    P99,
+    AvgWait,
+    Fairness,
+    Throughput,
+}
+
+impl RankCriterion {
+    fn label(self) -> &'static str {
+        match self {
+            RankCriterion::P99 => "lowest p99",
+            RankCriterion::AvgWait => "lowest avg wait",
+            RankCriterion::Fairness => "highest fairness",
+            RankCriterion::Throughput => "highest throughput",
+        }
+    }
+}
+
+/// How `recommend` orders algorithms, plus hard limits a candidate must meet
+/// to be recommended.
+#[derive(Clone, Debug, Default)]
+pub struct RecommendCriteria {
+    pub rank_by: RankCriterion,
+    pub min_fairness: Option<f64>,
+    pub max_p99_ms: Option<u64>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct CandidateRow {
+    pub rank: usize,
+    pub algo: String,
+    pub p99_ms: Option<u64>,
+    pub avg_wait_ms: u64,
+    pub throughput_rps: f64,
+    pub jain_fairness: f64,
+    pub eligible: bool,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Recommendation {
+    pub rank_by: RankCriterion,
+    pub candidates: Vec<CandidateRow>,
+    pub recommended: Option<String>,
+    pub reason: String,
+}
+
+/// Runs `config` once per algorithm, ranks the results by `criteria.rank_by`
+/// (ties keep `algos` order), and recommends the best candidate that meets
+/// every limit.
+pub fn recommend(
+    config: &SimConfig,
+    algos: &[AlgoConfig],
+    criteria: &RecommendCriteria,
+) -> Result<Recommendation> {
+    let configs = algos
+        .iter()
+        .map(|algo| {
+            let mut config = config.clone();
+            config.algo = algo.clone();
+            config
+        })
+        .collect::<Vec<_>>();
+    let mut candidates = engine::run_summaries(&configs)?
+        .into_iter()
+        .zip(algos)
+        .map(|(result, algo)| {
+            let metrics = result.phase1_metrics;
+            let p99_ms = metrics.response_time.p99_ms;
+            let eligible = criteria
+                .min_fairness
+                .is_none_or(|min| metrics.jain_fairness >= min)
+                && criteria
+                    .max_p99_ms
+                    .is_none_or(|max| p99_ms.is_some_and(|p99| p99 <= max));
+            CandidateRow {
+                rank: 0,
+                algo: algo.to_string(),
+                p99_ms,
+                avg_wait_ms: metrics.avg_wait_ms,
+                throughput_rps: metrics.throughput_rps,
+                jain_fairness: metrics.jain_fairness,
+                eligible,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    candidates.sort_by(|left, right| match criteria.rank_by {
+        RankCriterion::P99 => left
+            .p99_ms
+            .unwrap_or(u64::MAX)
+            .cmp(&right.p99_ms.unwrap_or(u64::MAX)),
+        RankCriterion::AvgWait => left.avg_wait_ms.cmp(&right.avg_wait_ms),
+        RankCriterion::Fairness => right.jain_fairness.total_cmp(&left.jain_fairness),
+        RankCriterion::Throughput => right.throughput_rps.total_cmp(&left.throughput_rps),
+    });
+    for (idx, candidate) in candidates.iter_mut().enumerate() {
+        candidate.rank = idx + 1;
+    }
+
+    let best = candidates.iter().find(|candidate| candidate.eligible);
+    let reason = match best {
+        Some(best) => {
+            let mut reason = format!(
+                "{} ({})",
+                criteria.rank_by.label(),
+                metric_text(best, criteria.rank_by)
+            );
+            let limits = limits_text(criteria);
+            if !limits.is_empty() {
+                reason.push_str(&format!(" with {}", limits));
+            }
+            reason
+        }
+        None => format!("no algorithm meets {}", limits_text(criteria)),
+    };
+    Ok(Recommendation {
+        rank_by: criteria.rank_by,
+        recommended: best.map(|candidate| candidate.algo.clone()),
+        candidates,
+        reason,
+    })
+}
+
+fn metric_text(candidate: &CandidateRow, criterion: RankCriterion) -> String {
+    match criterion {
+        RankCriterion::P99 => candidate
+            .p99_ms
+            .map_or_else(|| "-".to_string(), |p99| format!("{}ms", p99)),
+        RankCriterion::AvgWait => format!("{}ms", candidate.avg_wait_ms),
+        RankCriterion::Fairness => format!("{}", candidate.jain_fairness),
+        RankCriterion::Throughput => format!("{} rps", candidate.throughput_rps),
+    }
+}
+
+fn limits_text(criteria: &RecommendCriteria) -> String {
+    let mut limits = Vec::new();
+    if let Some(min) = criteria.min_fairness {
+        limits.push(format!("fairness >= {}", min));
+    }
+    if let Some(max) = criteria.max_p99_ms {
+        limits.push(format!("p99 <= {}ms", max));
+    }
+    limits.join(" and ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        EventOrder, RandomnessConfig, RequestProfile, RngKind, ServerConfig, ServerModel,
+    };
+
+    fn config() -> SimConfig {
+        SimConfig {
+            servers: [("a", 10), ("b", 20), ("c", 30)]
+                .into_iter()
+                .map(|(name, latency)| ServerConfig {
+                    name: name.to_string(),
+                    base_latency_ms: latency,
+                    weight: 1,
+                    model: ServerModel::default(),
+                })
+                .collect(),
+            requests: RequestProfile::Burst {
+                count: 30,
+                at_ms: 0,
+            },
+            algo: AlgoConfig::RoundRobin,
+            randomness: RandomnessConfig::Stable,
+            rng: RngKind::Std,
+            event_order: EventOrder::CompletionsFirst,
+            allow_large: false,
+            timeseries_bucket_ms: None,
+            record_timelines: false,
+            slos: Vec::new(),
+            slowest_requests: 0,
+            explain: false,
+            max_virtual_time_ms: None,
+            max_events: None,
+        }
+    }
+
+    const ALGOS: [AlgoConfig; 2] = [AlgoConfig::RoundRobin, AlgoConfig::LeastResponseTime];
+
+    #[test]
+    fn recommend_ranks_by_criterion() {
+        let by_p99 = recommend(&config(), &ALGOS, &RecommendCriteria::default())
+            .expect("recommend should run");
+        assert_eq!(by_p99.recommended.as_deref(), Some("least-response-time"));
+        assert_eq!(by_p99.candidates[0].rank, 1);
+
+        let by_fairness = recommend(
+            &config(),
+            &ALGOS,
+            &RecommendCriteria {
+                rank_by: RankCriterion::Fairness,
+                ..RecommendCriteria::default()
+            },
+        )
+        .expect("recommend should run");
+        assert_eq!(by_fairness.recommended.as_deref(), Some("round-robin"));
+    }
+
+    #[test]
+    fn recommend_skips_candidates_outside_limits() {
+        let criteria = RecommendCriteria {
+            rank_by: RankCriterion::P99,
+            min_fairness: Some(0.99),
+            max_p99_ms: None,
+        };
+        let report = recommend(&config(), &ALGOS, &criteria).expect("recommend should run");
+        assert_eq!(report.candidates[0].algo, "least-response-time");
+        assert!(!report.candidates[0].eligible);
+        assert_eq!(report.recommended.as_deref(), Some("round-robin"));
+        assert!(report.reason.contains("fairness >= 0.99"));
+
+        let impossible = RecommendCriteria {
+            max_p99_ms: Some(1),
+            ..criteria
+        };
+        let report = recommend(&config(), &ALGOS, &impossible).expect("recommend should run");
+        assert_eq!(report.recommended, None);
+        assert!(report.reason.starts_with("no algorithm meets"));
+    }
+}
@@ -0,0 +1,152 @@
+use std::cell::RefCell;
+use std::io::Write;
+use std::rc::Rc;
+
+use crate::engine;
+use crate::error::{Error, Result};
+use crate::models::SimConfig;
+
+/// First line of a `--trace-file` log; the rest of the line is the resolved
+/// config as JSON, so `replay` can re-execute the run.
+pub const CONFIG_HEADER: &str = "# lb-sim config ";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReplayReport {
+    pub events: usize,
+}
+
+pub fn write_config_header(writer: &mut dyn Write, config: &SimConfig) -> Result<()> {
+    let json = serde_json::to_string(config)
+        .map_err(|err| Error::Output(format!("failed to serialize config: {}", err)))?;
+    writeln!(writer, "{}{}", CONFIG_HEADER, json)
+        .map_err(|err| Error::Output(format!("failed to write trace: {}", err)))
+}
+
+/// Splits an event log into the config it was recorded with and its event lines.
+pub fn parse_event_log(contents: &str) -> Result<(SimConfig, Vec<&str>)> {
+    let mut lines = contents.lines();
+    let config = lines
+        .next()
+        .and_then(|line| line.strip_prefix(CONFIG_HEADER))
+        .ok_or_else(|| {
+            Error::InvalidEventLog(format!(
+                "missing `{}` header; record the log with --trace-file",
+                CONFIG_HEADER.trim()
+            ))
+        })?;
+    let config = serde_json::from_str(config)
+        .map_err(|err| Error::InvalidEventLog(format!("invalid config header: {}", err)))?;
+    Ok((config, lines.collect()))
+}
+
+/// Re-runs the recorded config and compares its event stream line by line
+/// with the log, failing at the first event that differs.
+pub fn replay_event_log(contents: &str) -> Result<ReplayReport> {
+    let (config, recorded) = parse_event_log(contents)?;
+    let buffer = SharedBuffer::default();
+    engine::run_simulation_with_trace(&config, false, Box::new(buffer.clone()))?;
+    let replayed = String::from_utf8(buffer.0.take())
+        .map_err(|err| Error::Output(format!("trace is not UTF-8: {}", err)))?;
+    let replayed = replayed.lines().collect::<Vec<_>>();
+
+    for idx in 0..recorded.len().max(replayed.len()) {
+        let (expected, actual) = (recorded.get(idx), replayed.get(idx));
+        if expected != actual {
+            return Err(Error::ReplayDivergence {
+                event: idx + 1,
+                recorded: expected.map_or("<end of log>", |line| line).to_string(),
+                replayed: actual.map_or("<end of run>", |line| line).to_string(),
+            });
+        }
+    }
+    Ok(ReplayReport {
+        events: recorded.len(),
+    })
+}
+
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        AlgoConfig, EventOrder, RandomnessConfig, RequestProfile, RngKind, ServerConfig,
+        ServerModel,
+    };
+
+    fn recorded_log(config: &SimConfig) -> String {
+        let buffer = SharedBuffer::default();
+        let mut writer = buffer.clone();
+        write_config_header(&mut writer, config).expect("header should write");
+        engine::run_simulation_with_trace(config, false, Box::new(buffer.clone()))
+            .expect("simulation should succeed");
+        let bytes = buffer.0.take();
+        String::from_utf8(bytes).expect("trace is UTF-8")
+    }
+
+    fn sample_config() -> SimConfig {
+        SimConfig {
+            servers: vec![
+                ServerConfig {
+                    name: "a".to_string(),
+                    base_latency_ms: 10,
+                    weight: 1,
+                    model: ServerModel::default(),
+                },
+                ServerConfig {
+                    name: "b".to_string(),
+                    base_latency_ms: 20,
+                    weight: 1,
+                    model: ServerModel::default(),
+                },
+            ],
+            requests: RequestProfile::Poisson {
+                rate: 80.0,
+                duration_ms: 100,
+            },
+            algo: AlgoConfig::LeastConnections,
+            randomness: RandomnessConfig::Seeded(9),
+            rng: RngKind::Std,
+            event_order: EventOrder::CompletionsFirst,
+            allow_large: false,
+            timeseries_bucket_ms: None,
+            record_timelines: false,
+            slos: Vec::new(),
+            slowest_requests: 0,
+            explain: false,
+            max_virtual_time_ms: None,
+            max_events: None,
+        }
+    }
+
+    #[test]
+    fn replay_matches_recorded_log() {
+        let log = recorded_log(&sample_config());
+        let report = replay_event_log(&log).expect("replay should match");
+
+        assert_eq!(report.events, log.lines().count() - 1);
+        assert!(report.events > 0);
+    }
+
+    #[test]
+    fn replay_flags_the_first_divergent_event() {
+        let log = recorded_log(&sample_config());
+        let tampered = log.replacen("server=a", "server=b", 1);
+        let err = replay_event_log(&tampered).expect_err("tampered log should diverge");
+
+        assert!(matches!(err, Error::ReplayDivergence { .. }));
+        assert!(replay_event_log("t=0 arrival request=1\n").is_err());
+    }
+}
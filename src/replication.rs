@@ -0,0 +1,235 @@
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::state::SimulationResult;
+
+/// Two-sided 95% Student t critical values for 1..=30 degrees of freedom;
+/// larger samples use the normal approximation.
+const T_CRITICAL_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+    2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+    2.052, 2.048, 2.045, 2.042,
+];
+const Z_CRITICAL_95: f64 = 1.96;
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct MetricSummary {
+    pub metric: String,
+    pub mean: f64,
+    pub stddev: f64,
+    pub ci95_low: f64,
+    pub ci95_high: f64,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ReplicationReport {
+    pub algo: String,
+    pub replications: usize,
+    pub seeds: Vec<u64>,
+    pub metrics: Vec<MetricSummary>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct MetricDistribution {
+    pub metric: String,
+    pub min: f64,
+    pub p50: f64,
+    pub mean: f64,
+    pub stddev: f64,
+    pub max: f64,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct SeedSweepReport {
+    pub algo: String,
+    pub first_seed: u64,
+    pub last_seed: u64,
+    pub runs: usize,
+    pub metrics: Vec<MetricDistribution>,
+}
+
+/// Derives `count` well-spread seeds from `base` with the SplitMix64 finalizer,
+/// so neighbouring base seeds don't produce overlapping replication streams.
+pub fn derive_seeds(base: u64, count: usize) -> Vec<u64> {
+    (0..count as u64)
+        .map(|idx| {
+            let mut z = base.wrapping_add(idx.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        })
+        .collect()
+}
+
+type MetricExtractor = fn(&SimulationResult) -> f64;
+
+const METRICS: [(&str, MetricExtractor); 7] = [
+    ("p95_ms", |result| {
+        result.phase1_metrics.response_time.p95_ms.unwrap_or(0) as f64
+    }),
+    ("p99_ms", |result| {
+        result.phase1_metrics.response_time.p99_ms.unwrap_or(0) as f64
+    }),
+    ("avg_wait_ms", |result| {
+        result.phase1_metrics.avg_wait_ms as f64
+    }),
+    ("throughput_rps", |result| result.global.throughput_rps),
+    ("jain_fairness", |result| {
+        result.phase1_metrics.jain_fairness
+    }),
+    ("makespan_ms", |result| result.global.makespan_ms as f64),
+    ("avg_concurrency", |result| result.global.avg_concurrency),
+];
+
+/// The key metrics tracked across replications, in report order.
+pub fn key_metrics(result: &SimulationResult) -> Vec<(&'static str, f64)> {
+    METRICS
+        .iter()
+        .map(|(name, extract)| (*name, extract(result)))
+        .collect()
+}
+
+fn metric_values(results: &[SimulationResult], extract: MetricExtractor) -> Vec<f64> {
+    results.iter().map(extract).collect()
+}
+
+fn algo_label(results: &[SimulationResult]) -> String {
+    results
+        .first()
+        .map(|result| result.metadata.algo.clone())
+        .unwrap_or_default()
+}
+
+pub fn summarize_replications(results: &[SimulationResult], seeds: &[u64]) -> ReplicationReport {
+    ReplicationReport {
+        algo: algo_label(results),
+        replications: results.len(),
+        seeds: seeds.to_vec(),
+        metrics: METRICS
+            .iter()
+            .map(|(name, extract)| summarize_metric(name, &metric_values(results, *extract)))
+            .collect(),
+    }
+}
+
+pub fn summarize_seed_sweep(results: &[SimulationResult], seeds: &[u64]) -> SeedSweepReport {
+    SeedSweepReport {
+        algo: algo_label(results),
+        first_seed: seeds.first().copied().unwrap_or(0),
+        last_seed: seeds.last().copied().unwrap_or(0),
+        runs: results.len(),
+        metrics: METRICS
+            .iter()
+            .map(|(name, extract)| metric_distribution(name, &metric_values(results, *extract)))
+            .collect(),
+    }
+}
+
+pub fn metric_distribution(name: &str, values: &[f64]) -> MetricDistribution {
+    let summary = summarize_metric(name, values);
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let median = match sorted.len() {
+        0 => 0.0,
+        len if len % 2 == 1 => sorted[len / 2],
+        len => (sorted[len / 2 - 1] + sorted[len / 2]) / 2.0,
+    };
+    MetricDistribution {
+        metric: summary.metric,
+        min: sorted.first().copied().unwrap_or(0.0),
+        p50: round_to(median, 4),
+        mean: summary.mean,
+        stddev: summary.stddev,
+        max: sorted.last().copied().unwrap_or(0.0),
+    }
+}
+
+pub fn summarize_metric(name: &str, values: &[f64]) -> MetricSummary {
+    let count = values.len();
+    let mean = if count == 0 {
+        0.0
+    } else {
+        values.iter().sum::<f64>() / count as f64
+    };
+    let stddev = if count < 2 {
+        0.0
+    } else {
+        let variance = values
+            .iter()
+            .map(|value| (value - mean) * (value - mean))
+            .sum::<f64>()
+            / (count - 1) as f64;
+        variance.sqrt()
+    };
+    let half_width = if count < 2 {
+        0.0
+    } else {
+        t_critical_95(count - 1) * stddev / (count as f64).sqrt()
+    };
+
+    MetricSummary {
+        metric: name.to_string(),
+        mean: round_to(mean, 4),
+        stddev: round_to(stddev, 4),
+        ci95_low: round_to(mean - half_width, 4),
+        ci95_high: round_to(mean + half_width, 4),
+    }
+}
+
+fn t_critical_95(degrees_of_freedom: usize) -> f64 {
+    T_CRITICAL_95
+        .get(degrees_of_freedom.saturating_sub(1))
+        .copied()
+        .unwrap_or(Z_CRITICAL_95)
+}
+
+fn round_to(value: f64, decimals: i32) -> f64 {
+    let factor = 10_f64.powi(decimals);
+    (value * factor).round() / factor
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarize_metric_uses_student_t_interval() {
+        let summary = summarize_metric("x", &[1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(summary.mean, 2.5);
+        assert_eq!(summary.stddev, 1.291);
+        assert_eq!(summary.ci95_low, 0.446);
+        assert_eq!(summary.ci95_high, 4.554);
+    }
+
+    #[test]
+    fn summarize_metric_handles_single_sample() {
+        let summary = summarize_metric("x", &[7.0]);
+        assert_eq!(summary.mean, 7.0);
+        assert_eq!(summary.stddev, 0.0);
+        assert_eq!((summary.ci95_low, summary.ci95_high), (7.0, 7.0));
+    }
+
+    #[test]
+    fn metric_distribution_reports_spread() {
+        let distribution = metric_distribution("x", &[4.0, 1.0, 3.0, 2.0]);
+        assert_eq!(distribution.min, 1.0);
+        assert_eq!(distribution.p50, 2.5);
+        assert_eq!(distribution.mean, 2.5);
+        assert_eq!(distribution.max, 4.0);
+    }
+
+    #[test]
+    fn derived_seeds_are_distinct_and_reproducible() {
+        let seeds = derive_seeds(42, 5);
+        assert_eq!(seeds, derive_seeds(42, 5));
+        let mut unique = seeds.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(unique.len(), 5);
+        assert_ne!(derive_seeds(43, 1), derive_seeds(42, 1));
+    }
+}
@@ -0,0 +1,110 @@
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::state::Assignment;
+
+/// Keeps a uniform random sample of at most `limit` assignments (reservoir
+/// sampling), so huge runs can still show raw assignments without storing
+/// every one. The sampler has its own generator, so sampling never shifts the
+/// run's tie-breaks or workload.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct AssignmentReservoir {
+    limit: usize,
+    seen: u64,
+    kept: Vec<Assignment>,
+    rng: Pcg64Mcg,
+}
+
+impl AssignmentReservoir {
+    pub fn new(limit: usize, seed: u64) -> Self {
+        Self {
+            limit,
+            seen: 0,
+            kept: Vec::with_capacity(limit),
+            rng: Pcg64Mcg::seed_from_u64(seed),
+        }
+    }
+
+    pub fn record(&mut self, assignment: &Assignment) {
+        self.seen += 1;
+        if self.kept.len() < self.limit {
+            self.kept.push(assignment.clone());
+            return;
+        }
+        let slot = self.rng.gen_range(0..self.seen);
+        if let Some(kept) = usize::try_from(slot)
+            .ok()
+            .and_then(|slot| self.kept.get_mut(slot))
+        {
+            *kept = assignment.clone();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.kept.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.kept.is_empty()
+    }
+
+    /// The sample in request order.
+    pub fn finish(mut self) -> Vec<Assignment> {
+        self.kept.sort_by_key(|assignment| assignment.request_id);
+        self.kept
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assignment(request_id: u64) -> Assignment {
+        Assignment {
+            request_id,
+            server_id: 0,
+            arrival_time_ms: request_id,
+            started_at: request_id,
+            completed_at: request_id + 1,
+            queued_ms: 0,
+            service_ms: 1,
+            score: None,
+        }
+    }
+
+    #[test]
+    fn keeps_everything_under_the_limit_and_a_uniform_sample_over_it() {
+        let mut small = AssignmentReservoir::new(10, 0);
+        (1..=4).for_each(|id| small.record(&assignment(id)));
+        let ids = small
+            .finish()
+            .iter()
+            .map(|assignment| assignment.request_id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 2, 3, 4]);
+
+        let mut hits = [0u32; 10];
+        for seed in 0..2_000 {
+            let mut reservoir = AssignmentReservoir::new(3, seed);
+            (0..10).for_each(|id| reservoir.record(&assignment(id)));
+            let sample = reservoir.finish();
+            assert_eq!(sample.len(), 3);
+            assert!(sample
+                .windows(2)
+                .all(|pair| pair[0].request_id < pair[1].request_id));
+            sample
+                .iter()
+                .for_each(|assignment| hits[assignment.request_id as usize] += 1);
+        }
+        // Each request should land in ~3/10 of the 2000 samples.
+        assert!(
+            hits.iter().all(|&count| (480..=720).contains(&count)),
+            "{:?}",
+            hits
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::config_file::{
+    check_config_keys, expand_config_templates, load_config_value, merge_config_values,
+};
+use crate::error::{Error, Result};
+use crate::models::SimConfig;
+use crate::state::SimulationResult;
+
+#[derive(Clone, Debug)]
+pub struct ScenarioRun {
+    pub name: String,
+    pub config: SimConfig,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ScenarioRow {
+    pub name: String,
+    pub algo: String,
+    pub requests: u64,
+    pub p95_ms: Option<u64>,
+    pub p99_ms: Option<u64>,
+    pub avg_wait_ms: u64,
+    pub throughput_rps: f64,
+    pub jain_fairness: f64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ScenarioReport {
+    pub runs: Vec<ScenarioRow>,
+}
+
+/// Loads a scenario file: top-level keys form the shared base config and each
+/// `[[runs]]` entry is merged on top of it under its `name`.
+pub fn load_scenario(path: &Path, lenient: bool) -> Result<Vec<ScenarioRun>> {
+    let mut base = load_config_value(path, &mut Vec::new())?;
+    let runs = match base.as_object_mut().and_then(|map| map.remove("runs")) {
+        Some(serde_json::Value::Array(runs)) if !runs.is_empty() => runs,
+        _ => {
+            return Err(Error::InvalidScenario(
+                "expected a non-empty `runs` list".to_string(),
+            ))
+        }
+    };
+
+    let mut names = HashSet::new();
+    runs.into_iter()
+        .enumerate()
+        .map(|(idx, mut run)| {
+            let name = match run.as_object_mut().and_then(|map| map.remove("name")) {
+                Some(serde_json::Value::String(name)) => name,
+                None => format!("run-{}", idx + 1),
+                Some(other) => {
+                    return Err(Error::InvalidScenario(format!(
+                        "run name must be a string, got {}",
+                        other
+                    )))
+                }
+            };
+            if !names.insert(name.clone()) {
+                return Err(Error::InvalidScenario(format!(
+                    "duplicate run name '{}'",
+                    name
+                )));
+            }
+            let mut merged = base.clone();
+            merge_config_values(&mut merged, run);
+            if !lenient {
+                check_config_keys(&merged)
+                    .map_err(|err| Error::InvalidScenario(format!("run '{}': {}", name, err)))?;
+            }
+            expand_config_templates(&mut merged)?;
+            let config = serde_json::from_value(merged)
+                .map_err(|err| Error::InvalidScenario(format!("run '{}': {}", name, err)))?;
+            Ok(ScenarioRun { name, config })
+        })
+        .collect()
+}
+
+pub fn summarize_scenario(results: &[(String, SimulationResult)]) -> ScenarioReport {
+    ScenarioReport {
+        runs: results
+            .iter()
+            .map(|(name, result)| ScenarioRow {
+                name: name.clone(),
+                algo: result.metadata.algo.clone(),
+                requests: result.global.total_requests,
+                p95_ms: result.phase1_metrics.response_time.p95_ms,
+                p99_ms: result.phase1_metrics.response_time.p99_ms,
+                avg_wait_ms: result.phase1_metrics.avg_wait_ms,
+                throughput_rps: result.global.throughput_rps,
+                jain_fairness: result.phase1_metrics.jain_fairness,
+            })
+            .collect(),
+    }
+}
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use super::*;
+    use crate::models::{AlgoConfig, RequestProfile};
+    use std::fs;
+
+    #[test]
+    fn load_scenario_merges_each_run_over_shared_base() {
+        let mut path = std::env::temp_dir();
+        path.push(format!("lb-scenario-unit-{}.toml", std::process::id()));
+        fs::write(
+            &path,
+            r#"
+requests = 10
+servers = [{ name = "a", base_latency_ms = 10 }]
+
+[[runs]]
+name = "rr"
+algo = "round-robin"
+
+[[runs]]
+algo = "least-connections"
+requests = 4
+"#,
+        )
+        .expect("scenario write should succeed");
+        let runs = load_scenario(&path, false);
+        fs::remove_file(&path).ok();
+        let runs = runs.expect("scenario should load");
+
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].name, "rr");
+        assert!(matches!(runs[0].config.algo, AlgoConfig::RoundRobin));
+        assert!(matches!(
+            runs[0].config.requests,
+            RequestProfile::FixedCount(10)
+        ));
+        assert_eq!(runs[1].name, "run-2");
+        assert!(matches!(runs[1].config.algo, AlgoConfig::LeastConnections));
+        assert!(matches!(
+            runs[1].config.requests,
+            RequestProfile::FixedCount(4)
+        ));
+        assert_eq!(runs[1].config.servers[0].name, "a");
+    }
+}
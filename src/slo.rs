@@ -0,0 +1,116 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::models::SloConfig;
+use crate::state::{ServerSloCompliance, SloCompliance, SloReport};
+
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+struct ComplianceCounter {
+    total: u64,
+    within: u64,
+    first_violation_ms: Option<u64>,
+}
+
+impl ComplianceCounter {
+    fn record(&mut self, completed_at: u64, within: bool) {
+        self.total += 1;
+        if within {
+            self.within += 1;
+        } else {
+            self.first_violation_ms = Some(
+                self.first_violation_ms
+                    .map_or(completed_at, |current| current.min(completed_at)),
+            );
+        }
+    }
+
+    fn compliance(&self, percentile: f64) -> SloCompliance {
+        let compliance_pct = if self.total == 0 {
+            100.0
+        } else {
+            (self.within as f64 / self.total as f64) * 100.0
+        };
+        SloCompliance {
+            compliance_pct: (compliance_pct * 100.0).round() / 100.0,
+            met: compliance_pct >= percentile,
+            first_violation_ms: self.first_violation_ms,
+        }
+    }
+}
+
+/// Tracks, for every configured SLO, how many requests completed within the
+/// latency threshold overall and per server.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct SloTracker {
+    slos: Vec<SloConfig>,
+    overall: Vec<ComplianceCounter>,
+    per_server: Vec<Vec<ComplianceCounter>>,
+}
+
+impl SloTracker {
+    pub fn new(slos: &[SloConfig], server_count: usize) -> Self {
+        Self {
+            slos: slos.to_vec(),
+            overall: vec![ComplianceCounter::default(); slos.len()],
+            per_server: vec![vec![ComplianceCounter::default(); server_count]; slos.len()],
+        }
+    }
+
+    pub fn record(&mut self, server_idx: usize, completed_at: u64, response_time_ms: u64) {
+        for (idx, slo) in self.slos.iter().enumerate() {
+            let within = response_time_ms <= slo.threshold_ms;
+            self.overall[idx].record(completed_at, within);
+            self.per_server[idx][server_idx].record(completed_at, within);
+        }
+    }
+
+    pub fn finish(self, server_names: &[String]) -> Vec<SloReport> {
+        self.slos
+            .iter()
+            .zip(self.overall.iter().zip(&self.per_server))
+            .map(|(slo, (overall, per_server))| SloReport {
+                percentile: slo.percentile,
+                threshold_ms: slo.threshold_ms,
+                overall: overall.compliance(slo.percentile),
+                per_server: per_server
+                    .iter()
+                    .zip(server_names)
+                    .map(|(counter, name)| ServerSloCompliance {
+                        name: name.clone(),
+                        compliance: counter.compliance(slo.percentile),
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracker_reports_compliance_and_first_violation() {
+        let slos = [SloConfig {
+            percentile: 50.0,
+            threshold_ms: 20,
+        }];
+        let mut tracker = SloTracker::new(&slos, 2);
+        tracker.record(0, 10, 10);
+        tracker.record(1, 40, 30);
+        tracker.record(1, 35, 25);
+        tracker.record(0, 50, 20);
+
+        let reports = tracker.finish(&["a".to_string(), "b".to_string()]);
+        let report = &reports[0];
+        assert_eq!(report.overall.compliance_pct, 50.0);
+        assert!(report.overall.met);
+        assert_eq!(report.overall.first_violation_ms, Some(35));
+        assert_eq!(report.per_server[0].compliance.compliance_pct, 100.0);
+        assert_eq!(report.per_server[0].compliance.first_violation_ms, None);
+        assert_eq!(report.per_server[1].compliance.compliance_pct, 0.0);
+        assert!(!report.per_server[1].compliance.met);
+    }
+}
@@ -0,0 +1,115 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::state::SlowRequest;
+
+/// Key ordering the heap: longest response first, then earliest request id,
+/// so the retained set is deterministic when response times tie.
+type SlowKey = (u64, Reverse<u64>);
+
+/// Keeps the `limit` slowest requests in a bounded min-heap so the engine can
+/// report the tail without storing every assignment.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct SlowestRecorder {
+    limit: usize,
+    heap: BinaryHeap<Reverse<(SlowKey, SlowEntry)>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+struct SlowEntry {
+    server_idx: usize,
+    arrival_time_ms: u64,
+    started_at: u64,
+    score: Option<u64>,
+}
+
+impl SlowestRecorder {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            heap: BinaryHeap::with_capacity(limit + 1),
+        }
+    }
+
+    pub fn record(
+        &mut self,
+        request_id: u64,
+        server_idx: usize,
+        arrival_time_ms: u64,
+        started_at: u64,
+        completed_at: u64,
+        score: Option<u64>,
+    ) {
+        if self.limit == 0 {
+            return;
+        }
+        let key = (completed_at - arrival_time_ms, Reverse(request_id));
+        if self.heap.len() == self.limit {
+            match self.heap.peek() {
+                Some(Reverse((smallest, _))) if key <= *smallest => return,
+                _ => {}
+            }
+            self.heap.pop();
+        }
+        self.heap.push(Reverse((
+            key,
+            SlowEntry {
+                server_idx,
+                arrival_time_ms,
+                started_at,
+                score,
+            },
+        )));
+    }
+
+    pub fn finish(self, server_names: &[String]) -> Vec<SlowRequest> {
+        let mut entries = self
+            .heap
+            .into_iter()
+            .map(|Reverse(entry)| entry)
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|(key, _)| Reverse(*key));
+        entries
+            .into_iter()
+            .map(|((response_ms, Reverse(request_id)), entry)| SlowRequest {
+                request_id,
+                server: server_names[entry.server_idx].clone(),
+                arrival_time_ms: entry.arrival_time_ms,
+                started_at: entry.started_at,
+                queue_wait_ms: entry.started_at - entry.arrival_time_ms,
+                response_ms,
+                score: entry.score,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_slowest_requests_in_descending_order() {
+        let mut recorder = SlowestRecorder::new(2);
+        recorder.record(1, 0, 0, 0, 10, None);
+        recorder.record(2, 1, 0, 5, 30, Some(20));
+        recorder.record(3, 0, 5, 10, 20, None);
+        recorder.record(4, 1, 10, 12, 25, None);
+
+        let slowest = recorder.finish(&["a".to_string(), "b".to_string()]);
+        let ids = slowest
+            .iter()
+            .map(|request| request.request_id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![2, 3]);
+        assert_eq!(slowest[0].server, "b");
+        assert_eq!(slowest[0].queue_wait_ms, 5);
+        assert_eq!(slowest[0].response_ms, 30);
+        assert_eq!(slowest[1].score, None);
+    }
+}
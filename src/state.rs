@@ -1,54 +1,71 @@
-use serde::Serialize;
+#[cfg(feature = "serde")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct ServerState {
     pub id: usize,
     pub name: String,
     pub base_latency_ms: u64,
     pub weight: u32,
     pub active_connections: u32,
-    pub pick_count: u32,
+    pub pick_count: u64,
     pub in_flight: u32,
     pub next_available_ms: u64,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct EngineState {
     pub time_ms: u64,
     pub servers: Vec<ServerState>,
     pub assignments: Vec<Assignment>,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
 pub struct Assignment {
-    pub request_id: usize,
+    pub request_id: u64,
     pub server_id: usize,
     pub arrival_time_ms: u64,
     pub started_at: u64,
     pub completed_at: u64,
+    pub queued_ms: u64,
+    pub service_ms: u64,
     pub score: Option<u64>,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
 pub struct ServerSummary {
     pub name: String,
-    pub requests: u32,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub zone: Option<String>,
+    pub requests: u64,
     pub avg_response_ms: u64,
+    pub avg_queued_ms: u64,
+    pub avg_service_ms: u64,
+    pub p99_ms: Option<u64>,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
 pub struct ResponseTimePercentiles {
     pub p95_ms: Option<u64>,
     pub p99_ms: Option<u64>,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
 pub struct ServerUtilization {
     pub name: String,
     pub utilization_pct: f64,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
 pub struct Phase1Metrics {
     pub response_time: ResponseTimePercentiles,
     pub per_server_utilization: Vec<ServerUtilization>,
@@ -57,17 +74,246 @@ pub struct Phase1Metrics {
     pub avg_wait_ms: u64,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct HistogramBucket {
+    pub lower_ms: u64,
+    pub upper_ms: u64,
+    pub count: u64,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct LatencyHistogram {
+    pub count: u64,
+    pub min_ms: Option<u64>,
+    pub max_ms: Option<u64>,
+    pub buckets: Vec<HistogramBucket>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct ServerLatencyHistogram {
+    pub name: String,
+    pub histogram: LatencyHistogram,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct LatencyHistograms {
+    pub overall: LatencyHistogram,
+    pub per_server: Vec<ServerLatencyHistogram>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct TimeseriesBucket {
+    pub start_ms: u64,
+    pub arrivals: u64,
+    pub completions: u64,
+    pub throughput_rps: f64,
+    pub avg_latency_ms: Option<u64>,
+    pub max_in_flight: Vec<u32>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct Timeseries {
+    pub bucket_ms: u64,
+    pub servers: Vec<String>,
+    pub buckets: Vec<TimeseriesBucket>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct TimelinePoint {
+    pub time_ms: u64,
+    pub queue_depth: u64,
+    pub active_connections: u64,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct ServerTimeline {
+    pub name: String,
+    pub points: Vec<TimelinePoint>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct GlobalStats {
+    pub total_requests: u64,
+    pub makespan_ms: u64,
+    pub throughput_rps: f64,
+    pub avg_concurrency: f64,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct SloCompliance {
+    pub compliance_pct: f64,
+    pub met: bool,
+    pub first_violation_ms: Option<u64>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct ServerSloCompliance {
+    pub name: String,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub compliance: SloCompliance,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct SloReport {
+    pub percentile: f64,
+    pub threshold_ms: u64,
+    pub overall: SloCompliance,
+    pub per_server: Vec<ServerSloCompliance>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
 pub struct RunMetadata {
     pub algo: String,
     pub tie_break: String,
     pub duration_ms: u64,
+    /// The seed the workload, service times, and any seeded tie-breaks drew
+    /// from, so the run can be reproduced with `--seed`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub seed: Option<u64>,
+    /// The generator the run drew from (`std`, `small`, or `xoshiro`). Only
+    /// results saved before it was always recorded lack it.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub rng: Option<String>,
+    /// How simultaneous events were ordered, when not the default
+    /// `completions-first`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub event_order: Option<String>,
+    /// What each assignment's `score` measures (`ScoreMetric::name`); absent
+    /// for strategies that do not say, whose scores read as ms.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub score_metric: Option<String>,
+    /// The sample limit when `assignments` holds a uniform sample rather than
+    /// every request (`--sample-assignments`).
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub assignment_sample: Option<usize>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct ServerTail {
+    pub name: String,
+    pub tail_requests: u64,
+    pub tail_pct: f64,
+    pub p99_ms: Option<u64>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct TailBreakdown {
+    pub threshold_ms: Option<u64>,
+    pub per_server: Vec<ServerTail>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct LittlesLaw {
+    pub avg_in_system: f64,
+    pub arrival_rate_rps: f64,
+    pub avg_latency_ms: f64,
+    pub predicted_in_system: f64,
+    pub residual: f64,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct AnalyticComparison {
+    pub model: String,
+    pub servers: usize,
+    pub arrival_rate_rps: f64,
+    pub service_time_ms: f64,
+    pub utilization: f64,
+    pub simulated_utilization: f64,
+    pub mmc_wait_ms: Option<f64>,
+    pub approx_wait_ms: Option<f64>,
+    pub simulated_wait_ms: f64,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct CandidateExplain {
+    pub server: String,
+    pub active_connections: u32,
+    pub score: Option<u64>,
+}
+
+/// Why a request went where it did: every server's state at selection time,
+/// the servers that tied for best, and how the tie was resolved.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct SelectionExplain {
+    pub candidates: Vec<CandidateExplain>,
+    pub tied: Vec<String>,
+    pub tie_break: String,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
+pub struct SlowRequest {
+    pub request_id: u64,
+    pub server: String,
+    pub arrival_time_ms: u64,
+    pub started_at: u64,
+    pub queue_wait_ms: u64,
+    pub response_ms: u64,
+    pub score: Option<u64>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize, JsonSchema))]
 pub struct SimulationResult {
     pub assignments: Vec<Assignment>,
     pub totals: Vec<ServerSummary>,
     pub metadata: RunMetadata,
+    pub global: GlobalStats,
     pub phase1_metrics: Phase1Metrics,
+    pub latency_histograms: LatencyHistograms,
+    pub littles_law: LittlesLaw,
+    pub tail: TailBreakdown,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub analytic: Option<AnalyticComparison>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub timeseries: Option<Timeseries>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub timelines: Option<Vec<ServerTimeline>>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub slo: Vec<SloReport>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub slowest: Vec<SlowRequest>,
+    /// One entry per assignment, in the same order, when `explain` is enabled.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub explanations: Vec<SelectionExplain>,
 }
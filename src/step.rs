@@ -0,0 +1,158 @@
+use std::io::{BufRead, Write};
+
+use crate::engine::StepHook;
+use crate::error::{Error, Result};
+use crate::state::ServerState;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+struct ServerSnapshot {
+    in_flight: u32,
+    active_connections: u32,
+    pick_count: u64,
+}
+
+impl From<&ServerState> for ServerSnapshot {
+    fn from(server: &ServerState) -> Self {
+        Self {
+            in_flight: server.in_flight,
+            active_connections: server.active_connections,
+            pick_count: server.pick_count,
+        }
+    }
+}
+
+/// Interactive `--step` debugger: prints the per-server delta after each event
+/// and waits for a command (Enter/`n` next, `c` continue, `q` quit).
+pub struct StepDebugger<R, W> {
+    input: R,
+    output: W,
+    previous: Vec<ServerSnapshot>,
+    events: usize,
+    running: bool,
+}
+
+impl<R: BufRead, W: Write> StepDebugger<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Self {
+            input,
+            output,
+            previous: Vec::new(),
+            events: 0,
+            running: false,
+        }
+    }
+
+    fn write_delta(&mut self, time_ms: u64, servers: &[ServerState]) -> std::io::Result<()> {
+        writeln!(self.output, "[event {}] t={}ms", self.events, time_ms)?;
+        for (idx, server) in servers.iter().enumerate() {
+            let before = self.previous.get(idx).copied().unwrap_or_default();
+            let after = ServerSnapshot::from(server);
+            if before == after {
+                continue;
+            }
+            writeln!(
+                self.output,
+                "  {}: in_flight {} -> {}, active {} -> {}, picks {} -> {}",
+                server.name,
+                before.in_flight,
+                after.in_flight,
+                before.active_connections,
+                after.active_connections,
+                before.pick_count,
+                after.pick_count
+            )?;
+        }
+        Ok(())
+    }
+
+    fn prompt(&mut self) -> std::io::Result<String> {
+        write!(self.output, "step [Enter/n=next, c=continue, q=quit]> ")?;
+        self.output.flush()?;
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            writeln!(self.output)?;
+            return Ok("c".to_string());
+        }
+        Ok(line.trim().to_string())
+    }
+}
+
+impl<R: BufRead, W: Write> StepHook for StepDebugger<R, W> {
+    fn after_event(&mut self, time_ms: u64, servers: &[ServerState]) -> Result<()> {
+        self.events += 1;
+        if !self.running {
+            self.write_delta(time_ms, servers).map_err(step_error)?;
+            loop {
+                match self.prompt().map_err(step_error)?.as_str() {
+                    "" | "n" => break,
+                    "c" => {
+                        self.running = true;
+                        break;
+                    }
+                    "q" => return Err(Error::Stopped(time_ms)),
+                    _ => writeln!(self.output, "unknown command").map_err(step_error)?,
+                }
+            }
+        }
+        self.previous = servers.iter().map(ServerSnapshot::from).collect();
+        Ok(())
+    }
+}
+
+fn step_error(err: std::io::Error) -> Error {
+    Error::Output(format!("step mode I/O failed: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(name: &str, in_flight: u32, pick_count: u64) -> ServerState {
+        ServerState {
+            id: 0,
+            name: name.to_string(),
+            base_latency_ms: 10,
+            weight: 1,
+            active_connections: in_flight,
+            pick_count,
+            in_flight,
+            next_available_ms: 0,
+        }
+    }
+
+    #[test]
+    fn step_debugger_prints_deltas_until_continue() {
+        let mut output = Vec::new();
+        let mut debugger = StepDebugger::new("n\nc\n".as_bytes(), &mut output);
+        debugger
+            .after_event(0, &[server("a", 1, 1), server("b", 0, 0)])
+            .expect("first step");
+        debugger
+            .after_event(0, &[server("a", 1, 1), server("b", 1, 1)])
+            .expect("second step");
+        debugger
+            .after_event(10, &[server("a", 0, 1), server("b", 1, 1)])
+            .expect("continued step");
+        drop(debugger);
+
+        let output = String::from_utf8(output).expect("utf8 output");
+        assert_eq!(
+            output,
+            concat!(
+                "[event 1] t=0ms\n",
+                "  a: in_flight 0 -> 1, active 0 -> 1, picks 0 -> 1\n",
+                "step [Enter/n=next, c=continue, q=quit]> ",
+                "[event 2] t=0ms\n",
+                "  b: in_flight 0 -> 1, active 0 -> 1, picks 0 -> 1\n",
+                "step [Enter/n=next, c=continue, q=quit]> ",
+            )
+        );
+    }
+
+    #[test]
+    fn step_debugger_quit_stops_the_run() {
+        let mut debugger = StepDebugger::new("q\n".as_bytes(), Vec::new());
+        let result = debugger.after_event(42, &[server("a", 1, 1)]);
+        assert!(matches!(result, Err(Error::Stopped(42))));
+    }
+}
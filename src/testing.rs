@@ -0,0 +1,315 @@
+//! Property-testing harness for strategy authors (`testing` feature):
+//! proptest generators for valid configs and checks for the invariants every
+//! run must satisfy.
+//!
+//! ```ignore
+//! proptest! {
+//!     #[test]
+//!     fn my_strategy_keeps_invariants(config in arb_sim_config()) {
+//!         check_strategy(&config, Box::new(MyStrategy::default())).unwrap();
+//!     }
+//! }
+//! ```
+
+use std::collections::HashSet;
+
+use proptest::prelude::*;
+use rand::{RngCore, SeedableRng};
+
+use crate::algorithms::SelectionStrategy;
+use crate::engine::{build_config_requests, EngineRng, PcgRng, SimulationEngine, XoshiroRng};
+use crate::error::{Error, Result};
+use crate::events::EngineEvent;
+use crate::models::{
+    AlgoConfig, CapacityConfig, EventOrder, LatencyDistribution, RandomnessConfig, RequestProfile,
+    RngKind, ServerConfig, ServerModel, SimConfig,
+};
+use crate::state::SimulationResult;
+
+pub fn arb_latency() -> impl Strategy<Value = Option<LatencyDistribution>> {
+    prop_oneof![
+        Just(None),
+        Just(Some(LatencyDistribution::Fixed)),
+        (0u64..20).prop_map(|jitter_ms| Some(LatencyDistribution::Uniform { jitter_ms })),
+        Just(Some(LatencyDistribution::Exponential)),
+    ]
+}
+
+/// A server named `s{idx}` with up to 4 slots and any latency model.
+pub fn arb_server_config(idx: usize) -> impl Strategy<Value = ServerConfig> {
+    (1u64..200, 1u32..10, 1u32..5, arb_latency()).prop_map(
+        move |(base_latency_ms, weight, concurrency, latency)| ServerConfig {
+            name: format!("s{}", idx),
+            base_latency_ms,
+            weight,
+            model: ServerModel {
+                latency,
+                capacity: (concurrency > 1).then_some(CapacityConfig { concurrency }),
+                ..ServerModel::default()
+            },
+        },
+    )
+}
+
+/// Fixed-count, Poisson, and burst workloads of up to a few hundred requests.
+pub fn arb_request_profile() -> impl Strategy<Value = RequestProfile> {
+    prop_oneof![
+        (1usize..200).prop_map(RequestProfile::FixedCount),
+        (50.0f64..2000.0, 100u64..1000)
+            .prop_map(|(rate, duration_ms)| RequestProfile::Poisson { rate, duration_ms }),
+        (1usize..100, 0u64..100).prop_map(|(count, at_ms)| RequestProfile::Burst { count, at_ms }),
+    ]
+}
+
+pub fn arb_builtin_algo() -> impl Strategy<Value = AlgoConfig> {
+    prop::sample::select(AlgoConfig::BUILTIN.to_vec())
+}
+
+/// Valid configs with 1-8 servers, any built-in algorithm, and stable or
+/// seeded tie-breaking.
+pub fn arb_sim_config() -> impl Strategy<Value = SimConfig> {
+    let servers =
+        (1usize..=8).prop_flat_map(|count| (0..count).map(arb_server_config).collect::<Vec<_>>());
+    (
+        servers,
+        arb_request_profile(),
+        arb_builtin_algo(),
+        prop::option::of(any::<u64>()),
+    )
+        .prop_map(|(servers, requests, algo, seed)| SimConfig {
+            servers,
+            requests,
+            algo,
+            randomness: seed.map_or(RandomnessConfig::Stable, RandomnessConfig::Seeded),
+            rng: RngKind::Std,
+            event_order: EventOrder::CompletionsFirst,
+            allow_large: false,
+            timeseries_bucket_ms: None,
+            record_timelines: false,
+            slos: Vec::new(),
+            slowest_requests: 0,
+            explain: false,
+            max_virtual_time_ms: None,
+            max_events: None,
+        })
+}
+
+/// Runs `config` with `strategy` one event at a time, checking after every
+/// event that in-flight counts match arrivals minus completions, then checks
+/// the result with `check_result`.
+pub fn check_strategy(
+    config: &SimConfig,
+    strategy: Box<dyn SelectionStrategy>,
+) -> Result<SimulationResult> {
+    match config.rng {
+        RngKind::Std => check_strategy_with::<EngineRng>(config, strategy),
+        RngKind::Small => check_strategy_with::<PcgRng>(config, strategy),
+        RngKind::Xoshiro => check_strategy_with::<XoshiroRng>(config, strategy),
+    }
+}
+
+fn check_strategy_with<R: RngCore + SeedableRng>(
+    config: &SimConfig,
+    strategy: Box<dyn SelectionStrategy>,
+) -> Result<SimulationResult> {
+    let mut engine = SimulationEngine::<R>::seeded(config.clone(), strategy);
+    engine.start(true)?;
+    let (mut arrived, mut completed) = (0u64, 0u64);
+    while let Some(event) = engine.step()? {
+        match event {
+            EngineEvent::Assigned { assignment, .. } => {
+                if assignment.server_id >= config.servers.len() {
+                    return Err(violation(format!(
+                        "request {} assigned to server index {} of {}",
+                        assignment.request_id,
+                        assignment.server_id,
+                        config.servers.len()
+                    )));
+                }
+                arrived += 1;
+            }
+            EngineEvent::Completed { .. } => completed += 1,
+        }
+        let state = engine.state();
+        let in_flight = state
+            .servers
+            .iter()
+            .map(|server| u64::from(server.in_flight))
+            .sum::<u64>();
+        if completed > arrived || in_flight != arrived - completed {
+            return Err(violation(format!(
+                "t={}ms: {} requests in flight after {} arrivals and {} completions",
+                state.time_ms, in_flight, arrived, completed
+            )));
+        }
+        if let Some(server) = state
+            .servers
+            .iter()
+            .find(|server| server.in_flight != server.active_connections)
+        {
+            return Err(violation(format!(
+                "t={}ms: {} has {} in flight but {} active connections",
+                state.time_ms, server.name, server.in_flight, server.active_connections
+            )));
+        }
+    }
+    let result = engine.finish()?;
+    check_result(config, &result)?;
+    Ok(result)
+}
+
+/// Every invariant a full-output run (assignments stored) must satisfy.
+pub fn check_result(config: &SimConfig, result: &SimulationResult) -> Result<()> {
+    check_conservation(config, result)?;
+    check_assignment_times(result)?;
+    check_totals_match_assignments(result)
+}
+
+/// Every generated request is assigned exactly once, and the totals count
+/// each of them.
+pub fn check_conservation(config: &SimConfig, result: &SimulationResult) -> Result<()> {
+    let expected = build_config_requests(config)?.len();
+    if result.assignments.len() != expected {
+        return Err(violation(format!(
+            "{} requests generated but {} assigned",
+            expected,
+            result.assignments.len()
+        )));
+    }
+    let mut seen = HashSet::new();
+    if let Some(assignment) = result
+        .assignments
+        .iter()
+        .find(|assignment| !seen.insert(assignment.request_id))
+    {
+        return Err(violation(format!(
+            "request {} assigned more than once",
+            assignment.request_id
+        )));
+    }
+    let total = result
+        .totals
+        .iter()
+        .map(|server| server.requests)
+        .sum::<u64>();
+    if total != expected as u64 || result.global.total_requests != expected as u64 {
+        return Err(violation(format!(
+            "{} requests generated but totals count {} (global {})",
+            expected, total, result.global.total_requests
+        )));
+    }
+    Ok(())
+}
+
+/// Arrival <= start <= completion, with queue and service times matching.
+pub fn check_assignment_times(result: &SimulationResult) -> Result<()> {
+    for assignment in &result.assignments {
+        if assignment.started_at < assignment.arrival_time_ms
+            || assignment.completed_at < assignment.started_at
+        {
+            return Err(violation(format!(
+                "request {}: arrived {}ms, started {}ms, completed {}ms",
+                assignment.request_id,
+                assignment.arrival_time_ms,
+                assignment.started_at,
+                assignment.completed_at
+            )));
+        }
+        if assignment.queued_ms != assignment.started_at - assignment.arrival_time_ms
+            || assignment.service_ms != assignment.completed_at - assignment.started_at
+        {
+            return Err(violation(format!(
+                "request {}: queued {}ms / service {}ms disagree with its timestamps",
+                assignment.request_id, assignment.queued_ms, assignment.service_ms
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Per-server request counts and average response times agree with the
+/// assignments.
+pub fn check_totals_match_assignments(result: &SimulationResult) -> Result<()> {
+    for (server_id, summary) in result.totals.iter().enumerate() {
+        let responses = result
+            .assignments
+            .iter()
+            .filter(|assignment| assignment.server_id == server_id)
+            .map(|assignment| assignment.completed_at - assignment.arrival_time_ms)
+            .collect::<Vec<_>>();
+        if responses.len() != summary.requests as usize {
+            return Err(violation(format!(
+                "{}: totals count {} requests but {} are assigned to it",
+                summary.name,
+                summary.requests,
+                responses.len()
+            )));
+        }
+        let average = responses
+            .iter()
+            .sum::<u64>()
+            .checked_div(responses.len() as u64)
+            .unwrap_or(0);
+        if average != summary.avg_response_ms {
+            return Err(violation(format!(
+                "{}: avg response {}ms but assignments average {}ms",
+                summary.name, summary.avg_response_ms, average
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn violation(message: String) -> Error {
+    Error::Invariant(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::build_strategy;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn builtin_strategies_keep_every_invariant(config in arb_sim_config()) {
+            let strategy = build_strategy(config.algo.clone()).expect("built-in algorithm");
+            if let Err(err) = check_strategy(&config, strategy) {
+                prop_assert!(false, "{}", err);
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn generated_configs_round_trip_through_json_and_toml(config in arb_sim_config()) {
+            let json = serde_json::to_string(&config).expect("config should serialize");
+            prop_assert_eq!(&serde_json::from_str::<SimConfig>(&json).expect("JSON"), &config);
+            let toml = toml::to_string(&config).expect("config should serialize");
+            prop_assert_eq!(&toml::from_str::<SimConfig>(&toml).expect("TOML"), &config);
+        }
+    }
+
+    #[test]
+    fn checks_report_the_broken_invariant() {
+        let config = SimConfig::builder()
+            .server("a", 10)
+            .requests(2)
+            .algo(AlgoConfig::RoundRobin)
+            .build()
+            .expect("config should build");
+        let mut result = crate::engine::run_simulation(&config).expect("simulation should run");
+        check_result(&config, &result).expect("a real run keeps the invariants");
+
+        result.assignments[1].started_at = 0;
+        assert_eq!(
+            check_result(&config, &result).unwrap_err().to_string(),
+            "invariant violated: request 2: arrived 1ms, started 0ms, completed 20ms"
+        );
+        result.assignments.pop();
+        assert_eq!(
+            check_result(&config, &result).unwrap_err().to_string(),
+            "invariant violated: 2 requests generated but 1 assigned"
+        );
+    }
+}
@@ -0,0 +1,94 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::state::{ServerTimeline, TimelinePoint};
+
+/// Collects queue and connection changes per server and folds them into step
+/// functions once the run is over, so the engine doesn't need extra events.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TimelineRecorder {
+    deltas: Vec<Vec<(u64, i64, i64)>>,
+}
+
+impl TimelineRecorder {
+    pub fn new(server_count: usize) -> Self {
+        Self {
+            deltas: vec![Vec::new(); server_count],
+        }
+    }
+
+    pub fn record(
+        &mut self,
+        server_idx: usize,
+        arrival_ms: u64,
+        started_at: u64,
+        completed_at: u64,
+    ) {
+        let deltas = &mut self.deltas[server_idx];
+        if started_at > arrival_ms {
+            deltas.push((arrival_ms, 1, 1));
+            deltas.push((started_at, -1, 0));
+        } else {
+            deltas.push((arrival_ms, 0, 1));
+        }
+        deltas.push((completed_at, 0, -1));
+    }
+
+    pub fn finish(self, server_names: Vec<String>) -> Vec<ServerTimeline> {
+        self.deltas
+            .into_iter()
+            .zip(server_names)
+            .map(|(mut deltas, name)| {
+                deltas.sort_by_key(|(time_ms, _, _)| *time_ms);
+                let mut points = Vec::new();
+                let mut queue_depth = 0i64;
+                let mut active_connections = 0i64;
+                let mut idx = 0;
+                while idx < deltas.len() {
+                    let time_ms = deltas[idx].0;
+                    let (prev_queue, prev_active) = (queue_depth, active_connections);
+                    while idx < deltas.len() && deltas[idx].0 == time_ms {
+                        queue_depth += deltas[idx].1;
+                        active_connections += deltas[idx].2;
+                        idx += 1;
+                    }
+                    if queue_depth != prev_queue || active_connections != prev_active {
+                        // Every instant's deltas are applied together, and a
+                        // request leaves only after it arrived, so neither
+                        // count goes negative.
+                        points.push(TimelinePoint {
+                            time_ms,
+                            queue_depth: u64::try_from(queue_depth)
+                                .expect("queue depth is never negative"),
+                            active_connections: u64::try_from(active_connections)
+                                .expect("active connections are never negative"),
+                        });
+                    }
+                }
+                ServerTimeline { name, points }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeline_folds_simultaneous_changes() {
+        let mut recorder = TimelineRecorder::new(1);
+        recorder.record(0, 0, 0, 10);
+        recorder.record(0, 0, 10, 20);
+        recorder.record(0, 10, 20, 30);
+
+        let timelines = recorder.finish(vec!["a".to_string()]);
+        let points = timelines[0]
+            .points
+            .iter()
+            .map(|point| (point.time_ms, point.queue_depth, point.active_connections))
+            .collect::<Vec<_>>();
+        assert_eq!(points, vec![(0, 1, 2), (20, 0, 1), (30, 0, 0)]);
+    }
+}
@@ -0,0 +1,124 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::state::{Timeseries, TimeseriesBucket};
+
+/// Accumulates per-interval activity while the engine runs. Arrivals and
+/// in-flight counts are observed in event order; completions are attributed
+/// to the bucket their completion time falls into.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TimeseriesRecorder {
+    bucket_ms: u64,
+    current_in_flight: Vec<u32>,
+    arrivals: Vec<u64>,
+    max_in_flight: Vec<Vec<u32>>,
+    completions: Vec<u64>,
+    latency_totals: Vec<u64>,
+}
+
+impl TimeseriesRecorder {
+    pub fn new(bucket_ms: u64, server_count: usize) -> Self {
+        Self {
+            bucket_ms,
+            current_in_flight: vec![0; server_count],
+            arrivals: Vec::new(),
+            max_in_flight: Vec::new(),
+            completions: Vec::new(),
+            latency_totals: Vec::new(),
+        }
+    }
+
+    pub fn record_arrival(&mut self, time_ms: u64, server_idx: usize, in_flight: u32) {
+        let idx = self.bucket_index(time_ms);
+        self.ensure_activity_buckets(idx);
+        self.arrivals[idx] += 1;
+        self.observe_in_flight(idx, server_idx, in_flight);
+    }
+
+    pub fn record_release(&mut self, time_ms: u64, server_idx: usize, in_flight: u32) {
+        let idx = self.bucket_index(time_ms);
+        self.ensure_activity_buckets(idx);
+        self.observe_in_flight(idx, server_idx, in_flight);
+    }
+
+    pub fn record_completion(&mut self, completed_at_ms: u64, response_time_ms: u64) {
+        let idx = self.bucket_index(completed_at_ms);
+        if idx >= self.completions.len() {
+            self.completions.resize(idx + 1, 0);
+            self.latency_totals.resize(idx + 1, 0);
+        }
+        self.completions[idx] += 1;
+        self.latency_totals[idx] += response_time_ms;
+    }
+
+    pub fn finish(mut self, server_names: Vec<String>) -> Timeseries {
+        let len = self.arrivals.len().max(self.completions.len());
+        self.ensure_activity_buckets(len.saturating_sub(1));
+        self.completions.resize(len, 0);
+        self.latency_totals.resize(len, 0);
+
+        let buckets = (0..len)
+            .map(|idx| {
+                let completions = self.completions[idx];
+                let throughput_rps = (completions as f64 / self.bucket_ms as f64) * 1000.0;
+                TimeseriesBucket {
+                    start_ms: idx as u64 * self.bucket_ms,
+                    arrivals: self.arrivals[idx],
+                    completions,
+                    throughput_rps: (throughput_rps * 100.0).round() / 100.0,
+                    avg_latency_ms: self.latency_totals[idx].checked_div(completions),
+                    max_in_flight: std::mem::take(&mut self.max_in_flight[idx]),
+                }
+            })
+            .collect();
+
+        Timeseries {
+            bucket_ms: self.bucket_ms,
+            servers: server_names,
+            buckets,
+        }
+    }
+
+    fn bucket_index(&self, time_ms: u64) -> usize {
+        (time_ms / self.bucket_ms) as usize
+    }
+
+    fn ensure_activity_buckets(&mut self, idx: usize) {
+        while self.arrivals.len() <= idx {
+            self.arrivals.push(0);
+            self.max_in_flight.push(self.current_in_flight.clone());
+        }
+    }
+
+    fn observe_in_flight(&mut self, idx: usize, server_idx: usize, in_flight: u32) {
+        self.current_in_flight[server_idx] = in_flight;
+        let slot = &mut self.max_in_flight[idx][server_idx];
+        *slot = (*slot).max(in_flight);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_flight_carries_into_quiet_buckets() {
+        let mut recorder = TimeseriesRecorder::new(10, 2);
+        recorder.record_arrival(0, 0, 1);
+        recorder.record_completion(25, 25);
+        recorder.record_release(25, 0, 0);
+
+        let timeseries = recorder.finish(vec!["a".to_string(), "b".to_string()]);
+        let in_flight = timeseries
+            .buckets
+            .iter()
+            .map(|bucket| bucket.max_in_flight.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(in_flight, vec![vec![1, 0], vec![1, 0], vec![1, 0]]);
+        assert_eq!(timeseries.buckets[2].completions, 1);
+        assert_eq!(timeseries.buckets[2].avg_latency_ms, Some(25));
+        assert_eq!(timeseries.buckets[2].throughput_rps, 100.0);
+        assert_eq!(timeseries.buckets[1].avg_latency_ms, None);
+    }
+}
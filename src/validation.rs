@@ -0,0 +1,273 @@
+//! Every rule a `SimConfig` must satisfy, in one place. `config_problems`
+//! lists all violations (the `validate` subcommand, `--dry-run`); `validate`
+//! fails on the first (the builder, the engine, and the wasm/gRPC entry
+//! points). The CLI parsers and the workload generator check single values
+//! with the same helpers, so every path reports the same error.
+
+use std::collections::HashSet;
+
+use crate::algorithms::{self, cycle_length};
+use crate::error::{Error, Result};
+use crate::models::{AlgoConfig, RequestProfile, SimConfig};
+
+/// Largest total weight, after dividing out the weights' GCD, that a
+/// weighted round-robin config may use; its rotation is this many picks long.
+pub const MAX_TOTAL_WEIGHT: u64 = u32::MAX as u64;
+/// Most servers a config may have without `allow_large`.
+pub const MAX_SERVERS: usize = 10_000;
+/// Most requests (expected, for Poisson) a config may generate without
+/// `allow_large`; a full-output run stores every one of them.
+pub const MAX_REQUESTS: u64 = 100_000_000;
+
+/// `Ok` when `config` has no problems, otherwise the first of them.
+pub fn validate(config: &SimConfig) -> Result<()> {
+    match config_problems(config).into_iter().next() {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// Every validation problem in `config`, in the order `validate` would
+/// report them.
+pub fn config_problems(config: &SimConfig) -> Vec<Error> {
+    let mut problems = Vec::new();
+    if config.servers.is_empty() {
+        problems.push(Error::EmptyServers);
+    }
+    let mut names = HashSet::new();
+    for server in &config.servers {
+        if server.name.trim().is_empty() {
+            problems.push(Error::InvalidServerEntry(server.name.clone()));
+        }
+        problems.extend(latency_problem(server.base_latency_ms, &server.name));
+        problems.extend(weight_problem(server.weight, &server.name));
+        if server.model.concurrency() == 0 {
+            problems.push(Error::InvalidServerModel(
+                server.name.clone(),
+                "capacity.concurrency must be > 0".to_string(),
+            ));
+        }
+        let outages = server.model.failure.iter().flat_map(|f| &f.outages);
+        if outages.into_iter().any(|outage| outage.duration_ms == 0) {
+            problems.push(Error::InvalidServerModel(
+                server.name.clone(),
+                "outage duration_ms must be > 0".to_string(),
+            ));
+        }
+        if !names.insert(server.name.clone()) {
+            problems.push(Error::DuplicateServerName(server.name.clone()));
+        }
+    }
+
+    if config.algo == AlgoConfig::WeightedRoundRobin {
+        let cycle = cycle_length(config.servers.iter().map(|server| server.weight));
+        if cycle > MAX_TOTAL_WEIGHT {
+            problems.push(Error::InvalidTotalWeight(cycle));
+        }
+    }
+    if config.servers.len() > MAX_SERVERS && !config.allow_large {
+        problems.push(Error::TooManyServers(config.servers.len()));
+    }
+    let expected_requests = match config.requests {
+        RequestProfile::FixedCount(count) | RequestProfile::Burst { count, .. } => count as f64,
+        RequestProfile::Poisson { rate, duration_ms } => rate * duration_ms as f64 / 1000.0,
+    };
+    if expected_requests > MAX_REQUESTS as f64 && !config.allow_large {
+        problems.push(Error::TooManyRequests(expected_requests.round() as u64));
+    }
+
+    problems.extend(request_problems(&config.requests));
+
+    if config.timeseries_bucket_ms == Some(0) {
+        problems.push(Error::InvalidTimeseriesBucket(0));
+    }
+
+    for slo in &config.slos {
+        problems.extend(slo_problem(slo.percentile, &slo.to_string()));
+    }
+
+    if let AlgoConfig::Custom(name) = &config.algo {
+        if !algorithms::is_registered(name) {
+            problems.push(Error::UnknownAlgorithm(name.clone()));
+        }
+    }
+
+    problems
+}
+
+/// Problems with a workload on its own: no requests, or a Poisson rate that is
+/// not a positive finite number, or an empty arrival window.
+pub fn request_problems(profile: &RequestProfile) -> Vec<Error> {
+    let mut problems = Vec::new();
+    match *profile {
+        RequestProfile::FixedCount(0) | RequestProfile::Burst { count: 0, .. } => {
+            problems.push(Error::RequestsZero)
+        }
+        RequestProfile::FixedCount(_) | RequestProfile::Burst { .. } => {}
+        RequestProfile::Poisson { rate, duration_ms } => {
+            if !(rate > 0.0 && rate.is_finite()) {
+                problems.push(Error::InvalidRequestRate(rate));
+            }
+            if duration_ms == 0 {
+                problems.push(Error::InvalidRequestDuration(duration_ms));
+            }
+        }
+    }
+    problems
+}
+
+/// `label` names the server, or quotes the spec a parser read it from.
+pub(crate) fn latency_problem(latency_ms: u64, label: &str) -> Option<Error> {
+    (latency_ms == 0).then(|| Error::InvalidLatencyValue(label.to_string()))
+}
+
+pub(crate) fn weight_problem(weight: u32, label: &str) -> Option<Error> {
+    (weight == 0).then(|| Error::InvalidWeightValue(label.to_string()))
+}
+
+pub(crate) fn slo_problem(percentile: f64, label: &str) -> Option<Error> {
+    (!(percentile > 0.0 && percentile <= 100.0)).then(|| Error::InvalidSlo(label.to_string()))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::models::{
+        CapacityConfig, EventOrder, RandomnessConfig, RngKind, ServerConfig, ServerModel,
+    };
+
+    /// A round-robin config over `servers` with one request and every
+    /// option off; the engine tests build on it too.
+    pub(crate) fn config_with_servers(servers: Vec<ServerConfig>) -> SimConfig {
+        SimConfig {
+            servers,
+            requests: RequestProfile::FixedCount(1),
+            algo: AlgoConfig::RoundRobin,
+            randomness: RandomnessConfig::Stable,
+            rng: RngKind::Std,
+            event_order: EventOrder::CompletionsFirst,
+            allow_large: false,
+            timeseries_bucket_ms: None,
+            record_timelines: false,
+            slos: Vec::new(),
+            slowest_requests: 0,
+            explain: false,
+            max_virtual_time_ms: None,
+            max_events: None,
+        }
+    }
+
+    /// A server named `api` (10ms, weight 1) with `model`.
+    pub(crate) fn modeled_server(model: ServerModel) -> ServerConfig {
+        ServerConfig {
+            name: "api".to_string(),
+            base_latency_ms: 10,
+            weight: 1,
+            model,
+        }
+    }
+
+    #[test]
+    fn workload_rules_reject_rates_that_are_not_positive_and_finite() {
+        for rate in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            let problems = request_problems(&RequestProfile::Poisson {
+                rate,
+                duration_ms: 10,
+            });
+            assert!(
+                matches!(problems.as_slice(), [Error::InvalidRequestRate(_)]),
+                "{}",
+                rate
+            );
+        }
+        assert!(matches!(
+            request_problems(&RequestProfile::Burst { count: 0, at_ms: 5 }).as_slice(),
+            [Error::RequestsZero]
+        ));
+        assert!(request_problems(&RequestProfile::FixedCount(1)).is_empty());
+    }
+
+    #[test]
+    fn config_problems_reports_every_issue() {
+        let config = config_with_servers(vec![
+            ServerConfig {
+                name: "a".to_string(),
+                base_latency_ms: 0,
+                weight: 1,
+                model: ServerModel::default(),
+            },
+            ServerConfig {
+                name: "a".to_string(),
+                base_latency_ms: 20,
+                weight: 0,
+                model: ServerModel::default(),
+            },
+        ]);
+        let problems = config_problems(&config)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            problems,
+            vec![
+                "latency must be > 0 in 'a'",
+                "weight must be > 0 in 'a'",
+                "duplicate server name 'a'",
+            ]
+        );
+    }
+
+    #[test]
+    fn config_problems_reject_pathological_sizes_unless_allowed() {
+        let server = |idx: usize, weight: u32| ServerConfig {
+            name: format!("s{}", idx),
+            base_latency_ms: 10,
+            weight,
+            model: ServerModel::default(),
+        };
+        let mut config = config_with_servers((0..=MAX_SERVERS).map(|idx| server(idx, 1)).collect());
+        config.requests = RequestProfile::Poisson {
+            rate: 1_000_000.0,
+            duration_ms: 200_000,
+        };
+        let problems = config_problems(&config);
+        assert!(matches!(
+            problems.as_slice(),
+            [
+                Error::TooManyServers(10_001),
+                Error::TooManyRequests(200_000_000)
+            ]
+        ));
+        assert!(problems[0].to_string().contains("pass --allow-large"));
+
+        config.allow_large = true;
+        assert!(config_problems(&config).is_empty());
+
+        // Weights sharing a factor are reduced before the cap applies.
+        config.algo = AlgoConfig::WeightedRoundRobin;
+        config.servers = vec![server(0, u32::MAX), server(1, u32::MAX)];
+        assert!(config_problems(&config).is_empty());
+        config.servers[1].weight = u32::MAX - 1;
+        assert!(matches!(
+            config_problems(&config).as_slice(),
+            [Error::InvalidTotalWeight(cycle)] if *cycle == 2 * u64::from(u32::MAX) - 1
+        ));
+
+        // Only weighted round-robin walks the rotation, so the cap is its own.
+        config.algo = AlgoConfig::RoundRobin;
+        assert!(config_problems(&config).is_empty());
+    }
+
+    #[test]
+    fn zero_concurrency_is_a_config_problem() {
+        let config = config_with_servers(vec![modeled_server(ServerModel {
+            capacity: Some(CapacityConfig { concurrency: 0 }),
+            ..ServerModel::default()
+        })]);
+
+        assert!(matches!(
+            validate(&config),
+            Err(Error::InvalidServerModel(name, _)) if name == "api"
+        ));
+    }
+}
@@ -0,0 +1,56 @@
+use crate::engine::run_simulation;
+use crate::error::{Error, Result};
+use crate::models::SimConfig;
+use crate::output::{Formatter, JsonFormatter};
+use crate::validation;
+
+/// Runs a JSON-encoded `SimConfig` and returns the `--format json` document.
+/// Backs the `run_simulation_json` binding exported by the `wasm` feature.
+pub fn simulate_json(config_json: &str) -> Result<String> {
+    let config: SimConfig =
+        serde_json::from_str(config_json).map_err(|err| Error::json("config JSON", None, err))?;
+    validation::validate(&config)?;
+    let result = run_simulation(&config)?;
+    Ok(JsonFormatter::default().write(&result))
+}
+
+#[cfg(feature = "wasm")]
+mod bindings {
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen]
+    pub fn run_simulation_json(config_json: &str) -> Result<String, JsError> {
+        super::simulate_json(config_json).map_err(|err| JsError::new(&err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simulate_json_runs_a_json_config() {
+        let output = simulate_json(
+            r#"{
+                "servers": [{ "name": "a", "base_latency_ms": 10 }, { "name": "b", "base_latency_ms": 20 }],
+                "requests": 4,
+                "algo": "round-robin"
+            }"#,
+        )
+        .expect("simulation should succeed");
+        let result: serde_json::Value = serde_json::from_str(&output).expect("output is JSON");
+
+        assert_eq!(result["global"]["total_requests"], 4);
+        assert_eq!(result["assignments"][1]["server_name"], "b");
+    }
+
+    #[test]
+    fn simulate_json_reports_config_errors() {
+        let err = simulate_json(r#"{ "servers": [], "requests": 4, "algo": "round-robin" }"#)
+            .expect_err("empty servers should fail");
+        assert_eq!(err.to_string(), "servers must not be empty");
+
+        let err = simulate_json("{").expect_err("malformed JSON should fail");
+        assert!(err.to_string().starts_with("invalid config JSON:"));
+    }
+}
@@ -69,3 +69,176 @@ fn duplicate_server_names_fail() {
         .failure()
         .stderr(contains("Error: duplicate server name 'a'"));
 }
+
+#[test]
+fn assertion_violation_exits_non_zero() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "least-response-time",
+        "--server",
+        "fast:10",
+        "--server",
+        "slow:100",
+        "--requests",
+        "4",
+        "--quiet",
+        "--assert-max-imbalance",
+        "0.1",
+    ]);
+    cmd.assert().failure().stderr(predicates::str::diff(
+        "Error: assertion failed: request imbalance 1.0000 exceeds 0.1\n",
+    ));
+}
+
+#[test]
+fn assertions_within_limits_succeed() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "round-robin",
+        "--server",
+        "a:10",
+        "--server",
+        "b:10",
+        "--requests",
+        "4",
+        "--quiet",
+        "--assert-max-imbalance",
+        "0",
+        "--assert-p99-ms",
+        "50",
+    ]);
+    cmd.assert().success();
+}
+
+#[test]
+fn rate_conflicts_with_requests() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "run",
+        "--algo",
+        "round-robin",
+        "--server",
+        "a:10",
+        "--rate",
+        "100",
+        "--duration-ms",
+        "1000",
+        "--requests",
+        "3",
+    ]);
+    cmd.assert().failure().stderr(contains(
+        "'--rate <RPS>' cannot be used with '--requests <REQUESTS>'",
+    ));
+}
+
+#[test]
+fn deterministic_rejects_implicit_and_entropy_seeds() {
+    let mut implicit = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    implicit.args([
+        "run",
+        "--algo",
+        "round-robin",
+        "--server",
+        "a:10",
+        "--rate",
+        "10",
+        "--duration-ms",
+        "1000",
+        "--deterministic",
+    ]);
+    implicit.assert().failure().stderr(contains(
+        "Error: --deterministic: Poisson arrivals would use the implicit seed 0; pass --seed",
+    ));
+
+    let mut entropy = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    entropy.args([
+        "run",
+        "--algo",
+        "round-robin",
+        "--server",
+        "a:10",
+        "--requests",
+        "3",
+        "--seed",
+        "auto",
+        "--deterministic",
+    ]);
+    entropy
+        .assert()
+        .failure()
+        .stderr(contains("`--seed auto` draws the seed from entropy"));
+}
+
+#[test]
+fn max_virtual_time_aborts_with_partial_metadata() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "run",
+        "--algo",
+        "round-robin",
+        "--server",
+        "a:10",
+        "--requests",
+        "100",
+        "--max-virtual-time",
+        "200",
+    ]);
+    cmd.assert().failure().stderr(contains(
+        "Error: simulation aborted: --max-virtual-time 200ms exceeded (algo round-robin, t=200ms, 120 events processed, 20 of 100 requests completed)",
+    ));
+}
+
+#[test]
+fn zero_rate_fails() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "run",
+        "--algo",
+        "round-robin",
+        "--server",
+        "a:10",
+        "--rate",
+        "0",
+        "--duration-ms",
+        "1000",
+    ]);
+    cmd.assert()
+        .failure()
+        .stderr(contains("Error: request rate must be > 0 (got 0)"));
+}
+
+#[test]
+fn oversized_runs_need_allow_large() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "run",
+        "--algo",
+        "round-robin",
+        "--server",
+        "a:10",
+        "--requests",
+        "100000001",
+    ]);
+    cmd.assert().failure().stderr(contains(
+        "Error: 100000001 requests is over the limit of 100000000; pass --allow-large",
+    ));
+}
+
+#[test]
+fn zero_heatmap_bucket_fails() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "run",
+        "--server",
+        "a:10",
+        "--requests",
+        "2",
+        "--heatmap-bucket-ms",
+        "0",
+    ]);
+    cmd.assert().failure().stderr(contains(
+        "invalid value '0' for '--heatmap-bucket-ms <MS>': 0 is not in 1..",
+    ));
+}
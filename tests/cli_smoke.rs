@@ -1,3 +1,4 @@
+use predicates::prelude::PredicateBooleanExt;
 use predicates::str::diff;
 
 #[test]
@@ -8,8 +9,8 @@ fn summary_round_robin_is_stable() {
         "tie_break: seeded(42)\n",
         "duration_ms: 21\n",
         "Summary:\n",
-        "a: 2 requests (avg response: 14ms)\n",
-        "b: 1 requests (avg response: 20ms)\n",
+        "a: 2 requests (avg response: 14ms, queued: 4ms, service: 10ms)\n",
+        "b: 1 requests (avg response: 20ms, queued: 0ms, service: 20ms)\n",
     );
 
     let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
@@ -38,8 +39,8 @@ fn summary_least_response_time_is_stable() {
         "tie_break: seeded(7)\n",
         "duration_ms: 20\n",
         "Summary:\n",
-        "fast: 2 requests (avg response: 14ms)\n",
-        "slow: 0 requests (avg response: 0ms)\n",
+        "fast: 2 requests (avg response: 14ms, queued: 4ms, service: 10ms)\n",
+        "slow: 0 requests (avg response: 0ms, queued: 0ms, service: 0ms)\n",
     );
 
     let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
@@ -68,9 +69,9 @@ fn summary_preserves_input_order() {
         "tie_break: stable\n",
         "duration_ms: 10\n",
         "Summary:\n",
-        "z: 1 requests (avg response: 10ms)\n",
-        "a: 0 requests (avg response: 0ms)\n",
-        "m: 0 requests (avg response: 0ms)\n",
+        "z: 1 requests (avg response: 10ms, queued: 0ms, service: 10ms)\n",
+        "a: 0 requests (avg response: 0ms, queued: 0ms, service: 0ms)\n",
+        "m: 0 requests (avg response: 0ms, queued: 0ms, service: 0ms)\n",
     );
 
     let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
@@ -97,11 +98,11 @@ fn summary_preserves_input_order_for_least_connections() {
         "Metadata:\n",
         "algo: least-connections\n",
         "tie_break: seeded(11)\n",
-        "duration_ms: 42\n",
+        "duration_ms: 32\n",
         "Summary:\n",
-        "first: 1 requests (avg response: 10ms)\n",
-        "second: 2 requests (avg response: 29ms)\n",
-        "third: 1 requests (avg response: 30ms)\n",
+        "first: 2 requests (avg response: 14ms, queued: 4ms, service: 10ms)\n",
+        "second: 1 requests (avg response: 20ms, queued: 0ms, service: 20ms)\n",
+        "third: 1 requests (avg response: 30ms, queued: 0ms, service: 30ms)\n",
     );
 
     let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
@@ -136,8 +137,21 @@ fn full_output_least_response_time_includes_scores() {
         "Request 2 -> b (score: 11ms)\n",
         "Request 3 -> a (score: 20ms)\n",
         "Summary:\n",
-        "a: 2 requests (avg response: 14ms)\n",
-        "b: 1 requests (avg response: 10ms)\n",
+        "a: 2 requests (avg response: 14ms, queued: 4ms, service: 10ms)\n",
+        "b: 1 requests (avg response: 10ms, queued: 0ms, service: 10ms)\n",
+        "Global:\n",
+        "total_requests: 3\n",
+        "makespan_ms: 20\n",
+        "throughput_rps: 150\n",
+        "avg_concurrency: 1.9\n",
+        "Little's law:\n",
+        "L (avg in system): 1.9\n",
+        "lambda (arrival rate): 150 rps\n",
+        "W (avg latency): 12.6667ms\n",
+        "lambda * W: 1.9 (residual: 0)\n",
+        "Tail (>= p99 18ms):\n",
+        "a: 1 tail requests (50% of its requests), p99: 18ms\n",
+        "b: 0 tail requests (0% of its requests), p99: 10ms\n",
     );
 
     let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
@@ -170,8 +184,21 @@ fn full_output_round_robin_omits_scores() {
         "Request 2 -> b\n",
         "Request 3 -> a\n",
         "Summary:\n",
-        "a: 2 requests (avg response: 14ms)\n",
-        "b: 1 requests (avg response: 20ms)\n",
+        "a: 2 requests (avg response: 14ms, queued: 4ms, service: 10ms)\n",
+        "b: 1 requests (avg response: 20ms, queued: 0ms, service: 20ms)\n",
+        "Global:\n",
+        "total_requests: 3\n",
+        "makespan_ms: 21\n",
+        "throughput_rps: 142.86\n",
+        "avg_concurrency: 2.29\n",
+        "Little's law:\n",
+        "L (avg in system): 2.2857\n",
+        "lambda (arrival rate): 142.8571 rps\n",
+        "W (avg latency): 16ms\n",
+        "lambda * W: 2.2857 (residual: 0)\n",
+        "Tail (>= p99 20ms):\n",
+        "a: 0 tail requests (0% of its requests), p99: 18ms\n",
+        "b: 1 tail requests (100% of its requests), p99: 20ms\n",
     );
 
     let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
@@ -191,3 +218,712 @@ fn full_output_round_robin_omits_scores() {
     ]);
     cmd.assert().success().stdout(diff(expected));
 }
+
+#[test]
+fn trace_flag_logs_events_to_stderr() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "round-robin",
+        "--server",
+        "a:10",
+        "--requests",
+        "1",
+        "--summary",
+        "--trace",
+    ]);
+    cmd.assert().success().stderr(diff(concat!(
+        "t=0 arrival request=1\n",
+        "t=0 select request=1 server=a candidates=[a] score=0\n",
+        "t=10 complete request=1 server=a in_flight=0\n",
+    )));
+}
+
+#[test]
+fn event_log_writes_one_json_event_per_line() {
+    let mut path = std::env::temp_dir();
+    path.push(format!("lb-event-log-{}.jsonl", std::process::id()));
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "round-robin",
+        "--server",
+        "a:10",
+        "--requests",
+        "1",
+        "--summary",
+        "--event-log",
+    ])
+    .arg(&path);
+    cmd.assert().success();
+    let contents = std::fs::read_to_string(&path).expect("event log should exist");
+    std::fs::remove_file(&path).ok();
+    assert_eq!(
+        contents,
+        concat!(
+            "{\"time_ms\":0,\"event\":{\"RequestArrival\":{\"id\":1,\"arrival_time_ms\":0}}}\n",
+            "{\"time_ms\":10,\"event\":{\"RequestComplete\":{\"server_id\":0,\"request_id\":1}}}\n",
+        )
+    );
+}
+
+#[test]
+fn replications_report_confidence_intervals() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "least-connections",
+        "--server",
+        "a:10",
+        "--server",
+        "b:20",
+        "--overload",
+        "--overload-duration-ms",
+        "200",
+        "--seed",
+        "7",
+        "--replications",
+        "4",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::starts_with(
+            "Replications:\nalgo: least-connections\nruns: 4\n",
+        ))
+        .stdout(predicates::str::contains("p95_ms"))
+        .stdout(predicates::str::contains("95% CI"));
+}
+
+#[test]
+fn replications_reject_zero() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "round-robin",
+        "--server",
+        "a:10",
+        "--replications",
+        "0",
+    ]);
+    cmd.assert().failure().stderr(predicates::str::contains(
+        "replications must be greater than 0",
+    ));
+}
+
+#[test]
+fn seed_range_reports_metric_distributions() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "least-connections",
+        "--servers",
+        "a:10,b:20",
+        "--overload",
+        "--overload-duration-ms",
+        "200",
+        "--seed-range",
+        "1..5",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::starts_with(
+            "Seed sweep:\nalgo: least-connections\nseeds: 1..=5 (5 runs)\n",
+        ))
+        .stdout(predicates::str::contains(
+            "min        p50       mean     stddev        max",
+        ));
+}
+
+#[test]
+fn seed_range_rejects_reversed_bounds() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "round-robin",
+        "--server",
+        "a:10",
+        "--requests",
+        "3",
+        "--seed-range",
+        "5..1",
+    ]);
+    cmd.assert()
+        .failure()
+        .stderr(predicates::str::contains("invalid seed range '5..1'"));
+}
+
+#[test]
+fn step_mode_pauses_and_quits_on_command() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "round-robin",
+        "--servers",
+        "a:10,b:20",
+        "--requests",
+        "3",
+        "--step",
+    ])
+    .write_stdin("n\nq\n");
+    cmd.assert()
+        .failure()
+        .stdout(predicates::str::is_empty())
+        .stderr(predicates::str::contains(
+            "[event 2] t=1ms\n  b: in_flight 0 -> 1, active 0 -> 1, picks 0 -> 1\n",
+        ))
+        .stderr(predicates::str::contains(
+            "Error: simulation stopped at t=1ms",
+        ));
+}
+
+#[test]
+fn real_time_paces_the_run_without_changing_results() {
+    let args = [
+        "--algo",
+        "round-robin",
+        "--servers",
+        "a:10,b:20",
+        "--requests",
+        "3",
+        "--format",
+        "summary",
+    ];
+    let expected = assert_cmd::cargo::cargo_bin_cmd!("lb-sim")
+        .args(args)
+        .output()
+        .expect("plain run should execute")
+        .stdout;
+
+    let started = std::time::Instant::now();
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args(args).args(["--real-time", "0.5"]);
+    cmd.assert()
+        .success()
+        .stdout(diff(String::from_utf8(expected).expect("utf-8 output")));
+    assert!(started.elapsed() >= std::time::Duration::from_millis(40));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args(args).args(["--real-time", "0"]);
+    cmd.assert().failure().stderr(predicates::str::contains(
+        "invalid value '0' for '--real-time <SPEED>': expected a positive number, got '0'",
+    ));
+}
+
+#[test]
+fn rate_and_duration_build_poisson_workload() {
+    let expected = concat!(
+        "Metadata:\n",
+        "algo: round-robin\n",
+        "tie_break: seeded(1)\n",
+        "duration_ms: 107\n",
+        "Summary:\n",
+        "a: 3 requests (avg response: 10ms, queued: 0ms, service: 10ms)\n",
+        "b: 3 requests (avg response: 20ms, queued: 0ms, service: 20ms)\n",
+    );
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "round-robin",
+        "--servers",
+        "a:10,b:20",
+        "--rate",
+        "50",
+        "--duration",
+        "100",
+        "--seed",
+        "1",
+        "--summary",
+    ]);
+    cmd.assert().success().stdout(diff(expected));
+}
+
+#[test]
+fn auto_seed_is_reported_in_metadata() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "least-connections",
+        "--servers",
+        "a:10,b:10",
+        "--requests",
+        "4",
+        "--seed",
+        "auto",
+        "--format",
+        "json",
+    ]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let result: serde_json::Value = serde_json::from_slice(&output).expect("json output");
+    let seed = result["metadata"]["seed"]
+        .as_u64()
+        .expect("seed is reported");
+    assert_eq!(
+        result["metadata"]["tie_break"],
+        serde_json::json!(format!("seeded({})", seed))
+    );
+}
+
+#[test]
+fn dry_run_prints_resolved_config_without_running() {
+    let expected = concat!(
+        "# expected_arrivals = 3\n",
+        "# offered_rps = 1000\n",
+        "# capacity_rps = 150\n",
+        "# offered_load = 6.6667\n",
+        "requests = 3\n",
+        "algo = \"least-connections\"\n",
+        "randomness = \"seeded(9)\"\n",
+        "record_timelines = false\n",
+        "slos = []\n",
+        "slowest_requests = 0\n",
+        "explain = false\n",
+        "max_events = 500\n",
+        "\n",
+        "[[servers]]\n",
+        "name = \"a\"\n",
+        "base_latency_ms = 10\n",
+        "weight = 1\n",
+        "\n",
+        "[[servers]]\n",
+        "name = \"b\"\n",
+        "base_latency_ms = 20\n",
+        "weight = 1\n",
+    );
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "least-connections",
+        "--servers",
+        "a:10,b:20",
+        "--requests",
+        "3",
+        "--seed",
+        "9",
+        "--max-events",
+        "500",
+        "--dry-run",
+    ]);
+    cmd.assert().success().stdout(diff(expected));
+}
+
+#[test]
+fn quiet_prints_nothing_on_success() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "round-robin",
+        "--server",
+        "a:10",
+        "--requests",
+        "3",
+        "--quiet",
+    ]);
+    cmd.assert().success().stdout(diff(""));
+}
+
+#[test]
+fn quiet_summary_line_with_output_file() {
+    let mut path = std::env::temp_dir();
+    path.push(format!("lb-quiet-{}.txt", std::process::id()));
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "round-robin",
+        "--server",
+        "a:10",
+        "--requests",
+        "3",
+        "--summary",
+        "--quiet",
+        "--summary-line",
+        "--output",
+    ])
+    .arg(&path);
+    cmd.assert().success().stdout(diff(
+        "ok algo=round-robin requests=3 makespan_ms=30 p99_ms=28 throughput_rps=100\n",
+    ));
+    let written = std::fs::read_to_string(&path).expect("output file should exist");
+    std::fs::remove_file(&path).ok();
+    assert!(written.starts_with("Metadata:\nalgo: round-robin\n"));
+    assert!(written.contains("Summary:\na: 3 requests"));
+}
+
+#[test]
+fn sort_by_name_orders_summary() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "round-robin",
+        "--server",
+        "zeta:10",
+        "--server",
+        "alpha:20",
+        "--requests",
+        "2",
+        "--summary",
+        "--sort-by",
+        "name",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::ends_with(concat!(
+            "Summary:\n",
+            "alpha: 1 requests (avg response: 20ms, queued: 0ms, service: 20ms)\n",
+            "zeta: 1 requests (avg response: 10ms, queued: 0ms, service: 10ms)\n",
+        )));
+}
+
+#[test]
+fn slowest_lists_tail_requests_in_summary() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "least-response-time",
+        "--server",
+        "a:10",
+        "--server",
+        "b:25",
+        "--requests",
+        "6",
+        "--summary",
+        "--slowest",
+        "2",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::ends_with(concat!(
+            "Slowest requests:\n",
+            "Request 6 -> a: 45ms (arrival: 5ms, start: 40ms, queue wait: 35ms, score: 50ms)\n",
+            "Request 5 -> a: 36ms (arrival: 4ms, start: 30ms, queue wait: 26ms, score: 40ms)\n",
+        )));
+}
+
+#[test]
+fn explain_shows_candidates_and_tie_break() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "least-response-time",
+        "--server",
+        "a:10",
+        "--server",
+        "b:10",
+        "--requests",
+        "2",
+        "--explain",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains(concat!(
+            "Request 1 -> a (score: 10ms)\n",
+            "    candidates: a(conn=0, score=10) b(conn=0, score=10)\n",
+            "    tie-break: stable-first among [a, b]\n",
+            "Request 2 -> b (score: 11ms)\n",
+            "    candidates: a(conn=1, score=20) b(conn=0, score=11)\n",
+            "    tie-break: unique-best\n",
+        )));
+}
+
+#[test]
+fn log_level_writes_structured_logs_to_stderr() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "run",
+        "--algo",
+        "round-robin",
+        "--server",
+        "a:10",
+        "--requests",
+        "1",
+        "--format",
+        "summary",
+        "--log-level",
+        "debug",
+        "--log-format",
+        "json",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("a: 1 requests"))
+        .stdout(predicates::str::contains("request assigned").not())
+        .stderr(predicates::str::contains(
+            r#""message":"request assigned","server":"a""#,
+        ));
+}
+
+#[test]
+fn default_log_level_keeps_stderr_quiet() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "round-robin",
+        "--server",
+        "a:10",
+        "--requests",
+        "1",
+    ]);
+    cmd.assert().success().stderr(predicates::str::is_empty());
+}
+
+#[test]
+fn algo_alias_prints_canonical_name() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "lc",
+        "--server",
+        "a:10",
+        "--requests",
+        "1",
+        "--summary",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("algo: least-connections\n"));
+}
+
+#[test]
+fn display_unit_and_thousands_format_summary() {
+    let expected = concat!(
+        "Metadata:\n",
+        "algo: round-robin\n",
+        "tie_break: stable\n",
+        "duration_s: 1.500\n",
+        "Summary:\n",
+        "a: 1 requests (avg response: 1.500s, queued: 0.000s, service: 1.500s)\n",
+    );
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "rr",
+        "--server",
+        "a:1500",
+        "--requests",
+        "1",
+        "--summary",
+        "--display-unit",
+        "s",
+        "--thousands",
+    ]);
+    cmd.assert().success().stdout(diff(expected));
+}
+
+#[test]
+fn interactive_prompts_for_missing_arguments() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args(["--interactive", "--summary"])
+        .write_stdin("least-connections\na:10,b:20\n2\n");
+    cmd.assert()
+        .success()
+        .stderr(predicates::str::contains("Algorithm [1-4 or name]: "))
+        .stdout(predicates::str::contains("algo: least-connections\n"))
+        .stdout(predicates::str::contains("b: 1 requests"));
+}
+
+#[test]
+fn stream_assignments_writes_ndjson_instead_of_keeping_them() {
+    let mut path = std::env::temp_dir();
+    path.push(format!("lb-stream-{}.ndjson", std::process::id()));
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "round-robin",
+        "--servers",
+        "a:10,b:20",
+        "--requests",
+        "2",
+        "--format",
+        "json",
+        "--stream-assignments",
+    ])
+    .arg(&path);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("\"assignments\": []"));
+    let contents = std::fs::read_to_string(&path).expect("stream should exist");
+    std::fs::remove_file(&path).ok();
+    assert_eq!(
+        contents,
+        concat!(
+            "{\"request_id\":1,\"server_id\":0,\"server_name\":\"a\",\"arrival_time_ms\":0,\"started_at\":0,\"completed_at\":10,\"queued_ms\":0,\"service_ms\":10,\"score\":0}\n",
+            "{\"request_id\":2,\"server_id\":1,\"server_name\":\"b\",\"arrival_time_ms\":1,\"started_at\":1,\"completed_at\":21,\"queued_ms\":0,\"service_ms\":20,\"score\":1}\n",
+        )
+    );
+}
+
+#[test]
+fn rng_flag_reports_the_generator_and_rejects_unknown_ones() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "round-robin",
+        "--servers",
+        "a:10,b:20",
+        "--requests",
+        "5",
+        "--seed",
+        "4",
+        "--rng",
+        "xoshiro",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("rng: xoshiro\n"));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "round-robin",
+        "--servers",
+        "a:10",
+        "--requests",
+        "5",
+        "--rng",
+        "mt19937",
+    ]);
+    cmd.assert().failure().stderr(predicates::str::contains(
+        "invalid value 'mt19937' for '--rng",
+    ));
+}
+
+#[test]
+fn event_order_flag_changes_simultaneous_picks_and_is_reported() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "least-connections",
+        "--servers",
+        "a:1,b:1",
+        "--requests",
+        "2",
+        "--event-order",
+        "arrivals-first",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("event_order: arrivals-first\n"))
+        .stdout(predicates::str::contains(
+            "Request 2 -> b (connections: 0)\n",
+        ));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "least-connections",
+        "--servers",
+        "a:1,b:1",
+        "--requests",
+        "2",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("event_order").not())
+        .stdout(predicates::str::contains(
+            "Request 2 -> a (connections: 0)\n",
+        ));
+}
+
+#[test]
+fn sample_assignments_prints_only_the_sample() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "round-robin",
+        "--servers",
+        "a:10,b:20",
+        "--requests",
+        "500",
+        "--sample-assignments",
+        "5",
+        "--format",
+        "json",
+    ]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).expect("valid JSON");
+    assert_eq!(json["assignments"].as_array().map(Vec::len), Some(5));
+    assert_eq!(json["metadata"]["assignment_sample"], 5);
+    assert_eq!(json["global"]["total_requests"], 500);
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "round-robin",
+        "--servers",
+        "a:10,b:20",
+        "--requests",
+        "500",
+        "--sample-assignments",
+        "5",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("assignments: sampled 5 of 500\n"));
+}
+
+#[test]
+fn stats_memory_reports_what_the_run_kept() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "least-connections",
+        "--servers",
+        "a:10,b:20",
+        "--requests",
+        "50",
+        "--format",
+        "summary",
+        "--stats-memory",
+    ]);
+    cmd.assert().success().stderr(predicates::str::contains(
+        "memory: peak_queued_events=44 stored_assignments=0 retained_samples=50",
+    ));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "--algo",
+        "round-robin",
+        "--servers",
+        "a:10,b:20",
+        "--requests",
+        "50",
+        "--format",
+        "summary",
+        "--histogram-percentiles",
+        "--stats-memory",
+    ]);
+    // Round-robin ignores connection counts, so no completions are queued.
+    cmd.assert().success().stderr(predicates::str::contains(
+        "memory: peak_queued_events=1 stored_assignments=0 retained_samples=0",
+    ));
+}
+
+#[test]
+fn verify_determinism_checks_a_twin_run_before_reporting() {
+    let args = [
+        "--algo",
+        "least-connections",
+        "--servers",
+        "a:10,b:20",
+        "--rate",
+        "200",
+        "--duration-ms",
+        "100",
+        "--seed",
+        "7",
+        "--format",
+        "summary",
+    ];
+    let plain = assert_cmd::cargo::cargo_bin_cmd!("lb-sim")
+        .args(args)
+        .output()
+        .expect("run should start");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args(args).arg("--verify-determinism");
+    cmd.assert()
+        .success()
+        .stdout(String::from_utf8(plain.stdout).expect("output is UTF-8"))
+        .stderr(predicates::str::starts_with("determinism verified: ").and(
+            predicates::str::contains("events identical across two runs"),
+        ));
+}
@@ -14,12 +14,200 @@ fn list_algorithms_prints_supported_values() {
     cmd.assert().success().stdout(diff(expected));
 }
 
+#[test]
+fn list_formats_prints_formats_and_commands() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.arg("list-formats");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::starts_with(
+            "human     run, recommend, bench, render",
+        ))
+        .stdout(predicates::str::contains("\ncsv       render "));
+}
+
+#[cfg(not(feature = "grpc"))]
+#[test]
+fn serve_explains_the_missing_grpc_feature() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args(["serve", "--addr", "127.0.0.1:0"]);
+    cmd.assert().failure().stderr(diff(
+        "Error: cannot serve on 127.0.0.1:0: lb-sim was built without the `grpc` feature\n",
+    ));
+}
+
+fn write_targets(tag: &str, contents: &str) -> std::path::PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("lb-targets-{}-{}.txt", tag, std::process::id()));
+    std::fs::write(&path, contents).expect("targets write should succeed");
+    path
+}
+
+#[cfg(not(feature = "calibrate"))]
+#[test]
+fn calibrate_explains_the_missing_calibrate_feature() {
+    let path = write_targets("no-feature", "api http://127.0.0.1:9/health\n");
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args(["calibrate", "--targets"]).arg(&path);
+    let assert = cmd.assert();
+    std::fs::remove_file(&path).ok();
+    assert.failure().stderr(diff(
+        "Error: cannot probe 'http://127.0.0.1:9/health': lb-sim was built without the `calibrate` feature\n",
+    ));
+}
+
+#[cfg(feature = "calibrate")]
+#[test]
+fn calibrate_probes_targets_and_prints_servers() {
+    use std::io::{Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("listener should bind");
+    let addr = listener.local_addr().expect("listener has an address");
+    std::thread::spawn(move || {
+        for stream in listener.incoming().take(3) {
+            let mut stream = stream.expect("connection should open");
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request);
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            let _ = stream
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok");
+        }
+    });
+    let path = write_targets("probe", &format!("local http://{}/health\n", addr));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args(["calibrate", "--probes", "3", "--targets"])
+        .arg(&path);
+    let assert = cmd.assert();
+    std::fs::remove_file(&path).ok();
+    assert
+        .success()
+        .stdout(predicates::str::contains(format!(
+            "# http://{}/health: 3 probes, median ",
+            addr
+        )))
+        .stdout(predicates::str::contains("[[servers]]\nname = \"local\"\n"));
+}
+
+#[cfg(not(feature = "calibrate"))]
+#[test]
+fn calibrate_prometheus_explains_the_missing_calibrate_feature() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "calibrate",
+        "--prometheus",
+        "http://127.0.0.1:9",
+        "--query",
+        "http_request_duration_seconds_bucket",
+    ]);
+    cmd.assert().failure().stderr(diff(
+        "Error: cannot query 'http://127.0.0.1:9': lb-sim was built without the `calibrate` feature\n",
+    ));
+}
+
+#[cfg(feature = "calibrate")]
+#[test]
+fn calibrate_fits_servers_from_prometheus_histograms() {
+    use std::io::{Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("listener should bind");
+    let addr = listener.local_addr().expect("listener has an address");
+    std::thread::spawn(move || {
+        for stream in listener.incoming().take(2) {
+            let mut stream = stream.expect("connection should open");
+            let mut request = [0u8; 2048];
+            let read = stream.read(&mut request).unwrap_or(0);
+            let request = String::from_utf8_lossy(&request[..read]);
+            let result = if request.contains("_bucket") {
+                r#"{"metric":{"instance":"api","le":"0.02"},"value":[0,"10"]},{"metric":{"instance":"api","le":"+Inf"},"value":[0,"10"]}"#
+            } else {
+                r#"{"metric":{"instance":"api"},"value":[0,"150"]}"#
+            };
+            let body = format!(
+                r#"{{"status":"success","data":{{"resultType":"vector","result":[{}]}}}}"#,
+                result
+            );
+            let _ = stream.write_all(
+                format!(
+                    "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+                .as_bytes(),
+            );
+        }
+    });
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args(["calibrate", "--prometheus"])
+        .arg(format!("http://{}/", addr))
+        .args([
+            "--query",
+            "sum by (le, instance) (rate(http_request_duration_seconds_bucket[5m]))",
+            "--rate-query",
+            "sum by (instance) (rate(http_requests_total[5m]))",
+        ]);
+    cmd.assert().success().stdout(diff(concat!(
+        "# prometheus instance=\"api\": median 10.0ms, p95 19.0ms, mean 10.0ms, stddev 0.0ms, 150.0 req/s\n",
+        "[[servers]]\n",
+        "name = \"api\"\n",
+        "base_latency_ms = 10\n",
+        "weight = 1\n",
+        "\n",
+        "[servers.capacity]\n",
+        "concurrency = 2\n",
+    )));
+}
+
+#[test]
+fn import_envoy_prints_a_config_fragment() {
+    let mut path = std::env::temp_dir();
+    path.push(format!("lb-envoy-{}.yaml", std::process::id()));
+    std::fs::write(
+        &path,
+        concat!(
+            "name: api\n",
+            "lb_policy: ROUND_ROBIN\n",
+            "load_assignment:\n",
+            "  endpoints:\n",
+            "  - locality: { zone: a }\n",
+            "    lb_endpoints:\n",
+            "    - endpoint: { address: { socket_address: { address: 10.0.0.1, port_value: 80 } } }\n",
+            "      load_balancing_weight: 2\n",
+            "    - endpoint: { hostname: web-2 }\n",
+        ),
+    )
+    .expect("Envoy config write should succeed");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args(["import-envoy", "--latency-ms", "25", "--input"])
+        .arg(&path);
+    let assert = cmd.assert();
+    std::fs::remove_file(&path).ok();
+    assert.success().stdout(diff(concat!(
+        "# imported from Envoy cluster 'api'\n",
+        "algo = \"weighted-round-robin\"\n",
+        "\n",
+        "[[servers]]\n",
+        "name = \"10.0.0.1:80\"\n",
+        "base_latency_ms = 25\n",
+        "weight = 2\n",
+        "zone = \"a\"\n",
+        "\n",
+        "[[servers]]\n",
+        "name = \"web-2\"\n",
+        "base_latency_ms = 25\n",
+        "weight = 1\n",
+        "zone = \"a\"\n",
+    )));
+}
+
 #[test]
 fn show_config_prints_parsed_configuration() {
     let expected = concat!(
         "Algorithm: round-robin\n",
         "Requests: 3\n",
-        "Tie-break: seeded(42)\n",
+        "Randomness: seeded(42)\n",
         "Servers:\n",
         "- api (latency: 10ms, weight: 1)\n",
         "- db (latency: 20ms, weight: 2)\n",
@@ -39,3 +227,435 @@ fn show_config_prints_parsed_configuration() {
     ]);
     cmd.assert().success().stdout(diff(expected));
 }
+
+#[test]
+fn show_config_json_loads_back_as_the_same_config() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "show-config",
+        "--format",
+        "json",
+        "--algo",
+        "least-connections",
+        "--servers",
+        "api:10,db:20:2",
+        "--rate",
+        "123.456",
+        "--duration-ms",
+        "250",
+        "--seed",
+        "42",
+        "--rng",
+        "xoshiro",
+        "--event-order",
+        "fifo",
+        "--slo",
+        "99:50",
+    ]);
+    let exported = cmd.output().expect("show-config should run").stdout;
+    let exported = String::from_utf8(exported).expect("JSON is UTF-8");
+    assert!(exported.contains("\"randomness\": \"seeded(42)\""));
+
+    let mut path = std::env::temp_dir();
+    path.push(format!("lb-show-config-{}.json", std::process::id()));
+    std::fs::write(&path, &exported).expect("config write should succeed");
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args(["show-config", "--format", "json", "--config"])
+        .arg(&path);
+    let assert = cmd.assert();
+    std::fs::remove_file(&path).ok();
+    assert.success().stdout(diff(exported));
+}
+
+#[test]
+fn schema_prints_json_schema_for_output() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.arg("schema");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "\"$schema\": \"https://json-schema.org/draft/2020-12/schema\"",
+        ))
+        .stdout(predicates::str::contains("\"title\": \"SimulationResult\""));
+}
+
+#[test]
+fn schema_config_prints_json_schema_for_config_files() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args(["schema", "--config"]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("\"title\": \"SimConfig\""))
+        .stdout(predicates::str::contains("\"server_template\""))
+        .stdout(predicates::str::contains(
+            "\"required\": [\n    \"requests\",\n    \"algo\"\n  ]",
+        ));
+}
+
+#[test]
+fn render_reformats_saved_json_result() {
+    let mut run = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    run.args([
+        "run",
+        "--algo",
+        "round-robin",
+        "--servers",
+        "a:10,b:20",
+        "--requests",
+        "4",
+        "--format",
+        "json",
+    ]);
+    let saved = run.assert().success().get_output().stdout.clone();
+    let mut path = std::env::temp_dir();
+    path.push(format!("lb-render-{}.json", std::process::id()));
+    std::fs::write(&path, &saved).expect("result write should succeed");
+
+    let mut markdown = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    markdown
+        .args(["render", "--format", "markdown", "--input"])
+        .arg(&path);
+    markdown
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("**round-robin**"))
+        .stdout(predicates::str::contains(
+            "| a | 2 | 14 | 4 | 10 | 18 | 48.78 |",
+        ));
+
+    let mut csv = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    csv.args([
+        "render",
+        "--format",
+        "csv",
+        "--sort-by",
+        "name",
+        "--sort-order",
+        "desc",
+    ])
+    .arg("--input")
+    .arg(&path);
+    csv.assert().success().stdout(diff(concat!(
+        "server,requests,avg_response_ms,avg_queued_ms,avg_service_ms,p99_ms,utilization_pct\n",
+        "b,2,29,9,20,38,97.56\n",
+        "a,2,14,4,10,18,48.78\n",
+    )));
+
+    let mut summary = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    summary
+        .args([
+            "render",
+            "--format",
+            "summary",
+            "--display-unit",
+            "s",
+            "--input",
+        ])
+        .arg(&path);
+    summary.assert().success().stdout(predicates::str::contains(
+        "b: 2 requests (avg response: 0.029s, queued: 0.009s, service: 0.020s)",
+    ));
+
+    let mut json = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    json.args(["render", "--format", "json", "--input"])
+        .arg(&path);
+    let assert = json.assert().success();
+    std::fs::remove_file(&path).ok();
+    assert.stdout(diff(String::from_utf8(saved).unwrap()));
+}
+
+#[test]
+fn export_writes_weighted_proxy_snippets_from_a_saved_result() {
+    let mut run = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    run.args([
+        "run",
+        "--algo",
+        "round-robin",
+        "--servers",
+        "a:10,b:20",
+        "--requests",
+        "4",
+        "--format",
+        "json",
+    ]);
+    let saved = run.assert().success().get_output().stdout.clone();
+    let mut path = std::env::temp_dir();
+    path.push(format!("lb-export-{}.json", std::process::id()));
+    std::fs::write(&path, &saved).expect("result write should succeed");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args(["export", "--format", "nginx", "--name", "api", "--input"])
+        .arg(&path);
+    let assert = cmd.assert();
+    std::fs::remove_file(&path).ok();
+    assert.success().stdout(diff(concat!(
+        "# weights from lb-sim (round-robin, 4 requests): inverse of avg service time\n",
+        "upstream api {\n",
+        "    server a:80 weight=2;\n",
+        "    server b:80 weight=1;\n",
+        "}\n",
+    )));
+}
+
+#[test]
+fn export_workload_writes_a_k6_script_with_arrival_times() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "export-workload",
+        "--tool",
+        "k6",
+        "--url",
+        "http://staging.internal/api",
+        "--servers",
+        "a:10",
+        "--burst",
+        "3",
+        "--burst-at",
+        "250",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::starts_with(
+            "// lb-sim workload: 3 requests over 251ms\n",
+        ))
+        .stdout(predicates::str::contains(
+            "const ARRIVALS_MS = [250, 250, 250];\n",
+        ))
+        .stdout(predicates::str::contains(
+            "  http.get(\"http://staging.internal/api\");\n",
+        ));
+}
+
+#[test]
+fn replay_verifies_a_recorded_trace_file() {
+    let mut path = std::env::temp_dir();
+    path.push(format!("lb-replay-{}.log", std::process::id()));
+    let mut run = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    run.args([
+        "run",
+        "--algo",
+        "least-connections",
+        "--servers",
+        "a:10,b:20",
+        "--rate",
+        "100",
+        "--duration-ms",
+        "50",
+        "--seed",
+        "3",
+        "--format",
+        "summary",
+        "--trace-file",
+    ])
+    .arg(&path);
+    run.assert().success();
+
+    let mut replay = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    replay.arg("replay").arg("--trace-file").arg(&path);
+    replay
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("replay matches"));
+
+    let log = std::fs::read_to_string(&path).expect("trace should be readable");
+    std::fs::write(
+        &path,
+        log.replacen("arrival request=1", "arrival request=9", 1),
+    )
+    .expect("trace write should succeed");
+    let mut tampered = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    tampered.arg("replay").arg("--trace-file").arg(&path);
+    let assert = tampered.assert().failure();
+    std::fs::remove_file(&path).ok();
+    assert.stderr(predicates::str::contains("replay diverged at event 1"));
+}
+
+#[test]
+fn recommend_ranks_every_algorithm() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "recommend",
+        "--servers",
+        "a:10,b:20,c:30",
+        "--overload",
+        "--overload-factor",
+        "1.1",
+        "--seed",
+        "42",
+        "--min-fairness",
+        "0.8",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("least-response-time"))
+        .stdout(predicates::str::contains("weighted-round-robin"))
+        .stdout(predicates::str::contains("(excluded)"))
+        .stdout(predicates::str::contains(
+            "Recommendation: least-connections — lowest p99 (248ms) with fairness >= 0.8",
+        ));
+
+    let mut with_algo = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    with_algo.args([
+        "recommend",
+        "--algo",
+        "round-robin",
+        "--servers",
+        "a:10",
+        "--requests",
+        "3",
+    ]);
+    with_algo
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("drop --algo"));
+}
+
+#[test]
+fn bench_prints_one_row_per_algorithm_and_size() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "bench",
+        "--algo",
+        "round-robin,least-connections",
+        "--server-counts",
+        "2,8",
+        "--requests",
+        "200",
+        "--iterations",
+        "1",
+        "--format",
+        "json",
+    ]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let rows: serde_json::Value = serde_json::from_slice(&output).expect("json output");
+    let cases = rows
+        .as_array()
+        .expect("rows")
+        .iter()
+        .map(|row| {
+            (
+                row["algo"].as_str().unwrap().to_string(),
+                row["servers"].as_u64().unwrap(),
+                row["events"].as_u64().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        cases,
+        vec![
+            ("round-robin".to_string(), 2, 400),
+            ("least-connections".to_string(), 2, 400),
+            ("round-robin".to_string(), 8, 400),
+            ("least-connections".to_string(), 8, 400),
+        ]
+    );
+}
+
+#[test]
+fn check_compares_rerun_against_baseline() {
+    let run_args = [
+        "--algo",
+        "round-robin",
+        "--server",
+        "a:10",
+        "--server",
+        "b:20",
+        "--requests",
+        "10",
+    ];
+    let mut baseline_cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    baseline_cmd.args(run_args).args(["--format", "json"]);
+    let baseline = baseline_cmd.assert().success().get_output().stdout.clone();
+    let mut path = std::env::temp_dir();
+    path.push(format!("lb-baseline-{}.json", std::process::id()));
+    std::fs::write(&path, baseline).expect("baseline write should succeed");
+
+    let mut same = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    same.arg("check")
+        .arg("--baseline")
+        .arg(&path)
+        .args(run_args);
+    same.assert()
+        .success()
+        .stdout(predicates::str::contains("totals.b.requests"));
+
+    let mut slower = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    slower.arg("check").arg("--baseline").arg(&path).args([
+        "--algo",
+        "round-robin",
+        "--servers",
+        "a:10,b:25",
+        "--requests",
+        "10",
+    ]);
+    let assert = slower.assert().failure();
+    std::fs::remove_file(&path).ok();
+    assert
+        .stdout(predicates::str::contains("totals.b.avg_response_ms"))
+        .stderr(predicates::str::contains(
+            "metric(s) deviate from the baseline beyond tolerance",
+        ));
+}
+
+#[test]
+fn validate_reports_every_config_problem() {
+    let mut path = std::env::temp_dir();
+    path.push(format!("lb-validate-{}.toml", std::process::id()));
+    std::fs::write(
+        &path,
+        r#"
+algo = "round-robin"
+requests = 0
+servers = [
+  { name = "a", base_latency_ms = 0, weight = 1 },
+  { name = "a", base_latency_ms = 20, weight = 1 }
+]
+"#,
+    )
+    .expect("config write should succeed");
+    let display = path.display().to_string();
+    let expected = [
+        "latency must be > 0 in 'a'",
+        "duplicate server name 'a'",
+        "requests must be greater than 0",
+    ]
+    .iter()
+    .map(|problem| format!("{}: {}\n", display, problem))
+    .collect::<String>();
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.arg("validate").arg("--config").arg(&path);
+    let assert = cmd.assert().failure();
+    std::fs::remove_file(&path).ok();
+    assert
+        .stdout(diff(expected))
+        .stderr(predicates::str::contains("3 problem(s) found in config"));
+}
+
+#[test]
+fn validate_accepts_valid_config() {
+    let mut path = std::env::temp_dir();
+    path.push(format!("lb-validate-ok-{}.json", std::process::id()));
+    std::fs::write(
+        &path,
+        r#"{"algo": "round-robin", "requests": 2, "servers": [{"name": "a", "base_latency_ms": 10, "weight": 1}]}"#,
+    )
+    .expect("config write should succeed");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.arg("validate").arg("--config").arg(&path);
+    let assert = cmd.assert().success();
+    std::fs::remove_file(&path).ok();
+    assert.stdout(diff(format!("{}: ok\n", path.display())));
+}
+
+#[test]
+fn completions_include_subcommands_and_algorithms() {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args(["completions", "bash"]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("_lb-sim()"))
+        .stdout(predicates::str::contains("list-algorithms"))
+        .stdout(predicates::str::contains("least-response-time"));
+}
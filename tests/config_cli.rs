@@ -33,8 +33,8 @@ servers = [
         "tie_break: seeded(42)\n",
         "duration_ms: 21\n",
         "Summary:\n",
-        "a: 2 requests (avg response: 14ms)\n",
-        "b: 1 requests (avg response: 20ms)\n",
+        "a: 2 requests (avg response: 14ms, queued: 4ms, service: 10ms)\n",
+        "b: 1 requests (avg response: 20ms, queued: 0ms, service: 20ms)\n",
     );
     let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
     cmd.args(["run", "--config", path.to_str().unwrap(), "--summary"]);
@@ -49,8 +49,8 @@ fn repeatable_server_flag_parses() {
         "tie_break: stable\n",
         "duration_ms: 21\n",
         "Summary:\n",
-        "api: 1 requests (avg response: 10ms)\n",
-        "db: 1 requests (avg response: 20ms)\n",
+        "api: 1 requests (avg response: 10ms, queued: 0ms, service: 10ms)\n",
+        "db: 1 requests (avg response: 20ms, queued: 0ms, service: 20ms)\n",
     );
 
     let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
@@ -69,6 +69,93 @@ fn repeatable_server_flag_parses() {
     cmd.assert().success().stdout(diff(expected));
 }
 
+#[test]
+fn server_template_generates_numbered_servers() {
+    let expected = concat!(
+        "Metadata:\n",
+        "algo: round-robin\n",
+        "tie_break: stable\n",
+        "duration_ms: 12\n",
+        "Summary:\n",
+        "api-1: 1 requests (avg response: 10ms, queued: 0ms, service: 10ms)\n",
+        "api-2: 1 requests (avg response: 10ms, queued: 0ms, service: 10ms)\n",
+        "api-3: 1 requests (avg response: 10ms, queued: 0ms, service: 10ms)\n",
+    );
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "run",
+        "--algo",
+        "round-robin",
+        "--server-template",
+        "api-{i}:10",
+        "--server-count",
+        "3",
+        "--requests",
+        "3",
+        "--summary",
+    ]);
+    cmd.assert().success().stdout(diff(expected));
+}
+
+#[test]
+fn servers_file_combines_with_server_flag() {
+    let inventory = write_temp_config("# generated\napi-1:10\napi-2:10 # canary\n\n", "txt");
+    let expected = concat!(
+        "Metadata:\n",
+        "algo: round-robin\n",
+        "tie_break: stable\n",
+        "duration_ms: 22\n",
+        "Summary:\n",
+        "api-1: 1 requests (avg response: 10ms, queued: 0ms, service: 10ms)\n",
+        "api-2: 1 requests (avg response: 10ms, queued: 0ms, service: 10ms)\n",
+        "db: 1 requests (avg response: 20ms, queued: 0ms, service: 20ms)\n",
+    );
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "run",
+        "--algo",
+        "round-robin",
+        "--servers-file",
+        inventory.to_str().unwrap(),
+        "--server",
+        "db:20",
+        "--requests",
+        "3",
+        "--summary",
+    ]);
+    cmd.assert().success().stdout(diff(expected));
+}
+
+#[test]
+fn skip_invalid_servers_keeps_valid_entries_and_warns() {
+    let inventory = write_temp_config("a:10\nb:x\nc:30\n", "txt");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "run",
+        "--algo",
+        "round-robin",
+        "--servers-file",
+        inventory.to_str().unwrap(),
+        "--requests",
+        "2",
+        "--summary",
+        "--skip-invalid-servers",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "c: 1 requests (avg response: 30ms, queued: 0ms, service: 30ms)\n",
+        ))
+        .stderr(predicates::str::contains(
+            "skipping server entry: invalid latency in 'b:x'",
+        ))
+        .stderr(predicates::str::contains(":2:1"))
+        .stderr(predicates::str::contains("skipped 1 invalid server entry"));
+}
+
 #[test]
 fn empty_servers_csv_with_server_entries_succeeds() {
     let expected = concat!(
@@ -77,8 +164,8 @@ fn empty_servers_csv_with_server_entries_succeeds() {
         "tie_break: stable\n",
         "duration_ms: 21\n",
         "Summary:\n",
-        "web: 1 requests (avg response: 10ms)\n",
-        "cache: 1 requests (avg response: 20ms)\n",
+        "web: 1 requests (avg response: 10ms, queued: 0ms, service: 10ms)\n",
+        "cache: 1 requests (avg response: 20ms, queued: 0ms, service: 20ms)\n",
     );
 
     let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
@@ -98,3 +185,413 @@ fn empty_servers_csv_with_server_entries_succeeds() {
     ]);
     cmd.assert().success().stdout(diff(expected));
 }
+
+#[test]
+fn config_file_slos_are_reported_in_summary() {
+    let config = r#"
+algo = "round-robin"
+requests = 2
+servers = [
+  { name = "fast", base_latency_ms = 5 },
+  { name = "slow", base_latency_ms = 50 }
+]
+slos = [{ percentile = 50.0, threshold_ms = 20 }]
+"#;
+    let path = write_temp_config(config, "toml");
+
+    let expected = concat!(
+        "Metadata:\n",
+        "algo: round-robin\n",
+        "tie_break: stable\n",
+        "duration_ms: 51\n",
+        "Summary:\n",
+        "fast: 1 requests (avg response: 5ms, queued: 0ms, service: 5ms)\n",
+        "slow: 1 requests (avg response: 50ms, queued: 0ms, service: 50ms)\n",
+        "SLO:\n",
+        "p50 <= 20ms: 50% met (first violation at 51ms)\n",
+        "  fast: 100% met\n",
+        "  slow: 0% violated (first violation at 51ms)\n",
+    );
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args(["run", "--config", path.to_str().unwrap(), "--summary"]);
+    cmd.assert().success().stdout(diff(expected));
+}
+
+#[test]
+fn poisson_config_reports_analytic_comparison() {
+    let config = r#"
+algo = "least-connections"
+randomness = "seeded(5)"
+requests = { rate = 100.0, duration_ms = 5000 }
+servers = [
+  { name = "a", base_latency_ms = 10, weight = 1 },
+  { name = "b", base_latency_ms = 10, weight = 1 }
+]
+"#;
+    let path = write_temp_config(config, "toml");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.arg("--config").arg(&path);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "Analytic (M/M/c, c=2, lambda=100 rps, S=10ms):\nutilization: theory 0.5, simulated ",
+        ))
+        .stdout(predicates::str::contains(
+            "avg wait: M/M/c 3.3333ms, M/D/c approx 1.6667ms, simulated ",
+        ));
+}
+
+#[test]
+fn config_include_merges_shared_inventory_with_overrides() {
+    let inventory = r#"{
+  "algo": "least-connections",
+  "requests": 10,
+  "servers": [
+    { "name": "a", "base_latency_ms": 10, "weight": 1 },
+    { "name": "b", "base_latency_ms": 20, "weight": 1 }
+  ]
+}"#;
+    let inventory_path = write_temp_config(inventory, "json");
+    let scenario = format!(
+        "include = [\"{}\"]\nalgo = \"round-robin\"\nrequests = 3\ntie_break = \"seeded\"\nseed = 42\n",
+        inventory_path.file_name().unwrap().to_str().unwrap()
+    );
+    let path = write_temp_config(&scenario, "toml");
+
+    let expected = concat!(
+        "Metadata:\n",
+        "algo: round-robin\n",
+        "tie_break: seeded(42)\n",
+        "duration_ms: 21\n",
+        "Summary:\n",
+        "a: 2 requests (avg response: 14ms, queued: 4ms, service: 10ms)\n",
+        "b: 1 requests (avg response: 20ms, queued: 0ms, service: 20ms)\n",
+    );
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args(["run", "--config", path.to_str().unwrap(), "--summary"]);
+    cmd.assert().success().stdout(diff(expected));
+}
+
+#[test]
+fn config_include_cycle_is_rejected() {
+    let dir = std::env::temp_dir();
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("time should be available")
+        .as_nanos();
+    let first = dir.join(format!("lb-cycle-a-{}.toml", nanos));
+    let second = dir.join(format!("lb-cycle-b-{}.toml", nanos));
+    fs::write(
+        &first,
+        format!(
+            "include = \"{}\"\n",
+            second.file_name().unwrap().to_str().unwrap()
+        ),
+    )
+    .expect("config write should succeed");
+    fs::write(
+        &second,
+        format!(
+            "include = \"{}\"\n",
+            first.file_name().unwrap().to_str().unwrap()
+        ),
+    )
+    .expect("config write should succeed");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args(["run", "--config", first.to_str().unwrap(), "--summary"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicates::str::contains("config include cycle:"));
+}
+
+#[test]
+fn scenario_runs_each_named_run_and_compares() {
+    let scenario = r#"
+requests = 3
+servers = [
+  { name = "a", base_latency_ms = 10 },
+  { name = "b", base_latency_ms = 20 },
+]
+
+[[runs]]
+name = "rr"
+algo = "round-robin"
+
+[[runs]]
+name = "lc"
+algo = "least-connections"
+requests = 4
+"#;
+    let path = write_temp_config(scenario, "toml");
+
+    let expected = concat!(
+        "Scenario:\n",
+        "run  algo              requests   p95_ms   p99_ms  wait_ms        rps fairness\n",
+        "rr   round-robin              3       20       20        2     142.86    0.900\n",
+        "lc   least-connections        4       38       38        6      97.56    1.000\n",
+    );
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args(["run", "--scenario", path.to_str().unwrap()]);
+    cmd.assert().success().stdout(diff(expected));
+}
+
+#[test]
+fn scenario_without_runs_is_rejected() {
+    let path = write_temp_config("requests = 3\n", "toml");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args(["run", "--scenario", path.to_str().unwrap()]);
+    cmd.assert().failure().stderr(predicates::str::contains(
+        "invalid scenario: expected a non-empty `runs` list",
+    ));
+}
+
+#[test]
+fn rich_server_blocks_shape_the_run() {
+    let config = r#"
+algo = "round-robin"
+requests = { count = 4, at_ms = 0 }
+
+[[servers]]
+name = "api"
+base_latency_ms = 10
+zone = "us-east-1a"
+capacity = { concurrency = 2 }
+
+[[servers]]
+name = "db"
+base_latency_ms = 10
+failure = { outages = [{ start_ms = 0, duration_ms = 30 }] }
+"#;
+    let path = write_temp_config(config, "toml");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "run",
+        "--config",
+        path.to_str().unwrap(),
+        "--format",
+        "json",
+    ]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).expect("json output");
+
+    assert_eq!(json["totals"][0]["zone"], "us-east-1a");
+    assert!(json["totals"][1].get("zone").is_none());
+    let started = json["assignments"]
+        .as_array()
+        .expect("assignments")
+        .iter()
+        .map(|assignment| assignment["started_at"].as_u64().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(started, vec![0, 30, 0, 40]);
+}
+
+#[test]
+fn set_overrides_one_server_without_replacing_the_fleet() {
+    let config = r#"
+algo = "weighted-round-robin"
+requests = 8
+servers = [{ name = "api", base_latency_ms = 10 }, { name = "db", base_latency_ms = 10 }]
+"#;
+    let path = write_temp_config(config, "toml");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "run",
+        "--config",
+        path.to_str().unwrap(),
+        "--set",
+        "server.api.weight=3",
+        "--format",
+        "summary",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("api: 6 requests"))
+        .stdout(predicates::str::contains("db: 2 requests"));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "run",
+        "--config",
+        path.to_str().unwrap(),
+        "--set",
+        "server.web.weight=3",
+    ]);
+    cmd.assert().failure().stderr(predicates::str::contains(
+        "invalid --set override 'server.web.weight=3': no server named 'web'",
+    ));
+}
+
+#[test]
+fn misspelled_config_key_is_rejected_unless_lenient() {
+    let config = r#"
+algo = "round-robin"
+requests = 4
+servers = [{ name = "a", base_latency_ms = 10, wieght = 2 }]
+"#;
+    let path = write_temp_config(config, "toml");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args(["run", "--config", path.to_str().unwrap()]);
+    cmd.assert().failure().stderr(predicates::str::contains(
+        "unknown config key(s): servers[0].wieght (did you mean `weight`?)",
+    ));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "run",
+        "--config",
+        path.to_str().unwrap(),
+        "--lenient",
+        "--format",
+        "summary",
+    ]);
+    cmd.assert().success();
+}
+
+#[test]
+fn config_profile_overrides_base_settings() {
+    let config = r#"
+algo = "round-robin"
+requests = 3
+servers = [
+  { name = "a", base_latency_ms = 10 },
+  { name = "b", base_latency_ms = 20 },
+]
+
+[profiles.peak]
+algo = "least-connections"
+requests = 6
+"#;
+    let path = write_temp_config(config, "toml");
+
+    let expected = concat!(
+        "Metadata:\n",
+        "algo: least-connections\n",
+        "tie_break: stable\n",
+        "duration_ms: 61\n",
+        "Summary:\n",
+        "a: 3 requests (avg response: 18ms, queued: 8ms, service: 10ms)\n",
+        "b: 3 requests (avg response: 38ms, queued: 18ms, service: 20ms)\n",
+    );
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "run",
+        "--config",
+        path.to_str().unwrap(),
+        "--profile",
+        "peak",
+        "--summary",
+    ]);
+    cmd.assert().success().stdout(diff(expected));
+
+    let mut unknown = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    unknown.args([
+        "run",
+        "--config",
+        path.to_str().unwrap(),
+        "--profile",
+        "off-peak",
+    ]);
+    unknown.assert().failure().stderr(predicates::str::contains(
+        "unknown profile 'off-peak' (available: peak)",
+    ));
+}
+
+#[test]
+fn watch_runs_immediately_and_keeps_polling() {
+    let config = r#"
+algo = "round-robin"
+requests = 3
+servers = [{ name = "a", base_latency_ms = 10 }]
+"#;
+    let path = write_temp_config(config, "toml");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args([
+        "run",
+        "--config",
+        path.to_str().unwrap(),
+        "--summary",
+        "--watch",
+    ])
+    .timeout(std::time::Duration::from_millis(1500));
+    cmd.assert().interrupted().stdout(predicates::str::contains(
+        "a: 3 requests (avg response: 19ms, queued: 9ms, service: 10ms)\n",
+    ));
+}
+
+#[test]
+fn config_file_rejects_unregistered_algorithm() {
+    let config = r#"
+algo = "my-algo"
+requests = 2
+servers = [{ name = "a", base_latency_ms = 10 }]
+"#;
+    let path = write_temp_config(config, "toml");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args(["run", "--config", path.to_str().unwrap()]);
+    cmd.assert().failure().stderr(predicates::str::contains(
+        "unknown algorithm 'my-algo' (see list-algorithms)",
+    ));
+}
+
+#[test]
+fn config_file_accepts_algorithm_alias() {
+    let config = r#"
+algo = "wrr"
+requests = 2
+servers = [{ name = "a", base_latency_ms = 10 }]
+"#;
+    let path = write_temp_config(config, "toml");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.args(["run", "--config", path.to_str().unwrap(), "--summary"]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("algo: weighted-round-robin\n"));
+}
+
+#[test]
+fn dry_run_layers_env_overrides_between_the_file_and_set() {
+    let config = r#"
+algo = "round-robin"
+requests = 8
+servers = [{ name = "api", base_latency_ms = 10 }, { name = "db", base_latency_ms = 10 }]
+"#;
+    let path = write_temp_config(config, "toml");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.env("LB_SIM_SET", "requests=20; server.api.weight=3")
+        .args([
+            "run",
+            "--config",
+            path.to_str().unwrap(),
+            "--set",
+            "requests=30",
+            "--dry-run",
+        ]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("requests = 30\n"))
+        .stdout(predicates::str::contains("weight = 3\n"));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("lb-sim");
+    cmd.env("LB_SIM_SET", "requests=20").args([
+        "run",
+        "--algo",
+        "round-robin",
+        "--server",
+        "a:10",
+        "--requests",
+        "1",
+    ]);
+    cmd.assert().failure().stderr(predicates::str::contains(
+        "LB_SIM_SET overrides apply to a config file; pass --config",
+    ));
+}